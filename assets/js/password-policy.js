@@ -0,0 +1,56 @@
+// Checks new passwords against the instance's password policy.
+//
+// The policy is enforced by the server (which receives new passwords as-is),
+// this only reports the violations before submitting the form.
+
+let commonPasswords = null;
+
+async function loadCommonPasswords() {
+    if (commonPasswords === null) {
+        const response = await fetch("/account/common-passwords");
+        const text = response.ok ? await response.text() : "";
+        commonPasswords = new Set(
+            text.split("\n")
+                .map((it) => it.trim().toLowerCase())
+                .filter((it) => it.length > 0)
+        );
+    }
+    return commonPasswords;
+}
+
+// Returns the list of the policy's rules that the password does not comply with.
+export async function checkPassword(password, policy) {
+    const errors = [];
+    if ([...password].length < policy.min_length) {
+        errors.push(`must be at least ${policy.min_length} characters long.`);
+    }
+    if (policy.require_lowercase && !/\p{Ll}/u.test(password)) {
+        errors.push("must contain a lowercase letter.");
+    }
+    if (policy.require_uppercase && !/\p{Lu}/u.test(password)) {
+        errors.push("must contain an uppercase letter.");
+    }
+    if (policy.require_digit && !/\p{N}/u.test(password)) {
+        errors.push("must contain a digit.");
+    }
+    if (policy.require_symbol && !/[^\p{L}\p{N}]/u.test(password)) {
+        errors.push("must contain a symbol.");
+    }
+    if (policy.reject_common) {
+        const common = await loadCommonPasswords();
+        if (common.has(password.toLowerCase())) {
+            errors.push("is too common.");
+        }
+    }
+    return errors;
+}
+
+// Displays the errors of a password field in the given element (or hides it if there are none).
+export function displayErrors(element, errors) {
+    element.replaceChildren(...errors.map((error) => {
+        const item = document.createElement("div");
+        item.textContent = `Error: the password ${error}`;
+        return item;
+    }));
+    element.hidden = errors.length === 0;
+}
@@ -0,0 +1,97 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+
+use crate::models::{ConfigFile, CrateVersion};
+
+/// The default maximum number of crates whose records are kept in memory.
+pub(crate) const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// A thread-safe, bounded (LRU) in-memory cache of crate index reads.
+///
+/// It memoizes the index's configuration file and the records of the most recently accessed crates.  
+/// The records of a crate are invalidated whenever that crate's file is modified through the index,
+/// and everything is invalidated whenever the index is refreshed from its remote.
+#[derive(Debug)]
+pub(crate) struct IndexCache {
+    configuration: Mutex<Option<ConfigFile>>,
+    records: Mutex<CachedRecords>,
+}
+
+/// The cached records, along with a counter of the invalidations made so far.
+#[derive(Debug)]
+struct CachedRecords {
+    entries: LruCache<String, Arc<Vec<CrateVersion>>>,
+    /// Incremented on every invalidation, to detect the records loaded while an invalidation happened.
+    generation: u64,
+}
+
+impl IndexCache {
+    /// Creates a new cache, holding the records of at most `capacity` crates.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            configuration: Mutex::new(None),
+            records: Mutex::new(CachedRecords {
+                entries: LruCache::new(capacity),
+                generation: 0,
+            }),
+        }
+    }
+
+    /// Gets the cached configuration, or loads (and caches) it using the given function.
+    pub fn configuration<E, F>(&self, load: F) -> Result<ConfigFile, E>
+    where
+        F: FnOnce() -> Result<ConfigFile, E>,
+    {
+        let mut configuration = self.configuration.lock().unwrap();
+        if let Some(cached) = configuration.as_ref() {
+            return Ok(cached.clone());
+        }
+
+        let loaded = load()?;
+        *configuration = Some(loaded.clone());
+        Ok(loaded)
+    }
+
+    /// Gets the cached records of a crate, or loads (and caches) them using the given function.
+    pub fn records<E, F>(&self, name: &str, load: F) -> Result<Arc<Vec<CrateVersion>>, E>
+    where
+        F: FnOnce() -> Result<Vec<CrateVersion>, E>,
+    {
+        let key = name.to_ascii_lowercase();
+        let generation = {
+            let mut records = self.records.lock().unwrap();
+            if let Some(cached) = records.entries.get(&key) {
+                return Ok(Arc::clone(cached));
+            }
+            records.generation
+        };
+
+        // The records are loaded without holding the lock (so that reads of other crates are not blocked),
+        // and are only cached if nothing got invalidated in the meantime (as they might have been read
+        // before the crate's file got modified).
+        let loaded = Arc::new(load()?);
+        let mut records = self.records.lock().unwrap();
+        if records.generation == generation {
+            records.entries.put(key, Arc::clone(&loaded));
+        }
+        Ok(loaded)
+    }
+
+    /// Invalidates the cached records of a crate.
+    pub fn invalidate(&self, name: &str) {
+        let key = name.to_ascii_lowercase();
+        let mut records = self.records.lock().unwrap();
+        records.entries.pop(&key);
+        records.generation += 1;
+    }
+
+    /// Invalidates everything held by the cache.
+    pub fn clear(&self) {
+        *self.configuration.lock().unwrap() = None;
+        let mut records = self.records.lock().unwrap();
+        records.entries.clear();
+        records.generation += 1;
+    }
+}
@@ -1,8 +1,12 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::cache::DEFAULT_CACHE_CAPACITY;
 use crate::cli::CommandLineIndex;
+use crate::debounce::DEFAULT_REFRESH_DEBOUNCE;
+use crate::{CommitIdentity, RecordSchema, DEFAULT_COMMIT_EMAIL, DEFAULT_COMMIT_NAME};
 
 /// The configuration struct for the 'command-line' index management strategy.
 ///
@@ -10,15 +14,68 @@ use crate::cli::CommandLineIndex;
 /// [index]
 /// type = "command-line" # required
 /// path = "crate-index"  # required
+/// cache_capacity = 1024 # optional, `0` disables the in-memory cache
+/// schema = 2            # optional, the schema version of written records (`1` for Cargo versions older than 1.60)
+/// commit_name = "Alexandrie"                  # optional, the name used for index commits
+/// commit_email = "noreply@alexandrie.invalid" # optional, the email used for index commits
+/// refresh_debounce = 5000 # optional, the window within which refreshes are collapsed into a single fetch (in milliseconds)
+/// prefer_stable = true    # optional, whether the latest version of crates is their latest stable one (if they have any)
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CommandLineIndexConfig {
     /// The path to the local index repository.
     pub path: PathBuf,
+    /// How many crates can have their records cached in memory (`0` disables caching).
+    #[serde(default = "default_cache_capacity")]
+    pub cache_capacity: usize,
+    /// The schema version at which new records are written.
+    #[serde(default)]
+    pub schema: RecordSchema,
+    /// The name used as the author and committer of index commits.
+    #[serde(default = "default_commit_name")]
+    pub commit_name: String,
+    /// The email used as the author and committer of index commits.
+    #[serde(default = "default_commit_email")]
+    pub commit_email: String,
+    /// The window within which refreshes are collapsed into a single fetch (in milliseconds).
+    #[serde(default = "default_refresh_debounce")]
+    pub refresh_debounce: u64,
+    /// Whether the latest version of a crate is its latest stable version (if it has any), rather than its highest version.
+    #[serde(default = "default_prefer_stable")]
+    pub prefer_stable: bool,
+}
+
+fn default_cache_capacity() -> usize {
+    DEFAULT_CACHE_CAPACITY
+}
+
+fn default_commit_name() -> String {
+    String::from(DEFAULT_COMMIT_NAME)
+}
+
+fn default_commit_email() -> String {
+    String::from(DEFAULT_COMMIT_EMAIL)
+}
+
+fn default_refresh_debounce() -> u64 {
+    DEFAULT_REFRESH_DEBOUNCE.as_millis() as u64
+}
+
+fn default_prefer_stable() -> bool {
+    true
 }
 
 impl From<CommandLineIndexConfig> for CommandLineIndex {
     fn from(config: CommandLineIndexConfig) -> CommandLineIndex {
+        let identity = CommitIdentity {
+            name: config.commit_name,
+            email: config.commit_email,
+        };
         CommandLineIndex::new(config.path)
+            .with_cache_capacity(config.cache_capacity)
+            .with_schema(config.schema)
+            .with_commit_identity(identity)
+            .with_refresh_debounce(Duration::from_millis(config.refresh_debounce))
+            .with_prefer_stable(config.prefer_stable)
     }
 }
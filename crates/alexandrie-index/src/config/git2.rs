@@ -1,8 +1,13 @@
+use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use crate::index::git2::Git2Index;
+use crate::cache::DEFAULT_CACHE_CAPACITY;
+use crate::debounce::DEFAULT_REFRESH_DEBOUNCE;
+use crate::index::git2::{Git2Index, SshKey, DEFAULT_PUSH_RETRIES, DEFAULT_PUSH_RETRY_DELAY};
+use crate::{CommitIdentity, RecordSchema, DEFAULT_COMMIT_EMAIL, DEFAULT_COMMIT_NAME};
 
 /// The configuration struct for the 'git2' index management strategy.
 ///
@@ -10,15 +15,110 @@ use crate::index::git2::Git2Index;
 /// [index]
 /// type = "git2"        # required
 /// path = "crate-index" # required
+/// cache_capacity = 1024 # optional, `0` disables the in-memory cache
+/// schema = 2            # optional, the schema version of written records (`1` for Cargo versions older than 1.60)
+/// commit_name = "Alexandrie"                  # optional, the name used for index commits
+/// commit_email = "noreply@alexandrie.invalid" # optional, the email used for index commits
+/// refresh_debounce = 5000 # optional, the window within which refreshes are collapsed into a single fetch (in milliseconds)
+/// prefer_stable = true    # optional, whether the latest version of crates is their latest stable one (if they have any)
+/// push_retries = 3       # optional, how many times to retry pushing when the remote has advanced
+/// push_retry_delay = 250 # optional, the delay before the first push retry (in milliseconds)
+/// ssh_key = "~/.ssh/id_ed25519" # optional, the SSH key to use (defaults to the SSH agent, a leading `~` is the home directory)
+/// ssh_key_passphrase = "..."     # optional, the passphrase of the SSH key
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Git2IndexConfig {
     /// The path to the local index repository.
     pub path: PathBuf,
+    /// How many crates can have their records cached in memory (`0` disables caching).
+    #[serde(default = "default_cache_capacity")]
+    pub cache_capacity: usize,
+    /// The schema version at which new records are written.
+    #[serde(default)]
+    pub schema: RecordSchema,
+    /// The name used as the author and committer of index commits.
+    #[serde(default = "default_commit_name")]
+    pub commit_name: String,
+    /// The email used as the author and committer of index commits.
+    #[serde(default = "default_commit_email")]
+    pub commit_email: String,
+    /// The window within which refreshes are collapsed into a single fetch (in milliseconds).
+    #[serde(default = "default_refresh_debounce")]
+    pub refresh_debounce: u64,
+    /// Whether the latest version of a crate is its latest stable version (if it has any), rather than its highest version.
+    #[serde(default = "default_prefer_stable")]
+    pub prefer_stable: bool,
+    /// How many times to retry pushing when the remote has advanced concurrently.
+    #[serde(default = "default_push_retries")]
+    pub push_retries: u32,
+    /// The delay before the first push retry (in milliseconds, doubled after each attempt).
+    #[serde(default = "default_push_retry_delay")]
+    pub push_retry_delay: u64,
+    /// The path to the SSH private key to authenticate with to the remote.
+    /// A leading `~` is expanded to the home directory (libgit2 does not expand it on its own).
+    /// If not set, the SSH agent is used instead.
+    pub ssh_key: Option<PathBuf>,
+    /// The passphrase of the SSH private key (if it is encrypted).
+    pub ssh_key_passphrase: Option<String>,
+}
+
+fn default_cache_capacity() -> usize {
+    DEFAULT_CACHE_CAPACITY
+}
+
+fn default_commit_name() -> String {
+    String::from(DEFAULT_COMMIT_NAME)
+}
+
+fn default_commit_email() -> String {
+    String::from(DEFAULT_COMMIT_EMAIL)
+}
+
+fn default_refresh_debounce() -> u64 {
+    DEFAULT_REFRESH_DEBOUNCE.as_millis() as u64
+}
+
+fn default_prefer_stable() -> bool {
+    true
+}
+
+fn default_push_retries() -> u32 {
+    DEFAULT_PUSH_RETRIES
+}
+
+fn default_push_retry_delay() -> u64 {
+    DEFAULT_PUSH_RETRY_DELAY.as_millis() as u64
+}
+
+/// Expands a leading `~` in a path to the home directory (if the `HOME` environment variable is set).
+fn expand_home(path: PathBuf) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path,
+    }
 }
 
 impl From<Git2IndexConfig> for Git2Index {
     fn from(config: Git2IndexConfig) -> Git2Index {
-        Git2Index::new(config.path).expect("could not initialize the 'git2' index")
+        let ssh_key = config.ssh_key.map(|path| SshKey {
+            path: expand_home(path),
+            passphrase: config.ssh_key_passphrase,
+        });
+        let identity = CommitIdentity {
+            name: config.commit_name,
+            email: config.commit_email,
+        };
+        Git2Index::new(config.path)
+            .expect("could not initialize the 'git2' index")
+            .with_cache_capacity(config.cache_capacity)
+            .with_schema(config.schema)
+            .with_push_retries(
+                config.push_retries,
+                Duration::from_millis(config.push_retry_delay),
+            )
+            .with_ssh_key(ssh_key)
+            .with_commit_identity(identity)
+            .with_refresh_debounce(Duration::from_millis(config.refresh_debounce))
+            .with_prefer_stable(config.prefer_stable)
     }
 }
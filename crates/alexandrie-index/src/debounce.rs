@@ -0,0 +1,194 @@
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+/// The default window within which index refreshes are collapsed into a single fetch.
+pub(crate) const DEFAULT_REFRESH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// A debouncer of index refreshes, collapsing the refreshes requested within a window into a single one.
+///
+/// Callers arriving while a refresh is in flight wait for it to complete rather than launching their own,
+/// and callers arriving shortly after a successful refresh (within the window) return right away.  
+/// Failed refreshes are never debounced, so that the next caller attempts a new one.
+#[derive(Debug)]
+pub(crate) struct Debouncer {
+    window: Duration,
+    state: Mutex<DebounceState>,
+    completed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct DebounceState {
+    /// Whether a refresh is currently in flight.
+    in_flight: bool,
+    /// Whether the last completed refresh succeeded.
+    last_succeeded: bool,
+    /// When the last successful refresh completed.
+    last_success: Option<Instant>,
+}
+
+impl Debouncer {
+    /// Creates a new debouncer, with the given window (`0` only collapses concurrent refreshes).
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            state: Mutex::new(DebounceState::default()),
+            completed: Condvar::new(),
+        }
+    }
+
+    /// Runs a refresh, unless one is in flight or recently succeeded (in which case it is reused).
+    ///
+    /// If `force` is set, a new refresh is always run (once the one in flight, if any, has completed).
+    pub fn run<F>(&self, force: bool, refresh: F) -> Result<(), Error>
+    where
+        F: FnOnce() -> Result<(), Error>,
+    {
+        let mut state = self.state.lock().unwrap();
+        if state.in_flight {
+            state = self
+                .completed
+                .wait_while(state, |state| state.in_flight)
+                .unwrap();
+            if !force && state.last_succeeded {
+                return Ok(());
+            }
+        }
+
+        let is_fresh = state
+            .last_success
+            .map_or(false, |at| at.elapsed() < self.window);
+        if !force && is_fresh {
+            return Ok(());
+        }
+
+        state.in_flight = true;
+        drop(state);
+
+        let result = refresh();
+
+        let mut state = self.state.lock().unwrap();
+        state.in_flight = false;
+        state.last_succeeded = result.is_ok();
+        if result.is_ok() {
+            state.last_success = Some(Instant::now());
+        }
+        self.completed.notify_all();
+
+        result
+    }
+}
+
+impl PartialEq for Debouncer {
+    fn eq(&self, other: &Self) -> bool {
+        self.window == other.window
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFRESH_DEBOUNCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+
+    fn failure() -> Error {
+        Error::from(io::Error::new(io::ErrorKind::Other, "fetch failed"))
+    }
+
+    #[test]
+    fn reuses_recent_refreshes() {
+        let debouncer = Debouncer::new(Duration::from_secs(60));
+        let count = AtomicUsize::new(0);
+        let refresh = || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        debouncer.run(false, refresh).unwrap();
+        debouncer.run(false, refresh).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn forced_refreshes_bypass_the_window() {
+        let debouncer = Debouncer::new(Duration::from_secs(60));
+        let count = AtomicUsize::new(0);
+        let refresh = || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        debouncer.run(false, refresh).unwrap();
+        debouncer.run(true, refresh).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_refreshes_are_not_reused() {
+        let debouncer = Debouncer::new(Duration::from_secs(60));
+        let count = AtomicUsize::new(0);
+
+        let result = debouncer.run(false, || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Err(failure())
+        });
+        assert!(result.is_err());
+
+        debouncer
+            .run(false, || {
+                count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn empty_windows_only_collapse_concurrent_refreshes() {
+        let debouncer = Debouncer::new(Duration::ZERO);
+        let count = AtomicUsize::new(0);
+        let refresh = || {
+            count.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        debouncer.run(false, refresh).unwrap();
+        debouncer.run(false, refresh).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn concurrent_callers_share_the_refresh_in_flight() {
+        let debouncer = Arc::new(Debouncer::new(Duration::ZERO));
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let debouncer = Arc::clone(&debouncer);
+                let count = Arc::clone(&count);
+                thread::spawn(move || {
+                    debouncer.run(false, || {
+                        count.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(200));
+                        Ok(())
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        assert!(count.load(Ordering::SeqCst) < 8);
+    }
+}
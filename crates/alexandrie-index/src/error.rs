@@ -15,6 +15,10 @@ pub enum Error {
     #[error("libgit2 error: {0}")]
     #[cfg(feature = "git2")]
     Git2Error(#[from] git2::Error),
+    /// Could not authenticate to the index's remote.
+    #[error("could not authenticate to the index's remote (check the configured SSH key, or the SSH agent if none is configured): {0}")]
+    #[cfg(feature = "git2")]
+    AuthenticationError(#[source] git2::Error),
     /// Other index-specific error (crate not found, etc...).
     #[error("index-specific error: {0}")]
     IndexError(#[from] IndexError),
@@ -29,4 +33,20 @@ pub enum IndexError {
         /// The requested crate's name.
         name: String,
     },
+    /// The crate has been modified concurrently in the remote index.
+    #[error("the crate '{name}' has been concurrently modified in the remote index, please retry")]
+    ConcurrentModification {
+        /// The involved crate's name.
+        name: String,
+    },
+    /// The record cannot be represented at the configured schema version of the index.
+    #[error("the record of `{name}#{version}` cannot be written at the index's schema version ({schema}), as it uses features with the `dep:` or `?` syntaxes")]
+    UnsupportedSchema {
+        /// The involved crate's name.
+        name: String,
+        /// The involved crate's version.
+        version: semver::Version,
+        /// The index's configured schema version.
+        schema: u32,
+    },
 }
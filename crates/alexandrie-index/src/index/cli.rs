@@ -1,11 +1,15 @@
+use std::io;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
 
 use semver::{Version, VersionReq};
 
+use crate::debounce::Debouncer;
 use crate::error::Error;
 use crate::tree::Tree;
-use crate::{CrateVersion, Indexer};
+use crate::{CommitIdentity, ConfigFile, CrateVersion, Indexer, RecordSchema};
 
 /// The 'command-line' crate index management strategy type.
 ///
@@ -14,15 +18,61 @@ use crate::{CrateVersion, Indexer};
 pub struct CommandLineIndex {
     repo: Repository,
     tree: Tree,
+    refreshes: Arc<Debouncer>,
 }
 
 impl CommandLineIndex {
     /// Create a CommandLineIndex instance with the given path.
     pub fn new<P: Into<PathBuf>>(path: P) -> CommandLineIndex {
         let path = path.into();
-        let repo = Repository { path: path.clone() };
+        let repo = Repository {
+            path: path.clone(),
+            identity: CommitIdentity::default(),
+        };
         let tree = Tree::new(path);
-        CommandLineIndex { repo, tree }
+        let refreshes = Arc::new(Debouncer::default());
+        CommandLineIndex {
+            repo,
+            tree,
+            refreshes,
+        }
+    }
+
+    /// Sets how many crates can have their records cached in memory (`0` disables caching).
+    pub fn with_cache_capacity(mut self, capacity: usize) -> CommandLineIndex {
+        self.tree.set_cache_capacity(capacity);
+        self
+    }
+
+    /// Sets the schema version at which new records are written.
+    pub fn with_schema(mut self, schema: RecordSchema) -> CommandLineIndex {
+        self.tree.set_schema(schema);
+        self
+    }
+
+    /// Sets whether the latest record of a crate is its latest stable version (if it has any), rather than its highest version.
+    pub fn with_prefer_stable(mut self, prefer_stable: bool) -> CommandLineIndex {
+        self.tree.set_prefer_stable(prefer_stable);
+        self
+    }
+
+    /// Sets the identity used as the author and committer of index commits.
+    pub fn with_commit_identity(mut self, identity: CommitIdentity) -> CommandLineIndex {
+        self.repo.identity = identity;
+        self
+    }
+
+    /// Sets the window within which refreshes are collapsed into a single `git pull` (`0` only collapses concurrent ones).
+    pub fn with_refresh_debounce(mut self, window: Duration) -> CommandLineIndex {
+        self.refreshes = Arc::new(Debouncer::new(window));
+        self
+    }
+
+    /// Pulls the remote's changes (and invalidates the cached records, as they may have changed).
+    fn pull(&self) -> Result<(), Error> {
+        let result = self.repo.refresh();
+        self.tree.invalidate();
+        result
     }
 }
 
@@ -31,14 +81,30 @@ impl Indexer for CommandLineIndex {
         self.repo.url()
     }
 
+    fn configuration(&self) -> Result<ConfigFile, Error> {
+        self.tree.configuration()
+    }
+
     fn refresh(&self) -> Result<(), Error> {
-        self.repo.refresh()
+        self.refreshes.run(false, || self.pull())
+    }
+
+    fn force_refresh(&self) -> Result<(), Error> {
+        self.refreshes.run(true, || self.pull())
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        self.repo.check_health()
     }
 
     fn commit_and_push(&self, msg: &str) -> Result<(), Error> {
         self.repo.commit_and_push(msg)
     }
 
+    fn has_uncommitted_changes(&self) -> Result<bool, Error> {
+        self.repo.has_uncommitted_changes()
+    }
+
     fn match_record(&self, name: &str, req: VersionReq) -> Result<CrateVersion, Error> {
         self.tree.match_record(name, req)
     }
@@ -47,6 +113,13 @@ impl Indexer for CommandLineIndex {
         self.tree.all_records(name)
     }
 
+    fn for_each_record<F>(&self, name: &str, func: F) -> Result<(), Error>
+    where
+        F: FnMut(&CrateVersion),
+    {
+        self.tree.for_each_record(name, func)
+    }
+
     fn latest_record(&self, name: &str) -> Result<CrateVersion, Error> {
         self.tree.latest_record(name)
     }
@@ -55,6 +128,10 @@ impl Indexer for CommandLineIndex {
         self.tree.add_record(record)
     }
 
+    fn remove_record(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.tree.remove_record(name, version)
+    }
+
     fn alter_record<F>(&self, name: &str, version: Version, func: F) -> Result<(), Error>
     where
         F: FnOnce(&mut CrateVersion),
@@ -66,6 +143,7 @@ impl Indexer for CommandLineIndex {
 #[derive(Debug, Clone, PartialEq)]
 struct Repository {
     path: PathBuf,
+    identity: CommitIdentity,
 }
 
 impl Repository {
@@ -92,6 +170,39 @@ impl Repository {
         Ok(())
     }
 
+    fn check_health(&self) -> Result<(), Error> {
+        let status = Command::new("git")
+            .arg("rev-parse")
+            .arg("--git-dir")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .current_dir(self.path.canonicalize()?)
+            .status()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            let message = format!("'{0}' is not a git repository", self.path.display());
+            Err(Error::from(io::Error::new(io::ErrorKind::Other, message)))
+        }
+    }
+
+    fn has_uncommitted_changes(&self) -> Result<bool, Error> {
+        let output = Command::new("git")
+            .arg("status")
+            .arg("--porcelain")
+            .stdout(Stdio::piped())
+            .current_dir(self.path.canonicalize()?)
+            .output()?;
+
+        if !output.status.success() {
+            let message = format!("could not get the status of '{0}'", self.path.display());
+            return Err(Error::from(io::Error::new(io::ErrorKind::Other, message)));
+        }
+
+        Ok(!output.stdout.iter().all(u8::is_ascii_whitespace))
+    }
+
     fn commit_and_push(&self, msg: &str) -> Result<(), Error> {
         Command::new("git")
             .arg("add")
@@ -100,6 +211,10 @@ impl Repository {
             .spawn()?
             .wait()?;
         Command::new("git")
+            .arg("-c")
+            .arg(format!("user.name={0}", self.identity.name))
+            .arg("-c")
+            .arg(format!("user.email={0}", self.identity.email))
             .arg("commit")
             .arg("-m")
             .arg(msg)
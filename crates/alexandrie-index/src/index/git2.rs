@@ -1,21 +1,58 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use semver::{Version, VersionReq};
 
-use crate::error::Error;
+use crate::debounce::Debouncer;
+use crate::error::{Error, IndexError};
 use crate::tree::Tree;
-use crate::{CrateVersion, Indexer};
+use crate::{CommitIdentity, ConfigFile, CrateVersion, Indexer, RecordSchema};
 
 /// The 'git2' crate index management strategy type.
 ///
 /// It manages the crate index using the [**`libgit2`**][libgit2] library.
 ///
 /// [libgit2]: https://libgit2.org
+///
+/// Accesses to the repository are serialized through a mutex, and all of its operations are blocking
+/// (see the threading contract of [`Indexer`]).
 pub struct Git2Index {
     /// The path of the crate index.
     pub(crate) repo: Mutex<git2::Repository>,
     tree: Tree,
+    /// How many times to retry pushing when the remote has advanced in the meantime.
+    push_retries: u32,
+    /// The delay before the first push retry (doubled after each attempt).
+    push_retry_delay: Duration,
+    /// The SSH key to authenticate with to the remote (if not set, the SSH agent is used instead).
+    ssh_key: Option<SshKey>,
+    /// The identity used as the author and committer of index commits.
+    identity: CommitIdentity,
+    /// The debouncer of index refreshes.
+    refreshes: Debouncer,
+}
+
+/// An SSH private key, used to authenticate to the index's remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshKey {
+    /// The path to the private key file.
+    pub path: PathBuf,
+    /// The passphrase of the private key (if it is encrypted).
+    pub passphrase: Option<String>,
+}
+
+/// The default number of push retries (see [`Git2Index::with_push_retries`]).
+pub const DEFAULT_PUSH_RETRIES: u32 = 3;
+/// The default delay before the first push retry (see [`Git2Index::with_push_retries`]).
+pub const DEFAULT_PUSH_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// The outcome of a push to the remote.
+enum PushOutcome {
+    /// The remote has been updated.
+    Pushed,
+    /// The remote has advanced since our last fetch, so the push got rejected.
+    Outdated,
 }
 
 impl Git2Index {
@@ -25,7 +62,236 @@ impl Git2Index {
         let repo = git2::Repository::open(&path)?;
         let repo = Mutex::new(repo);
         let tree = Tree::new(path);
-        Ok(Git2Index { repo, tree })
+        Ok(Git2Index {
+            repo,
+            tree,
+            push_retries: DEFAULT_PUSH_RETRIES,
+            push_retry_delay: DEFAULT_PUSH_RETRY_DELAY,
+            ssh_key: None,
+            identity: CommitIdentity::default(),
+            refreshes: Debouncer::default(),
+        })
+    }
+
+    /// Sets how many crates can have their records cached in memory (`0` disables caching).
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Git2Index {
+        self.tree.set_cache_capacity(capacity);
+        self
+    }
+
+    /// Sets the schema version at which new records are written.
+    pub fn with_schema(mut self, schema: RecordSchema) -> Git2Index {
+        self.tree.set_schema(schema);
+        self
+    }
+
+    /// Sets whether the latest record of a crate is its latest stable version (if it has any), rather than its highest version.
+    pub fn with_prefer_stable(mut self, prefer_stable: bool) -> Git2Index {
+        self.tree.set_prefer_stable(prefer_stable);
+        self
+    }
+
+    /// Sets how many times (and with which initial delay) to retry pushing when the remote
+    /// has advanced concurrently (the delay is doubled after each attempt).
+    pub fn with_push_retries(mut self, retries: u32, delay: Duration) -> Git2Index {
+        self.push_retries = retries;
+        self.push_retry_delay = delay;
+        self
+    }
+
+    /// Sets the SSH key to authenticate with to the remote (instead of relying on the SSH agent).
+    pub fn with_ssh_key(mut self, ssh_key: Option<SshKey>) -> Git2Index {
+        self.ssh_key = ssh_key;
+        self
+    }
+
+    /// Sets the identity used as the author and committer of index commits.
+    pub fn with_commit_identity(mut self, identity: CommitIdentity) -> Git2Index {
+        self.identity = identity;
+        self
+    }
+
+    /// Sets the window within which refreshes are collapsed into a single fetch (`0` only collapses concurrent ones).
+    pub fn with_refresh_debounce(mut self, window: Duration) -> Git2Index {
+        self.refreshes = Debouncer::new(window);
+        self
+    }
+
+    /// Gets the name of the currently checked-out branch.
+    fn current_branch(repo: &git2::Repository) -> Result<String, Error> {
+        let branch = repo
+            .branches(Some(git2::BranchType::Local))?
+            .flatten()
+            .map(|(branch, _)| branch)
+            .find(|branch| branch.is_head())
+            .ok_or_else(|| git2::Error::from_str("detached HEAD not supported"))?;
+        let branch_name = branch.name()?.expect("branch name is invalid UTF-8");
+        Ok(String::from(branch_name))
+    }
+
+    /// Fetches the remote's changes of the given branch (into `FETCH_HEAD`).
+    fn fetch(&self, repo: &git2::Repository, branch_name: &str) -> Result<(), Error> {
+        let mut remote = repo.find_remote("origin")?;
+        with_credentials(repo, self.ssh_key.as_ref(), |cred_callback| {
+            let mut opts = git2::FetchOptions::new();
+            let mut callbacks = git2::RemoteCallbacks::new();
+            callbacks.credentials(cred_callback);
+            opts.remote_callbacks(callbacks);
+            remote.fetch(&[branch_name], Some(&mut opts), None)
+        })
+        .map_err(authentication_error)
+    }
+
+    /// Pushes the given branch to the remote.
+    fn push(&self, repo: &git2::Repository, branch_name: &str) -> Result<PushOutcome, Error> {
+        let mut remote = repo.find_remote("origin")?;
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch_name);
+
+        let mut rejection: Option<String> = None;
+        let outcome = with_credentials(repo, self.ssh_key.as_ref(), |cred_callback| {
+            let mut opts = git2::PushOptions::new();
+            let mut callbacks = git2::RemoteCallbacks::new();
+            callbacks.credentials(cred_callback);
+            callbacks.push_update_reference(|_, status| {
+                rejection = status.map(String::from);
+                Ok(())
+            });
+            opts.remote_callbacks(callbacks);
+            remote.push(&[refspec.as_str()], Some(&mut opts))
+        });
+
+        match (outcome, rejection) {
+            (Ok(()), None) => Ok(PushOutcome::Pushed),
+            //? The server rejects outdated pushes with statuses like "non-fast-forward" or "fetch first".
+            (Ok(()), Some(status))
+                if status.contains("fast-forward") || status.contains("fetch first") =>
+            {
+                Ok(PushOutcome::Outdated)
+            }
+            (Ok(()), Some(status)) => Err(Error::from(git2::Error::from_str(&format!(
+                "the remote rejected the push: {status}"
+            )))),
+            (Err(err), _) if err.code() == git2::ErrorCode::NotFastForward => {
+                Ok(PushOutcome::Outdated)
+            }
+            (Err(err), _) => Err(authentication_error(err)),
+        }
+    }
+
+    /// Replays the latest local commit on top of the freshly fetched remote branch.
+    ///
+    /// The files modified by the local commit are carried over onto the remote's tree.  
+    /// If the remote also modified one of these files (meaning the same crate got modified concurrently),
+    /// the replay is aborted with an [`IndexError::ConcurrentModification`] error.
+    fn replay_onto_remote(&self, repo: &git2::Repository, branch_name: &str) -> Result<(), Error> {
+        let local = repo.head()?.peel_to_commit()?;
+        let base = local.parent(0)?;
+
+        self.fetch(repo, branch_name)?;
+        let remote = repo.find_reference("FETCH_HEAD")?.peel_to_commit()?;
+
+        let local_tree = local.tree()?;
+        let base_tree = base.tree()?;
+        let remote_tree = remote.tree()?;
+
+        let diff = repo.diff_tree_to_tree(Some(&base_tree), Some(&local_tree), None)?;
+        let mut update = git2::build::TreeUpdateBuilder::new();
+        for delta in diff.deltas() {
+            let deleted = delta.status() == git2::Delta::Deleted;
+            let file = if deleted {
+                delta.old_file()
+            } else {
+                delta.new_file()
+            };
+            let Some(path) = file.path() else {
+                continue;
+            };
+
+            //? Was this file also modified in the remote ?
+            let entry_id =
+                |tree: &git2::Tree, path: &Path| tree.get_path(path).ok().map(|entry| entry.id());
+            if entry_id(&base_tree, path) != entry_id(&remote_tree, path) {
+                let name = path
+                    .file_name()
+                    .map_or_else(String::default, |name| name.to_string_lossy().into_owned());
+                return Err(Error::from(IndexError::ConcurrentModification { name }));
+            }
+
+            if deleted {
+                update.remove(path);
+            } else {
+                update.upsert(path, file.id(), file.mode());
+            }
+        }
+
+        let tree_id = update.create_updated(repo, &remote_tree)?;
+        let tree = repo.find_tree(tree_id)?;
+        let message = local.message().unwrap_or_default();
+        let replayed = repo.commit(
+            None,
+            &local.author(),
+            &local.committer(),
+            message,
+            &tree,
+            &[&remote],
+        )?;
+
+        let refname = format!("refs/heads/{}", branch_name);
+        let mut reference = repo.find_reference(&refname)?;
+        reference.set_target(replayed, "Replay onto remote")?;
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+
+        //? The remote's changes may have modified any crate's records.
+        self.tree.invalidate();
+
+        Ok(())
+    }
+
+    /// Pushes the latest local commit to the remote.
+    ///
+    /// If another instance pushed in the meantime, the commit is replayed on top of theirs and the push is retried.
+    fn push_with_retries(&self, repo: &git2::Repository) -> Result<(), Error> {
+        let branch_name = Self::current_branch(repo)?;
+        let mut delay = self.push_retry_delay;
+        for attempt in 0..=self.push_retries {
+            if attempt > 0 {
+                std::thread::sleep(delay);
+                delay *= 2;
+                self.replay_onto_remote(repo, &branch_name)?;
+            }
+
+            if let PushOutcome::Pushed = self.push(repo, &branch_name)? {
+                return Ok(());
+            }
+        }
+
+        Err(Error::from(git2::Error::from_str(&format!(
+            "the remote index kept advancing, gave up pushing after {0} retries",
+            self.push_retries,
+        ))))
+    }
+
+    /// Fetches the remote's changes and fast-forwards the local branch to them.
+    fn pull(&self) -> Result<(), Error> {
+        let repo = self.repo.lock().unwrap();
+        let branch_name = Self::current_branch(&repo)?;
+        self.fetch(&repo, &branch_name)?;
+
+        let fetch_head = repo.find_reference("FETCH_HEAD")?;
+        let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)?;
+        let (analysis, _) = repo.merge_analysis(&[&fetch_commit])?;
+        if analysis.is_up_to_date() {
+            Ok(())
+        } else if analysis.is_fast_forward() {
+            let refname = format!("refs/heads/{}", branch_name);
+            let mut reference = repo.find_reference(&refname)?;
+            reference.set_target(fetch_commit.id(), "Fast-forward")?;
+            repo.set_head(&refname)?;
+            repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+            Ok(())
+        } else {
+            Err(Error::from(git2::Error::from_str("fast-forward only!")))
+        }
     }
 }
 
@@ -34,7 +300,11 @@ impl Git2Index {
 /// This is inspired by [the way Cargo handles this][cargo-impl].
 ///
 /// [cargo-impl]: https://github.com/rust-lang/cargo/blob/94bf4781d0bbd266abe966c6fe1512bb1725d368/src/cargo/sources/git/utils.rs#L437
-fn with_credentials<F, T>(repo: &git2::Repository, mut f: F) -> Result<T, git2::Error>
+fn with_credentials<F, T>(
+    repo: &git2::Repository,
+    ssh_key: Option<&SshKey>,
+    mut f: F,
+) -> Result<T, git2::Error>
 where
     F: FnMut(&mut git2::Credentials) -> Result<T, git2::Error>,
 {
@@ -52,7 +322,12 @@ where
         if allowed.contains(git2::CredentialType::SSH_KEY) && !tried_sshkey {
             tried_sshkey = true;
             let username = username.unwrap();
-            return git2::Cred::ssh_key_from_agent(username);
+            return match ssh_key {
+                Some(key) => {
+                    git2::Cred::ssh_key(username, None, &key.path, key.passphrase.as_deref())
+                }
+                None => git2::Cred::ssh_key_from_agent(username),
+            };
         }
 
         if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) && !tried_cred_helper {
@@ -65,10 +340,23 @@ where
             return git2::Cred::default();
         }
 
-        Err(git2::Error::from_str("no authentication methods succeeded"))
+        Err(git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Ssh,
+            "no authentication methods succeeded",
+        ))
     })
 }
 
+/// Converts authentication failures into [`Error::AuthenticationError`], passing other errors through.
+fn authentication_error(err: git2::Error) -> Error {
+    if err.code() == git2::ErrorCode::Auth {
+        Error::AuthenticationError(err)
+    } else {
+        Error::Git2Error(err)
+    }
+}
+
 impl Indexer for Git2Index {
     fn url(&self) -> Result<String, Error> {
         let repo = self.repo.lock().unwrap();
@@ -76,41 +364,30 @@ impl Indexer for Git2Index {
         Ok(remote.url().map_or_else(String::default, String::from))
     }
 
+    fn configuration(&self) -> Result<ConfigFile, Error> {
+        self.tree.configuration()
+    }
+
     fn refresh(&self) -> Result<(), Error> {
-        let repo = self.repo.lock().unwrap();
-        let mut remote = repo.find_remote("origin")?;
-        let branch = repo
-            .branches(Some(git2::BranchType::Local))?
-            .flatten()
-            .map(|(branch, _)| branch)
-            .find(|branch| branch.is_head())
-            .ok_or_else(|| git2::Error::from_str("detached HEAD not supported"))?;
-        let branch_name = branch.name()?.expect("branch name is invalid UTF-8");
+        self.refreshes.run(false, || {
+            let result = self.pull();
+            self.tree.invalidate();
+            result
+        })
+    }
 
-        with_credentials(&repo, |cred_callback| {
-            let mut opts = git2::FetchOptions::new();
-            let mut callbacks = git2::RemoteCallbacks::new();
-            callbacks.credentials(cred_callback);
-            opts.remote_callbacks(callbacks);
-            remote.fetch(&[branch_name], Some(&mut opts), None)?;
-            Ok(())
-        })?;
+    fn force_refresh(&self) -> Result<(), Error> {
+        self.refreshes.run(true, || {
+            let result = self.pull();
+            self.tree.invalidate();
+            result
+        })
+    }
 
-        let fetch_head = repo.find_reference("FETCH_HEAD")?;
-        let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)?;
-        let (analysis, _) = repo.merge_analysis(&[&fetch_commit])?;
-        if analysis.is_up_to_date() {
-            Ok(())
-        } else if analysis.is_fast_forward() {
-            let refname = format!("refs/heads/{}", branch_name);
-            let mut reference = repo.find_reference(&refname)?;
-            reference.set_target(fetch_commit.id(), "Fast-forward")?;
-            repo.set_head(&refname)?;
-            repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
-            Ok(())
-        } else {
-            Err(Error::from(git2::Error::from_str("fast-forward only!")))
-        }
+    fn check_health(&self) -> Result<(), Error> {
+        let repo = self.repo.lock().unwrap();
+        repo.head()?.peel_to_commit()?;
+        Ok(())
     }
 
     fn commit_and_push(&self, msg: &str) -> Result<(), Error> {
@@ -120,7 +397,7 @@ impl Indexer for Git2Index {
             index.add_all(&["."], git2::IndexAddOption::DEFAULT, None)?;
             index.write_tree()?
         };
-        let signature = repo.signature()?;
+        let signature = git2::Signature::now(&self.identity.name, &self.identity.email)?;
         let tree = repo.find_tree(oid)?;
         let parent = {
             let head = repo.head()?;
@@ -129,10 +406,34 @@ impl Indexer for Git2Index {
 
         repo.commit(Some("HEAD"), &signature, &signature, msg, &tree, &[&parent])?;
 
-        let mut remote = repo.find_remote("origin")?;
-        remote.push::<&'static str>(&[], None)?;
+        let fetched_before = repo.refname_to_id("FETCH_HEAD").ok();
+        let Err(error) = self.push_with_retries(&repo) else {
+            return Ok(());
+        };
 
-        Ok(())
+        //? Drop our (unpushed) commit along with its changes, so that the local branch does not diverge from the remote.
+        //? If the remote got fetched in the meantime (to replay our commit), the working tree already holds
+        //? the remote's files, so we move to the remote's head rather than back to our (stale) parent.
+        //? A failure to do so is not reported, in favor of the push's failure.
+        let fetched = repo.refname_to_id("FETCH_HEAD").ok();
+        let target = match fetched {
+            Some(fetched) if fetched_before != Some(fetched) => fetched,
+            _ => parent.id(),
+        };
+        let _ = repo
+            .find_object(target, None)
+            .and_then(|target| repo.reset(&target, git2::ResetType::Hard, None));
+        self.tree.invalidate();
+
+        Err(error)
+    }
+
+    fn has_uncommitted_changes(&self) -> Result<bool, Error> {
+        let repo = self.repo.lock().unwrap();
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = repo.statuses(Some(&mut opts))?;
+        Ok(!statuses.is_empty())
     }
 
     fn match_record(&self, name: &str, req: VersionReq) -> Result<CrateVersion, Error> {
@@ -143,6 +444,13 @@ impl Indexer for Git2Index {
         self.tree.all_records(name)
     }
 
+    fn for_each_record<F>(&self, name: &str, func: F) -> Result<(), Error>
+    where
+        F: FnMut(&CrateVersion),
+    {
+        self.tree.for_each_record(name, func)
+    }
+
     fn latest_record(&self, name: &str) -> Result<CrateVersion, Error> {
         self.tree.latest_record(name)
     }
@@ -151,6 +459,10 @@ impl Indexer for Git2Index {
         self.tree.add_record(record)
     }
 
+    fn remove_record(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.tree.remove_record(name, version)
+    }
+
     fn alter_record<F>(&self, name: &str, version: Version, func: F) -> Result<(), Error>
     where
         F: FnOnce(&mut CrateVersion),
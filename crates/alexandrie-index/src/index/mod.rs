@@ -5,3 +5,26 @@ pub mod cli;
 /// [libgit2]: https://libgit2.org
 #[cfg(feature = "git2")]
 pub mod git2;
+
+/// The default name used as the author and committer of index commits.
+pub const DEFAULT_COMMIT_NAME: &str = "Alexandrie";
+/// The default email used as the author and committer of index commits.
+pub const DEFAULT_COMMIT_EMAIL: &str = "noreply@alexandrie.invalid";
+
+/// The identity used as the author and committer of index commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitIdentity {
+    /// The name of the author/committer.
+    pub name: String,
+    /// The email of the author/committer.
+    pub email: String,
+}
+
+impl Default for CommitIdentity {
+    fn default() -> CommitIdentity {
+        CommitIdentity {
+            name: String::from(DEFAULT_COMMIT_NAME),
+            email: String::from(DEFAULT_COMMIT_EMAIL),
+        }
+    }
+}
@@ -3,12 +3,14 @@ use semver::{Version, VersionReq};
 pub mod config;
 pub mod error;
 
+mod cache;
+mod debounce;
 mod index;
 mod models;
 mod tree;
 
 pub use index::*;
-pub use models::{CrateDependency, CrateDependencyKind, CrateVersion};
+pub use models::{ConfigFile, CrateDependency, CrateDependencyKind, CrateVersion, RecordSchema};
 
 use crate::cli::CommandLineIndex;
 use crate::error::Error;
@@ -29,21 +31,46 @@ pub enum Index {
 }
 
 /// The required trait that any crate index management type must implement.
+///
+/// # Threading contract
+///
+/// Every method of this trait is blocking (it performs filesystem, `git` or network IO),
+/// and implementations are expected to be safe to call concurrently from multiple threads.  
+/// When used from an async runtime, these methods must therefore be called from a thread
+/// dedicated to blocking work (like with `tokio::task::spawn_blocking`), rather than directly
+/// from an async task, to avoid stalling the runtime's worker threads.
 pub trait Indexer {
     /// Gives back the URL of the managed crate index.
     fn url(&self) -> Result<String, Error>;
+    /// Gives back the contents of the index's configuration file (`config.json`).
+    fn configuration(&self) -> Result<ConfigFile, Error>;
     /// Refreshes the managed crate index (in case another instance made modification to it).
+    ///
+    /// Refreshes are debounced: concurrent callers share the refresh in flight, and callers arriving shortly
+    /// after a successful refresh reuse it (use `force_refresh` when an immediate update is required).
     fn refresh(&self) -> Result<(), Error>;
+    /// Refreshes the managed crate index right away, bypassing the refresh debouncing.
+    fn force_refresh(&self) -> Result<(), Error>;
+    /// Checks that the managed crate index is present and usable.
+    fn check_health(&self) -> Result<(), Error>;
     /// Retrieves all the version records of a crate.
     fn all_records(&self, name: &str) -> Result<Vec<CrateVersion>, Error>;
+    /// Visits the version records of a crate one by one (without collecting them all, unlike `all_records`).
+    fn for_each_record<F>(&self, name: &str, func: F) -> Result<(), Error>
+    where
+        F: FnMut(&CrateVersion);
     /// Retrieves the latest version record of a crate.
     fn latest_record(&self, name: &str) -> Result<CrateVersion, Error>;
     /// Retrieves the latest crate version record that matches the given name and version requirement.
     fn match_record(&self, name: &str, req: VersionReq) -> Result<CrateVersion, Error>;
     /// Commits and pushes changes upstream.
     fn commit_and_push(&self, msg: &str) -> Result<(), Error>;
+    /// Checks whether the index has local changes which have not been committed yet.
+    fn has_uncommitted_changes(&self) -> Result<bool, Error>;
     /// Adds a new crate record into the index.
     fn add_record(&self, record: CrateVersion) -> Result<(), Error>;
+    /// Removes a crate version record from the index (used to undo a failed publication).
+    fn remove_record(&self, name: &str, version: Version) -> Result<(), Error>;
     /// Alters an index's crate version record with the passed-in function.
     fn alter_record<F>(&self, name: &str, version: Version, func: F) -> Result<(), Error>
     where
@@ -67,6 +94,14 @@ impl Indexer for Index {
         }
     }
 
+    fn configuration(&self) -> Result<ConfigFile, Error> {
+        match self {
+            Index::CommandLine(idx) => idx.configuration(),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.configuration(),
+        }
+    }
+
     fn refresh(&self) -> Result<(), Error> {
         match self {
             Index::CommandLine(idx) => idx.refresh(),
@@ -75,6 +110,22 @@ impl Indexer for Index {
         }
     }
 
+    fn force_refresh(&self) -> Result<(), Error> {
+        match self {
+            Index::CommandLine(idx) => idx.force_refresh(),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.force_refresh(),
+        }
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        match self {
+            Index::CommandLine(idx) => idx.check_health(),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.check_health(),
+        }
+    }
+
     fn commit_and_push(&self, msg: &str) -> Result<(), Error> {
         match self {
             Index::CommandLine(idx) => idx.commit_and_push(msg),
@@ -83,6 +134,14 @@ impl Indexer for Index {
         }
     }
 
+    fn has_uncommitted_changes(&self) -> Result<bool, Error> {
+        match self {
+            Index::CommandLine(idx) => idx.has_uncommitted_changes(),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.has_uncommitted_changes(),
+        }
+    }
+
     fn all_records(&self, name: &str) -> Result<Vec<CrateVersion>, Error> {
         match self {
             Index::CommandLine(idx) => idx.all_records(name),
@@ -91,6 +150,17 @@ impl Indexer for Index {
         }
     }
 
+    fn for_each_record<F>(&self, name: &str, func: F) -> Result<(), Error>
+    where
+        F: FnMut(&CrateVersion),
+    {
+        match self {
+            Index::CommandLine(idx) => idx.for_each_record(name, func),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.for_each_record(name, func),
+        }
+    }
+
     fn latest_record(&self, name: &str) -> Result<CrateVersion, Error> {
         match self {
             Index::CommandLine(idx) => idx.latest_record(name),
@@ -115,6 +185,14 @@ impl Indexer for Index {
         }
     }
 
+    fn remove_record(&self, name: &str, version: Version) -> Result<(), Error> {
+        match self {
+            Index::CommandLine(idx) => idx.remove_record(name, version),
+            #[cfg(feature = "git2")]
+            Index::Git2(idx) => idx.remove_record(name, version),
+        }
+    }
+
     fn alter_record<F>(&self, name: &str, version: Version, func: F) -> Result<(), Error>
     where
         F: FnOnce(&mut CrateVersion),
@@ -3,6 +3,72 @@ use std::collections::HashMap;
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
+use crate::error::{Error, IndexError};
+
+/// Represents the index's configuration file (`config.json`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigFile {
+    /// The URL template from which crate archives are downloaded.
+    pub dl: String,
+
+    /// The base URL of the registry's programmatic API.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub api: Option<String>,
+
+    /// The URLs of the other registries that crates of this registry are allowed to depend on.
+    #[serde(
+        rename = "allowed-registries",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    pub allowed_registries: Vec<String>,
+
+    /// Whether authentication is required for every request to the registry.
+    #[serde(rename = "auth-required", skip_serializing_if = "is_false", default)]
+    pub auth_required: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// The schema version of the index records (the lines of the crate files).
+///
+/// Records are read whatever their schema version is (unknown fields are ignored, for forward compatibility),
+/// but are written at the configured schema version, so that older Cargo versions can still parse them.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum RecordSchema {
+    /// The original format (without the `features2` and `v` fields).
+    V1,
+    /// The format supporting features using the newer syntaxes (`dep:` or `?` entries), in `features2`.
+    #[default]
+    V2,
+}
+
+impl TryFrom<u32> for RecordSchema {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(RecordSchema::V1),
+            2 => Ok(RecordSchema::V2),
+            _ => Err(format!("unsupported index record schema version: {value}")),
+        }
+    }
+}
+
+impl From<RecordSchema> for u32 {
+    fn from(schema: RecordSchema) -> u32 {
+        match schema {
+            RecordSchema::V1 => 1,
+            RecordSchema::V2 => 2,
+        }
+    }
+}
+
 /// Represents a crate version record.
 ///
 /// This is what's stored in the crate index.  
@@ -26,12 +92,94 @@ pub struct CrateVersion {
     #[serde(default)]
     pub features: HashMap<String, Vec<String>>,
 
+    /// The available features using the newer syntaxes (`dep:` or `?` entries) and what they enable.
+    ///
+    /// These are kept apart from `features` so that older Cargo versions can still parse the records.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub features2: Option<HashMap<String, Vec<String>>>,
+
     /// Is the crate yanked.
     pub yanked: Option<bool>,
 
     /// Related links about the crate.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub links: Option<String>,
+
+    /// The version of the record's format (`2` when `features2` is used).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub v: Option<u32>,
+}
+
+impl CrateVersion {
+    /// Returns whether a feature entry uses the newer syntaxes (`dep:` or `?` entries),
+    /// which must be stored in `features2` instead of `features`.
+    pub fn is_features2_entry(entry: &str) -> bool {
+        entry.starts_with("dep:") || entry.contains("?/")
+    }
+
+    /// Splits the given features between `features` and `features2`, and sets the record's format version accordingly.
+    pub fn set_features(&mut self, features: HashMap<String, Vec<String>>) {
+        let (features2, features): (HashMap<_, _>, HashMap<_, _>) =
+            features.into_iter().partition(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|entry| Self::is_features2_entry(entry.as_str()))
+            });
+
+        self.features = features;
+        if features2.is_empty() {
+            self.features2 = None;
+            self.v = None;
+        } else {
+            self.features2 = Some(features2);
+            self.v = Some(2);
+        }
+    }
+
+    /// Returns the lowest schema version able to represent this record.
+    pub fn schema(&self) -> RecordSchema {
+        if self.features2.is_some() || self.v.map_or(false, |v| v >= 2) {
+            RecordSchema::V2
+        } else {
+            RecordSchema::V1
+        }
+    }
+
+    /// Serializes this record as an index line, at the given schema version.
+    ///
+    /// Optional fields are omitted when absent (to keep lines minimal), and so is the `v` field
+    /// when the record does not need a newer schema version than the original one.  
+    /// Fails if the record cannot be represented at the given schema version.
+    pub fn to_line(&self, schema: RecordSchema) -> Result<String, Error> {
+        let required = self.schema();
+        if required > schema {
+            return Err(Error::from(IndexError::UnsupportedSchema {
+                name: self.name.clone(),
+                version: self.vers.clone(),
+                schema: schema.into(),
+            }));
+        }
+
+        match required {
+            RecordSchema::V1 if self.v.is_some() => {
+                let record = CrateVersion {
+                    v: None,
+                    ..self.clone()
+                };
+                Ok(json::to_string(&record)?)
+            }
+            _ => Ok(json::to_string(self)?),
+        }
+    }
+
+    /// Returns all the available features of the crate (from both `features` and `features2`).
+    pub fn all_features(&self) -> HashMap<String, Vec<String>> {
+        let mut features = self.features.clone();
+        if let Some(features2) = self.features2.as_ref() {
+            features.extend(features2.clone());
+        }
+        features
+    }
 }
 
 /// Represents a crate dependency.
@@ -89,3 +237,77 @@ pub enum CrateDependencyKind {
     /// A developement dependency.
     Dev,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> CrateVersion {
+        CrateVersion {
+            name: String::from("example"),
+            vers: Version::new(1, 2, 3),
+            deps: vec![CrateDependency {
+                name: String::from("serde"),
+                req: VersionReq::parse("^1.0").unwrap(),
+                features: vec![String::from("derive")],
+                optional: true,
+                default_features: true,
+                target: None,
+                kind: CrateDependencyKind::Normal,
+                registry: None,
+                package: None,
+            }],
+            cksum: "0".repeat(64),
+            features: HashMap::new(),
+            features2: None,
+            yanked: Some(false),
+            links: None,
+            v: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_at_current_schema() {
+        let mut record = record();
+        record.links = Some(String::from("git2"));
+        record.set_features(HashMap::from([
+            (String::from("default"), vec![String::from("std")]),
+            (String::from("std"), Vec::new()),
+            (String::from("serde"), vec![String::from("dep:serde")]),
+        ]));
+
+        let line = record.to_line(RecordSchema::default()).unwrap();
+        let parsed: CrateVersion = json::from_str(line.as_str()).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn omits_absent_optional_fields() {
+        let line = record().to_line(RecordSchema::V1).unwrap();
+        let value: json::Value = json::from_str(line.as_str()).unwrap();
+        for field in ["features2", "links", "v"] {
+            assert!(value.get(field).is_none(), "`{field}` should be omitted");
+        }
+    }
+
+    #[test]
+    fn rejects_records_needing_a_newer_schema() {
+        let mut record = record();
+        record.set_features(HashMap::from([(
+            String::from("serde"),
+            vec![String::from("dep:serde")],
+        )]));
+
+        assert!(record.to_line(RecordSchema::V1).is_err());
+    }
+
+    #[test]
+    fn tolerates_unknown_fields() {
+        let mut value = json::to_value(record()).unwrap();
+        value["yank_reason"] = json::Value::from("superseded");
+        value["rust_version"] = json::Value::from("1.60");
+
+        let parsed: CrateVersion = json::from_value(value).unwrap();
+        assert_eq!(parsed, record());
+    }
+}
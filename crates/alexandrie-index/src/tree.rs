@@ -1,73 +1,234 @@
 use std::fs;
 use std::io;
 use std::io::{BufRead, Write};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use semver::{Version, VersionReq};
 
+use crate::cache::{IndexCache, DEFAULT_CACHE_CAPACITY};
 use crate::error::IndexError;
-use crate::models::CrateVersion;
+use crate::models::{ConfigFile, CrateVersion, RecordSchema};
 use crate::Error;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Tree {
     path: PathBuf,
+    cache: Option<Arc<IndexCache>>,
+    schema: RecordSchema,
+    prefer_stable: bool,
+}
+
+impl PartialEq for Tree {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
 }
 
 impl Tree {
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        let mut tree = Self {
+            path,
+            cache: None,
+            schema: RecordSchema::default(),
+            prefer_stable: true,
+        };
+        tree.set_cache_capacity(DEFAULT_CACHE_CAPACITY);
+        tree
+    }
+
+    /// Sets how many crates can have their records cached in memory (`0` disables caching).
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache =
+            NonZeroUsize::new(capacity).map(|capacity| Arc::new(IndexCache::new(capacity)));
+    }
+
+    /// Sets the schema version at which new records are written.
+    pub fn set_schema(&mut self, schema: RecordSchema) {
+        self.schema = schema;
+    }
+
+    /// Sets whether the latest record of a crate is its latest stable version (if it has any), rather than its highest version.
+    pub fn set_prefer_stable(&mut self, prefer_stable: bool) {
+        self.prefer_stable = prefer_stable;
+    }
+
+    /// Serializes a record being rewritten (along with the other records of its crate).
+    ///
+    /// Existing records are kept at their own schema version if it is newer than the configured one,
+    /// so that lowering the configured schema version does not prevent altering the crate's records.
+    fn rewritten_line(&self, record: &CrateVersion) -> Result<String, Error> {
+        record.to_line(self.schema.max(record.schema()))
+    }
+
+    /// Invalidates everything that has been cached (to be called after a refresh from the remote).
+    pub fn invalidate(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.clear();
+        }
+    }
+
+    fn invalidate_record(&self, name: &str) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.invalidate(name);
+        }
+    }
+
+    /// Reads (or gets from the cache) all the records of a crate.
+    fn records(&self, name: &str) -> Result<Arc<Vec<CrateVersion>>, Error> {
+        match self.cache.as_ref() {
+            Some(cache) => cache.records(name, || self.read_records(name)),
+            None => self.read_records(name).map(Arc::new),
+        }
+    }
+
+    pub fn configuration(&self) -> Result<ConfigFile, Error> {
+        let read = || {
+            let contents = fs::read(self.path.join("config.json"))?;
+            Ok(json::from_slice(&contents)?)
+        };
+
+        match self.cache.as_ref() {
+            Some(cache) => cache.configuration(read),
+            None => read(),
+        }
     }
 
     fn compute_record_path(&self, name: &str) -> PathBuf {
+        //? Like in Cargo's index layout, the paths are lowercased (so that lookups are case-insensitive).
+        self.layout_path(name.to_ascii_lowercase().as_str())
+    }
+
+    /// Computes the path of a crate's file, using the name as-is.
+    fn layout_path(&self, name: &str) -> PathBuf {
         match name.len() {
-            1 => self.path.join("1").join(&name),
-            2 => self.path.join("2").join(&name),
-            3 => self.path.join("3").join(&name[..1]).join(&name),
-            _ => self.path.join(&name[0..2]).join(&name[2..4]).join(&name),
+            1 => self.path.join("1").join(name),
+            2 => self.path.join("2").join(name),
+            3 => self.path.join("3").join(&name[..1]).join(name),
+            _ => self.path.join(&name[0..2]).join(&name[2..4]).join(name),
         }
     }
 
-    pub fn match_record(&self, name: &str, req: VersionReq) -> Result<CrateVersion, Error> {
+    /// Finds the path of the file holding the records of a crate.
+    ///
+    /// Indices written before paths got lowercased may still hold crates at their mixed-case paths,
+    /// which are used if the crate has no file at its lowercased path (new crates always get lowercased paths).
+    fn resolve_record_path(&self, name: &str) -> PathBuf {
         let path = self.compute_record_path(name);
-        let file = fs::File::open(path).map_err(|err| match err.kind() {
+        if path.exists() {
+            return path;
+        }
+        let legacy_path = self.layout_path(name);
+        if legacy_path != path && legacy_path.exists() {
+            return legacy_path;
+        }
+        path
+    }
+
+    /// Finds the highest record of a crate matching the given requirement.
+    ///
+    /// As per semver's rules, pre-release versions only match requirements which explicitly mention a pre-release
+    /// of the same `major.minor.patch` version (like `^1.0.0-beta` matching `1.0.0-beta.2`, but not `1.1.0-beta`).
+    pub fn match_record(&self, name: &str, req: VersionReq) -> Result<CrateVersion, Error> {
+        let found = self.max_record(name, |krate| req.matches(&krate.vers))?;
+        Ok(found.ok_or_else(|| IndexError::CrateNotFound {
+            name: String::from(name),
+        })?)
+    }
+
+    /// Opens the file holding the records of a crate.
+    fn open_records(&self, name: &str) -> Result<fs::File, Error> {
+        let path = self.resolve_record_path(name);
+        fs::File::open(path).map_err(|err| match err.kind() {
             io::ErrorKind::NotFound => Error::from(IndexError::CrateNotFound {
                 name: String::from(name),
             }),
             _ => Error::from(err),
-        })?;
+        })
+    }
 
-        let found = io::BufReader::new(file)
-            .lines()
-            .filter_map(|line| json::from_str::<CrateVersion>(line.ok()?.as_str()).ok())
-            .filter(|krate| req.matches(&krate.vers))
-            .max_by(|k1, k2| k1.vers.cmp(&k2.vers));
+    fn read_records(&self, name: &str) -> Result<Vec<CrateVersion>, Error> {
+        let file = self.open_records(name)?;
+        let mut records = Vec::new();
+        for line in io::BufReader::new(file).lines() {
+            records.extend(parse_record(name, line?.as_str()));
+        }
+        Ok(records)
+    }
 
-        Ok(found.ok_or_else(|| IndexError::CrateNotFound {
-            name: String::from(name),
-        })?)
+    /// Visits the records of a crate one by one, without collecting them.
+    ///
+    /// When caching is enabled, the records are visited from the cache (reading them into it if needed),
+    /// otherwise the crate's file is read line by line.
+    pub fn for_each_record<F>(&self, name: &str, mut func: F) -> Result<(), Error>
+    where
+        F: FnMut(&CrateVersion),
+    {
+        if self.cache.is_some() {
+            let records = self.records(name)?;
+            records.iter().for_each(func);
+            return Ok(());
+        }
+
+        let file = self.open_records(name)?;
+        for line in io::BufReader::new(file).lines() {
+            if let Some(record) = parse_record(name, line?.as_str()) {
+                func(&record);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the highest version record of a crate among the ones matching the predicate
+    /// (only cloning the records which are higher than the ones visited before them).
+    fn max_record<P>(&self, name: &str, predicate: P) -> Result<Option<CrateVersion>, Error>
+    where
+        P: Fn(&CrateVersion) -> bool,
+    {
+        let mut found: Option<CrateVersion> = None;
+        self.for_each_record(name, |record| {
+            let is_higher = found
+                .as_ref()
+                .map_or(true, |found| record.vers > found.vers);
+            if is_higher && predicate(record) {
+                found = Some(record.clone());
+            }
+        })?;
+        Ok(found)
     }
 
     pub fn all_records(&self, name: &str) -> Result<Vec<CrateVersion>, Error> {
-        let path = self.compute_record_path(name);
-        let reader = io::BufReader::new(fs::File::open(path)?);
-        reader
-            .lines()
-            .map(|line| Ok(json::from_str::<CrateVersion>(line?.as_str())?))
-            .collect()
+        let records = self.records(name)?;
+        Ok(records.as_ref().clone())
     }
 
+    /// Finds the latest record of a crate.
+    ///
+    /// Pre-release versions (like `1.0.0-beta.1`) are skipped if the crate has any stable version (unless disabled),
+    /// since the latest version is the one users get by default.
     pub fn latest_record(&self, name: &str) -> Result<CrateVersion, Error> {
-        let records = self.all_records(name)?;
-        Ok(records
-            .into_iter()
-            .max_by(|k1, k2| k1.vers.cmp(&k2.vers))
-            .expect("at least one version should exist"))
+        let found = match self.prefer_stable {
+            true => self.max_record(name, |record| record.vers.pre.is_empty())?,
+            false => None,
+        };
+        let found = match found {
+            Some(found) => Some(found),
+            None => self.max_record(name, |_| true)?,
+        };
+        Ok(found.ok_or_else(|| IndexError::CrateNotFound {
+            name: String::from(name),
+        })?)
     }
 
     pub fn add_record(&self, record: CrateVersion) -> Result<(), Error> {
-        let path = self.compute_record_path(record.name.as_str());
+        let path = self.resolve_record_path(record.name.as_str());
+        let result = self.write_record(path, &record);
+        self.invalidate_record(record.name.as_str());
+        result
+    }
 
+    fn write_record(&self, path: PathBuf, record: &CrateVersion) -> Result<(), Error> {
         if !path.exists() {
             let parent = path.parent().unwrap();
             fs::create_dir_all(parent)?;
@@ -78,18 +239,47 @@ impl Tree {
             .append(true)
             .create(true)
             .open(path)?;
-        json::to_writer(&mut file, &record)?;
-        writeln!(file)?;
+        writeln!(file, "{0}", record.to_line(self.schema)?)?;
         file.flush()?;
 
         Ok(())
     }
 
+    pub fn remove_record(&self, name: &str, version: Version) -> Result<(), Error> {
+        let path = self.resolve_record_path(name);
+        let file = fs::File::open(path.as_path()).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => Error::from(IndexError::CrateNotFound {
+                name: String::from(name),
+            }),
+            _ => Error::from(err),
+        })?;
+        let krates: Vec<CrateVersion> = io::BufReader::new(file)
+            .lines()
+            .map(|line| Ok(json::from_str::<CrateVersion>(line?.as_str())?))
+            .collect::<Result<_, Error>>()?;
+        let lines = krates
+            .into_iter()
+            .filter(|krate| krate.vers != version)
+            .map(|krate| self.rewritten_line(&krate))
+            .collect::<Result<Vec<String>, _>>()?;
+
+        //? The crate's file is removed altogether once it has no more records.
+        let result = if lines.is_empty() {
+            fs::remove_file(path.as_path())
+        } else {
+            fs::write(path.as_path(), lines.join("\n") + "\n")
+        };
+        self.invalidate_record(name);
+        result?;
+
+        Ok(())
+    }
+
     pub fn alter_record<F>(&self, name: &str, version: Version, func: F) -> Result<(), Error>
     where
         F: FnOnce(&mut CrateVersion),
     {
-        let path = self.compute_record_path(name);
+        let path = self.resolve_record_path(name);
         let file = fs::File::open(path.as_path()).map_err(|err| match err.kind() {
             io::ErrorKind::NotFound => Error::from(IndexError::CrateNotFound {
                 name: String::from(name),
@@ -117,10 +307,28 @@ impl Tree {
 
         let lines = krates
             .into_iter()
-            .map(|krate| json::to_string(&krate))
+            .map(|krate| self.rewritten_line(&krate))
             .collect::<Result<Vec<String>, _>>()?;
-        fs::write(path.as_path(), lines.join("\n") + "\n")?;
+        let result = fs::write(path.as_path(), lines.join("\n") + "\n");
+        self.invalidate_record(name);
+        result?;
 
         Ok(())
     }
 }
+
+/// Parses a line of a crate's file, skipping (with a warning) the malformed ones.
+///
+/// This is only used for reads: rewriting a crate's file still fails on malformed lines, so that they are not lost.
+fn parse_record(name: &str, line: &str) -> Option<CrateVersion> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    match json::from_str::<CrateVersion>(line) {
+        Ok(record) => Some(record),
+        Err(error) => {
+            tracing::warn!("skipping a malformed record of '{name}' in the crate index: {error}");
+            None
+        }
+    }
+}
@@ -1,9 +1,12 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
+use ammonia::Builder;
 use serde::{Deserialize, Serialize};
 use syntect::dumps;
 use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
 
 /// The syntax-highlighting themes configuration struct.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -41,6 +44,164 @@ pub enum SyntectSyntaxesConfig {
     },
 }
 
+/// The classes used by alert callouts (see [`crate::ALERT_KINDS`]).
+const ALERT_CLASSES: &[&str] = &[
+    "markdown-alert",
+    "markdown-alert-note",
+    "markdown-alert-tip",
+    "markdown-alert-important",
+    "markdown-alert-warning",
+    "markdown-alert-caution",
+];
+
+/// The heading tags, whose ids are generated when rendering READMEs (see [`crate::heading_id`]).
+const HEADING_TAGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// The HTML sanitization policy configuration struct (applied to rendered READMEs).
+///
+/// Every option alters the default policy of [`ammonia`], which is used when none are specified.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SanitizationConfig {
+    /// Additional tags to allow (like `details` or `summary`).
+    #[serde(default)]
+    pub allowed_tags: Vec<String>,
+    /// Tags to disallow (their contents are kept, unless they are also in `removed_content_tags`).
+    #[serde(default)]
+    pub removed_tags: Vec<String>,
+    /// Tags to remove along with their contents (like `script` or `style`).
+    #[serde(default)]
+    pub removed_content_tags: Vec<String>,
+    /// Additional attributes to allow on every tag.
+    #[serde(default)]
+    pub allowed_attributes: Vec<String>,
+    /// Attributes to disallow on every tag.
+    #[serde(default)]
+    pub removed_attributes: Vec<String>,
+    /// Additional attributes to allow on specific tags (like `input = ["type", "checked", "disabled"]`).
+    #[serde(default)]
+    pub allowed_tag_attributes: HashMap<String, Vec<String>>,
+    /// The URL schemes allowed in links (replaces the default list, if specified).
+    #[serde(default)]
+    pub url_schemes: Option<Vec<String>>,
+}
+
+impl SanitizationConfig {
+    /// Constructs the [`ammonia`] sanitizer corresponding to this policy.
+    pub fn builder(&self) -> Builder<'_> {
+        let mut builder = Builder::default();
+        builder
+            .add_tags(self.allowed_tags.iter().map(String::as_str))
+            .rm_tags(self.removed_tags.iter().map(String::as_str))
+            .add_clean_content_tags(self.removed_content_tags.iter().map(String::as_str))
+            .add_generic_attributes(self.allowed_attributes.iter().map(String::as_str))
+            .rm_generic_attributes(self.removed_attributes.iter().map(String::as_str));
+        for (tag, attributes) in self.allowed_tag_attributes.iter() {
+            builder.add_tag_attributes(tag.as_str(), attributes.iter().map(String::as_str));
+        }
+        if let Some(url_schemes) = self.url_schemes.as_ref() {
+            let url_schemes: HashSet<&str> = url_schemes.iter().map(String::as_str).collect();
+            builder.url_schemes(url_schemes);
+        }
+
+        //? Allow the classes of alert callouts (unless the policy already allows any class on these tags).
+        let allows_class = |tag: &str| {
+            self.allowed_attributes.iter().any(|it| it == "class")
+                || self
+                    .allowed_tag_attributes
+                    .get(tag)
+                    .map_or(false, |attributes| {
+                        attributes.iter().any(|it| it == "class")
+                    })
+        };
+        if !allows_class("div") {
+            let alert_classes = ALERT_CLASSES.iter().copied();
+            builder.add_allowed_classes("div", alert_classes);
+        }
+        if !allows_class("p") {
+            builder.add_allowed_classes("p", ["markdown-alert-title"]);
+        }
+
+        //? Keep the ids of headings, which their permalinks point to (unless the policy removes ids).
+        if !self.removed_attributes.iter().any(|it| it == "id") {
+            for tag in HEADING_TAGS.iter().copied() {
+                builder.add_tag_attributes(tag, ["id"]);
+            }
+        }
+
+        builder
+    }
+}
+
+/// The syntax-highlighting limits configuration struct (guarding README renders against pathological code blocks).
+///
+/// Code blocks exceeding these limits are rendered as plain (escaped) text, without any highlighting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighlightingLimitsConfig {
+    /// The maximum number of lines of a highlighted code block.
+    #[serde(default = "HighlightingLimitsConfig::default_max_block_lines")]
+    pub max_block_lines: usize,
+    /// The maximum size of a highlighted code block (in bytes).
+    #[serde(default = "HighlightingLimitsConfig::default_max_block_bytes")]
+    pub max_block_bytes: usize,
+    /// The maximum time spent highlighting the code blocks of a README (in milliseconds),
+    /// the remaining code blocks are rendered as plain text once it is exceeded.
+    #[serde(default = "HighlightingLimitsConfig::default_timeout")]
+    pub timeout: u64,
+}
+
+impl HighlightingLimitsConfig {
+    fn default_max_block_lines() -> usize {
+        2_000
+    }
+
+    fn default_max_block_bytes() -> usize {
+        64 * 1024
+    }
+
+    fn default_timeout() -> u64 {
+        500
+    }
+}
+
+impl Default for HighlightingLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_block_lines: Self::default_max_block_lines(),
+            max_block_bytes: Self::default_max_block_bytes(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+/// The README links configuration struct (resolving the relative links of READMEs against the crate's repository).
+///
+/// Only repositories hosted on GitHub or GitLab are supported, as the URLs of their files are predictable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinksConfig {
+    /// Whether to rewrite the relative links (and image sources) of READMEs into absolute URLs to the crate's repository.
+    #[serde(default)]
+    pub rewrite_relative: bool,
+    /// The revision (like a branch name) of the repository that the rewritten links point to,
+    /// when the commit the crate was packaged from is unknown.
+    #[serde(default = "LinksConfig::default_revision")]
+    pub revision: String,
+}
+
+impl LinksConfig {
+    fn default_revision() -> String {
+        String::from("HEAD")
+    }
+}
+
+impl Default for LinksConfig {
+    fn default() -> Self {
+        Self {
+            rewrite_relative: false,
+            revision: Self::default_revision(),
+        }
+    }
+}
+
 /// The complete syntax-highlighting configuration struct.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SyntectConfig {
@@ -48,6 +209,105 @@ pub struct SyntectConfig {
     pub themes: SyntectThemesConfig,
     /// The highlighting syntaxes configuration.
     pub syntaxes: SyntectSyntaxesConfig,
+    /// Additional syntaxes to load (paths to `.sublime-syntax` files, or directories containing some).
+    #[serde(default)]
+    pub extra_syntaxes: Vec<PathBuf>,
+    /// Additional themes to load (paths to `.tmTheme` files, or directories containing some).
+    #[serde(default)]
+    pub extra_themes: Vec<PathBuf>,
+    /// The HTML sanitization policy of rendered READMEs.
+    #[serde(default)]
+    pub sanitization: SanitizationConfig,
+    /// The highlighting limits of README code blocks.
+    #[serde(default)]
+    pub limits: HighlightingLimitsConfig,
+    /// The rewriting of the relative links of READMEs.
+    #[serde(default)]
+    pub links: LinksConfig,
+}
+
+/// Recursively finds the files with the given extension (the path being either a file or a directory).
+fn find_files(path: &Path, extension: &str, found: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        found.push(path.to_path_buf());
+        return;
+    }
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            tracing::warn!("couldn't read '{0}' (skipped): {error}", path.display());
+            return;
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            find_files(&path, extension, found);
+        } else if path.extension().map_or(false, |it| it == extension) {
+            found.push(path);
+        }
+    }
+}
+
+/// Merges the additional syntaxes into the syntax set (invalid ones are skipped with a warning).
+fn load_extra_syntaxes(syntaxes: SyntaxSet, paths: &[PathBuf]) -> SyntaxSet {
+    if paths.is_empty() {
+        return syntaxes;
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        find_files(path, "sublime-syntax", &mut files);
+    }
+
+    let mut builder = syntaxes.into_builder();
+    for file in files {
+        let name = file.file_stem().and_then(|it| it.to_str());
+        let loaded = fs::read_to_string(&file)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                SyntaxDefinition::load_from_str(contents.as_str(), true, name)
+                    .map_err(|error| error.to_string())
+            });
+        match loaded {
+            Ok(syntax) => builder.add(syntax),
+            Err(error) => {
+                tracing::warn!(
+                    "couldn't load syntax '{0}' (skipped): {error}",
+                    file.display()
+                )
+            }
+        }
+    }
+
+    builder.build()
+}
+
+/// Merges the additional themes into the theme set (invalid ones are skipped with a warning).
+fn load_extra_themes(themes: &mut ThemeSet, paths: &[PathBuf]) {
+    let mut files = Vec::new();
+    for path in paths {
+        find_files(path, "tmTheme", &mut files);
+    }
+
+    for file in files {
+        let Some(name) = file.file_stem().and_then(|it| it.to_str()).map(String::from) else {
+            continue;
+        };
+        match ThemeSet::get_theme(&file) {
+            Ok(theme) => {
+                themes.themes.insert(name, theme);
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "couldn't load theme '{0}' (skipped): {error}",
+                    file.display()
+                )
+            }
+        }
+    }
 }
 
 /// The syntax-highlighting state struct, created from [SyntectConfig].
@@ -58,6 +318,12 @@ pub struct SyntectState {
     pub themes: ThemeSet,
     /// The chosen theme's name.
     pub theme_name: String,
+    /// The HTML sanitization policy of rendered READMEs.
+    pub sanitization: SanitizationConfig,
+    /// The highlighting limits of README code blocks.
+    pub limits: HighlightingLimitsConfig,
+    /// The rewriting of the relative links of READMEs.
+    pub links: LinksConfig,
 }
 
 impl From<SyntectConfig> for SyntectState {
@@ -70,7 +336,8 @@ impl From<SyntectConfig> for SyntectState {
                 SyntaxSet::load_from_folder(&path).expect("couldn't load syntaxes from directory")
             }
         };
-        let (themes, theme_name) = match config.themes {
+        let syntaxes = load_extra_syntaxes(syntaxes, &config.extra_syntaxes);
+        let (mut themes, theme_name) = match config.themes {
             SyntectThemesConfig::Dump { path, theme_name } => (
                 dumps::from_dump_file(&path).expect("couldn't load themes' dump"),
                 theme_name,
@@ -80,6 +347,7 @@ impl From<SyntectConfig> for SyntectState {
                 theme_name,
             ),
         };
+        load_extra_themes(&mut themes, &config.extra_themes);
         if !themes.themes.contains_key(theme_name.as_str()) {
             panic!("no theme named '{0}' has been found", theme_name);
         }
@@ -87,6 +355,9 @@ impl From<SyntectConfig> for SyntectState {
             syntaxes,
             themes,
             theme_name,
+            sanitization: config.sanitization,
+            limits: config.limits,
+            links: config.links,
         }
     }
 }
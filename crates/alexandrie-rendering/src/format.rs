@@ -0,0 +1,147 @@
+use std::path::Path;
+
+/// The markup format of a README.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadmeFormat {
+    /// CommonMark (along with GitHub's extensions), like `README.md`.
+    Markdown,
+    /// AsciiDoc, like `README.adoc`.
+    AsciiDoc,
+    /// reStructuredText, like `README.rst`.
+    ReStructuredText,
+    /// Any other format, shown as plain text (like `README.txt` or `README`).
+    PlainText,
+}
+
+impl ReadmeFormat {
+    /// Detects the format of a README from its file name (or path), using its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> ReadmeFormat {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("md" | "markdown" | "mdown" | "mkd") => ReadmeFormat::Markdown,
+            Some("adoc" | "asciidoc" | "asc") => ReadmeFormat::AsciiDoc,
+            Some("rst" | "rest") => ReadmeFormat::ReStructuredText,
+            _ => ReadmeFormat::PlainText,
+        }
+    }
+}
+
+/// Escapes a text, to be inserted in an HTML document.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Renders a README as preformatted plain text.
+pub(crate) fn render_plain_text(contents: &str) -> String {
+    format!(r#"<pre class="readme-raw">{0}</pre>"#, escape(contents))
+}
+
+/// How long `asciidoctor` can take to render a README, before being killed.
+#[cfg(feature = "asciidoc")]
+const ASCIIDOCTOR_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Renders an AsciiDoc document to HTML, using the `asciidoctor` command (in its secure safe mode).
+///
+/// The command is killed if it takes longer than [`ASCIIDOCTOR_TIMEOUT`].
+/// This blocks the current thread, so it must be called from a blocking context (like a `spawn_blocking` task).
+///
+/// Returns `None` if the document could not be rendered (like if `asciidoctor` is not installed, or timed out).
+#[cfg(feature = "asciidoc")]
+pub(crate) fn render_asciidoc(contents: &str) -> Option<String> {
+    use std::io::{Read, Write};
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    let child = Command::new("asciidoctor")
+        .args([
+            "--safe-mode",
+            "secure",
+            "--no-header-footer",
+            "--out-file",
+            "-",
+            "-",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            tracing::warn!("could not run `asciidoctor` to render an AsciiDoc README: {err}");
+            return None;
+        }
+    };
+
+    //? The document is written (and the output read) from other threads, so that large outputs cannot deadlock both processes.
+    let mut stdin = child.stdin.take()?;
+    let mut stdout = child.stdout.take()?;
+    let input = contents.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    //? Wait for the command to exit, killing it if it takes too long.
+    let deadline = Instant::now() + ASCIIDOCTOR_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                tracing::warn!(
+                    "`asciidoctor` took longer than {0} seconds to render an AsciiDoc README, killing it",
+                    ASCIIDOCTOR_TIMEOUT.as_secs(),
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+            Err(_) => break None,
+        }
+    };
+
+    //? Killing the command closes its pipes, so these threads always end.
+    let written = writer.join().ok().map_or(false, |outcome| outcome.is_ok());
+    let output = reader.join().ok().and_then(Result::ok);
+    let (Some(status), Some(output)) = (status, output) else {
+        return None;
+    };
+    if !written || !status.success() {
+        tracing::warn!("`asciidoctor` failed to render an AsciiDoc README");
+        return None;
+    }
+
+    String::from_utf8(output).ok()
+}
+
+/// Renders a reStructuredText document to HTML.
+///
+/// Returns `None` if the document could not be rendered (like if it is invalid).
+#[cfg(feature = "rst")]
+pub(crate) fn render_rst(contents: &str) -> Option<String> {
+    let document = match rst_parser::parse(contents) {
+        Ok(document) => document,
+        Err(err) => {
+            tracing::warn!("could not parse a reStructuredText README: {err}");
+            return None;
+        }
+    };
+
+    let mut output = Vec::new();
+    if let Err(err) = rst_renderer::render_html(&document, &mut output, false) {
+        tracing::warn!("could not render a reStructuredText README: {err}");
+        return None;
+    }
+
+    String::from_utf8(output).ok()
+}
@@ -1,13 +1,19 @@
-use cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use cmark::{CodeBlockKind, Event, HeadingLevel, LinkType, Options, Parser, Tag};
 use syntect::easy::HighlightLines;
 use syntect::html::{
     start_highlighted_html_snippet, styled_line_to_highlighted_html, IncludeBackground,
 };
 
 pub mod config;
+pub mod format;
 
 use crate::config::SyntectState;
 
+pub use crate::format::ReadmeFormat;
+
 /// Stores the location and level of an header tag inside a Markdown document.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HeaderRef {
@@ -19,24 +25,387 @@ pub struct HeaderRef {
     pub end: usize,
 }
 
+/// Turns the text of a heading into a valid (and stable) HTML id, for its permalink.
+///
+/// Letters are lowercased, whitespace and dashes become `-`, and other characters than letters, digits and `_` are dropped
+/// (non-ASCII letters and digits are kept as-is, like on GitHub).  
+/// Headings without any remaining character get the `section` id.
+pub fn heading_id(text: &str) -> String {
+    let id: String = text
+        .trim()
+        .chars()
+        .filter_map(|ch| match ch {
+            ch if ch.is_alphanumeric() || ch == '_' => Some(ch),
+            ch if ch.is_whitespace() || ch == '-' => Some('-'),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    if id.is_empty() {
+        String::from("section")
+    } else {
+        id
+    }
+}
+
+/// Assigns unique HTML ids to the headings of a document.
+#[derive(Debug, Clone, Default)]
+pub struct HeadingIds {
+    /// The ids already assigned.
+    taken: HashSet<String>,
+}
+
+impl HeadingIds {
+    /// Constructs a new empty set of heading ids.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a unique id to the heading with the given text.
+    ///
+    /// Headings whose id is already taken get `-1`, `-2`, ... appended to it (the first free one).
+    pub fn assign(&mut self, text: &str) -> String {
+        let base = heading_id(text);
+        let mut id = base.clone();
+        let mut suffix = 0;
+        while self.taken.contains(&id) {
+            suffix += 1;
+            id = format!("{base}-{suffix}");
+        }
+        self.taken.insert(id.clone());
+        id
+    }
+}
+
+/// The kinds of GitHub-style alerts (like `> [!NOTE]`), along with their titles.
+pub const ALERT_KINDS: &[(&str, &str)] = &[
+    ("note", "Note"),
+    ("tip", "Tip"),
+    ("important", "Important"),
+    ("warning", "Warning"),
+    ("caution", "Caution"),
+];
+
+/// Stores the location of a GitHub-style alert marker (like `[!NOTE]`), at the start of a blockquote.
+#[derive(Debug, Clone, PartialEq)]
+struct AlertMarker {
+    /// The alert's kind (as found in [`ALERT_KINDS`]).
+    kind: &'static str,
+    /// The alert's title.
+    title: &'static str,
+    /// The number of events making up the marker (after the blockquote's start).
+    len: usize,
+    /// Whether the marker was the only line of the blockquote's first paragraph.
+    whole_paragraph: bool,
+}
+
+/// Looks for an alert marker in the events following the start of a blockquote.
+fn find_alert_marker(events: &[Event]) -> Option<AlertMarker> {
+    if !matches!(events.first(), Some(Event::Start(Tag::Paragraph))) {
+        return None;
+    }
+
+    //? The marker can be split across multiple text events (like `[`, `!NOTE` and `]`).
+    let texts = events[1..]
+        .iter()
+        .take_while(|event| matches!(event, Event::Text(_)))
+        .count();
+    let marker = events[1..=texts]
+        .iter()
+        .fold(String::new(), |acc, event| match event {
+            Event::Text(text) => acc + text,
+            _ => acc,
+        });
+    let kind = marker.trim().strip_prefix("[!")?.strip_suffix(']')?;
+    let (kind, title) = ALERT_KINDS
+        .iter()
+        .copied()
+        .find(|(known, _)| known.eq_ignore_ascii_case(kind))?;
+
+    //? The marker must be alone on its line.
+    match events.get(1 + texts) {
+        Some(Event::SoftBreak | Event::HardBreak) => Some(AlertMarker {
+            kind,
+            title,
+            len: 2 + texts,
+            whole_paragraph: false,
+        }),
+        Some(Event::End(Tag::Paragraph)) => Some(AlertMarker {
+            kind,
+            title,
+            len: 2 + texts,
+            whole_paragraph: true,
+        }),
+        _ => None,
+    }
+}
+
+/// Turns blockquotes starting with an alert marker (like `> [!NOTE]`) into alert callouts, like GitHub does.
+///
+/// Blockquotes with unknown markers (like `> [!FOO]`) are left untouched.
+fn render_alerts(events: Vec<Event>) -> Vec<Event> {
+    let mut output = Vec::with_capacity(events.len());
+    //? Whether each of the currently open blockquotes got turned into an alert.
+    let mut blockquotes = Vec::new();
+
+    let mut idx = 0;
+    while idx < events.len() {
+        match &events[idx] {
+            Event::Start(Tag::BlockQuote) => match find_alert_marker(&events[(idx + 1)..]) {
+                Some(marker) => {
+                    output.push(Event::Html(
+                        format!(
+                            r#"<div class="markdown-alert markdown-alert-{0}"><p class="markdown-alert-title">{1}</p>"#,
+                            marker.kind, marker.title,
+                        )
+                        .into(),
+                    ));
+                    if !marker.whole_paragraph {
+                        output.push(Event::Start(Tag::Paragraph));
+                    }
+                    blockquotes.push(true);
+                    idx += 1 + marker.len;
+                    continue;
+                }
+                None => {
+                    blockquotes.push(false);
+                    output.push(events[idx].clone());
+                }
+            },
+            Event::End(Tag::BlockQuote) => {
+                if blockquotes.pop() == Some(true) {
+                    output.push(Event::Html("</div>".into()));
+                } else {
+                    output.push(events[idx].clone());
+                }
+            }
+            event => output.push(event.clone()),
+        }
+        idx += 1;
+    }
+
+    output
+}
+
+/// Computes the size of a code block (as its number of lines and bytes), from the events following its start.
+fn code_block_size(events: &[Event]) -> (usize, usize) {
+    events
+        .iter()
+        .take_while(|event| !matches!(event, Event::End(Tag::CodeBlock(_))))
+        .fold((0, 0), |(lines, bytes), event| match event {
+            Event::Text(text) => (lines + text.lines().count(), bytes + text.len()),
+            _ => (lines, bytes),
+        })
+}
+
+/// The location of a README within its crate's repository, which its relative links get resolved against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepositoryLocation<'a> {
+    /// The URL of the crate's repository.
+    pub url: &'a str,
+    /// The commit the crate was packaged from (the configured revision is used if unknown).
+    pub commit: Option<&'a str>,
+    /// The directory of the README within the repository (empty for the repository's root).
+    pub directory: &'a str,
+}
+
+/// The base URLs of the files of a repository, which the relative links of a README get resolved against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryUrls {
+    /// The base URL of the pages of the repository's files (for links).
+    pub blob: String,
+    /// The base URL of the raw contents of the repository's files (for images).
+    pub raw: String,
+}
+
+impl RepositoryUrls {
+    /// Determines the base URLs of the files of a repository, at the given revision.
+    ///
+    /// Only repositories hosted on GitHub or GitLab are supported (`None` is returned for any other URL).
+    pub fn new(repository: &str, revision: &str) -> Option<Self> {
+        let repository = repository.trim().trim_end_matches('/');
+        let repository = repository.strip_suffix(".git").unwrap_or(repository);
+        let (host, path) = repository
+            .strip_prefix("https://")
+            .or_else(|| repository.strip_prefix("http://"))?
+            .split_once('/')?;
+        let segments: Vec<&str> = path.split('/').collect();
+        let is_valid_segment = |segment: &&str| {
+            !segment.is_empty()
+                && !segment.starts_with('.')
+                && segment
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+        };
+        if !segments.iter().all(is_valid_segment) {
+            return None;
+        }
+
+        match (host, segments.as_slice()) {
+            ("github.com" | "www.github.com", [owner, repo]) => Some(Self {
+                blob: format!("https://github.com/{owner}/{repo}/blob/{revision}/"),
+                raw: format!("https://raw.githubusercontent.com/{owner}/{repo}/{revision}/"),
+            }),
+            ("gitlab.com" | "www.gitlab.com", [_, _, ..]) => Some(Self {
+                blob: format!("https://gitlab.com/{path}/-/blob/{revision}/"),
+                raw: format!("https://gitlab.com/{path}/-/raw/{revision}/"),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Moves the base URLs down into a directory of the repository (like the one of a crate within a workspace).
+    ///
+    /// Returns `None` if the directory has unexpected path segments (like `..`).
+    pub fn within(self, directory: &str) -> Option<Self> {
+        let directory = directory.trim_matches('/');
+        if directory.is_empty() {
+            return Some(self);
+        }
+        let is_valid_segment = |segment: &str| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+        };
+        if !directory.split('/').all(is_valid_segment) {
+            return None;
+        }
+        Some(Self {
+            blob: format!("{0}{directory}/", self.blob),
+            raw: format!("{0}{directory}/", self.raw),
+        })
+    }
+
+    /// Resolves a link (or an image source) of a README against the repository.
+    ///
+    /// This is conservative: only links that are clearly relative to the README's location (like `docs/x.md` or `./y.png`)
+    /// are resolved, absolute links (including those relative to the host, like `/x`), anchors and links going up (`../x`)
+    /// are left untouched (`None` is returned).
+    pub fn resolve(&self, dest: &str, image: bool) -> Option<String> {
+        let mut path = dest.trim();
+        while let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        }
+        if path.is_empty() || path.starts_with(['#', '?', '/', '\\']) {
+            return None;
+        }
+        //? Does it have a scheme (like `https:` or `mailto:`)?
+        let first_part = path.split(['/', '?', '#']).next().unwrap_or_default();
+        if first_part.contains(':') {
+            return None;
+        }
+        //? Does it go up the directory tree?
+        let file_path = path.split(['?', '#']).next().unwrap_or_default();
+        if file_path.split('/').any(|segment| segment == "..") {
+            return None;
+        }
+
+        let base = if image { &self.raw } else { &self.blob };
+        Some(format!("{base}{path}"))
+    }
+}
+
+/// Rewrites the relative links and image sources of a Markdown document into absolute URLs to its repository.
+fn rewrite_links<'a>(urls: &RepositoryUrls, events: Vec<Event<'a>>) -> Vec<Event<'a>> {
+    let rewrite = |tag: Tag<'a>| match tag {
+        Tag::Link(kind, dest, title) if !matches!(kind, LinkType::Autolink | LinkType::Email) => {
+            match urls.resolve(dest.as_ref(), false) {
+                Some(url) => Tag::Link(kind, url.into(), title),
+                None => Tag::Link(kind, dest, title),
+            }
+        }
+        Tag::Image(kind, dest, title) => match urls.resolve(dest.as_ref(), true) {
+            Some(url) => Tag::Image(kind, url.into(), title),
+            None => Tag::Image(kind, dest, title),
+        },
+        tag => tag,
+    };
+
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::Start(tag) => Event::Start(rewrite(tag)),
+            Event::End(tag) => Event::End(rewrite(tag)),
+            event => event,
+        })
+        .collect()
+}
+
 /// Renders a Markdown document to HTML using the provided configuration.
-pub fn render_readme(config: &SyntectState, contents: &str) -> String {
+///
+/// Code blocks exceeding the configured highlighting limits (or coming after the highlighting timeout is exceeded)
+/// are rendered as plain text, so that pathological code blocks cannot stall the rendering.
+///
+/// If enabled, the relative links of the document are resolved against its location in the crate's repository
+/// (see [`RepositoryUrls`]), at the commit the crate was packaged from (if known).
+pub fn render_readme(
+    config: &SyntectState,
+    contents: &str,
+    repository: Option<&RepositoryLocation>,
+) -> String {
+    let limits = &config.limits;
+    let deadline = Instant::now() + Duration::from_millis(limits.timeout);
+    let mut timed_out = false;
     let mut highlighter: Option<HighlightLines> = None;
+    //? Whether the current code block got opened as a highlighted snippet.
+    let mut highlighted = false;
+
     let events = Parser::new_ext(contents, Options::all()).collect::<Vec<_>>();
-    let mut events = events
-        .into_iter()
-        .map(|event| match event {
-            Event::Text(text) => highlighter
-                .as_mut()
-                .and_then(|highlighter| highlighter.highlight_line(&text, &config.syntaxes).ok())
-                .and_then(|highlighted| {
-                    styled_line_to_highlighted_html(&highlighted, IncludeBackground::Yes).ok()
-                })
-                .map(|html| Event::Html(html.into()))
-                .unwrap_or_else(|| Event::Text(text)),
+    let events = render_alerts(events);
+
+    //? Resolve the relative links against the crate's repository (if enabled, and if its host is supported).
+    let repository_urls = repository
+        .filter(|_| config.links.rewrite_relative)
+        .and_then(|location| {
+            let revision = location.commit.unwrap_or(&config.links.revision);
+            RepositoryUrls::new(location.url, revision)?.within(location.directory)
+        });
+    let events = match repository_urls {
+        Some(urls) => rewrite_links(&urls, events),
+        None => events,
+    };
+
+    let mut output = Vec::with_capacity(events.len());
+    for (idx, event) in events.iter().enumerate() {
+        let event = match event {
+            Event::Text(text) => {
+                if highlighter.is_some() && !timed_out && Instant::now() >= deadline {
+                    tracing::warn!(
+                        "README highlighting timed out (after {0}ms), the remaining code is rendered as plain text",
+                        limits.timeout
+                    );
+                    timed_out = true;
+                }
+                highlighter
+                    .as_mut()
+                    .filter(|_| !timed_out)
+                    .and_then(|highlighter| highlighter.highlight_line(text, &config.syntaxes).ok())
+                    .and_then(|highlighted| {
+                        styled_line_to_highlighted_html(&highlighted, IncludeBackground::Yes).ok()
+                    })
+                    .map(|html| Event::Html(html.into()))
+                    .unwrap_or_else(|| event.clone())
+            }
             Event::Start(Tag::CodeBlock(info)) => {
-                let theme = &config.themes.themes[&config.theme_name];
+                //? Is this code block too large to be highlighted?
+                let (lines, bytes) = code_block_size(&events[(idx + 1)..]);
+                if lines > limits.max_block_lines || bytes > limits.max_block_bytes {
+                    tracing::warn!(
+                        "skipped the highlighting of a code block of {lines} lines ({bytes} bytes), exceeding the configured limits"
+                    );
+                    output.push(event.clone());
+                    continue;
+                }
+                if timed_out {
+                    output.push(event.clone());
+                    continue;
+                }
 
+                let theme = &config.themes.themes[&config.theme_name];
                 highlighter = Some(match info {
                     CodeBlockKind::Fenced(lang) => {
                         let syntax = config
@@ -49,16 +418,20 @@ pub fn render_readme(config: &SyntectState, contents: &str) -> String {
                         HighlightLines::new(config.syntaxes.find_syntax_plain_text(), theme)
                     }
                 });
+                highlighted = true;
                 let snippet = start_highlighted_html_snippet(theme);
                 Event::Html(snippet.0.into())
             }
-            Event::End(Tag::CodeBlock(_)) => {
+            Event::End(Tag::CodeBlock(_)) if highlighted => {
                 highlighter = None;
+                highlighted = false;
                 Event::Html("</pre>".into())
             }
-            _ => event,
-        })
-        .collect::<Vec<_>>();
+            _ => event.clone(),
+        };
+        output.push(event);
+    }
+    let mut events = output;
 
     let header_count = events.iter().fold(0usize, |acc, event| match event {
         Event::Start(Tag::Heading(_, _, _)) => acc + 1,
@@ -83,6 +456,7 @@ pub fn render_readme(config: &SyntectState, contents: &str) -> String {
             _ => {}
         });
 
+    let mut heading_ids = HeadingIds::new();
     for href in header_refs.into_iter() {
         fn get_text(events: &[Event]) -> String {
             events.iter().fold(String::new(), |acc, event| match event {
@@ -91,10 +465,7 @@ pub fn render_readme(config: &SyntectState, contents: &str) -> String {
             })
         }
 
-        let mut id = get_text(&events[(href.start + 1)..href.end])
-            .replace(' ', "-")
-            .replace('"', "\"");
-        id.make_ascii_lowercase();
+        let id = heading_ids.assign(&get_text(&events[(href.start + 1)..href.end]));
         events[href.start] = Event::Html(
             format!(
                 r##"<h{0} class="header" id="{1}"><a class="permalink" href="#{1}">#</a>&nbsp;"##,
@@ -108,5 +479,191 @@ pub fn render_readme(config: &SyntectState, contents: &str) -> String {
     let mut html = String::new();
     cmark::html::push_html(&mut html, events.into_iter());
 
-    ammonia::clean(html.as_str())
+    sanitize_html(config, html.as_str())
+}
+
+/// Renders a README to HTML, according to its format.
+///
+/// AsciiDoc and reStructuredText READMEs need the `asciidoc` and `rst` features, respectively,
+/// and are shown as plain text otherwise (as are READMEs in unknown formats, or which could not be rendered).
+/// Whatever its format, the rendered HTML goes through the configured sanitization policy.
+/// The relative links are only resolved against the repository for Markdown READMEs.
+pub fn render(
+    config: &SyntectState,
+    format: ReadmeFormat,
+    contents: &str,
+    repository: Option<&RepositoryLocation>,
+) -> String {
+    let rendered = match format {
+        ReadmeFormat::Markdown => return render_readme(config, contents, repository),
+        #[cfg(feature = "asciidoc")]
+        ReadmeFormat::AsciiDoc => format::render_asciidoc(contents),
+        #[cfg(feature = "rst")]
+        ReadmeFormat::ReStructuredText => format::render_rst(contents),
+        _ => None,
+    };
+    let html = rendered.unwrap_or_else(|| format::render_plain_text(contents));
+
+    sanitize_html(config, html.as_str())
+}
+
+/// Sanitizes an HTML document using the configured sanitization policy.
+///
+/// This is applied to every README, including the already rendered ones provided by publishing tools.
+pub fn sanitize_html(config: &SyntectState, html: &str) -> String {
+    config.sanitization.builder().clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SanitizationConfig;
+
+    #[test]
+    fn deduplicates_identical_headings() {
+        let mut ids = HeadingIds::new();
+        let assigned: Vec<String> = ["Usage", "Usage", "usage", "Usage"]
+            .into_iter()
+            .map(|text| ids.assign(text))
+            .collect();
+        assert_eq!(assigned, ["usage", "usage-1", "usage-2", "usage-3"]);
+    }
+
+    #[test]
+    fn skips_ids_taken_by_other_headings() {
+        let mut ids = HeadingIds::new();
+        assert_eq!(ids.assign("Usage 1"), "usage-1");
+        assert_eq!(ids.assign("Usage"), "usage");
+        assert_eq!(ids.assign("Usage"), "usage-2");
+    }
+
+    #[test]
+    fn drops_punctuation_from_ids() {
+        assert_eq!(heading_id("What's new?"), "whats-new");
+        assert_eq!(heading_id("Foo (bar) & \"baz\""), "foo-bar--baz");
+        assert_eq!(heading_id("  `serde_json::Value`  "), "serde_jsonvalue");
+        assert_eq!(heading_id("<script>#1</script>"), "script1script");
+        assert_eq!(heading_id("?!"), "section");
+        assert_eq!(heading_id(""), "section");
+    }
+
+    #[test]
+    fn keeps_non_ascii_letters_in_ids() {
+        assert_eq!(heading_id("Présentation Générale"), "présentation-générale");
+        assert_eq!(heading_id("Ünïcödé—Test"), "ünïcödétest");
+        assert_eq!(heading_id("使用方法"), "使用方法");
+        assert_eq!(heading_id("Straße"), "straße");
+    }
+
+    #[test]
+    fn generates_valid_html_ids() {
+        let texts = ["Hello, World!", "a\tb\nc", "Ünïcödé", "#", "1.0 → 2.0"];
+        for text in texts {
+            let id = heading_id(text);
+            assert!(!id.is_empty(), "id of {text:?} is empty");
+            assert!(
+                !id.chars()
+                    .any(|ch| ch.is_whitespace() || ch == '"' || ch == '#'),
+                "id of {text:?} is invalid: {id:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn sanitization_keeps_heading_ids() {
+        let html = r##"<h2 id="usage"><a href="#usage">#</a>&nbsp;Usage</h2>"##;
+        let cleaned = SanitizationConfig::default()
+            .builder()
+            .clean(html)
+            .to_string();
+        assert!(cleaned.contains(r#"<h2 id="usage">"#), "{cleaned}");
+
+        let config = SanitizationConfig {
+            removed_attributes: vec![String::from("id")],
+            ..SanitizationConfig::default()
+        };
+        let cleaned = config.builder().clean(html).to_string();
+        assert!(!cleaned.contains("id="), "{cleaned}");
+    }
+
+    #[test]
+    fn resolves_repository_urls() {
+        let urls = RepositoryUrls::new("https://github.com/Hirevo/alexandrie.git", "HEAD").unwrap();
+        assert_eq!(urls.blob, "https://github.com/Hirevo/alexandrie/blob/HEAD/");
+        assert_eq!(
+            urls.raw,
+            "https://raw.githubusercontent.com/Hirevo/alexandrie/HEAD/"
+        );
+
+        let urls = RepositoryUrls::new("https://gitlab.com/group/sub/project/", "main").unwrap();
+        assert_eq!(
+            urls.blob,
+            "https://gitlab.com/group/sub/project/-/blob/main/"
+        );
+        assert_eq!(urls.raw, "https://gitlab.com/group/sub/project/-/raw/main/");
+
+        assert_eq!(
+            RepositoryUrls::new("https://github.com/Hirevo", "HEAD"),
+            None
+        );
+        assert_eq!(
+            RepositoryUrls::new("https://github.com/Hirevo/alexandrie/tree/main", "HEAD"),
+            None
+        );
+        assert_eq!(
+            RepositoryUrls::new("https://git.example.com/a/b", "HEAD"),
+            None
+        );
+        assert_eq!(
+            RepositoryUrls::new("git@github.com:Hirevo/alexandrie.git", "HEAD"),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_repository_urls_within_directories() {
+        let urls = RepositoryUrls::new("https://github.com/a/b", "0123abcd").unwrap();
+        let urls = urls.within("crates/c/").unwrap();
+        assert_eq!(urls.blob, "https://github.com/a/b/blob/0123abcd/crates/c/");
+        assert_eq!(
+            urls.raw,
+            "https://raw.githubusercontent.com/a/b/0123abcd/crates/c/"
+        );
+
+        let urls = RepositoryUrls::new("https://github.com/a/b", "HEAD").unwrap();
+        assert_eq!(urls.clone().within(""), Some(urls.clone()));
+        assert_eq!(urls.clone().within("crates/../x"), None);
+        assert_eq!(urls.within("crates//x"), None);
+    }
+
+    #[test]
+    fn only_resolves_clearly_relative_links() {
+        let urls = RepositoryUrls::new("https://github.com/a/b", "HEAD").unwrap();
+        let resolve = |dest| urls.resolve(dest, false);
+        assert_eq!(
+            resolve("docs/x.md").as_deref(),
+            Some("https://github.com/a/b/blob/HEAD/docs/x.md")
+        );
+        assert_eq!(
+            resolve("./x.md#usage").as_deref(),
+            Some("https://github.com/a/b/blob/HEAD/x.md#usage")
+        );
+        assert_eq!(
+            urls.resolve("images/y.png", true).as_deref(),
+            Some("https://raw.githubusercontent.com/a/b/HEAD/images/y.png"),
+        );
+        for dest in [
+            "#usage",
+            "https://x.com/y",
+            "mailto:a@b.c",
+            "/docs",
+            "//x.com/y",
+            "../x.md",
+            "a/../../x",
+            "",
+            "?q=1",
+        ] {
+            assert_eq!(resolve(dest), None, "{dest:?} should be left untouched");
+        }
+    }
 }
@@ -0,0 +1,422 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{Duration, Utc};
+use once_cell::sync::OnceCell;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{CONTENT_LENGTH, LAST_MODIFIED};
+use reqwest::{Method, StatusCode};
+use ring::hmac;
+use semver::Version;
+
+use crate::error::Error;
+use crate::{CrateMetadata, Store};
+
+/// The version of the Blob service REST API used (which also dictates how signatures are computed).
+const API_VERSION: &str = "2020-12-06";
+
+/// The characters to escape in the path segments of blob URLs.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// The credentials used to access the storage account.
+#[derive(Clone)]
+pub enum AzureCredentials {
+    /// One of the storage account's access keys (as found in the Azure portal, in base64).
+    AccessKey(String),
+    /// A shared access signature (SAS) token, granting access to the container.
+    SasToken(String),
+}
+
+/// The Azure Blob Storage-backed storage strategy.
+///
+/// This uses the same layout as the S3 storage strategy: given a container (e.g., "crates") and a key prefix
+/// (e.g., "crates"), the tarball of `serde` version `1.0.0` is stored as the `crates/serde/serde-1.0.0.crate` blob
+/// (alongside its README and manifest), allowing to move the blobs from one to the other as-is.
+#[derive(Clone)]
+pub struct AzureStorage {
+    /// The HTTP client, created on first use.
+    ///
+    /// A blocking client cannot be created from within the async runtime (where the store gets instantiated),
+    /// whereas the store's methods are always called from blocking contexts.
+    client: Arc<OnceCell<Client>>,
+    account: String,
+    container: String,
+    endpoint: String,
+    credentials: AzureCredentials,
+    key_prefix: String,
+    url_lifetime: u64,
+}
+
+impl fmt::Debug for AzureStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        // The credentials are intentionally left out.
+        f.debug_struct("AzureStorage")
+            .field("account", &self.account)
+            .field("container", &self.container)
+            .field("endpoint", &self.endpoint)
+            .field("key_prefix", &self.key_prefix)
+            .finish()
+    }
+}
+
+impl AzureStorage {
+    /// Instantiate a new `AzureStorage` handle with the given storage account, container, credentials and key prefix.
+    ///
+    /// The endpoint defaults to `https://<account>.blob.core.windows.net`, and the signed download URLs
+    /// remain valid for `url_lifetime` seconds.
+    pub fn new(
+        account: String,
+        container: String,
+        endpoint: Option<String>,
+        credentials: AzureCredentials,
+        key_prefix: String,
+        url_lifetime: u64,
+    ) -> Self {
+        let endpoint = endpoint
+            .map(|endpoint| endpoint.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| format!("https://{account}.blob.core.windows.net"));
+        Self {
+            client: Arc::new(OnceCell::new()),
+            account,
+            container,
+            endpoint,
+            credentials,
+            key_prefix,
+            url_lifetime,
+        }
+    }
+
+    /// Returns the HTTP client, creating it if it is the first request.
+    fn client(&self) -> &Client {
+        self.client.get_or_init(Client::new)
+    }
+
+    /// Generate the blob key for the given crate name and version.
+    pub fn crate_key(&self, name: &str, version: Version) -> String {
+        format!("{}/{}/{}-{}.crate", self.key_prefix, name, name, version)
+    }
+
+    /// Generate the blob key for the html-rendered readme page for the
+    /// given crate name and version.
+    pub fn readme_key(&self, name: &str, version: Version) -> String {
+        format!("{}/{}/{}-{}.readme", self.key_prefix, name, name, version)
+    }
+
+    /// Generate the blob key for the manifest (`Cargo.toml`) of the
+    /// given crate name and version.
+    pub fn manifest_key(&self, name: &str, version: Version) -> String {
+        format!("{}/{}/{}-{}.toml", self.key_prefix, name, name, version)
+    }
+
+    /// Returns the (escaped) path of a blob, relative to the endpoint.
+    fn blob_path(&self, key: &str) -> String {
+        let segments = key
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, SEGMENT).to_string())
+            .collect::<Vec<_>>();
+        format!("/{}/{}", self.container, segments.join("/"))
+    }
+
+    /// Builds a signed request to the Blob service.
+    ///
+    /// `path` is the (escaped) path of the resource and `query` its (already escaped and sorted) query parameters.
+    /// `headers` are the `x-ms-*` headers to send along with the request (other than the date and the API version).
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, String)],
+        body: Option<Vec<u8>>,
+    ) -> Result<RequestBuilder, Error> {
+        let date = httpdate::fmt_http_date(SystemTime::now());
+        let mut headers = headers.to_vec();
+        headers.push(("x-ms-date", date));
+        headers.push(("x-ms-version", API_VERSION.to_string()));
+        headers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut url = format!("{}{}", self.endpoint, path);
+        let mut params = query
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>();
+        if let AzureCredentials::SasToken(token) = &self.credentials {
+            params.push(token.trim_start_matches('?').to_string());
+        }
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(params.join("&").as_str());
+        }
+
+        let content_length = body.as_ref().map_or(0, Vec::len);
+        let content_type = if body.is_some() {
+            "application/octet-stream"
+        } else {
+            ""
+        };
+
+        let mut request = self.client().request(method.clone(), url);
+        for (name, value) in headers.iter() {
+            request = request.header(*name, value.as_str());
+        }
+        if let Some(body) = body {
+            request = request
+                .header("content-type", content_type)
+                .header(CONTENT_LENGTH, content_length)
+                .body(body);
+        }
+
+        if let AzureCredentials::AccessKey(key) = &self.credentials {
+            // See: https://learn.microsoft.com/en-us/rest/api/storageservices/authorize-with-shared-key
+            let content_length = if content_length > 0 {
+                content_length.to_string()
+            } else {
+                String::new()
+            };
+            let canonicalized_headers: String = headers
+                .iter()
+                .map(|(name, value)| format!("{name}:{value}\n"))
+                .collect();
+            let canonicalized_resource: String =
+                std::iter::once(format!("/{}{}", self.account, path))
+                    .chain(
+                        query
+                            .iter()
+                            .map(|(name, value)| format!("\n{name}:{value}")),
+                    )
+                    .collect();
+            let string_to_sign = format!(
+                "{method}\n\n\n{content_length}\n\n{content_type}\n\n\n\n\n\n\n{canonicalized_headers}{canonicalized_resource}",
+            );
+            let signature = sign(key, string_to_sign.as_str())?;
+            request = request.header(
+                "authorization",
+                format!("SharedKey {}:{}", self.account, signature),
+            );
+        }
+
+        Ok(request)
+    }
+
+    /// Sends a request, turning unsuccessful responses into errors.
+    fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let response = request.send()?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        if status == StatusCode::NOT_FOUND {
+            let error = io::Error::new(io::ErrorKind::NotFound, "blob not found");
+            return Err(Error::IOError(error));
+        }
+
+        // Azure explains the failure (like an invalid signature) in the `x-ms-error-code` header.
+        let code = response
+            .headers()
+            .get("x-ms-error-code")
+            .and_then(|code| code.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        Err(Error::AzureStatusError { status, code })
+    }
+
+    fn get_blob(&self, key: String, range: Option<Range<u64>>) -> Result<Response, Error> {
+        // Azure expects the same syntax as the `Range` HTTP header (where the last position is inclusive).
+        let headers: Vec<_> = range
+            .map(|range| {
+                (
+                    "x-ms-range",
+                    format!("bytes={}-{}", range.start, range.end - 1),
+                )
+            })
+            .into_iter()
+            .collect();
+        let request = self.request(Method::GET, &self.blob_path(&key), &[], &headers, None)?;
+        self.send(request)
+    }
+
+    fn get_blob_data(&self, key: String, range: Option<Range<u64>>) -> Result<Vec<u8>, Error> {
+        let response = self.get_blob(key, range)?;
+        Ok(response.bytes()?.to_vec())
+    }
+
+    fn get_blob_reader(&self, key: String) -> Result<Box<dyn Read>, Error> {
+        // The response body is read as it arrives (the blob is not buffered in memory).
+        let response = self.get_blob(key, None)?;
+        Ok(Box::new(response))
+    }
+
+    fn put_blob(&self, key: String, data: Vec<u8>) -> Result<(), Error> {
+        let headers = [("x-ms-blob-type", String::from("BlockBlob"))];
+        let request = self.request(
+            Method::PUT,
+            &self.blob_path(&key),
+            &[],
+            &headers,
+            Some(data),
+        )?;
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn delete_blob(&self, key: String) -> Result<(), Error> {
+        let request = self.request(Method::DELETE, &self.blob_path(&key), &[], &[], None)?;
+        self.send(request)?;
+        Ok(())
+    }
+
+    fn head_blob(&self, key: String) -> Result<Response, Error> {
+        let request = self.request(Method::HEAD, &self.blob_path(&key), &[], &[], None)?;
+        self.send(request)
+    }
+
+    fn head_container(&self) -> Result<(), Error> {
+        let path = format!("/{}", self.container);
+        let query = [("restype", "container")];
+        let request = self.request(Method::HEAD, &path, &query, &[], None)?;
+        self.send(request)?;
+        Ok(())
+    }
+
+    /// Generates a URL granting read access to a blob until it expires.
+    ///
+    /// This is only possible with an access key (a service SAS restricted to this blob is signed for it),
+    /// a SAS token is never handed out since it also grants write access to the whole container.
+    fn signed_url(&self, key: String) -> Result<Option<String>, Error> {
+        let path = self.blob_path(&key);
+        let token = match &self.credentials {
+            AzureCredentials::SasToken(_) => return Ok(None),
+            AzureCredentials::AccessKey(access_key) => {
+                let expiry = Utc::now() + Duration::seconds(self.url_lifetime as i64);
+                let expiry = expiry.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+                // See: https://learn.microsoft.com/en-us/rest/api/storageservices/create-service-sas
+                let string_to_sign = format!(
+                    "r\n\n{expiry}\n/blob/{}{path}\n\n\n\n{API_VERSION}\nb\n\n\n\n\n\n\n",
+                    self.account,
+                );
+                let signature = sign(access_key, string_to_sign.as_str())?;
+                format!(
+                    "sv={API_VERSION}&sr=b&sp=r&se={}&sig={}",
+                    utf8_percent_encode(expiry.as_str(), NON_ALPHANUMERIC),
+                    utf8_percent_encode(signature.as_str(), NON_ALPHANUMERIC),
+                )
+            }
+        };
+        Ok(Some(format!("{}{}?{}", self.endpoint, path, token)))
+    }
+}
+
+/// Signs a string using an access key (as expected by Azure, using HMAC-SHA256 and base64).
+fn sign(access_key: &str, string_to_sign: &str) -> Result<String, Error> {
+    let key = BASE64
+        .decode(access_key.trim())
+        .map_err(|_| Error::InvalidAzureAccessKey)?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, &key);
+    let tag = hmac::sign(&key, string_to_sign.as_bytes());
+    Ok(BASE64.encode(tag.as_ref()))
+}
+
+impl Store for AzureStorage {
+    fn get_crate(&self, name: &str, version: Version) -> Result<Vec<u8>, Error> {
+        self.get_blob_data(self.crate_key(name, version), None)
+    }
+
+    fn read_crate(&self, name: &str, version: Version) -> Result<Box<dyn Read>, Error> {
+        self.get_blob_reader(self.crate_key(name, version))
+    }
+
+    fn get_crate_range(
+        &self,
+        name: &str,
+        version: Version,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.get_blob_data(self.crate_key(name, version), Some(range))
+    }
+
+    fn store_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
+        self.put_blob(self.crate_key(name, version), data)
+    }
+
+    fn replace_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
+        // Azure only makes the new blob visible once it is fully uploaded.
+        self.put_blob(self.crate_key(name, version), data)
+    }
+
+    fn delete_crate(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_blob(self.crate_key(name, version))
+    }
+
+    fn crate_metadata(&self, name: &str, version: Version) -> Result<CrateMetadata, Error> {
+        let response = self.head_blob(self.crate_key(name, version))?;
+        let headers = response.headers();
+
+        let size = headers
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse().ok())
+            .unwrap_or_default();
+        let last_modified = headers
+            .get(LAST_MODIFIED)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| httpdate::parse_http_date(date).ok());
+
+        Ok(CrateMetadata {
+            size,
+            last_modified,
+        })
+    }
+
+    fn download_url(&self, name: &str, version: Version) -> Result<Option<String>, Error> {
+        self.signed_url(self.crate_key(name, version))
+    }
+
+    fn get_readme(&self, name: &str, version: Version) -> Result<String, Error> {
+        let data = self.get_blob_data(self.readme_key(name, version), None)?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+
+    fn read_readme(&self, name: &str, version: Version) -> Result<Box<dyn Read>, Error> {
+        self.get_blob_reader(self.readme_key(name, version))
+    }
+
+    fn store_readme(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
+        self.put_blob(self.readme_key(name, version), data.into_bytes())
+    }
+
+    fn delete_readme(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_blob(self.readme_key(name, version))
+    }
+
+    fn get_manifest(&self, name: &str, version: Version) -> Result<String, Error> {
+        let data = self.get_blob_data(self.manifest_key(name, version), None)?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+
+    fn store_manifest(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
+        self.put_blob(self.manifest_key(name, version), data.into_bytes())
+    }
+
+    fn delete_manifest(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_blob(self.manifest_key(name, version))
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        self.head_container()
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::azure::{AzureCredentials, AzureStorage};
+
+/// The configuration struct for the 'azure' storage strategy.
+///
+/// ```toml
+/// [storage]
+/// type = "azure"                  # required
+/// account = "storage-account"     # required
+/// container = "container-name"    # required
+/// access_key = "<...>"            # either an access key of the storage account (in base64),
+/// # sas_token = "sv=...&sig=..."  # or a SAS token granting access to the container (required)
+/// key_prefix = "path/inside/container" # optional; defaults to "crates"
+/// endpoint = "http://127.0.0.1:10000/devstoreaccount1" # optional; e.g., local testing with Azurite
+/// url_lifetime = 300              # optional; how long signed download URLs remain valid (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AzureStorageConfig {
+    /// The storage account name.
+    pub account: String,
+    /// The container name.
+    pub container: String,
+    /// The credentials to access the storage account with.
+    #[serde(flatten)]
+    pub credentials: AzureCredentialsConfig,
+    /// The prefix to use for storage inside the container. Defaults to `crates`.
+    /// Should not end with a `/`.
+    #[serde(default = "default_key_prefix")]
+    pub key_prefix: String,
+    /// The Blob service endpoint. Defaults to `https://<account>.blob.core.windows.net`.
+    pub endpoint: Option<String>,
+    /// How long the signed download URLs remain valid (in seconds). Defaults to 5 minutes.
+    #[serde(default = "default_url_lifetime")]
+    pub url_lifetime: u64,
+}
+
+/// The credentials to access an Azure storage account with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AzureCredentialsConfig {
+    /// One of the storage account's access keys.
+    AccessKey {
+        /// The access key (in base64, as found in the Azure portal).
+        access_key: String,
+    },
+    /// A shared access signature (SAS) token.
+    SasToken {
+        /// The SAS token (as a query string, like `sv=...&sig=...`).
+        sas_token: String,
+    },
+}
+
+fn default_key_prefix() -> String {
+    "crates".to_string()
+}
+
+fn default_url_lifetime() -> u64 {
+    300
+}
+
+impl From<AzureCredentialsConfig> for AzureCredentials {
+    fn from(config: AzureCredentialsConfig) -> Self {
+        match config {
+            AzureCredentialsConfig::AccessKey { access_key } => Self::AccessKey(access_key),
+            AzureCredentialsConfig::SasToken { sas_token } => Self::SasToken(sas_token),
+        }
+    }
+}
+
+impl From<AzureStorageConfig> for AzureStorage {
+    fn from(config: AzureStorageConfig) -> Self {
+        Self::new(
+            config.account,
+            config.container,
+            config.endpoint,
+            config.credentials.into(),
+            config.key_prefix,
+            config.url_lifetime,
+        )
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// The 'azure' configuration.
+#[cfg(feature = "azure")]
+pub mod azure;
 /// The 'disk' configuration.
 pub mod disk;
 
@@ -24,6 +27,10 @@ pub enum StorageConfig {
     /// The S3 storage strategy (crates stored in an S3 bucket).
     #[cfg(feature = "s3")]
     S3(s3::S3StorageConfig),
+
+    /// The Azure storage strategy (crates stored in an Azure Blob Storage container).
+    #[cfg(feature = "azure")]
+    Azure(azure::AzureStorageConfig),
 }
 
 impl From<StorageConfig> for Storage {
@@ -32,6 +39,8 @@ impl From<StorageConfig> for Storage {
             StorageConfig::Disk(config) => Storage::Disk(config.into()),
             #[cfg(feature = "s3")]
             StorageConfig::S3(config) => Storage::S3(config.into()),
+            #[cfg(feature = "azure")]
+            StorageConfig::Azure(config) => Storage::Azure(config.into()),
         }
     }
 }
@@ -1,11 +1,12 @@
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::PathBuf;
 
 use semver::Version;
 
 use crate::error::Error;
-use crate::Store;
+use crate::{CrateMetadata, Store};
 
 /// The local on-disk storage strategy.  
 ///
@@ -34,6 +35,11 @@ impl DiskStorage {
     pub fn format_readme_name(name: &str, version: Version) -> String {
         format!("{0}-{1}.readme", name, version)
     }
+
+    /// Generate a unique filename for the manifest (`Cargo.toml`) of the given crate name and version.
+    pub fn format_manifest_name(name: &str, version: Version) -> String {
+        format!("{0}-{1}.toml", name, version)
+    }
 }
 
 impl Store for DiskStorage {
@@ -52,6 +58,21 @@ impl Store for DiskStorage {
         Ok(Box::new(file))
     }
 
+    fn get_crate_range(
+        &self,
+        name: &str,
+        version: Version,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let path = self.path.join(DiskStorage::format_name(name, version));
+        let mut file = fs::File::open(&path)?;
+        file.seek(SeekFrom::Start(range.start))?;
+        let len = range.end.saturating_sub(range.start);
+        let mut data = Vec::with_capacity(len as usize);
+        file.take(len).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
     fn store_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
         let path = self.path.join(DiskStorage::format_name(name, version));
         let mut file = fs::OpenOptions::new()
@@ -62,6 +83,34 @@ impl Store for DiskStorage {
         Ok(())
     }
 
+    fn replace_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
+        // The new tarball is written to a temporary file first, then renamed over the previous one (atomically).
+        let file_name = DiskStorage::format_name(name, version);
+        let path = self.path.join(&file_name);
+        let temp_path = self.path.join(format!("{file_name}.tmp"));
+        let outcome = fs::write(&temp_path, &data).and_then(|()| fs::rename(&temp_path, &path));
+        if outcome.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        outcome?;
+        Ok(())
+    }
+
+    fn delete_crate(&self, name: &str, version: Version) -> Result<(), Error> {
+        let path = self.path.join(DiskStorage::format_name(name, version));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn crate_metadata(&self, name: &str, version: Version) -> Result<CrateMetadata, Error> {
+        let path = self.path.join(DiskStorage::format_name(name, version));
+        let metadata = fs::metadata(path)?;
+        Ok(CrateMetadata {
+            size: metadata.len(),
+            last_modified: metadata.modified().ok(),
+        })
+    }
+
     fn get_readme(&self, name: &str, version: Version) -> Result<String, Error> {
         let path = self
             .path
@@ -88,4 +137,44 @@ impl Store for DiskStorage {
         file.write_all(data.as_bytes())?;
         Ok(())
     }
+
+    fn delete_readme(&self, name: &str, version: Version) -> Result<(), Error> {
+        let path = self
+            .path
+            .join(DiskStorage::format_readme_name(name, version));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn get_manifest(&self, name: &str, version: Version) -> Result<String, Error> {
+        let path = self
+            .path
+            .join(DiskStorage::format_manifest_name(name, version));
+        Ok(fs::read_to_string(path)?)
+    }
+
+    fn store_manifest(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
+        let path = self
+            .path
+            .join(DiskStorage::format_manifest_name(name, version));
+        let mut file = fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&path)?;
+        file.write_all(data.as_bytes())?;
+        Ok(())
+    }
+
+    fn delete_manifest(&self, name: &str, version: Version) -> Result<(), Error> {
+        let path = self
+            .path
+            .join(DiskStorage::format_manifest_name(name, version));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        fs::read_dir(&self.path)?;
+        Ok(())
+    }
 }
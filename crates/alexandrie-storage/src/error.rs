@@ -9,6 +9,10 @@ pub enum Error {
     #[error("IO error: {0}")]
     IOError(#[from] std::io::Error),
 
+    /// The operation is not supported by this store.
+    #[error("operation not supported by this store: {0}")]
+    Unsupported(&'static str),
+
     /// An S3 `GET` request failed.
     #[cfg(feature = "s3")]
     #[error("S3 GET error: {0}")]
@@ -18,4 +22,39 @@ pub enum Error {
     #[cfg(feature = "s3")]
     #[error("S3 PUT error: {0}")]
     S3PutError(#[from] rusoto_core::RusotoError<rusoto_s3::PutObjectError>),
+
+    /// An S3 `DELETE` request failed.
+    #[cfg(feature = "s3")]
+    #[error("S3 DELETE error: {0}")]
+    S3DeleteError(#[from] rusoto_core::RusotoError<rusoto_s3::DeleteObjectError>),
+
+    /// An S3 `HEAD` request failed.
+    #[cfg(feature = "s3")]
+    #[error("S3 HEAD error: {0}")]
+    S3HeadError(#[from] rusoto_core::RusotoError<rusoto_s3::HeadBucketError>),
+
+    /// An S3 `HEAD` request for an object failed.
+    #[cfg(feature = "s3")]
+    #[error("S3 HEAD error: {0}")]
+    S3HeadObjectError(#[from] rusoto_core::RusotoError<rusoto_s3::HeadObjectError>),
+
+    /// An Azure Blob Storage request could not be sent (or its response could not be read).
+    #[cfg(feature = "azure")]
+    #[error("Azure request error: {0}")]
+    AzureRequestError(#[from] reqwest::Error),
+
+    /// An Azure Blob Storage request was refused.
+    #[cfg(feature = "azure")]
+    #[error("Azure request failed with status {status} ({code})")]
+    AzureStatusError {
+        /// The response's status code.
+        status: reqwest::StatusCode,
+        /// The error code given by Azure (like `AuthenticationFailed`).
+        code: String,
+    },
+
+    /// The configured Azure access key is not valid base64.
+    #[cfg(feature = "azure")]
+    #[error("the Azure access key is not valid base64")]
+    InvalidAzureAccessKey,
 }
@@ -1,7 +1,12 @@
 use std::io::{self, Read};
+use std::ops::Range;
+use std::time::SystemTime;
 
 use semver::Version;
 
+/// Azure Blob Storage mechanism.
+#[cfg(feature = "azure")]
+pub mod azure;
 pub mod config;
 /// Local on-disk crate storage mechanism.
 pub mod disk;
@@ -24,10 +29,23 @@ pub enum Storage {
     /// S3 crate storage.
     #[cfg(feature = "s3")]
     S3(s3::S3Storage),
+
+    /// Azure Blob Storage crate storage.
+    #[cfg(feature = "azure")]
+    Azure(azure::AzureStorage),
     // TODO: Add a `Store` implementation using a git repository.
     // Git(GitStorage),
 }
 
+/// Metadata about a stored crate tarball.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrateMetadata {
+    /// The size of the tarball (in bytes).
+    pub size: u64,
+    /// When the tarball was last modified in the store (if the store knows about it).
+    pub last_modified: Option<SystemTime>,
+}
+
 /// The required trait that any storage type must implement.
 pub trait Store {
     /// Retrieves a crate tarball from the store.
@@ -38,8 +56,45 @@ pub trait Store {
         let reader = io::Cursor::new(contents);
         Ok(Box::new(reader))
     }
+    /// Retrieves a byte range of a crate tarball from the store.
+    fn get_crate_range(
+        &self,
+        name: &str,
+        version: Version,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let contents = self.get_crate(name, version)?;
+        let start = usize::try_from(range.start)
+            .unwrap_or(usize::MAX)
+            .min(contents.len());
+        let end = usize::try_from(range.end)
+            .unwrap_or(usize::MAX)
+            .clamp(start, contents.len());
+        Ok(contents[start..end].to_vec())
+    }
     /// Save a new crate tarball into the store.
     fn store_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error>;
+    /// Replaces a crate tarball in the store (or saves it, if it isn't already there).
+    ///
+    /// The new tarball is fully written before the previous one gets replaced,
+    /// so that failing to write it leaves the previous tarball untouched.
+    fn replace_crate(&self, _name: &str, _version: Version, _data: Vec<u8>) -> Result<(), Error> {
+        Err(Error::Unsupported("replace_crate"))
+    }
+    /// Deletes a crate tarball from the store.
+    fn delete_crate(&self, _name: &str, _version: Version) -> Result<(), Error> {
+        Err(Error::Unsupported("delete_crate"))
+    }
+    /// Retrieves metadata about a crate tarball, without fetching its contents.
+    fn crate_metadata(&self, _name: &str, _version: Version) -> Result<CrateMetadata, Error> {
+        Err(Error::Unsupported("crate_metadata"))
+    }
+    /// Generates a URL from which a crate tarball can be downloaded directly (bypassing the registry).
+    ///
+    /// Returns `None` if the store cannot serve tarballs by itself (like the local on-disk store).
+    fn download_url(&self, _name: &str, _version: Version) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
 
     /// Retrieves a rendered README from the store.
     fn get_readme(&self, name: &str, version: Version) -> Result<String, Error>;
@@ -51,6 +106,26 @@ pub trait Store {
     }
     /// Stores a new rendered README into the store.
     fn store_readme(&self, name: &str, version: Version, data: String) -> Result<(), Error>;
+    /// Deletes a rendered README from the store.
+    fn delete_readme(&self, _name: &str, _version: Version) -> Result<(), Error> {
+        Err(Error::Unsupported("delete_readme"))
+    }
+
+    /// Retrieves a crate's manifest (its `Cargo.toml` file, as published) from the store.
+    fn get_manifest(&self, _name: &str, _version: Version) -> Result<String, Error> {
+        Err(Error::Unsupported("get_manifest"))
+    }
+    /// Stores a new crate's manifest (its `Cargo.toml` file, as published) into the store.
+    fn store_manifest(&self, _name: &str, _version: Version, _data: String) -> Result<(), Error> {
+        Err(Error::Unsupported("store_manifest"))
+    }
+    /// Deletes a crate's manifest from the store.
+    fn delete_manifest(&self, _name: &str, _version: Version) -> Result<(), Error> {
+        Err(Error::Unsupported("delete_manifest"))
+    }
+
+    /// Checks that the store is reachable and usable.
+    fn check_health(&self) -> Result<(), Error>;
 }
 
 impl Store for Storage {
@@ -59,6 +134,8 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.get_crate(name, version),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.get_crate(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.get_crate(name, version),
         }
     }
 
@@ -67,6 +144,23 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.read_crate(name, version),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.read_crate(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.read_crate(name, version),
+        }
+    }
+
+    fn get_crate_range(
+        &self,
+        name: &str,
+        version: Version,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        match self {
+            Storage::Disk(storage) => storage.get_crate_range(name, version, range),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.get_crate_range(name, version, range),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.get_crate_range(name, version, range),
         }
     }
 
@@ -75,6 +169,48 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.store_crate(name, version, data),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.store_crate(name, version, data),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.store_crate(name, version, data),
+        }
+    }
+
+    fn replace_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.replace_crate(name, version, data),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.replace_crate(name, version, data),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.replace_crate(name, version, data),
+        }
+    }
+
+    fn delete_crate(&self, name: &str, version: Version) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.delete_crate(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.delete_crate(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.delete_crate(name, version),
+        }
+    }
+
+    fn crate_metadata(&self, name: &str, version: Version) -> Result<CrateMetadata, Error> {
+        match self {
+            Storage::Disk(storage) => storage.crate_metadata(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.crate_metadata(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.crate_metadata(name, version),
+        }
+    }
+
+    fn download_url(&self, name: &str, version: Version) -> Result<Option<String>, Error> {
+        match self {
+            Storage::Disk(storage) => storage.download_url(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.download_url(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.download_url(name, version),
         }
     }
 
@@ -83,6 +219,8 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.get_readme(name, version),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.get_readme(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.get_readme(name, version),
         }
     }
 
@@ -91,6 +229,8 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.read_readme(name, version),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.read_readme(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.read_readme(name, version),
         }
     }
 
@@ -99,6 +239,58 @@ impl Store for Storage {
             Storage::Disk(storage) => storage.store_readme(name, version, data),
             #[cfg(feature = "s3")]
             Storage::S3(storage) => storage.store_readme(name, version, data),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.store_readme(name, version, data),
+        }
+    }
+
+    fn delete_readme(&self, name: &str, version: Version) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.delete_readme(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.delete_readme(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.delete_readme(name, version),
+        }
+    }
+
+    fn get_manifest(&self, name: &str, version: Version) -> Result<String, Error> {
+        match self {
+            Storage::Disk(storage) => storage.get_manifest(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.get_manifest(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.get_manifest(name, version),
+        }
+    }
+
+    fn store_manifest(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.store_manifest(name, version, data),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.store_manifest(name, version, data),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.store_manifest(name, version, data),
+        }
+    }
+
+    fn delete_manifest(&self, name: &str, version: Version) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.delete_manifest(name, version),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.delete_manifest(name, version),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.delete_manifest(name, version),
+        }
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        match self {
+            Storage::Disk(storage) => storage.check_health(),
+            #[cfg(feature = "s3")]
+            Storage::S3(storage) => storage.check_health(),
+            #[cfg(feature = "azure")]
+            Storage::Azure(storage) => storage.check_health(),
         }
     }
 }
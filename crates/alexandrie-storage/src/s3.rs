@@ -1,13 +1,17 @@
 use std::convert::TryFrom;
 use std::fmt;
 use std::io::{self, Read};
+use std::ops::Range;
 
 use rusoto_core::Region;
-use rusoto_s3::{GetObjectOutput, GetObjectRequest, PutObjectRequest, S3Client, StreamingBody, S3};
+use rusoto_s3::{
+    DeleteObjectRequest, GetObjectOutput, GetObjectRequest, HeadBucketRequest, HeadObjectOutput,
+    HeadObjectRequest, PutObjectRequest, S3Client, StreamingBody, S3,
+};
 use semver::Version;
 
 use crate::error::Error;
-use crate::Store;
+use crate::{CrateMetadata, Store};
 
 /// The S3-backed storage strategy.
 ///
@@ -51,10 +55,27 @@ impl S3Storage {
         format!("{}/{}/{}-{}.readme", self.key_prefix, name, name, version)
     }
 
+    /// Generate the S3 bucket key for the manifest (`Cargo.toml`) of the
+    /// given crate name and version.
+    pub fn manifest_key(&self, name: &str, version: Version) -> String {
+        format!("{}/{}/{}-{}.toml", self.key_prefix, name, name, version)
+    }
+
     fn get_object(&self, key: String) -> Result<GetObjectOutput, Error> {
+        self.get_object_range(key, None)
+    }
+
+    fn get_object_range(
+        &self,
+        key: String,
+        range: Option<Range<u64>>,
+    ) -> Result<GetObjectOutput, Error> {
+        // S3 expects the same syntax as the `Range` HTTP header (where the last position is inclusive).
+        let range = range.map(|range| format!("bytes={0}-{1}", range.start, range.end - 1));
         let request = GetObjectRequest {
             bucket: self.bucket.clone(),
             key,
+            range,
             ..Default::default()
         };
         Ok(tokio::task::block_on(self.client.get_object(request))?)
@@ -66,7 +87,15 @@ impl S3Storage {
     // `get_object_reader` below convert "no body" into "no data" and return an
     // empty vec or empty reader.
     fn get_object_data(&self, key: String) -> Result<Vec<u8>, Error> {
-        let s3_object = self.get_object(key)?;
+        self.get_object_range_data(key, None)
+    }
+
+    fn get_object_range_data(
+        &self,
+        key: String,
+        range: Option<Range<u64>>,
+    ) -> Result<Vec<u8>, Error> {
+        let s3_object = self.get_object_range(key, range)?;
 
         let body = match s3_object.body {
             Some(body) => body,
@@ -113,6 +142,34 @@ impl S3Storage {
 
         Ok(())
     }
+
+    fn delete_object(&self, key: String) -> Result<(), Error> {
+        let request = DeleteObjectRequest {
+            bucket: self.bucket.clone(),
+            key,
+            ..Default::default()
+        };
+        tokio::task::block_on(self.client.delete_object(request))?;
+        Ok(())
+    }
+
+    fn head_object(&self, key: String) -> Result<HeadObjectOutput, Error> {
+        let request = HeadObjectRequest {
+            bucket: self.bucket.clone(),
+            key,
+            ..Default::default()
+        };
+        Ok(tokio::task::block_on(self.client.head_object(request))?)
+    }
+
+    fn head_bucket(&self) -> Result<(), Error> {
+        let request = HeadBucketRequest {
+            bucket: self.bucket.clone(),
+            ..Default::default()
+        };
+        tokio::task::block_on(self.client.head_bucket(request))?;
+        Ok(())
+    }
 }
 
 impl Store for S3Storage {
@@ -124,10 +181,48 @@ impl Store for S3Storage {
         self.get_object_reader(self.crate_key(name, version))
     }
 
+    fn get_crate_range(
+        &self,
+        name: &str,
+        version: Version,
+        range: Range<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.get_object_range_data(self.crate_key(name, version), Some(range))
+    }
+
     fn store_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
         self.put_object(self.crate_key(name, version), data)
     }
 
+    fn replace_crate(&self, name: &str, version: Version, data: Vec<u8>) -> Result<(), Error> {
+        // S3 only makes the new object visible once it is fully uploaded.
+        self.put_object(self.crate_key(name, version), data)
+    }
+
+    fn delete_crate(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_object(self.crate_key(name, version))
+    }
+
+    fn crate_metadata(&self, name: &str, version: Version) -> Result<CrateMetadata, Error> {
+        let s3_object = self.head_object(self.crate_key(name, version))?;
+
+        // S3 sends back the modification date in the HTTP date format (like in the `Last-Modified` header).
+        let last_modified = s3_object
+            .last_modified
+            .and_then(|date| httpdate::parse_http_date(date.as_str()).ok());
+
+        Ok(CrateMetadata {
+            size: s3_object
+                .content_length
+                .and_then(|length| u64::try_from(length).ok())
+                .unwrap_or_default(),
+            last_modified,
+        })
+    }
+
     fn get_readme(&self, name: &str, version: Version) -> Result<String, Error> {
         let data = self.get_object_data(self.readme_key(name, version))?;
 
@@ -144,4 +239,25 @@ impl Store for S3Storage {
     fn store_readme(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
         self.put_object(self.readme_key(name, version), data.into_bytes())
     }
+
+    fn delete_readme(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_object(self.readme_key(name, version))
+    }
+
+    fn get_manifest(&self, name: &str, version: Version) -> Result<String, Error> {
+        let data = self.get_object_data(self.manifest_key(name, version))?;
+        Ok(String::from_utf8_lossy(&data).to_string())
+    }
+
+    fn store_manifest(&self, name: &str, version: Version, data: String) -> Result<(), Error> {
+        self.put_object(self.manifest_key(name, version), data.into_bytes())
+    }
+
+    fn delete_manifest(&self, name: &str, version: Version) -> Result<(), Error> {
+        self.delete_object(self.manifest_key(name, version))
+    }
+
+    fn check_health(&self) -> Result<(), Error> {
+        self.head_bucket()
+    }
 }
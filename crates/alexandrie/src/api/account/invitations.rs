@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::error::ApiError;
+use crate::utils::auth::api::Auth;
+use crate::utils::invitations::{self, PendingInvitation};
+use crate::utils::page_cache;
+
+/// Response body for the listing route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListResponseBody {
+    /// The pending owner invitations of the author.
+    pub invitations: Vec<PendingInvitation>,
+}
+
+/// Request body for the answering route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnswerRequestBody {
+    /// Whether to accept (or decline) the invitation.
+    pub accepted: bool,
+}
+
+/// Response body for the answering route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnswerResponseBody {
+    /// Whether the operation went well.
+    pub ok: bool,
+    /// A human-displayable message describing the operation's outcome.
+    pub msg: String,
+}
+
+/// Route to list the pending owner invitations of the authenticated author.
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+) -> Result<Json<ListResponseBody>, ApiError> {
+    let db = &state.db;
+
+    let invitations = db
+        .transaction(move |conn| invitations::pending(conn, author.id))
+        .await?;
+
+    Ok(Json(ListResponseBody { invitations }))
+}
+
+/// Route to accept or decline an owner invitation.
+pub async fn put(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(invitation_id): Path<i64>,
+    Json(body): Json<AnswerRequestBody>,
+) -> Result<Json<AnswerResponseBody>, ApiError> {
+    let db = &state.db;
+
+    let accepted = body.accepted;
+    let maybe_crate_name = db
+        .transaction(move |conn| invitations::resolve(conn, author.id, invitation_id, accepted))
+        .await?;
+
+    //? Was a pending invitation found ?
+    let Some(crate_name) = maybe_crate_name else {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            "no pending invitation was found for the supplied ID (it may have expired)",
+        ));
+    };
+
+    if accepted {
+        page_cache::invalidate(&state, &crate_name);
+    }
+
+    let msg = if accepted {
+        format!("you are now an owner of {crate_name}")
+    } else {
+        format!("the invitation to become an owner of {crate_name} has been declined")
+    };
+
+    Ok(Json(AnswerResponseBody { ok: true, msg }))
+}
@@ -2,6 +2,7 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use ring::digest as hasher;
@@ -14,6 +15,7 @@ use crate::db::schema::*;
 use crate::error::ApiError;
 use crate::utils;
 use crate::utils::auth::api::Auth;
+use crate::utils::auth::passwd::PasswordVerification;
 
 /// Request body for this route.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -38,6 +40,7 @@ pub async fn post(
     Json(body): Json<RequestBody>,
 ) -> Result<Json<ResponseBody>, ApiError> {
     let db = &state.db;
+    let state = Arc::clone(&state);
 
     //? Is the author logged in ?
     if maybe_author.is_some() {
@@ -59,18 +62,7 @@ pub async fn post(
         let (author_id, encoded_salt, encoded_expected_hash) = match results {
             Some((author_id, salt, Some(passwd))) => (author_id, salt, passwd),
             _ => {
-                return Err(ApiError::msg("invalid email/password combination."));
-            }
-        };
-
-        //? Decode hex-encoded hashes.
-        let decode_results = hex::decode(encoded_salt.as_str())
-            .and_then(|fst| hex::decode(encoded_expected_hash.as_str()).map(move |snd| (fst, snd)));
-
-        let (decoded_salt, decoded_expected_hash) = match decode_results {
-            Ok(results) => results,
-            Err(_) => {
-                return Err(ApiError::msg("an author already exists for this email."));
+                return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid email/password combination."));
             }
         };
 
@@ -88,21 +80,23 @@ pub async fn post(
             out
         };
 
-        //? Verify client password against the expected hash (through PBKDF2).
-        let password_match = {
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-            let outcome = pbkdf2::verify(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                decoded_salt.as_slice(),
-                hashed_passwd.as_ref(),
-                decoded_expected_hash.as_slice(),
-            );
-            outcome.is_ok()
+        //? Verify client password against the expected hash.
+        let hasher = &state.general.password_hasher;
+        let verification = hasher.verify(
+            hashed_passwd.as_ref(),
+            encoded_salt.as_str(),
+            encoded_expected_hash.as_str(),
+        );
+        let PasswordVerification::Match { needs_rehash } = verification else {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "invalid email/password combination."));
         };
 
-        if !password_match {
-            return Err(ApiError::msg("invalid email/password combination."));
+        //? Upgrade the stored hash if it uses an older algorithm (or weaker parameters).
+        if needs_rehash {
+            let encoded_derived_hash = hasher.hash(hashed_passwd.as_ref())?;
+            diesel::update(authors::table.find(author_id))
+                .set(authors::passwd.eq(encoded_derived_hash.as_str()))
+                .execute(conn)?;
         }
 
         //? Generate new registry token.
@@ -1,3 +1,5 @@
+/// Crate owner invitations endpoints (eg. "/api/v1/account/invitations").
+pub mod invitations;
 /// Account login endpoint (eg. "/api/v1/account/login").
 pub mod login;
 /// Account registration endpoint (eg. "/api/v1/account/register").
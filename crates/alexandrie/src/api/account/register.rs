@@ -1,20 +1,22 @@
-use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use chrono::Utc;
 use diesel::dsl as sql;
 use diesel::prelude::*;
 use ring::digest as hasher;
-use ring::pbkdf2;
 use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
 
 use crate::config::AppState;
 use crate::db::models::{NewAuthor, NewAuthorToken, NewSalt};
 use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
 use crate::error::ApiError;
 use crate::utils;
 use crate::utils::auth::api::Auth;
+use crate::utils::auth::passwd;
 
 /// Request body for this route.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -47,29 +49,29 @@ pub async fn post(
         ));
     }
 
-    let transaction = state.db.transaction(move |conn| {
+    //? Does the password comply with the password policy?
+    if let Err(violation) = state.password_policy().check(body.passwd.as_str()) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, violation));
+    }
+
+    let db = &state.db;
+    let state = Arc::clone(&state);
+
+    let transaction = db.transaction(move |conn| {
         //? Does the user already exist ?
         let already_exists = sql::select(sql::exists(
             authors::table.filter(authors::email.eq(body.email.as_str())),
         ))
         .get_result(conn)?;
         if already_exists {
-            return Err(ApiError::msg("an author already exists for this email."));
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "an author already exists for this email.",
+            ));
         }
 
-        //? First rounds of PBKDF2 (5_000 rounds, it corresponds to what the frontend does, cf. `wasm-pbkdf2` sub-crate).
-        let hashed_passwd = {
-            let mut out = [0u8; hasher::SHA512_OUTPUT_LEN];
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(5_000) };
-            pbkdf2::derive(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                body.email.as_bytes(),
-                body.passwd.as_bytes(),
-                &mut out,
-            );
-            out
-        };
+        //? First rounds of PBKDF2 (what the frontend does before submitting passwords).
+        let hashed_passwd = passwd::prehash(body.email.as_str(), body.passwd.as_str());
 
         //? Generate the user's authentication salt.
         let decoded_generated_salt = {
@@ -79,27 +81,18 @@ pub async fn post(
             hasher::digest(&hasher::SHA512, data.as_ref())
         };
 
-        //? Derive the hashed password data with PBKDF2 (100_000 rounds).
-        let encoded_derived_hash = {
-            let mut out = [0u8; hasher::SHA512_OUTPUT_LEN];
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-            pbkdf2::derive(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                decoded_generated_salt.as_ref(),
-                hashed_passwd.as_ref(),
-                &mut out,
-            );
-            hex::encode(out.as_ref())
-        };
+        //? Hash the password (using Argon2id).
+        let encoded_derived_hash = state.general.password_hasher.hash(hashed_passwd.as_ref())?;
 
         //? Insert the new author data.
+        let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
         let new_author = NewAuthor {
             email: body.email.as_str(),
             name: body.name.as_str(),
             passwd: Some(encoded_derived_hash.as_str()),
             github_id: None,
             gitlab_id: None,
+            created_at: Some(created_at.as_str()),
         };
         diesel::insert_into(authors::table)
             .values(new_author)
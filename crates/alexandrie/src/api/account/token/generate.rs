@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -44,7 +45,8 @@ pub async fn put(
 
         //? Was a token found ?
         if token.is_some() {
-            return Err(ApiError::msg(
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
                 "a token of that same name already exist for your account",
             ));
         }
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -48,7 +49,7 @@ pub async fn get(
 
     //? Was a token found ?
     let Some(token) = maybe_token else {
-        return Err(ApiError::msg("no token was found for the supplied name"));
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "no token was found for the supplied name"));
     };
 
     Ok(Json(ResponseBody {
@@ -78,7 +79,7 @@ pub async fn post(
 
     //? Was a token found ?
     let Some(token) = maybe_token else {
-        return Err(ApiError::msg("unauthorized access to this token"));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "unauthorized access to this token"));
     };
 
     Ok(Json(ResponseBody {
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -42,12 +43,15 @@ pub async fn delete(
 
         //? Was a token found ?
         let Some(token) = maybe_token else {
-            return Err(ApiError::msg("unauthorized access to this token"));
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "unauthorized access to this token"));
         };
 
         //? Is the token from that same author ?
         if token.author_id != author.id {
-            return Err(ApiError::msg("unauthorized access to this token"));
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "unauthorized access to this token",
+            ));
         }
 
         //? Revoke that token.
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use axum::extract::State;
 use axum::Json;
+use diesel::dsl as sql;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -21,16 +22,7 @@ pub(crate) struct CategoriesResult {
     pub name: String,
     pub tag: String,
     pub description: String,
-}
-
-impl From<Category> for CategoriesResult {
-    fn from(category: Category) -> CategoriesResult {
-        CategoriesResult {
-            name: category.name,
-            tag: category.tag,
-            description: category.description,
-        }
-    }
+    pub crates_cnt: i64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -38,17 +30,42 @@ pub(crate) struct CategoriesMeta {
     pub total: usize,
 }
 
-/// Route to list categories.
+/// Route to list categories (along with how many crates are in each of them).
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<CategoriesResponse>, ApiError> {
     let db = &state.db;
 
-    let categories = db
-        .run(|conn| categories::table.load::<Category>(conn))
+    let (categories, counts) = db
+        .run(|conn| {
+            let categories = categories::table
+                .order_by(categories::tag.asc())
+                .load::<Category>(conn)?;
+            let counts = crate_categories::table
+                .group_by(crate_categories::category_id)
+                .select((crate_categories::category_id, sql::count_star()))
+                .load::<(i64, i64)>(conn)?;
+            Ok::<_, diesel::result::Error>((categories, counts))
+        })
         .await?;
 
-    let categories: Vec<_> = categories.into_iter().map(CategoriesResult::from).collect();
+    let allowed = state.general.allowed_categories.as_deref();
+    let categories: Vec<_> = categories
+        .into_iter()
+        .filter(|category| allowed.map_or(true, |allowed| allowed.contains(&category.tag)))
+        .map(|category| {
+            let crates_cnt = counts
+                .iter()
+                .find_map(|(id, count)| (*id == category.id).then_some(*count))
+                .unwrap_or(0);
+            CategoriesResult {
+                name: category.name,
+                tag: category.tag,
+                description: category.description,
+                crates_cnt,
+            }
+        })
+        .collect();
     let total = categories.len();
 
     Ok(Json(CategoriesResponse {
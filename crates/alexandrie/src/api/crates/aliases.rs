@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::aliases;
+use crate::utils::audit::{self, AuditAction};
+use crate::utils::auth::api::Auth;
+use crate::utils::page_cache;
+use crate::utils::visibility;
+
+/// Request body for the alias addition and removal routes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct AliasBody {
+    /// The alias to add (or remove), like the former name of the crate.
+    pub alias: String,
+}
+
+/// Response body for these routes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ResponseBody {
+    /// The crate's aliases (in their canonical form, sorted alphabetically).
+    pub aliases: Vec<String>,
+}
+
+/// Finds a crate by its (canonical) name, returning its ID and its name.
+fn find_crate(conn: &mut Connection, canon_name: &str) -> Result<(i64, String), ApiError> {
+    let maybe_crate = crates::table
+        .select((crates::id, crates::name))
+        .filter(crates::canon_name.eq(canon_name))
+        .first::<(i64, String)>(conn)
+        .optional()?;
+    maybe_crate.ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("no crates named '{canon_name}' could be found"),
+        )
+    })
+}
+
+/// Route to list the aliases of a crate.
+///
+/// Private crates are only visible using the token of one of their owners (or collaborators).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    user: Option<Auth>,
+) -> Result<Json<ResponseBody>, ApiError> {
+    let canon_name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
+
+    let aliases = state
+        .db
+        .run(move |conn| {
+            let (crate_id, _) = find_crate(conn, canon_name.as_str())?;
+            if !visibility::can_access(conn, crate_id, author_id)? {
+                return Err(ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no crates named '{canon_name}' could be found"),
+                ));
+            }
+            Ok(aliases::list(conn, crate_id)?)
+        })
+        .await?;
+
+    Ok(Json(ResponseBody { aliases }))
+}
+
+/// Route to add an alias to a crate (superadmins only).
+///
+/// Visiting the crate's page using the alias redirects to it, and the crate information endpoint reports it.
+/// An alias cannot be the name of an existing crate, nor an alias of another crate.
+pub(crate) async fn put(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Json(body): Json<AliasBody>,
+) -> Result<Json<ResponseBody>, ApiError> {
+    //? Is the user a superadmin?
+    if !utils::checks::is_superadmin(&state.general, &author) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "only superadmins can manage crate aliases",
+        ));
+    }
+
+    //? Is the alias a valid crate name?
+    utils::checks::validate_crate_name(&state.general, &author, body.alias.trim())?;
+
+    let canon_name = utils::canonical_name(name);
+    let alias = utils::canonical_name(body.alias.trim());
+    let response = state
+        .db
+        .transaction(move |conn| {
+            let (crate_id, _) = find_crate(conn, canon_name.as_str())?;
+
+            //? Is the alias already taken (by a crate, or by another alias)?
+            let is_crate: bool = sql::select(sql::exists(
+                crates::table.filter(crates::canon_name.eq(alias.as_str())),
+            ))
+            .get_result(conn)?;
+            if is_crate {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    format!("the alias '{alias}' is the name of an existing crate"),
+                ));
+            }
+            if let Some((_, target)) = aliases::resolve(conn, alias.as_str())? {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    format!("the alias '{alias}' already refers to the '{target}' crate"),
+                ));
+            }
+
+            aliases::add(conn, crate_id, alias.as_str())?;
+            let action = AuditAction::AddAlias;
+            audit::record(
+                conn,
+                crate_id,
+                author.id,
+                action,
+                None,
+                Some(alias.as_str()),
+            )?;
+
+            let aliases = aliases::list(conn, crate_id)?;
+            Ok::<_, ApiError>((canon_name, ResponseBody { aliases }))
+        })
+        .await;
+    let (canon_name, response) = response?;
+
+    page_cache::invalidate(&state, &canon_name);
+    Ok(Json(response))
+}
+
+/// Route to remove an alias from a crate (superadmins only).
+pub(crate) async fn delete(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Json(body): Json<AliasBody>,
+) -> Result<Json<ResponseBody>, ApiError> {
+    //? Is the user a superadmin?
+    if !utils::checks::is_superadmin(&state.general, &author) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "only superadmins can manage crate aliases",
+        ));
+    }
+
+    let canon_name = utils::canonical_name(name);
+    let alias = utils::canonical_name(body.alias.trim());
+    let response = state
+        .db
+        .transaction(move |conn| {
+            let (crate_id, crate_name) = find_crate(conn, canon_name.as_str())?;
+
+            //? Is this alias one of this crate's aliases?
+            let refers_to_crate = aliases::resolve(conn, alias.as_str())?
+                .map_or(false, |(target_id, _)| target_id == crate_id);
+            if !refers_to_crate {
+                return Err(ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("'{alias}' is not an alias of the '{crate_name}' crate"),
+                ));
+            }
+
+            aliases::remove(conn, alias.as_str())?;
+            let action = AuditAction::RemoveAlias;
+            audit::record(
+                conn,
+                crate_id,
+                author.id,
+                action,
+                None,
+                Some(alias.as_str()),
+            )?;
+
+            let aliases = aliases::list(conn, crate_id)?;
+            Ok::<_, ApiError>(ResponseBody { aliases })
+        })
+        .await?;
+
+    Ok(Json(response))
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::prelude::*;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::Indexer;
+
+use crate::config::AppState;
+use crate::db::schema::crates;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::audit::{self, AuditAction};
+use crate::utils::auth::api::Auth;
+use crate::utils::commit_messages::CommitFields;
+use crate::utils::page_cache;
+use crate::utils::versions;
+
+/// Request body for this route.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RequestBody {
+    /// The requirement matching the versions to yank (like `>=1.2.0, <1.4.3`).
+    #[serde(default)]
+    pub req: Option<VersionReq>,
+    /// The explicit list of versions to yank (instead of a requirement).
+    #[serde(default)]
+    pub versions: Option<Vec<Version>>,
+    /// The reason for yanking these versions (like the identifier of a security advisory).
+    pub reason: String,
+    /// Whether the operation is allowed to leave the crate without any non-yanked version.
+    #[serde(default)]
+    pub allow_yank_all: bool,
+}
+
+/// Response body for this route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ResponseBody {
+    /// Whether the operation went well.
+    pub ok: bool,
+    /// The versions that got yanked (the ones that were already yanked are left out).
+    pub yanked: Vec<Version>,
+}
+
+/// Route to yank several versions of a crate at once (like the ones affected by a security advisory).
+///
+/// Every version gets yanked in the same crate index commit, and is recorded in the audit log along with the given reason.
+pub(crate) async fn post(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Json(body): Json<RequestBody>,
+) -> Result<Json<ResponseBody>, ApiError> {
+    let name = utils::canonical_name(name);
+
+    let reason = body.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(ApiError::msg("a reason must be given for yanking versions"));
+    }
+    let matches: Box<dyn Fn(&Version) -> bool + Send> = match (body.req, body.versions) {
+        (Some(req), None) => Box::new(move |version| req.matches(version)),
+        (None, Some(versions)) => Box::new(move |version| versions.contains(version)),
+        _ => {
+            return Err(ApiError::msg(
+                "exactly one of `req` or `versions` must be specified",
+            ));
+        }
+    };
+
+    let db = &state.db;
+    let (crate_id, name) = db
+        .run(move |conn| {
+            //? Does this crate exists?
+            let exists = utils::checks::crate_exists(conn, name.as_str())?;
+            if !exists {
+                return Err(ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no crates named '{name}' could be found"),
+                ));
+            }
+
+            //? Is the user an owner of this crate (collaborators cannot yank nor un-yank versions)?
+            let is_owner = utils::checks::is_crate_owner(conn, name.as_str(), author.id)?;
+            if !is_owner {
+                return Err(ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    "you are not an owner of this crate",
+                ));
+            }
+
+            //? Get the non-canonical crate name from the canonical one.
+            let krate = crates::table
+                .select((crates::id, crates::name))
+                .filter(crates::canon_name.eq(name.as_str()))
+                .first::<(i64, String)>(conn)?;
+
+            Ok(krate)
+        })
+        .await?;
+
+    //? Update the crate index (without holding onto a database connection while pushing).
+    let allow_yank_all = body.allow_yank_all;
+    let (actor, commit_reason) = (author.name.clone(), reason.clone());
+    let cached_name = name.clone();
+    let (yanked, available) = utils::run_blocking(&state, move |state| {
+        let records = state.index.all_records(name.as_str())?;
+        if !records.iter().any(|record| matches(&record.vers)) {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("no versions of '{name}' match the request"),
+            ));
+        }
+
+        let (to_yank, remaining): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .filter(|record| record.yanked != Some(true))
+            .map(|record| record.vers)
+            .partition(|version| matches(version));
+
+        //? Would this leave the crate without any usable version?
+        if remaining.is_empty() && !to_yank.is_empty() && !allow_yank_all {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "this would yank every version of the crate (set `allow_yank_all` to proceed anyway)",
+            ));
+        }
+        //? The versions left unyanked are the crate's available versions.
+        let available = i64::try_from(remaining.len()).unwrap_or(i64::MAX);
+        if to_yank.is_empty() {
+            return Ok((to_yank, available));
+        }
+
+        for version in to_yank.iter() {
+            state.index.yank_record(name.as_str(), version.clone())?;
+        }
+        let versions: Vec<String> = to_yank.iter().map(Version::to_string).collect();
+        let msg = state.commit_messages.bulk_yank.render(&CommitFields {
+            action: "Yanking",
+            krate: name.as_str(),
+            version: versions.join(", ").as_str(),
+            actor: actor.as_str(),
+            reason: commit_reason.as_str(),
+        });
+        state.index.commit_and_push(msg.as_str())?;
+        for version in to_yank.iter() {
+            state.rdeps.set_yanked(name.as_str(), version, true)?;
+            state.metrics.record_yank(true);
+        }
+
+        Ok((to_yank, available))
+    })
+    .await?;
+
+    //? Update the crate's number of available versions, and record every yanked version in the audit log.
+    let recorded = yanked.clone();
+    db.transaction(move |conn| {
+        versions::store_available(conn, crate_id, available)?;
+        for version in recorded {
+            let version = version.to_string();
+            let (version, reason) = (Some(version.as_str()), Some(reason.as_str()));
+            audit::record(
+                conn,
+                crate_id,
+                author.id,
+                AuditAction::Yank,
+                version,
+                reason,
+            )?;
+        }
+        Ok::<_, diesel::result::Error>(())
+    })
+    .await?;
+
+    //? Invalidate the cached pages of this crate (now that its number of available versions is up-to-date).
+    if !yanked.is_empty() {
+        page_cache::invalidate(&state, cached_name.as_str());
+    }
+
+    Ok(Json(ResponseBody { ok: true, yanked }))
+}
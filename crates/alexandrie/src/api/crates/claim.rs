@@ -0,0 +1,279 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::prelude::*;
+use json::json;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::models::Author;
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::audit::{self, AuditAction};
+use crate::utils::auth::api::Auth;
+use crate::utils::claims;
+use crate::utils::owners::OwnerRole;
+use crate::utils::page_cache;
+use crate::utils::visibility;
+
+/// Request body for the claim route.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ClaimBody {
+    /// The email of the author to make the owner of the crate (superadmins only, defaults to the requester).
+    #[serde(default)]
+    pub user: Option<String>,
+}
+
+/// Response body for the claim instructions route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ClaimInfo {
+    /// Whether the crate already has owners (in which case it cannot be claimed).
+    pub owned: bool,
+    /// Whether authors can claim the crate by themselves (by proving that they control its repository).
+    pub self_claims: bool,
+    /// The token to commit to the repository, to claim the crate (only for self-claimable crates).
+    pub token: Option<String>,
+    /// The URL the proof file (containing the token) is looked for at (only for self-claimable crates).
+    pub proof_url: Option<String>,
+}
+
+/// An unowned crate, as listed for superadmins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct UnownedCrate {
+    /// The crate's name.
+    pub name: String,
+    /// The crate's repository URL (if any).
+    pub repository: Option<String>,
+    /// The crate's creation date.
+    pub created_at: String,
+}
+
+/// Response body for the unowned crates listing route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct UnownedCratesResponse {
+    /// The crates without any owners (sorted by name).
+    pub crates: Vec<UnownedCrate>,
+}
+
+/// Finds a crate (accessible to the user) by its canonical name, returning its ID, its name and its repository URL.
+///
+/// Superadmins can access every crate (private unowned crates being accessible to nobody else).
+fn find_crate(
+    conn: &mut Connection,
+    canon_name: &str,
+    author_id: i64,
+    is_superadmin: bool,
+) -> Result<(i64, String, Option<String>), ApiError> {
+    let maybe_crate = crates::table
+        .select((crates::id, crates::name, crates::repository))
+        .filter(crates::canon_name.eq(canon_name))
+        .first::<(i64, String, Option<String>)>(conn)
+        .optional()?;
+    match maybe_crate {
+        Some(krate) if is_superadmin => Ok(krate),
+        Some(krate) if visibility::can_access(conn, krate.0, Some(author_id))? => Ok(krate),
+        _ => Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("no crates named '{canon_name}' could be found"),
+        )),
+    }
+}
+
+/// Route to list the crates without any owners (superadmins only), like the ones imported without their owners.
+pub(crate) async fn unowned(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+) -> Result<Json<UnownedCratesResponse>, ApiError> {
+    //? Is the user a superadmin?
+    if !utils::checks::is_superadmin(&state.general, &author) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "only superadmins can list the unowned crates",
+        ));
+    }
+
+    let crates = state
+        .db
+        .run(move |conn| {
+            let owned = crate_authors::table
+                .select(crate_authors::crate_id)
+                .filter(crate_authors::role.eq(OwnerRole::Owner.as_str()));
+            crates::table
+                .select((crates::name, crates::repository, crates::created_at))
+                .filter(crates::id.ne_all(owned))
+                .order_by(crates::canon_name.asc())
+                .load::<(String, Option<String>, String)>(conn)
+        })
+        .await?;
+
+    let crates = crates
+        .into_iter()
+        .map(|(name, repository, created_at)| UnownedCrate {
+            name,
+            repository,
+            created_at,
+        })
+        .collect();
+    Ok(Json(UnownedCratesResponse { crates }))
+}
+
+/// Route to get the instructions to claim an unowned crate.
+///
+/// The claim token is specific to the requesting author (and to the crate).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+) -> Result<Json<ClaimInfo>, ApiError> {
+    let canon_name = utils::canonical_name(name);
+    let author_id = author.id;
+    let is_superadmin = utils::checks::is_superadmin(&state.general, &author);
+
+    let looked_up = canon_name.clone();
+    let (owned, repository) = state
+        .db
+        .run(move |conn| {
+            let (crate_id, _, repository) = find_crate(conn, &looked_up, author_id, is_superadmin)?;
+            let owned = !claims::is_unowned(conn, crate_id)?;
+            Ok::<_, ApiError>((owned, repository))
+        })
+        .await?;
+
+    //? Can this crate be claimed by proving control of its repository?
+    let config = state.claims.as_ref().filter(|_| !owned);
+    let (token, proof_url) = match (config, repository.as_deref()) {
+        (Some(config), Some(repository)) => (
+            Some(claims::token(&canon_name, &author)),
+            Some(claims::proof_url(config, repository)),
+        ),
+        _ => (None, None),
+    };
+
+    Ok(Json(ClaimInfo {
+        owned,
+        self_claims: token.is_some(),
+        token,
+        proof_url,
+    }))
+}
+
+/// Route to claim an unowned crate, like the ones imported without their owners.
+///
+/// Superadmins can make any author (including themselves) the owner of an unowned crate.  
+/// Other authors can only claim it for themselves, by proving that they control the crate's repository
+/// (if enabled by the `[claims]` section of the configuration).
+pub(crate) async fn put(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Json(body): Json<ClaimBody>,
+) -> Result<Json<json::Value>, ApiError> {
+    let canon_name = utils::canonical_name(name);
+    let is_superadmin = utils::checks::is_superadmin(&state.general, &author);
+
+    //? Only superadmins can assign crates to other authors.
+    let user = body.user.map(|user| user.trim().to_string());
+    if user.as_ref().map_or(false, |user| *user != author.email) && !is_superadmin {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "only superadmins can assign a crate to another author",
+        ));
+    }
+
+    let (looked_up, author_id) = (canon_name.clone(), author.id);
+    let (crate_id, crate_name, repository, new_owner) = state
+        .db
+        .run(move |conn| {
+            let (crate_id, crate_name, repository) =
+                find_crate(conn, &looked_up, author_id, is_superadmin)?;
+
+            //? Is the crate really unowned?
+            if !claims::is_unowned(conn, crate_id)? {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    format!("the '{crate_name}' crate already has owners (ownership is managed by them)"),
+                ));
+            }
+
+            //? Who is going to be the new owner?
+            let new_owner = match user {
+                Some(email) => {
+                    let maybe_author = authors::table
+                        .filter(authors::email.eq(email.as_str()))
+                        .first::<Author>(conn)
+                        .optional()?;
+                    maybe_author.ok_or_else(|| {
+                        ApiError::new(
+                            StatusCode::NOT_FOUND,
+                            format!("no authors with the email '{email}' could be found"),
+                        )
+                    })?
+                }
+                None => authors::table.find(author_id).first::<Author>(conn)?,
+            };
+
+            Ok::<_, ApiError>((crate_id, crate_name, repository, new_owner))
+        })
+        .await?;
+
+    //? Can the requester prove that they control the crate's repository?
+    let details = if is_superadmin {
+        format!("assigned to {0}", new_owner.name)
+    } else {
+        let Some(config) = state.claims.as_ref() else {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "unowned crates can only be assigned by superadmins on this registry",
+            ));
+        };
+        let Some(repository) = repository else {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                format!("the '{crate_name}' crate has no repository to prove control of (only superadmins can assign it)"),
+            ));
+        };
+        let token = claims::token(&canon_name, &author);
+        if let Err(reason) = claims::verify(config, &repository, &token).await {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                format!("could not verify the control of the repository: {reason}"),
+            ));
+        }
+        format!(
+            "claimed by {0} (proved control of {repository})",
+            new_owner.name
+        )
+    };
+
+    let (new_owner_id, new_owner_name) = (new_owner.id, new_owner.name);
+    let audited = details.clone();
+    state
+        .db
+        .transaction(move |conn| {
+            //? Has the crate been claimed in the meantime?
+            if !claims::is_unowned(conn, crate_id)? {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    "this crate has been claimed in the meantime",
+                ));
+            }
+
+            claims::assign(conn, crate_id, new_owner_id)?;
+            let action = AuditAction::ClaimOwnership;
+            audit::record(conn, crate_id, author_id, action, None, Some(&audited))?;
+            Ok(())
+        })
+        .await?;
+
+    tracing::info!("'{canon_name}' is now owned by author #{new_owner_id} ({details})");
+    page_cache::invalidate(&state, &canon_name);
+
+    Ok(Json(json!({
+        "ok": true,
+        "msg": format!("{new_owner_name} is now an owner of {crate_name}"),
+    })))
+}
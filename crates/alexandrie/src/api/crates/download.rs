@@ -1,50 +1,265 @@
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
+use axum::headers::{
+    AcceptRanges, ContentLength, ContentRange, ContentType, ETag, IfModifiedSince, IfNoneMatch,
+    IfRange, LastModified,
+};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::TypedHeader;
 use bytes::Bytes;
+use diesel::dsl as sql;
 use diesel::prelude::*;
 use semver::Version;
 
-use alexandrie_storage::Store;
+use alexandrie_index::Indexer;
+use alexandrie_storage::{CrateMetadata, Store};
 
 use crate::config::AppState;
 use crate::db::schema::*;
+use crate::db::Connection;
 use crate::error::{AlexError, ApiError};
 use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::page_cache;
+use crate::utils::response::conditional;
+use crate::utils::response::range::{self, UnsatisfiableRange};
+use crate::utils::versions;
+use crate::utils::visibility;
+
+/// A crate version's tarball, as found by the download routes (without reading it).
+struct Tarball {
+    /// The crate's ID.
+    crate_id: i64,
+    /// The crate's (non-canonical) name.
+    name: String,
+    /// The crate's download count.
+    downloads: i64,
+    /// The tarball's entity tag (derived from its checksum).
+    etag: ETag,
+    /// The tarball's metadata, from the store.
+    metadata: CrateMetadata,
+}
+
+/// Looks up a crate version's tarball (without reading it from the store).
+///
+/// Private crates are only found for their owners (or collaborators).
+/// Tarballs pruned by the retention policy are reported with a `410 Gone`.
+fn find_tarball(
+    state: &AppState,
+    conn: &mut Connection,
+    name: &str,
+    version: &Version,
+    author_id: Option<i64>,
+) -> Result<Tarball, ApiError> {
+    //? Fetch the download count for this crate.
+    let crate_info = state.db.timed("crate.lookup", || {
+        crates::table
+            .select((crates::id, crates::name, crates::downloads))
+            .filter(crates::canon_name.eq(name))
+            .first::<(i64, String, i64)>(conn)
+            .optional()
+    })?;
+
+    //? Is this crate accessible to the user (if it is private)?
+    let crate_info = match crate_info {
+        Some((id, _, _)) if !visibility::can_access(conn, id, author_id)? => None,
+        crate_info => crate_info,
+    };
+    let Some((crate_id, name, downloads)) = crate_info else {
+        let name = String::from(name);
+        return Err(ApiError::from(AlexError::CrateNotFound { name }));
+    };
+
+    //? Has this version's tarball been pruned by the retention policy?
+    let pruned: bool = sql::select(sql::exists(
+        pruned_tarballs::table
+            .filter(pruned_tarballs::crate_id.eq(crate_id))
+            .filter(pruned_tarballs::version.eq(version.to_string())),
+    ))
+    .get_result(conn)?;
+    if pruned {
+        let detail = format!(
+            "the tarball of `{name}#{version}` is no longer available (it has been removed by the registry's retention policy)"
+        );
+        return Err(ApiError::new(StatusCode::GONE, detail));
+    }
+
+    //? Get the tarball's validators (its checksum and modification date).
+    let record = state
+        .index
+        .all_records(&name)?
+        .into_iter()
+        .find(|record| &record.vers == version)
+        .ok_or_else(|| AlexError::CrateNotFound { name: name.clone() })?;
+    let metadata = state.storage.crate_metadata(&name, version.clone())?;
+    let etag = conditional::etag(record.cksum.as_str());
+
+    Ok(Tarball {
+        crate_id,
+        name,
+        downloads,
+        etag,
+        metadata,
+    })
+}
 
 /// Route to download a crate's tarball (used by `cargo build`).
 ///
-/// The response is streamed, for performance and memory footprint reasons.
+/// The response is streamed, for performance and memory footprint reasons.  
+/// Versions whose tarball got pruned by the retention policy are answered with a `410 Gone`.  
+/// Conditional requests are supported, using the tarball's checksum as its entity tag.  
+/// Single byte ranges can be requested (using the `Range` header), to resume interrupted downloads.  
+/// Whole tarballs are redirected to the store, if it can serve them by itself (like Azure Blob Storage, using signed URLs).  
+/// Private crates can only be downloaded using the token of one of their owners (or collaborators).
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Path((name, version)): Path<(String, Version)>,
-) -> Result<Bytes, ApiError> {
+    user: Option<Auth>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    if_modified_since: Option<TypedHeader<IfModifiedSince>>,
+    if_range: Option<TypedHeader<IfRange>>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let name = utils::canonical_name(name);
+    let range_header = headers
+        .get(header::RANGE)
+        .map(|value| value.to_str().unwrap_or_default().to_string());
 
     // state.index.refresh()?;
 
     let db = &state.db;
     let state = Arc::clone(&state);
     let transaction = db.transaction(move |conn| {
-        //? Fetch the download count for this crate.
-        let crate_info = crates::table
-            .select((crates::name, crates::downloads))
-            .filter(crates::canon_name.eq(name.as_str()))
-            .first::<(String, i64)>(conn)
-            .optional()?;
-
-        if let Some((name, downloads)) = crate_info {
-            //? Increment this crate's download count.
-            diesel::update(crates::table.filter(crates::name.eq(name.as_str())))
-                .set(crates::downloads.eq(downloads + 1))
-                .execute(conn)?;
-
-            let krate = state.storage.get_crate(&name, version)?;
-            Ok(Bytes::from(krate))
-        } else {
-            Err(ApiError::from(AlexError::CrateNotFound { name }))
+        let author_id = user.map(|Auth(author)| author.id);
+        let Tarball {
+            crate_id: id,
+            name,
+            downloads,
+            etag,
+            metadata,
+        } = find_tarball(state.as_ref(), conn, name.as_str(), &version, author_id)?;
+        let last_modified = metadata.last_modified.map(LastModified::from);
+
+        //? Is the client's cached copy still up-to-date?
+        let not_modified = conditional::is_not_modified(
+            if_none_match.as_ref().map(|TypedHeader(header)| header),
+            if_modified_since.as_ref().map(|TypedHeader(header)| header),
+            &etag,
+            metadata.last_modified,
+        );
+        if not_modified {
+            let headers = (TypedHeader(etag), last_modified.map(TypedHeader));
+            return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+        }
+
+        //? Is only a part of the tarball requested (and is it still the same tarball as the one of the `If-Range` header)?
+        let size = metadata.size;
+        let is_same_tarball = if_range.map_or(true, |TypedHeader(if_range)| {
+            !if_range.is_modified(Some(&etag), last_modified.as_ref())
+        });
+        let range = match range_header.filter(|_| is_same_tarball) {
+            Some(header) => match range::parse_byte_range(header.as_str(), size) {
+                Ok(range) => range,
+                Err(UnsatisfiableRange) => {
+                    let detail = format!(
+                        "the requested range is not satisfiable (the tarball of `{name}#{version}` is {size} bytes long)"
+                    );
+                    let headers = TypedHeader(ContentRange::unsatisfied_bytes(size));
+                    let err = ApiError::new(StatusCode::RANGE_NOT_SATISFIABLE, detail);
+                    return Ok((headers, err).into_response());
+                }
+            },
+            None => None,
+        };
+
+        //? Increment this crate's and this version's download counts (resumed downloads are not counted again).
+        //? The version may not be recorded yet (like if it was published before versions were recorded).
+        if range.as_ref().map_or(true, |range| range.start == 0) {
+            state.db.timed("crate.download_count", || {
+                diesel::update(crates::table.filter(crates::id.eq(id)))
+                    .set(crates::downloads.eq(downloads + 1))
+                    .execute(conn)?;
+                let version_id = versions::ensure_recorded(conn, id, version.to_string().as_str())?;
+                diesel::update(crate_versions::table.find(version_id))
+                    .set(crate_versions::downloads.eq(crate_versions::downloads + 1))
+                    .execute(conn)
+            })?;
+            state.metrics.record_download();
+
+            //? The crate's page shows its download count, so its cached variants will soon be stale.
+            page_cache::downloaded(state.as_ref(), name.as_str());
+        }
+
+        let headers = (
+            TypedHeader(etag),
+            last_modified.map(TypedHeader),
+            TypedHeader(AcceptRanges::bytes()),
+        );
+        if let Some(range) = range {
+            let content_range = ContentRange::bytes(range.clone(), size)
+                .expect("the range should have been validated against the tarball's size");
+            let krate = state.storage.get_crate_range(&name, version, range)?;
+            let headers = (headers, TypedHeader(content_range));
+            return Ok((StatusCode::PARTIAL_CONTENT, headers, Bytes::from(krate)).into_response());
         }
+
+        //? Can the store serve the tarball by itself?
+        if let Some(url) = state.storage.download_url(&name, version.clone())? {
+            return Ok((headers, Redirect::temporary(url.as_str())).into_response());
+        }
+
+        let krate = state.storage.get_crate(&name, version)?;
+        Ok((headers, Bytes::from(krate)).into_response())
     });
 
     transaction.await.map_err(ApiError::from)
 }
+
+/// Route to check a crate's tarball, without downloading it (like its existence or its size).
+///
+/// The response has the same headers as the one of a full download (`GET`), but without any body.  
+/// Only the store's metadata about the tarball is looked up (the tarball itself is not read),
+/// and the download counts are left untouched.  
+/// Conditional requests are supported, just like for downloads.
+pub(crate) async fn head(
+    State(state): State<Arc<AppState>>,
+    Path((name, version)): Path<(String, Version)>,
+    user: Option<Auth>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    if_modified_since: Option<TypedHeader<IfModifiedSince>>,
+) -> Result<Response, ApiError> {
+    let name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
+
+    let db = &state.db;
+    let state = Arc::clone(&state);
+    db.run(move |conn| {
+        let tarball = find_tarball(state.as_ref(), conn, name.as_str(), &version, author_id)?;
+        let metadata = tarball.metadata;
+        let last_modified = metadata.last_modified.map(LastModified::from);
+
+        //? Is the client's cached copy still up-to-date?
+        let not_modified = conditional::is_not_modified(
+            if_none_match.as_ref().map(|TypedHeader(header)| header),
+            if_modified_since.as_ref().map(|TypedHeader(header)| header),
+            &tarball.etag,
+            metadata.last_modified,
+        );
+        if not_modified {
+            let headers = (TypedHeader(tarball.etag), last_modified.map(TypedHeader));
+            return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+        }
+
+        let headers = (
+            TypedHeader(tarball.etag),
+            last_modified.map(TypedHeader),
+            TypedHeader(AcceptRanges::bytes()),
+            TypedHeader(ContentLength(metadata.size)),
+            TypedHeader(ContentType::octet_stream()),
+        );
+        Ok(headers.into_response())
+    })
+    .await
+}
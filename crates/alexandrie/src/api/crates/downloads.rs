@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::download_stats::{self, DATE_FORMAT};
+use crate::error::{ApiError, Error};
+use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::visibility;
+
+/// How many of the most recently published versions get their downloads broken down.
+const BROKEN_DOWN_VERSIONS: usize = 5;
+
+/// The default number of days covered by the statistics.
+const DEFAULT_WINDOW: i64 = 90;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// The first day covered by the statistics (as `YYYY-MM-DD`).
+    pub since: Option<String>,
+    /// The last day covered by the statistics (as `YYYY-MM-DD`).
+    pub until: Option<String>,
+}
+
+/// The downloads of a crate version for a given day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct VersionDownloadsEntry {
+    /// The version record's ID.
+    pub version: i64,
+    /// The related version.
+    pub num: String,
+    /// The number of downloads.
+    pub downloads: i64,
+    /// The day (as `YYYY-MM-DD`).
+    pub date: String,
+}
+
+/// The downloads of several crate versions for a given day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DownloadsEntry {
+    /// The day (as `YYYY-MM-DD`).
+    pub date: String,
+    /// The number of downloads.
+    pub downloads: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DownloadsMeta {
+    /// The daily downloads of the versions which are not broken down.
+    pub extra_downloads: Vec<DownloadsEntry>,
+    /// The daily downloads across every version.
+    pub total_downloads: Vec<DownloadsEntry>,
+}
+
+/// Response body for this route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ResponseBody {
+    /// The daily downloads of the most recently published versions.
+    pub version_downloads: Vec<VersionDownloadsEntry>,
+    pub meta: DownloadsMeta,
+}
+
+fn parse_date(name: &str, value: Option<String>) -> Result<Option<NaiveDate>, ApiError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    NaiveDate::parse_from_str(value.as_str(), DATE_FORMAT)
+        .map(Some)
+        .map_err(|_| {
+            ApiError::msg(format!(
+                "invalid `{name}` date: '{value}' (expected `YYYY-MM-DD`)"
+            ))
+        })
+}
+
+fn entries(days: BTreeMap<NaiveDate, i64>) -> Vec<DownloadsEntry> {
+    days.into_iter()
+        .map(|(date, downloads)| DownloadsEntry {
+            date: date.format(DATE_FORMAT).to_string(),
+            downloads,
+        })
+        .collect()
+}
+
+/// Route to get the daily downloads of a crate (by version, and across every version).
+///
+/// The statistics cover the last 90 days, unless another window is requested (using `since` and `until`).
+/// Private crates are only visible using the token of one of their owners (or collaborators).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+    user: Option<Auth>,
+) -> Result<Json<ResponseBody>, ApiError> {
+    if state.download_stats.is_none() {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            "download statistics are not enabled on this registry",
+        ));
+    }
+
+    let name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
+
+    //? Which window of days is requested?
+    let until = parse_date("until", params.until)?.unwrap_or_else(|| Utc::now().date_naive());
+    let since = parse_date("since", params.since)?
+        .unwrap_or_else(|| until - chrono::Duration::days(DEFAULT_WINDOW - 1));
+    if since > until {
+        return Err(ApiError::msg(
+            "the `since` date must not be later than the `until` date",
+        ));
+    }
+
+    //? Fetch the crate's daily downloads (if it is accessible to the user).
+    let canon_name = name.clone();
+    let downloads = state
+        .db
+        .run(move |conn| {
+            let crate_id = crates::table
+                .select(crates::id)
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first::<i64>(conn)
+                .optional()?;
+            match crate_id {
+                Some(id) if visibility::can_access(conn, id, author_id)? => {
+                    let downloads = download_stats::daily_downloads(conn, id, since, until)?;
+                    Ok::<_, Error>(Some(downloads))
+                }
+                _ => Ok(None),
+            }
+        })
+        .await?;
+    let Some(mut downloads) = downloads else {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("no crates named '{name}' could be found"),
+        ));
+    };
+
+    //? Only the most recently published versions are broken down, the other ones are aggregated together.
+    downloads.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+    let mut version_downloads = Vec::new();
+    let mut extra_downloads = BTreeMap::new();
+    let mut total_downloads = BTreeMap::new();
+    for (idx, version) in downloads.into_iter().enumerate() {
+        for (date, count) in version.days {
+            *total_downloads.entry(date).or_insert(0) += count;
+            if idx < BROKEN_DOWN_VERSIONS {
+                version_downloads.push(VersionDownloadsEntry {
+                    version: version.version_id,
+                    num: version.version.clone(),
+                    downloads: count,
+                    date: date.format(DATE_FORMAT).to_string(),
+                });
+            } else {
+                *extra_downloads.entry(date).or_insert(0) += count;
+            }
+        }
+    }
+    version_downloads.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.num.cmp(&b.num)));
+
+    Ok(Json(ResponseBody {
+        version_downloads,
+        meta: DownloadsMeta {
+            extra_downloads: entries(extra_downloads),
+            total_downloads: entries(total_downloads),
+        },
+    }))
+}
@@ -0,0 +1,81 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::audit::{self, CrateEvent};
+use crate::utils::auth::api::Auth;
+use crate::utils::pagination::{Page, PageMeta};
+use crate::utils::visibility;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// The page number to list (starting from 1).
+    pub page: Option<NonZeroUsize>,
+    /// How many events to list per page.
+    pub per_page: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CrateEventsResponse {
+    pub events: Vec<CrateEvent>,
+    pub meta: PageMeta,
+}
+
+/// Route to list the events of a crate (publications, yanks, un-yanks and ownership changes), from its audit log.
+///
+/// Only the owners of the crate can see its events.
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<CrateEventsResponse>, ApiError> {
+    let name = utils::canonical_name(name);
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+
+    let transaction = state.db.transaction(move |conn| {
+        //? Does this crate exists (and is it accessible to the user)?
+        let maybe_crate_id = crates::table
+            .select(crates::id)
+            .filter(crates::canon_name.eq(name.as_str()))
+            .first::<i64>(conn)
+            .optional()?;
+        let crate_id = match maybe_crate_id {
+            Some(crate_id) if visibility::can_access(conn, crate_id, Some(author.id))? => crate_id,
+            _ => {
+                return Err(ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no crates named '{name}' could be found"),
+                ));
+            }
+        };
+
+        //? Is the user an owner of this crate?
+        if !utils::checks::is_crate_owner(conn, name.as_str(), author.id)? {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "only the owners of a crate can see its events",
+            ));
+        }
+
+        //? Get the requested page of the crate's events.
+        let total = audit::count_crate_events(conn, crate_id)? as usize;
+        let events = audit::crate_events(conn, crate_id, page.per_page, page.offset())?;
+
+        Ok(Json(CrateEventsResponse {
+            events,
+            meta: page.meta(total),
+        }))
+    });
+
+    transaction.await
+}
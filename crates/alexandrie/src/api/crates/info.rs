@@ -1,15 +1,36 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use alexandrie_index::error::Error as IndexError;
+use alexandrie_index::Indexer;
+
 use crate::config::AppState;
 use crate::db::models::Crate;
 use crate::db::schema::*;
 use crate::error::ApiError;
 use crate::utils;
+use crate::utils::aliases;
+use crate::utils::auth::api::Auth;
+use crate::utils::licenses::{self, VersionLicense};
+use crate::utils::pagination::{Page, PageMeta};
+use crate::utils::provenance::{self, Provenance};
+use crate::utils::versions::{self, TimelineEntry};
+use crate::utils::visibility::{self, CrateVisibility};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryParams {
+    /// The page of versions to return (starting from 1, the most recently published versions first).
+    pub versions_page: Option<NonZeroUsize>,
+    /// How many versions to return per page.
+    pub versions_per_page: Option<NonZeroUsize>,
+}
 
 /// Response body for this route.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -22,6 +43,8 @@ pub struct ResponseBody {
     pub repository: Option<String>,
     /// The crate's documentation link.
     pub documentation: Option<String>,
+    /// The native library linked by the crate (its `links` manifest key).
+    pub links: Option<String>,
     /// The crate's download count.
     pub downloads: i64,
     /// The crate's creation date.
@@ -32,30 +55,76 @@ pub struct ResponseBody {
     pub keywords: Vec<String>,
     /// The crate's categories.
     pub categories: Vec<String>,
+    /// The features of the crate's latest version (and what they enable).
+    pub features: BTreeMap<String, Vec<String>>,
+    /// The license of the crate's latest version (if recorded).
+    pub license: Option<VersionLicense>,
+    /// Where the crate's latest version was published from (if recorded).
+    pub provenance: Option<Provenance>,
+    /// The requested page of the crate's versions, the most recently published first.
+    pub versions: Vec<TimelineEntry>,
+    /// The pagination metadata of the crate's versions.
+    pub versions_meta: PageMeta,
+    /// The link to the next page of the crate's versions (if there is one).
+    pub versions_next: Option<String>,
+    /// The crate's visibility.
+    pub visibility: CrateVisibility,
+    /// The crate's aliases (like its former names, in their canonical form).
+    pub aliases: Vec<String>,
+    /// The alias the crate was requested by (if it was not requested by its name), meaning it has been renamed.
+    pub renamed_from: Option<String>,
 }
 
 /// Route to get information about a crate.
+///
+/// Private crates are only visible using the token of one of their owners (or collaborators).
+///
+/// Crates can also be requested by one of their aliases (like their former names), which gets reported in `renamed_from`.
+///
+/// The crate's versions are paginated, to keep responses reasonably sized for crates with many releases.
+/// This only concerns this JSON API: the crate index (which Cargo needs in full) always lists every version.
 pub async fn get(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+    user: Option<Auth>,
 ) -> Result<Json<ResponseBody>, ApiError> {
     let name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
 
     let db = &state.db;
 
-    //? Fetch the crate data from the database.
+    //? Fetch the crate data from the database, by name or by alias (if accessible to the user).
     let maybe_krate = db
-        .run(move |conn| {
-            crates::table
+        .run_timed("crate.lookup", move |conn| {
+            let maybe_krate = crates::table
                 .filter(crates::canon_name.eq(name.as_str()))
                 .first::<Crate>(conn)
-                .optional()
+                .optional()?;
+            let (maybe_krate, renamed_from) = match maybe_krate {
+                Some(krate) => (Some(krate), None),
+                None => match aliases::resolve(conn, name.as_str())? {
+                    Some((crate_id, _)) => {
+                        let krate = crates::table.find(crate_id).first::<Crate>(conn)?;
+                        (Some(krate), Some(name))
+                    }
+                    None => (None, None),
+                },
+            };
+            match maybe_krate {
+                Some(krate) if !visibility::can_access(conn, krate.id, author_id)? => Ok(None),
+                Some(krate) => {
+                    let aliases = aliases::list(conn, krate.id)?;
+                    Ok(Some((krate, aliases, renamed_from)))
+                }
+                None => Ok::<_, diesel::result::Error>(None),
+            }
         })
         .await?;
 
     //? Was a crate found ?
-    let Some(krate) = maybe_krate else {
-        return Err(ApiError::msg("the crate could not be found"));
+    let Some((krate, aliases, renamed_from)) = maybe_krate else {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, "the crate could not be found"));
     };
 
     //? Fetch the crate's keywords
@@ -82,13 +151,69 @@ pub async fn get(
         })
         .await?;
 
+    //? Fetch the crate's provenance
+    let crate_id = krate.id;
+    let provenance = db
+        .run(move |conn| provenance::latest(conn, crate_id))
+        .await?;
+
+    //? Fetch the crate's versions (and the features of its latest version) from the index.
+    let crate_name = krate.name.clone();
+    let (latest, records) = utils::run_blocking(&state, move |state| {
+        let latest = state.index.latest_record(crate_name.as_str())?;
+        let records = state.index.all_records(crate_name.as_str())?;
+        Ok::<_, IndexError>((latest, records))
+    })
+    .await?;
+
+    //? Fetch the license of the crate's latest version (its text is only included for versions without a license expression).
+    let crate_id = krate.id;
+    let latest_version = latest.vers.to_string();
+    let license = db
+        .run(move |conn| licenses::get(conn, crate_id, latest_version.as_str()))
+        .await?;
+
+    //? Build the crate's version timeline.
+    let crate_id = krate.id;
+    let versions = db
+        .run_timed("crate.versions", move |conn| {
+            versions::timeline(conn, crate_id, &records)
+        })
+        .await?;
+
+    //? Only return the requested page of versions (along with a link to the next one).
+    let page = Page::with_default(
+        &state.pagination,
+        state.pagination.default_versions_per_page,
+        params.versions_page,
+        params.versions_per_page,
+    );
+    let total = versions.len();
+    let versions_meta = page.meta(total);
+    let versions_next = versions_meta.next_page.map(|next_page| {
+        format!(
+            "/api/v1/crates/{0}?versions_page={next_page}&versions_per_page={1}",
+            krate.name, page.per_page,
+        )
+    });
+
     Ok(Json(ResponseBody {
         keywords,
         categories,
+        features: latest.all_features().into_iter().collect(),
+        license,
+        provenance,
+        versions: page.slice(versions),
+        versions_meta,
+        versions_next,
+        visibility: CrateVisibility::parse(krate.visibility.as_str()),
+        aliases,
+        renamed_from,
         name: krate.name,
         description: krate.description,
         repository: krate.repository,
         documentation: krate.documentation,
+        links: krate.links,
         downloads: krate.downloads,
         created_at: krate.created_at,
         updated_at: krate.updated_at,
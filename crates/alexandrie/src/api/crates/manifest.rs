@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::headers::ContentType;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::TypedHeader;
+use diesel::prelude::*;
+use semver::Version;
+
+use alexandrie_index::error::Error as IndexError;
+use alexandrie_index::Indexer;
+use alexandrie_storage::error::Error as StorageError;
+use alexandrie_storage::Store;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::{AlexError, ApiError};
+use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::visibility;
+
+/// Route to get the manifest (`Cargo.toml` file) of a given crate version, as it was published.
+///
+/// Manifests are only available for versions published while the store supported storing them.  
+/// The manifests of private crates are only available using the token of one of their owners (or collaborators).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path((name, version)): Path<(String, Version)>,
+    user: Option<Auth>,
+) -> Result<Response, ApiError> {
+    let canon_name = utils::canonical_name(name.as_str());
+    let author_id = user.map(|Auth(author)| author.id);
+
+    //? Does this crate exist (and is it accessible to the user)?
+    let maybe_name: Option<String> = state
+        .db
+        .run(move |conn| {
+            let maybe_crate = crates::table
+                .select((crates::id, crates::name))
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first::<(i64, String)>(conn)
+                .optional()?;
+            match maybe_crate {
+                Some((id, _)) if !visibility::can_access(conn, id, author_id)? => Ok(None),
+                maybe_crate => Ok::<_, diesel::result::Error>(maybe_crate.map(|(_, name)| name)),
+            }
+        })
+        .await?;
+    let Some(name) = maybe_name else {
+        return Err(AlexError::CrateNotFound { name }.into());
+    };
+
+    let manifest = utils::run_blocking(&state, move |state| {
+        //? Does this version exist?
+        let exists = state
+            .index
+            .all_records(&name)?
+            .into_iter()
+            .any(|record| record.vers == version);
+        if !exists {
+            let detail = format!("no version `{version}` of crate '{name}' found");
+            return Ok::<_, IndexError>(Err(detail));
+        }
+
+        match state.storage.get_manifest(&name, version.clone()) {
+            Ok(manifest) => Ok(Ok(manifest)),
+            Err(StorageError::Unsupported(_)) => {
+                let detail = String::from("manifests are not stored by this registry");
+                Ok(Err(detail))
+            }
+            Err(err) => {
+                tracing::debug!("could not get the manifest of `{name}#{version}`: {err}");
+                let detail = format!("the manifest of `{name}#{version}` is not available");
+                Ok(Err(detail))
+            }
+        }
+    })
+    .await?;
+
+    match manifest {
+        Ok(manifest) => Ok((TypedHeader(ContentType::text_utf8()), manifest).into_response()),
+        Err(detail) => Err(ApiError::new(StatusCode::NOT_FOUND, detail)),
+    }
+}
@@ -1,16 +1,34 @@
+/// Crate aliases endpoint, managed by superadmins (eg. "/api/v1/crates/\<name\>/aliases").
+pub mod aliases;
+/// Bulk yanking endpoint (eg. "/api/v1/crates/\<name\>/yank").
+pub mod bulk_yank;
+/// Crate ownership claims endpoints, for unowned crates (eg. "/api/v1/crates/\<name\>/claim" or "/api/v1/crates/unowned").
+pub mod claim;
 /// Crate downloads endpoint (eg. "/api/v1/crates/\<name\>/\<version\>/download").
 pub mod download;
+/// Download statistics endpoint (eg. "/api/v1/crates/\<name\>/downloads").
+pub mod downloads;
+/// Crate events endpoint, for owners (eg. "/api/v1/crates/\<name\>/events").
+pub mod events;
 /// Crate information endpoints (eg. "/api/v1/crates/\<name\>").
 pub mod info;
+/// Crate manifests endpoint (eg. "/api/v1/crates/\<name\>/\<version\>/Cargo.toml").
+pub mod manifest;
 /// Owners management endpoint (eg. "/api/v1/crates/\<name\>/owners").
 pub mod owners;
-/// Publication endpoint (eg. "/api/v1/crates/new").
+/// Publication endpoints (eg. "/api/v1/crates/new" or "/api/v1/crates/new/validate").
 pub mod publish;
+/// Reverse dependencies endpoint (eg. "/api/v1/crates/\<name\>/reverse_dependencies").
+pub mod reverse_dependencies;
 /// Search endpoint (eg. "/api/v1/crates?q=\<term\>").
 pub mod search;
 /// Suggestion endpoint (eg. "/api/v1/crates/suggest?q=\<term\>").
 pub mod suggest;
+/// Tarball replacement endpoint, for superadmins (eg. "/api/v1/crates/\<name\>/\<version\>/tarball").
+pub mod tarball;
 /// Crate unyanking endpoint (eg. "/api/v1/crates/\<name\>/\<version\>/unyank").
 pub mod unyank;
+/// Crate visibility endpoint (eg. "/api/v1/crates/\<name\>/visibility").
+pub mod visibility;
 /// Crate yanking endpoint (eg. "/api/v1/crates/\<name\>/\<version\>/yank").
 pub mod yank;
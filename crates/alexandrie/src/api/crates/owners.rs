@@ -1,17 +1,23 @@
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use json::json;
 use serde::{Deserialize, Serialize};
 
 use crate::config::AppState;
-use crate::db::models::{Author, NewCrateAuthor};
+use crate::db::models::Author;
 use crate::db::schema::*;
 use crate::error::ApiError;
 use crate::utils;
+use crate::utils::audit::{self, AuditAction};
 use crate::utils::auth::api::Auth;
+use crate::utils::invitations;
+use crate::utils::owners::OwnerRole;
+use crate::utils::page_cache;
+use crate::utils::visibility;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct OwnerListResponse {
@@ -23,12 +29,16 @@ pub(crate) struct OwnerListEntry {
     pub id: i64,
     pub login: String,
     pub name: String,
+    pub role: OwnerRole,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct OwnerAddBody {
     /// Owners' emails to add.
     pub users: Vec<String>,
+    /// The role to invite them to take (full owners by default).
+    #[serde(default)]
+    pub role: OwnerRole,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -40,37 +50,54 @@ pub(crate) struct OwnerDeleteBody {
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
+    user: Option<Auth>,
 ) -> Result<Json<OwnerListResponse>, ApiError> {
     let name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
 
     let db = &state.db;
     let transaction = db.transaction(move |conn| {
-        //? Does this crate exists?
-        let exists = utils::checks::crate_exists(conn, name.as_str())?;
-        if !exists {
-            return Err(ApiError::msg(format!(
-                "no crates named '{name}' could be found",
-            )));
+        //? Does this crate exists (and is it accessible to the user)?
+        let maybe_crate_id = crates::table
+            .select(crates::id)
+            .filter(crates::canon_name.eq(name.as_str()))
+            .first::<i64>(conn)
+            .optional()?;
+        let accessible = match maybe_crate_id {
+            Some(crate_id) => visibility::can_access(conn, crate_id, author_id)?,
+            None => false,
+        };
+        if !accessible {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("no crates named '{name}' could be found",),
+            ));
         }
 
         //? Get all authors of this crate.
         let authors = crate_authors::table
             .inner_join(authors::table)
             .inner_join(crates::table)
-            .select(authors::all_columns)
+            .select((authors::all_columns, crate_authors::role))
             .filter(crates::canon_name.eq(name.as_str()))
-            .load::<Author>(conn)?;
+            .load::<(Author, String)>(conn)?;
 
         let users = authors
             .into_iter()
-            .map(|author| {
+            .map(|(author, role)| {
                 let Author {
                     id,
                     email: login,
                     name,
                     ..
                 } = author;
-                OwnerListEntry { id, login, name }
+                let role = OwnerRole::parse(role.as_str());
+                OwnerListEntry {
+                    id,
+                    login,
+                    name,
+                    role,
+                }
             })
             .collect();
 
@@ -88,9 +115,13 @@ pub(crate) async fn put(
 ) -> Result<Json<json::Value>, ApiError> {
     let name = utils::canonical_name(name);
 
-    let OwnerAddBody { users: new_authors } = body;
+    let OwnerAddBody {
+        users: new_authors,
+        role,
+    } = body;
 
     let db = &state.db;
+    let state = Arc::clone(&state);
     let transaction = db.transaction(move |conn| {
         //? Get this crate's ID.
         let maybe_crate_id = crates::table
@@ -99,56 +130,60 @@ pub(crate) async fn put(
             .first::<i64>(conn)
             .optional()?;
         let Some(crate_id) = maybe_crate_id else {
-            return Err(ApiError::msg(format!(
+            return Err(ApiError::new(StatusCode::NOT_FOUND, format!(
                 "no crates named '{name}' could be found"
             )));
         };
 
-        //? Get all authors of this crate.
+        //? Get all authors of this crate (along with their roles).
         let crate_authors = crate_authors::table
-            .inner_join(authors::table)
-            .inner_join(crates::table)
-            .select(authors::id)
-            .filter(crates::id.eq(crate_id))
-            .load::<i64>(conn)?;
+            .select((crate_authors::author_id, crate_authors::role))
+            .filter(crate_authors::crate_id.eq(crate_id))
+            .load::<(i64, String)>(conn)?;
 
-        //? Check if user is one of these authors.
-        if !crate_authors.contains(&author.id) {
-            return Err(ApiError::msg("you are not an author of this crate"));
+        //? Check if user is one of the owners (collaborators cannot manage owners).
+        let is_owner = crate_authors.iter().any(|(id, role)| {
+            *id == author.id && OwnerRole::parse(role.as_str()) == OwnerRole::Owner
+        });
+        if !is_owner {
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "you are not an owner of this crate"));
         }
 
+        //? Authors already having the requested role are left as they are.
+        let unchanged: Vec<i64> = crate_authors
+            .iter()
+            .filter(|(_, current)| OwnerRole::parse(current.as_str()) == role)
+            .map(|(id, _)| *id)
+            .collect();
+
         //? Get all registered authors which:
-        //?   - are not authors of this crate.
+        //?   - do not already have the requested role for this crate.
         //?   - are one of the requested new authors.
         let new_authors = authors::table
             .select((authors::id, authors::name))
-            .filter(authors::id.ne_all(crate_authors.as_slice()))
+            .filter(authors::id.ne_all(unchanged.as_slice()))
             .filter(authors::email.eq_any(new_authors.as_slice()))
             .load::<(i64, String)>(conn)?;
 
-        let (new_authors_ids, new_authors_names) = {
-            let len = new_authors.len();
-            let (mut fsts, mut snds) = (Vec::with_capacity(len), Vec::with_capacity(len));
-            for (fst, snd) in new_authors.into_iter() {
-                fsts.push(fst);
-                snds.push(snd);
+        //? Check if there would remain at least one owner for this crate (once every invitation is accepted).
+        if role == OwnerRole::Collaborator {
+            let remaining_owners = crate_authors
+                .iter()
+                .filter(|(_, current)| OwnerRole::parse(current.as_str()) == OwnerRole::Owner)
+                .filter(|(id, _)| !new_authors.iter().any(|(new_id, _)| new_id == id))
+                .count();
+            if remaining_owners == 0 {
+                return Err(ApiError::msg("cannot leave the crate without any owners"));
             }
-            (fsts, snds)
-        };
-
-        //? Prepare requests structures.
-        let new_authors = new_authors_ids
-            .into_iter()
-            .map(|author_id| NewCrateAuthor {
-                crate_id,
-                author_id,
-            })
-            .collect::<Vec<_>>();
+        }
 
-        //? Insert the new authors.
-        diesel::insert_into(crate_authors::table)
-            .values(new_authors)
-            .execute(conn)?;
+        //? Invite the new authors (they only become owners once they accept).
+        let lifetime = state.general.owner_invitation_lifetime;
+        let mut new_authors_names = Vec::with_capacity(new_authors.len());
+        for (invitee_id, invitee_name) in new_authors {
+            invitations::invite(conn, crate_id, invitee_id, author.id, role, lifetime)?;
+            new_authors_names.push(invitee_name);
+        }
 
         let authors_list = match new_authors_names.as_slice() {
             [] => String::new(),
@@ -162,7 +197,10 @@ pub(crate) async fn put(
 
         Ok(Json(json!({
             "ok": true,
-            "msg": format!("{authors_list} has been added as authors of {name}"),
+            "msg": match role {
+                OwnerRole::Owner => format!("{authors_list} has been invited to be an owner of {name}"),
+                OwnerRole::Collaborator => format!("{authors_list} has been invited to be a collaborator of {name}"),
+            },
         })))
     });
 
@@ -179,6 +217,7 @@ pub(crate) async fn delete(
 
     let OwnerDeleteBody { users: old_authors } = body;
 
+    let canon_name = name.clone();
     let db = &state.db;
     let transaction = db.transaction(move |conn| {
         //? Get this crate's ID.
@@ -188,22 +227,29 @@ pub(crate) async fn delete(
             .first::<i64>(conn)
             .optional()?;
         let Some(crate_id) = maybe_crate_id else {
-            return Err(ApiError::msg(format!(
+            return Err(ApiError::new(StatusCode::NOT_FOUND, format!(
                 "no crates named '{name}' could be found"
             )));
         };
 
-        //? Get all authors of this crate.
+        //? Get all authors of this crate (along with their roles).
         let crate_authors = crate_authors::table
-            .inner_join(authors::table)
-            .inner_join(crates::table)
-            .select(authors::id)
-            .filter(crates::id.eq(crate_id))
-            .load::<i64>(conn)?;
-
-        //? Check if user is one of these authors.
-        if !crate_authors.contains(&author.id) {
-            return Err(ApiError::msg("you are not an author of this crate"));
+            .select((crate_authors::author_id, crate_authors::role))
+            .filter(crate_authors::crate_id.eq(crate_id))
+            .load::<(i64, String)>(conn)?;
+        let owners: Vec<i64> = crate_authors
+            .iter()
+            .filter(|(_, role)| OwnerRole::parse(role.as_str()) == OwnerRole::Owner)
+            .map(|(id, _)| *id)
+            .collect();
+        let crate_authors: Vec<i64> = crate_authors.into_iter().map(|(id, _)| id).collect();
+
+        //? Check if user is one of the owners (collaborators cannot manage owners).
+        if !owners.contains(&author.id) {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "you are not an owner of this crate",
+            ));
         }
 
         //? Get all registered authors which:
@@ -215,9 +261,13 @@ pub(crate) async fn delete(
             .filter(authors::email.eq_any(old_authors.as_slice()))
             .load::<(i64, String)>(conn)?;
 
-        //? Check if there will remain at least one author for this crate.
-        if crate_authors.len() == 1 && old_authors.len() == 1 {
-            return Err(ApiError::msg("cannot leave the crate without any authors"));
+        //? Check if there will remain at least one owner for this crate.
+        let remaining_owners = owners
+            .iter()
+            .filter(|id| !old_authors.iter().any(|(old_id, _)| old_id == *id))
+            .count();
+        if remaining_owners == 0 {
+            return Err(ApiError::msg("cannot leave the crate without any owners"));
         }
 
         //? Split IDs and names into separate vectors.
@@ -239,6 +289,13 @@ pub(crate) async fn delete(
         )
         .execute(conn)?;
 
+        //? Record the ownership changes in the crate's audit log.
+        for old_author_name in old_authors_names.iter() {
+            let details = format!("removed {old_author_name}");
+            let action = AuditAction::RemoveOwner;
+            audit::record(conn, crate_id, author.id, action, None, Some(&details))?;
+        }
+
         let authors_list = match old_authors_names.as_slice() {
             [] => String::new(),
             [author] => author.clone(),
@@ -255,5 +312,7 @@ pub(crate) async fn delete(
         })))
     });
 
-    transaction.await.map_err(ApiError::from)
+    let response = transaction.await.map_err(ApiError::from)?;
+    page_cache::invalidate(&state, &canon_name);
+    Ok(response)
 }
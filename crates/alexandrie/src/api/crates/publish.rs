@@ -2,28 +2,33 @@ use std::collections::HashMap;
 use std::io::{self, Read};
 use std::path::PathBuf;
 use std::pin::pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use axum::extract::{BodyStream, State};
-use axum::Json;
+use axum::headers::UserAgent;
+use axum::{Json, TypedHeader};
 use byteorder::{LittleEndian, ReadBytesExt};
 use chrono::Utc;
 use diesel::dsl as sql;
 use diesel::prelude::*;
-use flate2::read::GzDecoder;
 use futures_util::io::AsyncReadExt;
 use futures_util::stream::TryStreamExt;
 use ring::digest as hasher;
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use tar::Archive;
+use tracing::field::{self, Empty};
+use tracing::{Instrument, Span};
 
-use alexandrie_index::{CrateDependency, CrateDependencyKind, CrateVersion, Indexer};
+use alexandrie_index::error::{Error as IndexError, IndexError as InnerIndexError};
+use alexandrie_index::{ConfigFile, CrateDependency, CrateDependencyKind, CrateVersion, Indexer};
+use alexandrie_storage::error::Error as StorageError;
 use alexandrie_storage::Store;
 
-use crate::config::AppState;
+use crate::config::{AppState, GeneralState, RegistryPolicy};
 use crate::db::models::{
-    Crate, NewBadge, NewCrate, NewCrateAuthor, NewCrateCategory, NewCrateKeyword,
+    Author, Crate, NewBadge, NewCrate, NewCrateAuthor, NewCrateCategory, NewCrateKeyword,
+    NewCrateProvenance,
 };
 use crate::db::schema::*;
 use crate::db::Connection;
@@ -31,10 +36,52 @@ use crate::db::DATETIME_FORMAT;
 use crate::error::{AlexError, ApiError, Error};
 use crate::fts::TantivyDocument;
 use crate::utils;
+use crate::utils::aliases;
+use crate::utils::audit::{self, AuditAction};
 use crate::utils::auth::api::Auth;
+use crate::utils::cargo_version;
+use crate::utils::commit_messages::CommitFields;
+use crate::utils::licenses::{self, VersionLicense};
+use crate::utils::owners::OwnerRole;
+use crate::utils::page_cache;
+use crate::utils::provenance::{self, Attestation};
+use crate::utils::publish_hook::HookPayload;
+use crate::utils::quotas::{self, PublishKind};
+use crate::utils::readme::{ReadmeOrigin, VCS_INFO_FILE_NAME};
+use crate::utils::request_id;
+use crate::utils::subscriptions;
+use crate::utils::tarball;
+use crate::utils::telemetry;
+use crate::utils::versions;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct PublishResponse {}
+pub(crate) struct PublishResponse {
+    pub warnings: PublishWarnings,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PublishWarnings {
+    pub invalid_categories: Vec<String>,
+    pub invalid_badges: Vec<String>,
+    pub other: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ValidationResponse {
+    /// Whether the publication would be accepted.
+    pub ok: bool,
+    /// The outcome of every publication check.
+    pub checks: Vec<ValidationCheck>,
+    pub warnings: PublishWarnings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ValidationCheck {
+    pub name: String,
+    pub passed: bool,
+    /// Why the check failed (the error the publication would be rejected with).
+    pub message: Option<String>,
+}
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct CrateMeta {
@@ -55,6 +102,12 @@ struct CrateMeta {
     pub repository: Option<String>,
     pub badges: Option<HashMap<String, HashMap<String, String>>>,
     pub links: Option<String>,
+    /// An attestation of where the crate was published from (not sent by Cargo itself, but by other publishing tools).
+    #[serde(default)]
+    pub attestation: Option<Attestation>,
+    /// An already rendered README, as HTML (not sent by Cargo itself, but by other publishing tools).
+    #[serde(default)]
+    pub rendered_readme: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -71,7 +124,676 @@ struct CrateMetaDependency {
     pub explicit_name: Option<String>,
 }
 
-fn link_keywords(
+/// What is left over from a previous publication of the same crate version (which may have been interrupted).
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Leftovers {
+    /// The version's record in the crate index.
+    record: Option<CrateVersion>,
+    /// Whether the version's tarball is in the crate store.
+    tarball: bool,
+    /// Whether the version's rendered README is in the crate store.
+    readme: bool,
+    /// Whether the version's manifest is in the crate store.
+    manifest: bool,
+}
+
+impl Leftovers {
+    fn find(state: &AppState, name: &str, version: &Version) -> Self {
+        let record = state
+            .index
+            .all_records(name)
+            .ok()
+            .and_then(|records| records.into_iter().find(|record| record.vers == *version));
+        let tarball = state.storage.crate_metadata(name, version.clone()).is_ok();
+        let readme = state.storage.get_readme(name, version.clone()).is_ok();
+        let manifest = state.storage.get_manifest(name, version.clone()).is_ok();
+        Self {
+            record,
+            tarball,
+            readme,
+            manifest,
+        }
+    }
+
+    /// Whether the version has already been published, either completely (its record, its tarball and its row
+    /// in the database all exist) or with a different tarball (in which case it cannot be completed).
+    fn is_already_uploaded(&self, version_recorded: bool, hash: &str) -> bool {
+        self.record.as_ref().map_or(false, |record| {
+            let is_complete = self.tarball && version_recorded;
+            is_complete || record.cksum != hash
+        })
+    }
+}
+
+/// A publication check, along with its outcome (the error the publication would be rejected with).
+#[derive(Debug)]
+struct PreCheck {
+    name: &'static str,
+    outcome: Result<(), Error>,
+}
+
+/// The checks a publication must pass before anything gets written (to the database, crate store or crate index).
+///
+/// They are shared by the publication and the validation endpoints, so that a validation is authoritative.
+#[derive(Debug)]
+struct PreChecks {
+    checks: Vec<PreCheck>,
+    /// What is left over from a previous publication of the same version.
+    leftovers: Leftovers,
+    /// Whether the crate already exists.
+    exists: bool,
+}
+
+impl PreChecks {
+    /// Runs every check (without stopping at the first failure), without writing anything.
+    fn run(
+        state: &AppState,
+        conn: &mut Connection,
+        author: &Author,
+        metadata: &CrateMeta,
+        hash: &str,
+    ) -> Result<Self, Error> {
+        let mut checks = Vec::new();
+        let canon_name = utils::canonical_name(metadata.name.as_str());
+
+        //? Are the keywords valid?
+        let outcome = validate_keywords(&state.general, &metadata.keywords);
+        checks.push(PreCheck {
+            name: "keywords",
+            outcome,
+        });
+
+        //? Are the categories known (and allowed)?
+        let allowed_categories = state.general.allowed_categories.as_deref();
+        let outcome = check_categories(conn, &metadata.categories, allowed_categories)?;
+        checks.push(PreCheck {
+            name: "categories",
+            outcome,
+        });
+
+        //? Do the dependencies only come from allowed registries (globally, and for this crate specifically)?
+        //? Policies are scoped by the crate's owners (the publisher being its owner-to-be, for a new crate).
+        let config = state.index_configuration()?;
+        let owners: Vec<String> = crate_authors::table
+            .inner_join(authors::table)
+            .inner_join(crates::table)
+            .select(authors::email)
+            .filter(crates::canon_name.eq(canon_name.as_str()))
+            .filter(crate_authors::role.eq(OwnerRole::Owner.as_str()))
+            .load(conn)?;
+        let owners = if owners.is_empty() {
+            vec![author.email.clone()]
+        } else {
+            owners
+        };
+        let policies: Vec<&RegistryPolicy> = (state.general.registry_policies.iter())
+            .filter(|policy| policy.applies_to(&metadata.name, &owners))
+            .collect();
+        let outcome = check_registries(&config, &policies, &metadata.deps);
+        checks.push(PreCheck {
+            name: "registries",
+            outcome,
+        });
+
+        //? Do the features only refer to existing features and dependencies (if enabled)?
+        if state.general.validate_features {
+            let outcome = validate_features(&metadata.deps, &metadata.features);
+            checks.push(PreCheck {
+                name: "features",
+                outcome,
+            });
+        }
+
+        //? Is the license a valid SPDX expression (if required), and is it allowed?
+        let outcome = licenses::check(
+            &state.general,
+            metadata.license.as_deref(),
+            metadata.license_file.as_deref(),
+        );
+        checks.push(PreCheck {
+            name: "license",
+            outcome,
+        });
+
+        //? Is the repository URL well-formed?
+        let outcome = match metadata.repository.as_deref() {
+            Some(repository) => provenance::validate_repository(repository),
+            None => Ok(()),
+        };
+        checks.push(PreCheck {
+            name: "repository",
+            outcome,
+        });
+
+        //? Has this exact version already been (even partially) published?
+        let leftovers = Leftovers::find(state, &metadata.name, &metadata.vers);
+        let exists = utils::checks::crate_exists(conn, &canon_name)?;
+        //? The version's row is what the database transaction commits last, so the crate existing is not enough.
+        let version_recorded: bool = sql::select(sql::exists(
+            crate_versions::table
+                .inner_join(crates::table)
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .filter(crate_versions::version.eq(metadata.vers.to_string())),
+        ))
+        .get_result(conn)?;
+        let already_uploaded = leftovers.is_already_uploaded(version_recorded, hash);
+        let outcome = if already_uploaded {
+            Err(Error::from(AlexError::VersionAlreadyUploaded {
+                name: metadata.name.clone(),
+                version: metadata.vers.clone(),
+            }))
+        } else {
+            Ok(())
+        };
+        checks.push(PreCheck {
+            name: "version-not-uploaded",
+            outcome,
+        });
+
+        //? Is the author within their publish quota (recovering an interrupted publication is not counted)?
+        if leftovers.record.is_none() {
+            let kind = if exists {
+                PublishKind::NewVersion
+            } else {
+                PublishKind::NewCrate
+            };
+            let outcome = quotas::check(state, conn, author, kind)?;
+            checks.push(PreCheck {
+                name: "quota",
+                outcome,
+            });
+        }
+
+        if exists {
+            let krate: Crate = crates::table
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first(conn)?;
+
+            //? Is the user an owner of this crate (collaborators cannot publish)?
+            let owned: bool = sql::select(sql::exists(
+                crate_authors::table
+                    .filter(crate_authors::crate_id.eq(&krate.id))
+                    .filter(crate_authors::author_id.eq(&author.id))
+                    .filter(crate_authors::role.eq(OwnerRole::Owner.as_str())),
+            ))
+            .get_result(conn)?;
+            let outcome = if owned {
+                Ok(())
+            } else {
+                Err(Error::from(AlexError::CrateNotOwned {
+                    author: author.clone(),
+                    name: krate.name.clone(),
+                }))
+            };
+            checks.push(PreCheck {
+                name: "ownership",
+                outcome,
+            });
+
+            //? Is the crate published under the same name as before (its display name is preserved)?
+            let outcome = if metadata.name == krate.name {
+                Ok(())
+            } else {
+                Err(Error::from(AlexError::CrateNameMismatch {
+                    name: metadata.name.clone(),
+                    existing: krate.name.clone(),
+                }))
+            };
+            checks.push(PreCheck {
+                name: "crate-name",
+                outcome,
+            });
+
+            //? Is there a compatible, higher version available than the attempted publication version?
+            //? (this version being already in the index when recovering an interrupted publication)
+            let requirement = VersionReq::parse(&format!("^{}", metadata.vers))?;
+            let latest_compatible = state
+                .index
+                .match_record(krate.name.as_str(), requirement)
+                .ok()
+                .filter(|_| leftovers.record.is_none());
+            let outcome = match latest_compatible {
+                Some(latest_compatible) => Err(Error::from(AlexError::VersionTooLow {
+                    krate: krate.name,
+                    hosted: latest_compatible.vers,
+                    published: metadata.vers.clone(),
+                })),
+                None => Ok(()),
+            };
+            checks.push(PreCheck {
+                name: "version-order",
+                outcome,
+            });
+        } else {
+            //? Is the crate's name valid (and not reserved)?
+            let name = metadata.name.as_str();
+            let outcome = utils::checks::validate_crate_name(&state.general, author, name);
+            checks.push(PreCheck {
+                name: "crate-name",
+                outcome,
+            });
+
+            //? Is the crate's name an alias of another crate (only superadmins can take it over)?
+            let alias = aliases::resolve(conn, utils::canonical_name(name).as_str())?;
+            let outcome = match alias {
+                Some((_, target)) if !utils::checks::is_superadmin(&state.general, author) => {
+                    Err(Error::from(AlexError::AliasedCrateName {
+                        name: String::from(name),
+                        target,
+                    }))
+                }
+                _ => Ok(()),
+            };
+            checks.push(PreCheck {
+                name: "crate-alias",
+                outcome,
+            });
+        }
+
+        Ok(Self {
+            checks,
+            leftovers,
+            exists,
+        })
+    }
+
+    /// Returns the error of the first failed check (if any).
+    fn into_result(self) -> Result<(Leftovers, bool), Error> {
+        match self
+            .checks
+            .into_iter()
+            .find_map(|check| check.outcome.err())
+        {
+            Some(error) => Err(error),
+            None => Ok((self.leftovers, self.exists)),
+        }
+    }
+}
+
+/// The side effects of a publication that happen outside of the database transaction.
+///
+/// They are applied in order (the tarball, the README and then the crate index record),
+/// and the ones that were applied are undone (in reverse order) if a later step fails.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SideEffects {
+    crate_stored: bool,
+    readme_stored: bool,
+    manifest_stored: bool,
+    record_added: bool,
+}
+
+impl SideEffects {
+    /// Applies the side effects, keeping track of which ones succeeded.
+    ///
+    /// When completing an interrupted publication, the parts that are already there are left as-is
+    /// (the tarball and index record, which match the checksum), and left over files are replaced.
+    fn apply(
+        &mut self,
+        state: &AppState,
+        crate_desc: &CrateVersion,
+        leftovers: &Leftovers,
+        crate_bytes: Vec<u8>,
+        rendered_readme: Option<String>,
+        manifest: Option<String>,
+        commit_msg: &str,
+    ) -> Result<(), Error> {
+        let (name, vers) = (crate_desc.name.as_str(), &crate_desc.vers);
+        let recovering = leftovers.record.is_some();
+
+        //? Store the crate's tarball.
+        if !(recovering && leftovers.tarball) {
+            if leftovers.tarball {
+                state.storage.delete_crate(name, vers.clone())?;
+            }
+            let span = telemetry::step_span("storage.store_crate", name, vers);
+            telemetry::in_span(span, || {
+                state.storage.store_crate(name, vers.clone(), crate_bytes)
+            })?;
+            self.crate_stored = true;
+        }
+
+        //? Store the crate's readme.
+        if let Some(rendered) = rendered_readme {
+            if leftovers.readme {
+                state.storage.delete_readme(name, vers.clone())?;
+            }
+            let span = telemetry::step_span("storage.store_readme", name, vers);
+            telemetry::in_span(span, || {
+                state.storage.store_readme(name, vers.clone(), rendered)
+            })?;
+            self.readme_stored = true;
+        }
+
+        //? Store the crate's manifest (if the store supports it).
+        if let Some(manifest) = manifest {
+            if leftovers.manifest {
+                state.storage.delete_manifest(name, vers.clone())?;
+            }
+            let span = telemetry::step_span("storage.store_manifest", name, vers);
+            let outcome = telemetry::in_span(span, || {
+                state.storage.store_manifest(name, vers.clone(), manifest)
+            });
+            match outcome {
+                Ok(()) => self.manifest_stored = true,
+                Err(StorageError::Unsupported(_)) => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        //? Update the crate index (the record may only be missing from the remote index when recovering).
+        if !recovering {
+            let span = telemetry::step_span("index.add_record", name, vers);
+            telemetry::in_span(span, || state.index.add_record(crate_desc.clone()))?;
+            self.record_added = true;
+        }
+        let span = telemetry::step_span("index.commit_and_push", name, vers);
+        let pushed = telemetry::in_span(span, || state.index.commit_and_push(commit_msg));
+        if pushed.is_err() && !state.index.has_uncommitted_changes().unwrap_or(true) {
+            //? The index discarded the failed commit along with its changes, so the record is already gone
+            //? (and removing it again could remove a record of the same version pushed concurrently).
+            self.record_added = false;
+        }
+        pushed?;
+
+        Ok(())
+    }
+
+    /// Undoes the side effects that were applied, in reverse order.
+    ///
+    /// Failures are only logged, since the publication is failing anyway.
+    fn undo(&self, state: &AppState, crate_desc: &CrateVersion) {
+        let (name, vers) = (crate_desc.name.as_str(), &crate_desc.vers);
+
+        if self.record_added {
+            if let Err(error) = state.index.remove_record(name, vers.clone()) {
+                tracing::error!("could not remove the index record of `{name}#{vers}`: {error}");
+            }
+        }
+        if self.manifest_stored {
+            if let Err(error) = state.storage.delete_manifest(name, vers.clone()) {
+                tracing::error!("could not delete the manifest of `{name}#{vers}`: {error}");
+            }
+        }
+        if self.readme_stored {
+            if let Err(error) = state.storage.delete_readme(name, vers.clone()) {
+                tracing::error!("could not delete the README of `{name}#{vers}`: {error}");
+            }
+        }
+        if self.crate_stored {
+            if let Err(error) = state.storage.delete_crate(name, vers.clone()) {
+                tracing::error!("could not delete the tarball of `{name}#{vers}`: {error}");
+            }
+        }
+    }
+
+    /// Reverts the side effects once they all got applied (when the database transaction fails to be committed).
+    ///
+    /// Unlike [`SideEffects::undo`], the removal of the index record is also committed and pushed (like its addition was).
+    fn revert(&self, state: &AppState, crate_desc: &CrateVersion) {
+        let (name, vers) = (crate_desc.name.as_str(), &crate_desc.vers);
+
+        self.undo(state, crate_desc);
+        if self.record_added {
+            let commit_msg = format!("Reverting the publication of crate `{name}#{vers}`");
+            if let Err(error) = state.index.commit_and_push(commit_msg.as_str()) {
+                tracing::error!(
+                    "could not push the removal of the index record of `{name}#{vers}`: {error}"
+                );
+            }
+        }
+    }
+}
+
+/// Checks that the keywords are valid (in count, length and characters), the same way crates.io does.
+fn validate_keywords(general: &GeneralState, keywords: &Option<Vec<String>>) -> Result<(), Error> {
+    let Some(keywords) = keywords else {
+        return Ok(());
+    };
+
+    if keywords.len() > general.max_keywords {
+        return Err(Error::from(AlexError::TooManyKeywords {
+            max_keywords: general.max_keywords,
+        }));
+    }
+
+    let max_keyword_length = general.max_keyword_length;
+    for keyword in keywords {
+        let mut chars = keyword.chars();
+        let valid = chars.next().map_or(false, |ch| ch.is_ascii_alphabetic())
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '+'))
+            && keyword.chars().count() <= max_keyword_length;
+        if !valid {
+            return Err(Error::from(AlexError::InvalidKeyword {
+                keyword: keyword.clone(),
+                max_keyword_length,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// The index URLs of crates.io, whose crates are allowed to be depended on if the crate index
+/// does not specify any allowed registries (as per RFC 2141).
+const CRATES_IO_INDEXES: &[&str] = &[
+    "https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io",
+];
+
+/// Lists the dependencies coming from registries other than the allowed ones
+/// (formatted like "`name` (from `registry`)").
+fn disallowed_dependencies(allowed: &[&str], deps: &[CrateMetaDependency]) -> Vec<String> {
+    let normalize = |url: &str| String::from(url.trim_end_matches('/'));
+    let allowed: Vec<String> = allowed.iter().map(|url| normalize(url)).collect();
+    deps.iter()
+        .filter_map(|dep| {
+            let registry = dep.registry.as_deref()?;
+            let is_allowed = allowed.contains(&normalize(registry));
+            (!is_allowed).then(|| format!("`{0}` (from `{registry}`)", dep.name))
+        })
+        .collect()
+}
+
+/// Checks that the dependencies only come from the registries allowed by the crate index,
+/// and by every registry policy applying to the crate
+/// (dependencies from this registry, which have no `registry` set, are always allowed).
+fn check_registries(
+    config: &ConfigFile,
+    policies: &[&RegistryPolicy],
+    deps: &[CrateMetaDependency],
+) -> Result<(), Error> {
+    let allowed: Vec<&str> = if config.allowed_registries.is_empty() {
+        CRATES_IO_INDEXES.to_vec()
+    } else {
+        config
+            .allowed_registries
+            .iter()
+            .map(String::as_str)
+            .collect()
+    };
+
+    let offending = disallowed_dependencies(&allowed, deps);
+    if !offending.is_empty() {
+        return Err(Error::from(AlexError::DisallowedRegistries {
+            dependencies: offending.join(", "),
+        }));
+    }
+
+    for policy in policies {
+        let allowed: Vec<&str> = policy
+            .allowed_registries
+            .iter()
+            .map(String::as_str)
+            .collect();
+        let offending = disallowed_dependencies(&allowed, deps);
+        if !offending.is_empty() {
+            let allowed = match allowed.as_slice() {
+                [] => String::from("none other than this registry"),
+                allowed => allowed.join(", "),
+            };
+            return Err(Error::from(AlexError::RestrictedRegistries {
+                policy: policy.describe(),
+                dependencies: offending.join(", "),
+                allowed,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the features only refer to existing features and (non-dev) dependencies,
+/// and that they do not enable each other in a cycle (mirroring Cargo's own checks).
+fn validate_features(
+    deps: &[CrateMetaDependency],
+    features: &HashMap<String, Vec<String>>,
+) -> Result<(), Error> {
+    let invalid = |feature: &str, reason: String| {
+        Error::from(AlexError::InvalidFeature {
+            feature: String::from(feature),
+            reason,
+        })
+    };
+
+    //? Features refer to dependencies by their name in the manifest (which is their explicit name, if renamed).
+    //? Dev-dependencies cannot be enabled by features.
+    let deps: HashMap<&str, &CrateMetaDependency> = deps
+        .iter()
+        .filter(|dep| dep.kind != Some(CrateDependencyKind::Dev))
+        .map(|dep| (dep.explicit_name.as_deref().unwrap_or(&dep.name), dep))
+        .collect();
+
+    //? Optional dependencies referred to using `dep:` do not get an implicit feature.
+    let explicit: Vec<&str> = features
+        .values()
+        .flatten()
+        .filter_map(|entry| entry.strip_prefix("dep:"))
+        .collect();
+    let is_implicit_feature =
+        |name: &str| deps.get(name).map_or(false, |dep| dep.optional) && !explicit.contains(&name);
+
+    let mut names: Vec<&String> = features.keys().collect();
+    names.sort();
+    for feature in names.iter().copied() {
+        let mut chars = feature.chars();
+        let valid = chars
+            .next()
+            .map_or(false, |ch| ch.is_ascii_alphanumeric() || ch == '_')
+            && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '+' | '.'));
+        if !valid {
+            let reason = String::from("feature names must start with a letter, a digit or '_', and only contain ASCII alphanumeric characters, '_', '-', '+' or '.'");
+            return Err(invalid(feature, reason));
+        }
+
+        for entry in features[feature].iter() {
+            if let Some(name) = entry.strip_prefix("dep:") {
+                //? `dep:<name>` must refer to an optional dependency.
+                match deps.get(name) {
+                    Some(dep) if dep.optional => {}
+                    Some(_) => {
+                        let reason = format!(
+                            "it enables `{entry}`, but `{name}` is not an optional dependency"
+                        );
+                        return Err(invalid(feature, reason));
+                    }
+                    None => {
+                        let reason =
+                            format!("it enables `{entry}`, but `{name}` is not a dependency");
+                        return Err(invalid(feature, reason));
+                    }
+                }
+            } else if let Some((name, _)) = entry.split_once('/') {
+                //? `<dep>/<feature>` and `<dep>?/<feature>` must refer to a dependency (an optional one, for the latter).
+                let (name, weak) = match name.strip_suffix('?') {
+                    Some(name) => (name, true),
+                    None => (name, false),
+                };
+                match deps.get(name) {
+                    Some(dep) if weak && !dep.optional => {
+                        let reason = format!(
+                            "it enables `{entry}`, but `{name}` is not an optional dependency"
+                        );
+                        return Err(invalid(feature, reason));
+                    }
+                    Some(_) => {}
+                    None => {
+                        let reason =
+                            format!("it enables `{entry}`, but `{name}` is not a dependency");
+                        return Err(invalid(feature, reason));
+                    }
+                }
+            } else if !features.contains_key(entry) && !is_implicit_feature(entry) {
+                //? `<name>` must refer to another feature (or an optional dependency's implicit feature).
+                let reason = match deps.get(entry.as_str()) {
+                    Some(dep) if !dep.optional => format!("it enables `{entry}`, but `{entry}` is not an optional dependency (nor a feature)"),
+                    Some(_) => format!("it enables `{entry}`, but `{entry}` has no implicit feature (as it is referred to using `dep:{entry}`)"),
+                    None => format!("it enables `{entry}`, which is neither a feature nor a dependency"),
+                };
+                return Err(invalid(feature, reason));
+            }
+        }
+    }
+
+    //? Features must not enable each other in a cycle.
+    fn find_cycle<'a>(
+        features: &'a HashMap<String, Vec<String>>,
+        feature: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut Vec<&'a str>,
+    ) -> Option<String> {
+        if let Some(start) = path.iter().position(|it| *it == feature) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(feature);
+            return Some(cycle.join(" -> "));
+        }
+        if done.contains(&feature) {
+            return None;
+        }
+        path.push(feature);
+        let enabled = features.get(feature).into_iter().flatten();
+        for entry in enabled.filter(|entry| features.contains_key(entry.as_str())) {
+            if let Some(cycle) = find_cycle(features, entry.as_str(), path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.push(feature);
+        None
+    }
+
+    let mut done = Vec::new();
+    for feature in names {
+        if let Some(cycle) = find_cycle(features, feature.as_str(), &mut Vec::new(), &mut done) {
+            let reason = format!("features cannot enable each other in a cycle ({cycle})");
+            return Err(invalid(feature, reason));
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the other crates already linking the same native library (the `links` manifest key),
+/// since Cargo only allows one crate linking a given native library in a build.
+fn find_links_conflicts(
+    conn: &mut Connection,
+    canon_name: &str,
+    links: Option<&str>,
+) -> Result<Vec<String>, Error> {
+    let Some(links) = links else {
+        return Ok(Vec::new());
+    };
+
+    let names = crates::table
+        .select(crates::name)
+        .filter(crates::links.eq(links))
+        .filter(crates::canon_name.ne(canon_name))
+        .load::<String>(conn)?;
+
+    Ok(names)
+}
+
+/// Replaces the keywords of a crate (creating the new keywords as needed).
+pub(crate) fn link_keywords(
     conn: &mut Connection,
     crate_id: i64,
     keywords: &Option<Vec<String>>,
@@ -117,7 +839,39 @@ fn link_keywords(
     Ok(())
 }
 
-fn link_categories(
+/// Checks that the categories are all known to the registry (and allowed by its configuration).
+///
+/// The outer result is for database errors, whereas the inner one is the outcome of the check.
+fn check_categories(
+    conn: &mut Connection,
+    categories: &Option<Vec<String>>,
+    allowed_categories: Option<&[String]>,
+) -> Result<Result<(), Error>, Error> {
+    let Some(categories) = categories else {
+        return Ok(Ok(()));
+    };
+
+    let found = categories::table
+        .select(categories::tag)
+        .filter(categories::tag.eq_any(categories))
+        .load::<String>(conn)?;
+
+    //? Reject unknown (or disallowed) categories.
+    let unknown = categories.iter().find(|category| {
+        let is_known = found.contains(category);
+        let is_allowed = allowed_categories.map_or(true, |allowed| allowed.contains(category));
+        !is_known || !is_allowed
+    });
+    match unknown {
+        Some(category) => Ok(Err(Error::from(AlexError::UnknownCategory {
+            category: category.clone(),
+        }))),
+        None => Ok(Ok(())),
+    }
+}
+
+/// Replaces the categories of a crate (unknown categories are ignored).
+pub(crate) fn link_categories(
     conn: &mut Connection,
     crate_id: i64,
     categories: &Option<Vec<String>>,
@@ -126,14 +880,15 @@ fn link_categories(
         .execute(conn)?;
 
     if let Some(categories) = categories {
-        let category_ids = categories::table
-            .select(categories::id)
+        //? The categories have already been checked (see `check_categories`).
+        let found = categories::table
+            .select((categories::id, categories::tag))
             .filter(categories::tag.eq_any(categories))
-            .load::<i64>(conn)?;
+            .load::<(i64, String)>(conn)?;
 
-        let entries: Vec<_> = category_ids
+        let entries: Vec<_> = found
             .into_iter()
-            .map(|category_id| NewCrateCategory {
+            .map(|(category_id, _)| NewCrateCategory {
                 crate_id,
                 category_id,
             })
@@ -176,6 +931,49 @@ fn link_badges(
     Ok(())
 }
 
+/// Extracts a file (at the root of the crate) from a crate tarball, if it is there.
+///
+/// The tarball is decompressed through a bounded reader, failing if it decompresses to more than `limit` bytes.
+pub(crate) fn extract_file(
+    crate_bytes: &[u8],
+    crate_desc: &CrateVersion,
+    file_name: &str,
+    limit: u64,
+) -> Result<Option<String>, Error> {
+    let mut archive = Archive::new(tarball::decompress(crate_bytes, limit));
+    let base_path = PathBuf::from(format!("{0}-{1}", crate_desc.name, crate_desc.vers));
+    let file_path = base_path.join(file_name);
+    let entries = archive.entries().map_err(tarball::into_error)?;
+    for entry in entries {
+        let mut entry = entry.map_err(tarball::into_error)?;
+        let is_match = entry.path().map(|path| path == file_path).unwrap_or(false);
+        if is_match {
+            let mut contents = String::new();
+            entry
+                .read_to_string(&mut contents)
+                .map_err(tarball::into_error)?;
+            return Ok(Some(contents));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Extracts the README of a crate from its tarball (looking for the usual README file names),
+/// along with its file name.
+pub(crate) fn extract_readme(
+    crate_bytes: &[u8],
+    crate_desc: &CrateVersion,
+    limit: u64,
+) -> Result<Option<(String, String)>, Error> {
+    for file_name in utils::readme::README_FILE_NAMES {
+        if let Some(contents) = extract_file(crate_bytes, crate_desc, file_name, limit)? {
+            return Ok(Some((String::from(*file_name), contents)));
+        }
+    }
+    Ok(None)
+}
+
 /// Checks whether the passed-in reader has ended (meaning it has reached EOF).
 ///
 /// This function tests for this by attempting to read one more byte from the passed-in reader.
@@ -195,8 +993,45 @@ where
 pub(crate) async fn put(
     State(state): State<Arc<AppState>>,
     Auth(author): Auth,
+    user_agent: Option<TypedHeader<UserAgent>>,
     body: BodyStream,
 ) -> Result<Json<PublishResponse>, ApiError> {
+    let span = tracing::info_span!(
+        "publish",
+        otel.status_code = Empty,
+        crate_name = Empty,
+        crate_version = Empty,
+        error = Empty,
+    );
+    //? This publication is waited for, if the registry gets shut down in the meantime.
+    let _in_flight = state.publishes.track();
+    let outcome = async {
+        //? Is the client's Cargo version older than the configured minimum (if one is configured, and the version is detectable)?
+        let user_agent = user_agent
+            .as_ref()
+            .map(|TypedHeader(user_agent)| user_agent.as_str());
+        let outdated = cargo_version::check(state.cargo_version.as_ref(), user_agent)?;
+
+        //? Wait for our turn, if the registry limits how many publications are processed at once.
+        let _permit = match state.publish_queue.as_ref() {
+            Some(queue) => Some(queue.enter().await?),
+            None => None,
+        };
+        let Json(mut response) = publish(Arc::clone(&state), author, body).await?;
+        response.warnings.other.extend(outdated);
+        Ok(Json(response))
+    }
+    .instrument(span.clone())
+    .await;
+    if let Err(error) = outcome.as_ref() {
+        telemetry::record_error(&span, error);
+    }
+    state.metrics.record_publish(outcome.is_ok());
+    outcome
+}
+
+/// Reads the publication payload (rejecting it if it exceeds the maximum allowed crate size).
+async fn read_payload(state: &AppState, body: BodyStream) -> Result<Vec<u8>, Error> {
     let mut body = body
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
         .into_async_read();
@@ -209,11 +1044,17 @@ pub(crate) async fn put(
             .await?;
 
         if !has_reader_ended(body).await? {
-            return Err(Error::from(AlexError::CrateTooLarge { max_crate_size }).into());
+            return Err(Error::from(AlexError::CrateTooLarge { max_crate_size }));
         }
     } else {
         body.read_to_end(&mut bytes).await?;
     }
+
+    Ok(bytes)
+}
+
+/// Splits the publication payload into the crate's metadata and the crate's tarball.
+fn parse_payload(bytes: Vec<u8>) -> Result<(CrateMeta, Vec<u8>), Error> {
     let mut cursor = std::io::Cursor::new(bytes);
 
     let metadata_size = cursor.read_u32::<LittleEndian>()?;
@@ -224,17 +1065,144 @@ pub(crate) async fn put(
     let crate_size = cursor.read_u32::<LittleEndian>()?;
     let mut crate_bytes = vec![0u8; crate_size as usize];
     cursor.read_exact(&mut crate_bytes)?;
+
+    Ok((metadata, crate_bytes))
+}
+
+/// Returns a warning if the same native library (the `links` manifest key) is already linked by other crates.
+fn links_warning(
+    conn: &mut Connection,
+    canon_name: &str,
+    links: Option<&str>,
+) -> Result<Option<String>, Error> {
+    let links_conflicts = find_links_conflicts(conn, canon_name, links)?;
+    let warning = links.filter(|_| !links_conflicts.is_empty()).map(|links| {
+        let names = links_conflicts.join("`, `");
+        format!(
+            "the native library `{links}` is also linked by `{names}`: these crates cannot be used together in the same build",
+        )
+    });
+
+    Ok(warning)
+}
+
+/// Route to validate a crate publication, without publishing anything.
+///
+/// It runs the exact same checks as the publication endpoint, but reports the outcome of each of them.
+pub(crate) async fn validate(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    body: BodyStream,
+) -> Result<Json<ValidationResponse>, ApiError> {
+    let bytes = match read_payload(state.as_ref(), body).await {
+        Ok(bytes) => bytes,
+        Err(error @ Error::AlexError(AlexError::CrateTooLarge { .. })) => {
+            let check = ValidationCheck {
+                name: String::from("size"),
+                passed: false,
+                message: Some(error.to_string()),
+            };
+            return Ok(Json(ValidationResponse {
+                ok: false,
+                checks: vec![check],
+                warnings: PublishWarnings::default(),
+            }));
+        }
+        Err(error) => return Err(error.into()),
+    };
+    let (metadata, crate_bytes) = parse_payload(bytes)?;
     let hash = hex::encode(hasher::digest(&hasher::SHA256, &crate_bytes).as_ref());
 
-    // state.index.refresh()?;
+    let db = &state.db;
+    let state = Arc::clone(&state);
+    let transaction = db.run(move |conn| {
+        let pre_checks = PreChecks::run(state.as_ref(), conn, &author, &metadata, &hash)?;
+
+        let size_check = ValidationCheck {
+            name: String::from("size"),
+            passed: true,
+            message: None,
+        };
+        let checks: Vec<ValidationCheck> = std::iter::once(size_check)
+            .chain(pre_checks.checks.into_iter().map(|check| ValidationCheck {
+                name: String::from(check.name),
+                passed: check.outcome.is_ok(),
+                message: check.outcome.err().map(|error| error.to_string()),
+            }))
+            .collect();
+
+        let canon_name = utils::canonical_name(metadata.name.as_str());
+        let mut warnings = PublishWarnings::default();
+        if let Some(warning) = links_warning(conn, &canon_name, metadata.links.as_deref())? {
+            warnings.other.push(warning);
+        }
+
+        let ok = checks.iter().all(|check| check.passed);
+        Ok::<_, Error>(Json(ValidationResponse {
+            ok,
+            checks,
+            warnings,
+        }))
+    });
+
+    transaction.await.map_err(ApiError::from)
+}
+
+async fn publish(
+    state: Arc<AppState>,
+    author: Author,
+    body: BodyStream,
+) -> Result<Json<PublishResponse>, ApiError> {
+    let bytes = read_payload(state.as_ref(), body).await?;
+    let (metadata, crate_bytes) = parse_payload(bytes)?;
+
+    let span = Span::current();
+    span.record("crate_name", metadata.name.as_str());
+    span.record("crate_version", field::display(&metadata.vers));
+
+    let hash = hex::encode(hasher::digest(&hasher::SHA256, &crate_bytes).as_ref());
+
+    //? Does the pre-publish hook accept this publication (if one is configured)?
+    if let Some(hook) = state.publish_hook.as_ref() {
+        let payload = HookPayload {
+            name: metadata.name.as_str(),
+            vers: &metadata.vers,
+            cksum: hash.as_str(),
+            author: author.email.as_str(),
+            request_id: request_id::current(),
+            metadata: json::to_value(&metadata)?,
+        };
+        let span = telemetry::step_span("publish_hook.check", &metadata.name, &metadata.vers);
+        hook.check(&payload, &crate_bytes).instrument(span).await?;
+    }
+
+    //? Catch up with the remote index (debounced, so that bursts of publications share a single fetch).
+    //? This is not fatal: pushing replays the publication onto the remote's changes anyway.
+    if let Err(error) = utils::run_blocking(&state, |state| state.index.refresh()).await {
+        tracing::warn!("could not refresh the crate index: {error}");
+    }
+
+    //? The side effects applied within the database transaction, to revert them if it fails to be committed.
+    let applied = Arc::new(Mutex::new(None::<(SideEffects, CrateVersion)>));
+    let applied_within = Arc::clone(&applied);
+    let cached_name = metadata.name.clone();
 
     let db = &state.db;
+    let outer_state = Arc::clone(&state);
     let state = Arc::clone(&state);
     let transaction = db.transaction(move |conn| {
+        //? Carry the publication span over to this blocking thread.
+        let _entered = span.enter();
+
         let canon_name = utils::canonical_name(metadata.name.as_str());
 
+        //? Would the publication be accepted?
+        let pre_checks = PreChecks::run(state.as_ref(), conn, &author, &metadata, &hash)?;
+        let (leftovers, exists) = pre_checks.into_result()?;
+        let recovering = leftovers.record.is_some();
+
         //? Construct a crate description.
-        let crate_desc = CrateVersion {
+        let mut crate_desc = CrateVersion {
             name: metadata.name,
             vers: metadata.vers,
             deps: metadata
@@ -260,177 +1228,365 @@ pub(crate) async fn put(
                 })
                 .collect(),
             cksum: hash,
-            features: metadata.features,
+            features: HashMap::new(),
+            features2: None,
             yanked: Some(false),
             links: metadata.links,
+            v: None,
         };
 
-        //? Attempt to insert the new crate.
-        let now = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
-        let new_crate = NewCrate {
-            name: crate_desc.name.as_str(),
-            canon_name: canon_name.as_str(),
-            description: metadata.description.as_deref(),
-            documentation: metadata.documentation.as_deref(),
-            repository: metadata.repository.as_deref(),
-            created_at: now.as_str(),
-            updated_at: now.as_str(),
-        };
+        //? Features using the newer syntaxes (`dep:` or `?` entries) go into `features2`.
+        crate_desc.set_features(metadata.features);
 
-        //? Does the crate already exists?
-        let exists = utils::checks::crate_exists(conn, new_crate.canon_name)?;
+        //? Is the same native library already linked by other crates?
+        let mut warnings = PublishWarnings::default();
+        if let Some(warning) = links_warning(conn, &canon_name, crate_desc.links.as_deref())? {
+            warnings.other.push(warning);
+        }
 
-        //? Are we adding a new crate or updating a new one?
-        let operation = if exists {
-            "Updating"
-        } else {
-            //? Insert the new crate (as it doesn't already exists).
-            diesel::insert_into(crates::table)
-                .values(new_crate)
-                .execute(conn)?;
+        //? Is the license an invalid SPDX expression (accepted outside of strict mode)?
+        if let Some(warning) = licenses::warning(metadata.license.as_deref()) {
+            warnings.other.push(warning);
+        }
 
-            "Adding"
-        };
+        //? Insert (or update) the crate's metadata in the database.
+        let span = telemetry::step_span("db.upsert_crate", &crate_desc.name, &crate_desc.vers);
+        let (krate, operation) = telemetry::in_span(span, || {
+            //? Attempt to insert the new crate.
+            let now = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+            let new_crate = NewCrate {
+                name: crate_desc.name.as_str(),
+                canon_name: canon_name.as_str(),
+                description: metadata.description.as_deref(),
+                documentation: metadata.documentation.as_deref(),
+                repository: metadata.repository.as_deref(),
+                links: crate_desc.links.as_deref(),
+                created_at: now.as_str(),
+                updated_at: now.as_str(),
+            };
 
-        //? Fetch the newly inserted (or already existant) crate.
-        let krate: Crate = crates::table
-            .filter(crates::name.eq(crate_desc.name.as_str()))
-            .first(conn)?;
+            //? Are we adding a new crate or updating a new one?
+            //? (the name reservation, ownership and version order have already been checked)
+            let operation = if exists {
+                "Updating"
+            } else {
+                //? Insert the new crate (as it doesn't already exists).
+                diesel::insert_into(crates::table)
+                    .values(new_crate)
+                    .execute(conn)?;
 
-        //? If newly inserted, add the current user as an author.
-        //? Else:
-        //?  - check if the current user is an author of the crate: if not, emit error.
-        //?  - check if the version number is higher than the latest stored one: if not, emit error.
-        //?  - update the crate's metadata.
-        if exists {
-            //? Is the user an author of this crate?
-            let owned: bool = sql::select(sql::exists(
-                crate_authors::table
-                    .filter(crate_authors::crate_id.eq(&krate.id))
-                    .filter(crate_authors::author_id.eq(&author.id)),
-            ))
-            .get_result(conn)?;
-            if !owned {
-                return Err(Error::from(AlexError::CrateNotOwned {
-                    author,
-                    name: krate.name,
-                }));
-            }
+                "Adding"
+            };
 
-            //? Is there a compatible, higher version available than the attempted publication version?
-            let requirement = VersionReq::parse(&format!("^{}", crate_desc.vers))?;
-            if let Ok(latest_compatible) =
-                state.index.match_record(krate.name.as_str(), requirement)
-            {
-                return Err(Error::from(AlexError::VersionTooLow {
-                    krate: krate.name,
-                    hosted: latest_compatible.vers,
-                    published: crate_desc.vers,
-                }));
-            }
+            //? Fetch the newly inserted (or already existant) crate.
+            let krate: Crate = crates::table
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first(conn)?;
 
-            //? Update the crate's metadata.
-            let description = metadata.description.as_deref();
-            let documentation = metadata.documentation.as_deref();
-            let repository = metadata.repository.as_deref();
-            diesel::update(crates::table.filter(crates::id.eq(krate.id)))
-                .set((
-                    crates::description.eq(description),
-                    crates::documentation.eq(documentation),
-                    crates::repository.eq(repository),
-                    crates::updated_at.eq(now.as_str()),
-                ))
-                .execute(conn)?;
-        } else {
-            //? Insert the current user as an initial author of the crate.
-            diesel::insert_into(crate_authors::table)
-                .values(NewCrateAuthor {
+            //? If newly inserted, add the current user as an author.
+            //? Else, update the crate's metadata.
+            if exists {
+                let description = metadata.description.as_deref();
+                let documentation = metadata.documentation.as_deref();
+                let repository = metadata.repository.as_deref();
+                let links = crate_desc.links.as_deref();
+                diesel::update(crates::table.filter(crates::id.eq(krate.id)))
+                    .set((
+                        crates::description.eq(description),
+                        crates::documentation.eq(documentation),
+                        crates::repository.eq(repository),
+                        crates::links.eq(links),
+                        crates::updated_at.eq(now.as_str()),
+                    ))
+                    .execute(conn)?;
+            } else {
+                //? Remove the alias this crate's name may have been (only superadmins can take aliases over).
+                aliases::remove(conn, canon_name.as_str())?;
+
+                //? Insert the current user as an initial author of the crate.
+                diesel::insert_into(crate_authors::table)
+                    .values(NewCrateAuthor {
+                        crate_id: krate.id,
+                        author_id: author.id,
+                        role: OwnerRole::Owner.as_str(),
+                    })
+                    .execute(conn)?;
+            };
+
+            //? Update keywords.
+            link_keywords(conn, krate.id, &metadata.keywords)?;
+
+            //? Update categories.
+            link_categories(conn, krate.id, &metadata.categories)?;
+
+            //? Update badges.
+            link_badges(conn, krate.id, metadata.badges)?;
+
+            Ok::<_, Error>((krate, operation))
+        })?;
+
+        //? Record where this version was published from (and warn if the repository changed since the last version).
+        let previous_repository = provenance::last_repository(conn, krate.id)?;
+        let repository = metadata.repository.as_deref();
+        if let Some(previous) = previous_repository.as_deref() {
+            if repository.map_or(false, |repository| repository != previous) {
+                warnings.other.push(format!(
+                    "the repository URL changed since the previous version (it was `{previous}`)",
+                ));
+            }
+        }
+        let now = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+        let version = crate_desc.vers.to_string();
+        let attestation = metadata.attestation.as_ref();
+        let recorded: bool = sql::select(sql::exists(
+            crate_provenance::table
+                .filter(crate_provenance::crate_id.eq(krate.id))
+                .filter(crate_provenance::version.eq(version.as_str())),
+        ))
+        .get_result(conn)?;
+        if !recorded {
+            diesel::insert_into(crate_provenance::table)
+                .values(NewCrateProvenance {
                     crate_id: krate.id,
-                    author_id: author.id,
+                    version: version.as_str(),
+                    repository,
+                    attestation_kind: attestation.map(|attestation| attestation.kind.as_str()),
+                    attestation: attestation.map(|attestation| attestation.payload.as_str()),
+                    recorded_at: now.as_str(),
                 })
                 .execute(conn)?;
-        };
+        }
 
-        //? Update keywords.
-        link_keywords(conn, krate.id, &metadata.keywords)?;
+        //? Record when this version was published.
+        versions::record_published(conn, krate.id, version.as_str(), now.as_str())?;
 
-        //? Update categories.
-        link_categories(conn, krate.id, &metadata.categories)?;
+        //? Record the publication in the crate's audit log.
+        let action = AuditAction::Publish;
+        audit::record(conn, krate.id, author.id, action, Some(version.as_str()), None)?;
 
-        //? Update badges.
-        link_badges(conn, krate.id, metadata.badges)?;
+        //? Queue a notification of this version for the crate's subscribers (if notifications are enabled).
+        if subscriptions::is_enabled(state.as_ref()) {
+            subscriptions::enqueue(conn, krate.id, version.as_str(), author.id)?;
+        }
 
-        //? Render the crate's readme.
-        let rendered_readme = {
-            let mut archive = Archive::new(GzDecoder::new(crate_bytes.as_slice()));
-            let base_path = PathBuf::from(format!("{0}-{1}", crate_desc.name, crate_desc.vers));
-            let readme_path = base_path.join("README.md");
-            let mut entries = archive.entries()?;
-            let found = entries.find(|entry| match entry {
-                Ok(entry) => entry
-                    .path()
-                    .map(|path| path == readme_path)
-                    .unwrap_or(false),
-                Err(_) => false,
-            });
+        //? Count this publication against the author's publish quota (if quotas are enabled).
+        if state.quotas.is_some() && !recovering {
+            let kind = if exists {
+                PublishKind::NewVersion
+            } else {
+                PublishKind::NewCrate
+            };
+            quotas::record(conn, author.id, kind)?;
+        }
 
-            //? Start render if it has a README.
-            match found {
-                Some(found) => {
-                    let mut contents = String::new();
-                    found?.read_to_string(&mut contents)?;
+        //? Extract the crate's manifest (as published).
+        let limit = tarball::decompression_limit(&state.general, crate_bytes.len());
+        let manifest = extract_file(&crate_bytes, &crate_desc, "Cargo.toml", limit)?;
 
-                    Some(alexandrie_rendering::render_readme(
-                        &state.syntect,
-                        contents.as_str(),
-                    ))
+        //? Record the version's license (along with the text of its license file, if it has no license expression).
+        let license_file = metadata.license_file.as_deref();
+        let license_text = match license_file.filter(|_| metadata.license.is_none()) {
+            Some(path) => {
+                let path = licenses::archive_path(path);
+                extract_file(&crate_bytes, &crate_desc, &path, limit)?
+            }
+            None => None,
+        };
+        let license = VersionLicense {
+            license: metadata.license.clone(),
+            license_file: license_file.map(String::from),
+            license_text,
+        };
+        licenses::record(conn, krate.id, version.as_str(), &license)?;
+
+        //? Render the crate's readme.
+        let span = telemetry::step_span("readme.render", &crate_desc.name, &crate_desc.vers);
+        let rendered_readme = telemetry::in_span(span, || {
+            //? Use the already rendered README, if one was provided (and this is allowed).
+            //? It is sanitized all the same, as it comes straight from the publisher.
+            let prerendered = metadata
+                .rendered_readme
+                .as_deref()
+                .filter(|_| state.general.allow_prerendered_readme);
+            if let Some(sanitized) = prerendered.and_then(|html| utils::readme::sanitize(&state, html)) {
+                return Ok(Some(sanitized));
+            }
+
+            //? Start render if it has a README (in the location declared by its manifest, if any).
+            let file_name = utils::readme::file_name(metadata.readme_file.as_deref());
+            let readme = match file_name {
+                Some(file_name) => extract_file(&crate_bytes, &crate_desc, &file_name, limit)?
+                    .map(|contents| (file_name, contents)),
+                None => extract_readme(&crate_bytes, &crate_desc, limit)?,
+            };
+            let rendered = match readme {
+                Some((file_name, contents)) => {
+                    //? Find where it comes from in the crate's repository (to resolve its relative links).
+                    let vcs_info = extract_file(&crate_bytes, &crate_desc, VCS_INFO_FILE_NAME, limit)?;
+                    let origin = ReadmeOrigin::new(
+                        metadata.repository.as_deref(),
+                        vcs_info.as_deref(),
+                        metadata.readme_file.as_deref(),
+                    );
+                    Some(utils::readme::render(&state, file_name.as_str(), contents.as_str(), &origin))
                 }
                 None => None,
-            }
+            };
+            Ok::<_, Error>(rendered)
+        })?;
+
+        //? Update the crate's number of available versions (the new version is only counted once, if recovering).
+        let records = if exists {
+            state.index.all_records(&crate_desc.name)?
+        } else {
+            Vec::new()
         };
+        let available = versions::count_available(&records) + i64::from(!recovering);
+        versions::store_available(conn, krate.id, available)?;
 
-        //? Store the crate's tarball.
-        state
-            .storage
-            .store_crate(&crate_desc.name, crate_desc.vers.clone(), crate_bytes)?;
+        //? Apply the side effects outside of the database (storage and crate index).
+        //? If one of them fails, the previous ones are undone and the database transaction is rolled back
+        //? (and if the database transaction fails to be committed afterwards, they all get reverted).
+        let version = crate_desc.vers.to_string();
+        let commit_msg = state.commit_messages.publish.render(&CommitFields {
+            action: operation,
+            krate: crate_desc.name.as_str(),
+            version: version.as_str(),
+            actor: author.name.as_str(),
+            ..CommitFields::default()
+        });
+        let mut side_effects = SideEffects::default();
+        let outcome = side_effects.apply(
+            state.as_ref(),
+            &crate_desc,
+            &leftovers,
+            crate_bytes,
+            rendered_readme,
+            manifest,
+            commit_msg.as_str(),
+        );
+        if let Err(error) = outcome {
+            side_effects.undo(state.as_ref(), &crate_desc);
+            //? The remote has been modified concurrently: catch up right away, so that a retried publication succeeds.
+            if matches!(
+                error,
+                Error::IndexError(IndexError::IndexError(
+                    InnerIndexError::ConcurrentModification { .. }
+                ))
+            ) {
+                if let Err(error) = state.index.force_refresh() {
+                    tracing::warn!("could not refresh the crate index: {error}");
+                }
+            }
+            return Err(error);
+        }
+        *applied_within.lock().unwrap() = Some((side_effects, crate_desc.clone()));
+        if recovering {
+            warnings.other.push(String::from(
+                "this version was already partially published (by an interrupted publication), its missing parts have been completed",
+            ));
+        }
 
+        //? The following steps are not fatal anymore: the reverse dependencies and the search index
+        //? are derived from the database and the crate index, and are both entirely rebuilt on startup.
+
+        //? Update the reverse dependencies index.
+        if let Err(error) = state.rdeps.add_record(&crate_desc) {
+            tracing::warn!("could not update the reverse dependencies of `{0}`: {error}", crate_desc.name);
+        }
+
+        //? Index into the full text search index.
         let id = krate.id;
         let name = krate.name.clone();
+        let span = telemetry::step_span("fts.index", &crate_desc.name, &crate_desc.vers);
+        let outcome = telemetry::in_span(span, || {
+            let mut document: TantivyDocument = krate.into();
+            //? The crate's row was fetched before its metadata got updated by this publication.
+            document.set_description(metadata.description);
+            if let Some(keywords) = metadata.keywords {
+                document.add_all_keywords(keywords);
+            }
+            if let Some(categories) = metadata.categories {
+                document.add_all_categories(categories);
+            }
 
-        // Index into full text index
-        let mut document: TantivyDocument = krate.into();
-        if let Some(keywords) = metadata.keywords {
-            document.add_all_keywords(keywords);
-        }
-        if let Some(categories) = metadata.categories {
-            document.add_all_categories(categories);
+            state.search.create_or_update(document)?;
+            state.search.commit().map(|_| ())
+        });
+        if let Err(error) = outcome {
+            tracing::warn!("could not index crate '{id}' ({name}) for search: {error}");
         }
 
-        if let Err(error) = state.search.create_or_update(document) {
-            tracing::warn!("Can't convert crate '{id}' ({name}) into Tantivy document : {error}");
-        } else {
-            state.search.commit()?;
+        Ok(Json(PublishResponse { warnings }))
+    });
+
+    let outcome = transaction.await;
+    if outcome.is_ok() {
+        //? Invalidate the cached pages of this crate (only once the new version is committed, to not cache stale pages).
+        page_cache::invalidate(&outer_state, &cached_name);
+    } else {
+        //? The side effects got applied, but the database transaction failed to be committed: revert them.
+        let applied = applied.lock().unwrap().take();
+        if let Some((side_effects, crate_desc)) = applied {
+            utils::run_blocking(&outer_state, move |state| {
+                side_effects.revert(state, &crate_desc)
+            })
+            .await;
         }
+    }
 
-        //? Store the crate's readme.
-        if let Some(rendered) = rendered_readme {
-            state
-                .storage
-                .store_readme(&crate_desc.name, crate_desc.vers.clone(), rendered)?;
+    outcome.map_err(ApiError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leftovers(cksum: &str, tarball: bool) -> Leftovers {
+        let record = CrateVersion {
+            name: String::from("example"),
+            vers: Version::new(1, 2, 3),
+            deps: Vec::new(),
+            cksum: String::from(cksum),
+            features: HashMap::new(),
+            features2: None,
+            yanked: Some(false),
+            links: None,
+            v: None,
+        };
+        Leftovers {
+            record: Some(record),
+            tarball,
+            ..Leftovers::default()
         }
+    }
 
-        //? Update the crate index.
-        let commit_msg = format!(
-            "{0} crate `{1}#{2}`",
-            operation,
-            crate_desc.name.as_str(),
-            &crate_desc.vers,
-        );
-        state.index.add_record(crate_desc)?;
-        state.index.commit_and_push(commit_msg.as_str())?;
+    #[test]
+    fn completes_versions_missing_from_the_database() {
+        let leftovers = leftovers("abc", true);
+        assert!(!leftovers.is_already_uploaded(false, "abc"));
+    }
 
-        Ok(Json(PublishResponse {}))
-    });
+    #[test]
+    fn completes_versions_missing_their_tarball() {
+        let leftovers = leftovers("abc", false);
+        assert!(!leftovers.is_already_uploaded(true, "abc"));
+    }
 
-    transaction.await.map_err(ApiError::from)
+    #[test]
+    fn rejects_complete_versions() {
+        let leftovers = leftovers("abc", true);
+        assert!(leftovers.is_already_uploaded(true, "abc"));
+    }
+
+    #[test]
+    fn rejects_different_tarballs() {
+        let leftovers = leftovers("abc", false);
+        assert!(leftovers.is_already_uploaded(false, "def"));
+    }
+
+    #[test]
+    fn accepts_new_versions() {
+        assert!(!Leftovers::default().is_already_uploaded(false, "abc"));
+    }
 }
@@ -0,0 +1,82 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::CrateDependencyKind;
+
+use crate::config::AppState;
+use crate::error::{ApiError, Error};
+use crate::rdeps::Dependent;
+use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::pagination::{Page, PageMeta};
+use crate::utils::visibility;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// Whether to also list yanked crate versions.
+    pub include_yanked: Option<bool>,
+    /// Only list dependents of this kind.
+    pub kind: Option<CrateDependencyKind>,
+    /// The page number to list (starting from 1).
+    pub page: Option<NonZeroUsize>,
+    /// How many dependents to list per page.
+    pub per_page: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ReverseDependenciesResponse {
+    pub dependents: Vec<Dependent>,
+    pub meta: PageMeta,
+}
+
+/// Route to list the crate versions depending on a crate.
+///
+/// Private dependents are only listed if they are accessible to the user.
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+    user: Option<Auth>,
+) -> Result<Json<ReverseDependenciesResponse>, ApiError> {
+    let name = utils::canonical_name(name);
+    let author_id = user.map(|Auth(author)| author.id);
+
+    //? Does this crate exists (and is it accessible to the user)?
+    let canon_name = name.clone();
+    let (exists, hidden) = state
+        .db
+        .run(move |conn| {
+            let hidden = visibility::hidden_crates(conn, author_id)?;
+            let exists = utils::checks::crate_exists(conn, canon_name.as_str())?
+                && !hidden.contains(&canon_name);
+            Ok::<_, Error>((exists, hidden))
+        })
+        .await?;
+    if !exists {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            format!("no crates named '{name}' could be found"),
+        ));
+    }
+
+    let include_yanked = params.include_yanked.unwrap_or(false);
+    let dependents: Vec<Dependent> = state
+        .rdeps
+        .dependents(name.as_str(), include_yanked)?
+        .into_iter()
+        .filter(|it| params.kind.map_or(true, |kind| it.kind == kind))
+        .filter(|it| !hidden.contains(&utils::canonical_name(it.name.as_str())))
+        .collect();
+
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let total = dependents.len();
+    Ok(Json(ReverseDependenciesResponse {
+        dependents: page.slice(dependents),
+        meta: page.meta(total),
+    }))
+}
@@ -5,7 +5,7 @@ use axum::extract::Query;
 use axum::extract::State;
 use axum::Json;
 use diesel::prelude::*;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 
 use alexandrie_index::Indexer;
@@ -15,11 +15,18 @@ use crate::db::models::Crate;
 use crate::db::schema::*;
 use crate::db::DATETIME_FORMAT;
 use crate::error::ApiError;
+use crate::fts::Highlights;
+use crate::utils::auth::api::Auth;
+use crate::utils::pagination::{Page, PageMeta};
+use crate::utils::visibility::{self, CrateVisibility};
+
+/// The maximum number of search hits checked against a version requirement (the most relevant ones).
+const MAX_VERSION_CANDIDATES: usize = 1000;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct SearchResponse {
     pub crates: Vec<SearchResult>,
-    pub meta: SearchMeta,
+    pub meta: PageMeta,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -32,11 +39,8 @@ pub(crate) struct SearchResult {
     pub updated_at: chrono::NaiveDateTime,
     pub documentation: Option<String>,
     pub repository: Option<String>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct SearchMeta {
-    pub total: usize,
+    /// The fragments of the crate's fields matching the query.
+    pub highlights: Highlights,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -44,32 +48,59 @@ pub(crate) struct QueryParams {
     pub q: String,
     pub per_page: Option<NonZeroUsize>,
     pub page: Option<NonZeroUsize>,
+    /// Only list crates having a version matching this requirement (like `^1.0`).
+    pub version: Option<String>,
 }
 
 /// Route to search through crates (used by `cargo search`).
+///
+/// Private crates are only listed if they are accessible to the user.
+/// When a version requirement is given, only the most relevant hits are checked against it
+/// (see [`MAX_VERSION_CANDIDATES`]), and the results are paginated after filtering.
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Query(params): Query<QueryParams>,
+    user: Option<Auth>,
 ) -> Result<Json<SearchResponse>, ApiError> {
+    let author_id = user.map(|Auth(author)| author.id);
     let query = params.q;
-    let per_page = params
-        .per_page
-        .map(|v| v.get())
-        .unwrap_or(crate::fts::DEFAULT_RESULT_PER_PAGE);
-    let page = params.page.map(|v| v.get()).unwrap_or(1) - 1;
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let requirement = match params.version.as_deref().map(str::trim) {
+        Some(requirement) if !requirement.is_empty() => {
+            let requirement = VersionReq::parse(requirement).map_err(|error| {
+                ApiError::msg(format!(
+                    "invalid version requirement '{requirement}': {error}"
+                ))
+            })?;
+            Some(requirement)
+        }
+        _ => None,
+    };
 
+    state.metrics.record_search();
     let searcher = &state.search;
     // Run query on tantivy and get total and matching ids
     // Perhaps should use suggest method as it allow to deal with "starts with", but I don't think
     // that's what is expected.
-    let (total, ids) = searcher.search(&query, page * per_page, per_page)?;
+    let (total, hits) = match requirement {
+        //? The candidates are paginated once filtered by the version requirement.
+        Some(_) => searcher.search(&query, 0, MAX_VERSION_CANDIDATES)?,
+        None => searcher.search(&query, page.offset(), page.per_page)?,
+    };
+    let ids: Vec<i64> = hits.iter().map(|hit| hit.id).collect();
 
     let db = &state.db;
     let state = Arc::clone(&state);
     let transaction = db.transaction(move |conn| {
-        // Get crate from database
+        // Get crate from database (hiding the private crates not accessible to the user)
+        let accessible = visibility::accessible_private_crates(conn, author_id)?;
         let mut crates = crates::table
             .filter(crates::id.eq_any(&ids))
+            .filter(
+                crates::visibility
+                    .eq(CrateVisibility::Public.as_str())
+                    .or(crates::id.eq_any(&accessible)),
+            )
             .load::<Crate>(conn)?;
 
         // Sort database result by relevance since we lost ordering...
@@ -80,6 +111,27 @@ pub(crate) async fn get(
                 .unwrap_or(ids.len())
         });
 
+        //? Only keep the crates having a version matching the requirement (if any), and paginate them.
+        let (total, crates) = match requirement {
+            Some(requirement) => {
+                let matching: Vec<Crate> = crates
+                    .into_iter()
+                    .filter(|krate| {
+                        let name = krate.name.as_str();
+                        state.index.match_record(name, requirement.clone()).is_ok()
+                    })
+                    .collect();
+                let total = matching.len();
+                let crates = matching
+                    .into_iter()
+                    .skip(page.offset())
+                    .take(page.per_page)
+                    .collect();
+                (total, crates)
+            }
+            None => (total, crates),
+        };
+
         // Fetch missing informations from index
         let crates = crates
             .into_iter()
@@ -95,7 +147,13 @@ pub(crate) async fn get(
                     DATETIME_FORMAT,
                 )
                 .unwrap();
+                let highlights = hits
+                    .iter()
+                    .find(|hit| hit.id == krate.id)
+                    .map(|hit| hit.highlights.clone())
+                    .unwrap_or_default();
                 Ok(SearchResult {
+                    highlights,
                     name: krate.name,
                     max_version: latest.vers,
                     description: krate.description,
@@ -110,7 +168,7 @@ pub(crate) async fn get(
 
         Ok::<_, ApiError>(Json(SearchResponse {
             crates,
-            meta: SearchMeta { total },
+            meta: page.meta(total),
         }))
     });
 
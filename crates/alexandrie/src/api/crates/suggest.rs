@@ -11,6 +11,8 @@ use alexandrie_index::Indexer;
 use crate::config::AppState;
 use crate::error::ApiError;
 use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::visibility;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct APIResponse {
@@ -30,18 +32,28 @@ pub(crate) struct QueryParams {
 }
 
 /// Route to search through crates (used by `cargo search`).
+///
+/// Private crates are only suggested if they are accessible to the user.
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Query(params): Query<QueryParams>,
+    user: Option<Auth>,
 ) -> Result<Json<APIResponse>, ApiError> {
     let name = utils::canonical_name(params.q);
     let limit = params.limit.map_or(10, |limit| limit.get() as usize);
+    let author_id = user.map(|Auth(author)| author.id);
 
     tracing::info!("Suggester : {name} & {limit}");
 
+    let hidden = state
+        .db
+        .run(move |conn| visibility::hidden_crates(conn, author_id))
+        .await?;
+
     let results = state.search.suggest(name, limit)?;
     let suggestions: Vec<Suggestion> = results
         .into_iter()
+        .filter(|krate| !hidden.contains(&utils::canonical_name(krate)))
         .map(|krate| {
             let latest = state.index.latest_record(krate.to_lowercase().as_str())?;
             Ok(Suggestion {
@@ -0,0 +1,218 @@
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::prelude::*;
+use ring::digest as hasher;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::Indexer;
+use alexandrie_storage::error::Error as StorageError;
+use alexandrie_storage::Store;
+
+use crate::api::crates::publish::{extract_file, extract_readme};
+use crate::config::AppState;
+use crate::db::schema::{crates, pruned_tarballs};
+use crate::error::{ApiError, Error};
+use crate::utils;
+use crate::utils::audit::{self, AuditAction};
+use crate::utils::auth::api::Auth;
+use crate::utils::commit_messages::CommitFields;
+use crate::utils::page_cache;
+use crate::utils::readme::{ReadmeOrigin, VCS_INFO_FILE_NAME};
+use crate::utils::tarball;
+
+/// The warning sent along with every tarball replacement.
+const CHECKSUM_WARNING: &str = "the checksum of this version changed: clients which already resolved it (in a `Cargo.lock` file, or in their local cache) will fail to verify it until they update it";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// The reason for replacing the tarball (like "strip an accidentally published secret").
+    pub reason: String,
+}
+
+/// Response body for this route.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ResponseBody {
+    /// Whether the operation went well.
+    pub ok: bool,
+    /// The checksum of the previous tarball.
+    pub previous_cksum: String,
+    /// The checksum of the new tarball.
+    pub cksum: String,
+    /// The consequences of this replacement, for clients of the registry.
+    pub warning: String,
+}
+
+/// Route to replace the tarball of an already published version (superadmins only).
+///
+/// Published versions are immutable by design: this is an escape hatch for exceptional situations
+/// (like stripping accidentally published secrets), which breaks Cargo's checksum expectations
+/// for every client which already resolved the version.
+/// The request body is the new tarball itself (a `.crate` file, not Cargo's publication payload).
+pub(crate) async fn put(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path((name, version)): Path<(String, Version)>,
+    Query(params): Query<QueryParams>,
+    body: Bytes,
+) -> Result<Json<ResponseBody>, ApiError> {
+    //? Is the user a superadmin?
+    if !utils::checks::is_superadmin(&state.general, &author) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "only superadmins can replace the tarball of a published version",
+        ));
+    }
+
+    let reason = params.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(ApiError::msg(
+            "a reason must be given for replacing a tarball",
+        ));
+    }
+
+    let canon_name = utils::canonical_name(name);
+    let db = &state.db;
+    let (crate_id, name, repository) = db
+        .run(move |conn| {
+            //? Get the non-canonical crate name from the canonical one.
+            let krate = crates::table
+                .select((crates::id, crates::name, crates::repository))
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first::<(i64, String, Option<String>)>(conn)
+                .optional()?;
+            krate.ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no crates named '{canon_name}' could be found"),
+                )
+            })
+        })
+        .await?;
+
+    let (actor, commit_reason) = (author.name.clone(), reason.clone());
+    let (author_id, author_email) = (author.id, author.email);
+    let crate_bytes = body.to_vec();
+    let audited_version = version.to_string();
+    let cached_name = name.clone();
+    let (previous_cksum, cksum) = utils::run_blocking(&state, move |state| {
+        //? Is this version published?
+        let records = state.index.all_records(name.as_str())?;
+        let Some(record) = records.into_iter().find(|record| record.vers == version) else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("no version '{version}' of '{name}' could be found"),
+            ));
+        };
+
+        //? Is the new tarball a valid crate tarball (for this crate and version)?
+        let limit = tarball::decompression_limit(&state.general, crate_bytes.len());
+        let Some(manifest) = extract_file(&crate_bytes, &record, "Cargo.toml", limit)? else {
+            return Err(ApiError::msg(format!(
+                "the tarball must contain a `{name}-{version}/Cargo.toml` file",
+            )));
+        };
+        let readme = extract_readme(&crate_bytes, &record, limit)?;
+        let vcs_info = extract_file(&crate_bytes, &record, VCS_INFO_FILE_NAME, limit)?;
+
+        let cksum = hex::encode(hasher::digest(&hasher::SHA256, &crate_bytes).as_ref());
+        let previous_cksum = record.cksum.clone();
+        if cksum == previous_cksum {
+            return Err(ApiError::msg(
+                "the tarball is identical to the published one",
+            ));
+        }
+
+        tracing::warn!(
+            "superadmin '{0}' is replacing the tarball of `{name}#{version}` (checksum: {previous_cksum} -> {cksum}): {CHECKSUM_WARNING}",
+            author_email,
+        );
+
+        //? Replace the tarball (keeping the previous one around, to restore it if updating the crate index fails).
+        //? The previous tarball might have been pruned by the retention policy.
+        //? The new tarball is written before the previous one goes away, so a failed write leaves it untouched.
+        let previous = state.storage.get_crate(&name, version.clone()).ok();
+        state
+            .storage
+            .replace_crate(&name, version.clone(), crate_bytes)?;
+
+        //? Update the checksum in the crate index.
+        let outcome = state
+            .index
+            .alter_record(&name, version.clone(), |record| {
+                record.cksum = cksum.clone();
+            })
+            .and_then(|()| {
+                let msg = state.commit_messages.replace_tarball.render(&CommitFields {
+                    action: "Replacing",
+                    krate: name.as_str(),
+                    version: version.to_string().as_str(),
+                    actor: actor.as_str(),
+                    reason: commit_reason.as_str(),
+                });
+                state.index.commit_and_push(msg.as_str())
+            });
+        if let Err(error) = outcome {
+            let _ = match previous {
+                Some(previous) => state.storage.replace_crate(&name, version.clone(), previous),
+                None => state.storage.delete_crate(&name, version.clone()),
+            };
+            return Err(Error::from(error).into());
+        }
+
+        //? Refresh the stored manifest and README (not fatal, as the new tarball is already in place).
+        let _ = state.storage.delete_manifest(&name, version.clone());
+        match state.storage.store_manifest(&name, version.clone(), manifest) {
+            Ok(()) | Err(StorageError::Unsupported(_)) => {}
+            Err(error) => tracing::warn!("could not store the manifest of `{name}#{version}`: {error}"),
+        }
+        if let Some((file_name, readme)) = readme {
+            //? The manifest's `readme` key is not looked at, so the README is assumed to be at the package's root.
+            let origin = ReadmeOrigin::new(repository.as_deref(), vcs_info.as_deref(), None);
+            let rendered =
+                utils::readme::render(state, file_name.as_str(), readme.as_str(), &origin);
+            let _ = state.storage.delete_readme(&name, version.clone());
+            if let Err(error) = state.storage.store_readme(&name, version.clone(), rendered) {
+                tracing::warn!("could not store the README of `{name}#{version}`: {error}");
+            }
+        }
+
+        Ok((previous_cksum, cksum))
+    })
+    .await?;
+
+    let audited_reason = format!("{reason} (checksum: {previous_cksum} -> {cksum})");
+    db.transaction(move |conn| {
+        //? The version's tarball is available again (if it had been pruned by the retention policy).
+        diesel::delete(
+            pruned_tarballs::table
+                .filter(pruned_tarballs::crate_id.eq(crate_id))
+                .filter(pruned_tarballs::version.eq(audited_version.as_str())),
+        )
+        .execute(conn)?;
+
+        //? Record the replacement in the audit log (along with both checksums).
+        audit::record(
+            conn,
+            crate_id,
+            author_id,
+            AuditAction::ReplaceTarball,
+            Some(audited_version.as_str()),
+            Some(audited_reason.as_str()),
+        )
+    })
+    .await?;
+
+    page_cache::invalidate(&state, &cached_name);
+
+    Ok(Json(ResponseBody {
+        ok: true,
+        previous_cksum,
+        cksum,
+        warning: String::from(CHECKSUM_WARNING),
+    }))
+}
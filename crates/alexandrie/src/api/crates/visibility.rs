@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use diesel::prelude::*;
+use json::json;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::page_cache;
+use crate::utils::visibility::CrateVisibility;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct VisibilityBody {
+    /// The crate's new visibility.
+    pub visibility: CrateVisibility,
+}
+
+/// Route to change the visibility of a crate (public or private).
+pub(crate) async fn put(
+    State(state): State<Arc<AppState>>,
+    Auth(author): Auth,
+    Path(name): Path<String>,
+    Json(body): Json<VisibilityBody>,
+) -> Result<Json<json::Value>, ApiError> {
+    let name = utils::canonical_name(name);
+    let VisibilityBody { visibility } = body;
+
+    let canon_name = name.clone();
+    let db = &state.db;
+    let response = db
+        .transaction(move |conn| {
+            //? Does this crate exists?
+            let maybe_name = crates::table
+                .select(crates::name)
+                .filter(crates::canon_name.eq(name.as_str()))
+                .first::<String>(conn)
+                .optional()?;
+            let Some(crate_name) = maybe_name else {
+            return Err(ApiError::new(StatusCode::NOT_FOUND, format!(
+                "no crates named '{name}' could be found"
+            )));
+        };
+
+            //? Is the user an owner of this crate (collaborators cannot change its visibility)?
+            let is_owner = utils::checks::is_crate_owner(conn, name.as_str(), author.id)?;
+            if !is_owner {
+                return Err(ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    "you are not an owner of this crate",
+                ));
+            }
+
+            diesel::update(crates::table.filter(crates::canon_name.eq(name.as_str())))
+                .set(crates::visibility.eq(visibility.as_str()))
+                .execute(conn)?;
+
+            let visibility = visibility.as_str();
+            Ok(Json(json!({
+                "ok": true,
+                "msg": format!("{crate_name} is now {visibility}"),
+            })))
+        })
+        .await?;
+
+    //? Invalidate the cached pages of this crate (so that a now private crate stops being served from the cache).
+    page_cache::invalidate(&state, &canon_name);
+    Ok(response)
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use axum::Json;
 use diesel::prelude::*;
 use json::json;
@@ -12,7 +13,11 @@ use crate::config::AppState;
 use crate::db::schema::crates;
 use crate::error::ApiError;
 use crate::utils;
+use crate::utils::audit::{self, AuditAction};
 use crate::utils::auth::api::Auth;
+use crate::utils::commit_messages::CommitFields;
+use crate::utils::page_cache;
+use crate::utils::versions;
 
 pub(crate) async fn delete(
     State(state): State<Arc<AppState>>,
@@ -22,37 +27,75 @@ pub(crate) async fn delete(
     let name = utils::canonical_name(name);
 
     let db = &state.db;
-    let state = Arc::clone(&state);
-    let transaction = db.transaction(move |conn| {
-        //? Does this crate exists?
-        let exists = utils::checks::crate_exists(conn, name.as_str())?;
-        if !exists {
-            return Err(ApiError::msg(format!(
-                "no crates named '{name}' could be found"
-            )));
-        }
-
-        //? Is the user an author of this crate?
-        let is_author = utils::checks::is_crate_author(conn, name.as_str(), author.id)?;
-        if !is_author {
-            return Err(ApiError::msg("you are not an author of this crate"));
-        }
-
-        //? Get the non-canonical crate name from the canonical one.
-        let name = crates::table
-            .select(crates::name)
-            .filter(crates::canon_name.eq(name.as_str()))
-            .first::<String>(conn)?;
+    let (crate_id, name) = db
+        .run(move |conn| {
+            //? Does this crate exists?
+            let exists = utils::checks::crate_exists(conn, name.as_str())?;
+            if !exists {
+                return Err(ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("no crates named '{name}' could be found"),
+                ));
+            }
 
+            //? Is the user an owner of this crate (collaborators cannot yank nor un-yank versions)?
+            let is_owner = utils::checks::is_crate_owner(conn, name.as_str(), author.id)?;
+            if !is_owner {
+                return Err(ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    "you are not an owner of this crate",
+                ));
+            }
+
+            //? Get the non-canonical crate name from the canonical one.
+            let krate = crates::table
+                .select((crates::id, crates::name))
+                .filter(crates::canon_name.eq(name.as_str()))
+                .first::<(i64, String)>(conn)?;
+
+            Ok(krate)
+        })
+        .await?;
+
+    //? Update the crate index (without holding onto a database connection while pushing).
+    let recorded_version = version.to_string();
+    let actor = author.name.clone();
+    let cached_name = name.clone();
+    let (response, available) = utils::run_blocking(&state, move |state| {
         state.index.yank_record(name.as_str(), version.clone())?;
 
-        let msg = format!("Yanking crate `{name}#{version}`");
+        let msg = state.commit_messages.yank.render(&CommitFields {
+            action: "Yanking",
+            krate: name.as_str(),
+            version: version.to_string().as_str(),
+            actor: actor.as_str(),
+            ..CommitFields::default()
+        });
         state.index.commit_and_push(msg.as_str())?;
+        state.rdeps.set_yanked(name.as_str(), &version, true)?;
+        state.metrics.record_yank(true);
 
-        Ok(Json(json!({
+        //? Count the crate's available versions, for its rollup.
+        let records = state.index.all_records(name.as_str())?;
+        let available = versions::count_available(&records);
+
+        let response = Json(json!({
             "ok": true
-        })))
-    });
+        }));
+        Ok::<_, ApiError>((response, available))
+    })
+    .await?;
+
+    //? Update the crate's number of available versions, and record the operation in the audit log.
+    db.run(move |conn| {
+        versions::store_available(conn, crate_id, available)?;
+        let version = Some(recorded_version.as_str());
+        audit::record(conn, crate_id, author.id, AuditAction::Yank, version, None)
+    })
+    .await?;
+
+    //? Invalidate the cached pages of this crate (now that its number of available versions is up-to-date).
+    page_cache::invalidate(&state, cached_name.as_str());
 
-    transaction.await.map_err(ApiError::from)
+    Ok(response)
 }
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::Json;
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::ApiError;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct KeywordsResponse {
+    pub keywords: Vec<KeywordsResult>,
+    pub meta: KeywordsMeta,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct KeywordsResult {
+    pub keyword: String,
+    pub crates_cnt: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct KeywordsMeta {
+    pub total: usize,
+}
+
+/// Route to list keywords (along with how many crates use each of them).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<KeywordsResponse>, ApiError> {
+    let db = &state.db;
+
+    let (keywords, counts) = db
+        .run(|conn| {
+            let counts = crate_keywords::table
+                .group_by(crate_keywords::keyword_id)
+                .select((crate_keywords::keyword_id, sql::count_star()))
+                .load::<(i64, i64)>(conn)?;
+            let ids: Vec<i64> = counts.iter().map(|(id, _)| *id).collect();
+            let keywords = keywords::table
+                .select((keywords::id, keywords::name))
+                .filter(keywords::id.eq_any(ids))
+                .order_by(keywords::name.asc())
+                .load::<(i64, String)>(conn)?;
+            Ok::<_, diesel::result::Error>((keywords, counts))
+        })
+        .await?;
+
+    //? Only keywords used by at least one crate are listed.
+    let keywords: Vec<_> = keywords
+        .into_iter()
+        .map(|(id, keyword)| {
+            let crates_cnt = counts
+                .iter()
+                .find_map(|(keyword_id, count)| (*keyword_id == id).then_some(*count))
+                .unwrap_or(0);
+            KeywordsResult {
+                keyword,
+                crates_cnt,
+            }
+        })
+        .collect();
+    let total = keywords.len();
+
+    Ok(Json(KeywordsResponse {
+        keywords,
+        meta: KeywordsMeta { total },
+    }))
+}
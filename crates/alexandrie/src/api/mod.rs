@@ -4,3 +4,5 @@ pub mod account;
 pub mod categories;
 /// Crate-related endpoints (eg. "/api/v1/crates/*").
 pub mod crates;
+/// Keyword listing endpoint (eg. "/api/v1/keywords").
+pub mod keywords;
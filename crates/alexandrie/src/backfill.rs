@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use diesel::prelude::*;
+use percent_encoding::percent_decode_str;
+use semver::Version;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::Error;
+use crate::utils;
+
+/// The path prefix of the crate download endpoint.
+const DOWNLOAD_PREFIX: &str = "/api/v1/crates/";
+
+/// The outcome of a download counts backfill.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillReport {
+    /// How many lines were read from the access logs.
+    pub lines: u64,
+    /// How many successful crate downloads were found in the access logs.
+    pub events: u64,
+    /// How many of these downloads were attributed to a version of the registry.
+    pub attributed: u64,
+    /// How many versions had their download count changed.
+    pub updated_versions: u64,
+}
+
+/// Extracts the crate version downloaded by a request, from a line of an access log.
+///
+/// Lines are expected in the Common (or Combined) Log Format, as written by most web servers and reverse proxies
+/// (like `127.0.0.1 - - [15/Oct/2026:13:55:36 +0000] "GET /api/v1/crates/foo/1.0.0/download HTTP/1.1" 200 2326`).
+/// Only successful (`200 OK`) downloads are counted, like the registry does (resumed and revalidated downloads are not).
+fn parse_line(line: &str) -> Option<(String, Version)> {
+    let mut parts = line.split('"');
+    let request = parts.nth(1)?;
+    let status = parts.next()?.split_whitespace().next()?;
+    if status != "200" {
+        return None;
+    }
+
+    let mut request = request.split_whitespace();
+    let (method, target) = (request.next()?, request.next()?);
+    if method != "GET" {
+        return None;
+    }
+
+    //? The endpoint may be served under a path prefix (by a reverse proxy), and be requested with a query string.
+    let path = target.split('?').next()?;
+    let path = &path[(path.find(DOWNLOAD_PREFIX)? + DOWNLOAD_PREFIX.len())..];
+    let segments: Vec<&str> = path.split('/').collect();
+    let [name, version, "download"] = segments.as_slice() else {
+        return None;
+    };
+
+    let name = percent_decode_str(name).decode_utf8().ok()?;
+    let version = percent_decode_str(version).decode_utf8().ok()?;
+    let version = Version::parse(version.as_ref()).ok()?;
+    Some((utils::canonical_name(name.as_ref()), version))
+}
+
+/// Backfills the download counts of crate versions by replaying access logs of the registry.
+///
+/// The backfilled downloads of each version are recorded separately from the ones counted by the registry,
+/// so running it again with the same access logs changes nothing: only the difference with the previous backfill is applied.
+/// Therefore, every access log to take into account must be passed at once (including the ones already replayed before).
+/// Only the download counts of versions are backfilled (creating the records of published versions which lack one),
+/// the crates' own download counts are left as counted by the registry.
+/// In dry-run mode, the counts are computed and reported, but nothing gets modified.
+pub async fn backfill_downloads(
+    state: Arc<AppState>,
+    paths: &[PathBuf],
+    dry_run: bool,
+) -> Result<BackfillReport, Error> {
+    let mut report = BackfillReport::default();
+
+    //? Count the downloads of each version found in the access logs.
+    let mut counts: HashMap<(String, Version), i64> = HashMap::new();
+    for path in paths {
+        let reader = BufReader::new(fs::File::open(path)?);
+        for line in reader.lines() {
+            let line = line?;
+            report.lines += 1;
+            if let Some(key) = parse_line(line.as_str()) {
+                report.events += 1;
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+    }
+
+    let index_state = Arc::clone(&state);
+    let report = state
+        .db
+        .transaction(move |conn| {
+            let versions = crate_versions::table
+                .inner_join(crates::table)
+                .select((
+                    crate_versions::id,
+                    crates::canon_name,
+                    crate_versions::version,
+                    crate_versions::backfilled_downloads,
+                ))
+                .load::<(i64, String, String, i64)>(conn)?;
+
+            for (id, canon_name, version, previous) in versions {
+                let Ok(version) = Version::parse(version.as_str()) else {
+                    continue;
+                };
+                let count = counts.remove(&(canon_name, version)).unwrap_or(0);
+                report.attributed += count as u64;
+
+                //? Only the difference with the previous backfill is applied (so that it is idempotent).
+                let delta = count - previous;
+                if delta == 0 {
+                    continue;
+                }
+                report.updated_versions += 1;
+                if !dry_run {
+                    diesel::update(crate_versions::table.filter(crate_versions::id.eq(id)))
+                        .set((
+                            crate_versions::downloads.eq(crate_versions::downloads + delta),
+                            crate_versions::backfilled_downloads.eq(count),
+                        ))
+                        .execute(conn)?;
+                }
+            }
+
+            //? The remaining downloads may be of published versions without a record yet (which then gets created).
+            let mut remaining: HashMap<String, Vec<(Version, i64)>> = HashMap::new();
+            for ((canon_name, version), count) in counts {
+                remaining
+                    .entry(canon_name)
+                    .or_default()
+                    .push((version, count));
+            }
+            for (canon_name, downloads) in remaining {
+                let krate = crates::table
+                    .select((crates::id, crates::name))
+                    .filter(crates::canon_name.eq(canon_name.as_str()))
+                    .first::<(i64, String)>(conn)
+                    .optional()?;
+                let Some((crate_id, name)) = krate else {
+                    continue;
+                };
+                let records = index_state.index.all_records(name.as_str())?;
+                for (version, count) in downloads {
+                    if !records.iter().any(|record| record.vers == version) {
+                        continue;
+                    }
+                    report.attributed += count as u64;
+                    report.updated_versions += 1;
+                    if !dry_run {
+                        let id =
+                            utils::versions::ensure_recorded(conn, crate_id, &version.to_string())?;
+                        diesel::update(crate_versions::table.filter(crate_versions::id.eq(id)))
+                            .set((
+                                crate_versions::downloads.eq(crate_versions::downloads + count),
+                                crate_versions::backfilled_downloads.eq(count),
+                            ))
+                            .execute(conn)?;
+                    }
+                }
+            }
+
+            Ok::<_, Error>(report)
+        })
+        .await?;
+
+    let unattributed = report.events - report.attributed;
+    tracing::info!(
+        "{0}found {1} downloads in {2} lines, attributed {3} of them ({unattributed} to unknown versions), updating {4} versions",
+        if dry_run { "(dry run) " } else { "" },
+        report.events,
+        report.lines,
+        report.attributed,
+        report.updated_versions,
+    );
+
+    Ok(report)
+}
@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::headers::{HeaderMapExt, IfNoneMatch};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::TypedHeader;
+use diesel::prelude::*;
+use ring::digest as hasher;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::Indexer;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::response::conditional;
+use crate::utils::visibility;
+
+/// The value shown by badges of unknown (or private) crates.
+const NOT_FOUND: &str = "not found";
+
+/// The approximate width of a character, in the badges' font (11px Verdana).
+const CHAR_WIDTH: usize = 7;
+
+/// The horizontal padding of each side of a badge (in pixels).
+const PADDING: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// Overrides the configured label.
+    pub label: Option<String>,
+    /// Overrides the configured value color (like `#4c1` or `green`).
+    pub color: Option<String>,
+}
+
+/// A crate, as shown by badges.
+struct BadgeCrate {
+    name: String,
+    downloads: i64,
+}
+
+/// Escapes a text, to be inserted in an SVG document.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Returns whether a color can safely be inserted in an SVG document (like `#4c1`, `#007ec6` or `green`).
+fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6) && hex.chars().all(|ch| ch.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|ch| ch.is_ascii_alphabetic()),
+    }
+}
+
+/// Formats a download count in a compact form (like `1.2k` or `3.4M`).
+fn compact_number(number: i64) -> String {
+    let number = number.max(0) as f64;
+    match number {
+        n if n >= 1e9 => format!("{:.1}G", n / 1e9),
+        n if n >= 1e6 => format!("{:.1}M", n / 1e6),
+        n if n >= 1e3 => format!("{:.1}k", n / 1e3),
+        n => format!("{n}"),
+    }
+}
+
+/// Renders a flat badge (a label and a value, side by side).
+fn render(label: &str, value: &str, label_color: &str, color: &str) -> String {
+    let label_width = label.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let value_width = value.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let width = label_width + value_width;
+    let (label_x, value_x) = (label_width / 2, label_width + value_width / 2);
+    let (label, value) = (escape(label), escape(value));
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}"><title>{label}: {value}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="{label_color}"/><rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text><text x="{value_x}" y="15" fill="#010101" fill-opacity=".3">{value}</text><text x="{value_x}" y="14">{value}</text></g></svg>"##
+    )
+}
+
+/// Finds a public crate (private crates are not shown by badges, as badges are embedded without authentication).
+async fn find_crate(state: &AppState, name: String) -> Result<Option<BadgeCrate>, ApiError> {
+    let canon_name = utils::canonical_name(name);
+    let krate = state
+        .db
+        .run(move |conn| {
+            let maybe_crate = crates::table
+                .select((crates::id, crates::name, crates::downloads))
+                .filter(crates::canon_name.eq(canon_name))
+                .first::<(i64, String, i64)>(conn)
+                .optional()?;
+            match maybe_crate {
+                Some((id, _, _)) if !visibility::can_access(conn, id, None)? => Ok(None),
+                maybe_crate => Ok::<_, diesel::result::Error>(
+                    maybe_crate.map(|(_, name, downloads)| BadgeCrate { name, downloads }),
+                ),
+            }
+        })
+        .await?;
+    Ok(krate)
+}
+
+/// Builds the response of a badge, with its caching headers (answering conditional requests).
+fn respond(state: &AppState, svg: String, if_none_match: Option<&IfNoneMatch>) -> Response {
+    let digest = hex::encode(hasher::digest(&hasher::SHA256, svg.as_bytes()).as_ref());
+    let etag = conditional::etag(&digest[..16]);
+
+    let mut headers = HeaderMap::new();
+    let cache_control = format!("public, max-age={0}", state.badges.max_age);
+    if let Ok(cache_control) = HeaderValue::from_str(cache_control.as_str()) {
+        headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    headers.typed_insert(etag.clone());
+
+    if conditional::is_not_modified(if_none_match, None, &etag, None) {
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+    }
+
+    let content_type = HeaderValue::from_static("image/svg+xml");
+    headers.insert(header::CONTENT_TYPE, content_type);
+    (StatusCode::OK, headers, svg).into_response()
+}
+
+/// Route to get a badge showing the latest (non-yanked) version of a crate (eg. "/crates/\<name\>/badge.svg").
+///
+/// Unknown (or private) crates get a "not found" badge, rather than an error, so that embeds degrade gracefully.
+pub(crate) async fn version(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> Result<Response, ApiError> {
+    let config = &state.badges;
+    let label = params.label.unwrap_or_else(|| config.label.clone());
+    let color = params.color.filter(|color| is_valid_color(color));
+
+    let maybe_version = match find_crate(state.as_ref(), name).await? {
+        Some(krate) => utils::run_blocking(&state, move |state| {
+            //? The latest version might be yanked, in which case the latest non-yanked one is shown.
+            let latest = state.index.latest_record(krate.name.as_str())?;
+            if latest.yanked != Some(true) {
+                return Ok::<_, ApiError>(Some(latest.vers));
+            }
+            let records = state.index.all_records(krate.name.as_str())?;
+            let version = records
+                .into_iter()
+                .filter(|record| record.yanked != Some(true))
+                .map(|record| record.vers)
+                .max();
+            Ok(version)
+        })
+        .await
+        .ok()
+        .flatten(),
+        None => None,
+    };
+
+    let svg = match maybe_version {
+        Some(version) => {
+            let color = color.as_deref().unwrap_or(config.color.as_str());
+            render(&label, &format!("v{version}"), &config.label_color, color)
+        }
+        None => render(
+            &label,
+            NOT_FOUND,
+            &config.label_color,
+            &config.not_found_color,
+        ),
+    };
+
+    Ok(respond(state.as_ref(), svg, if_none_match.as_deref()))
+}
+
+/// Route to get a badge showing the total download count of a crate (eg. "/crates/\<name\>/downloads.svg").
+///
+/// Unknown (or private) crates get a "not found" badge, rather than an error, so that embeds degrade gracefully.
+pub(crate) async fn downloads(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(params): Query<QueryParams>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> Result<Response, ApiError> {
+    let config = &state.badges;
+    let label = params
+        .label
+        .unwrap_or_else(|| config.downloads_label.clone());
+    let color = params.color.filter(|color| is_valid_color(color));
+
+    let svg = match find_crate(state.as_ref(), name).await? {
+        Some(krate) => {
+            let color = color.as_deref().unwrap_or(config.color.as_str());
+            let downloads = compact_number(krate.downloads);
+            render(&label, &downloads, &config.label_color, color)
+        }
+        None => render(
+            &label,
+            NOT_FOUND,
+            &config.label_color,
+            &config.not_found_color,
+        ),
+    };
+
+    Ok(respond(state.as_ref(), svg, if_none_match.as_deref()))
+}
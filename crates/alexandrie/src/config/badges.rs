@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+/// The crate badges configuration (`[badges]` section).
+///
+/// ```toml
+/// [badges]
+/// label = "crates"              # optional, the label of the version badges
+/// downloads_label = "downloads" # optional, the label of the downloads badges
+/// label_color = "#555"          # optional, the background color of the labels
+/// color = "#007ec6"             # optional, the background color of the values
+/// not_found_color = "#9f9f9f"   # optional, the background color of the values, for unknown crates
+/// max_age = 300                 # optional, how long clients (and proxies) can cache badges (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BadgesConfig {
+    /// The label of the version badges.
+    #[serde(default = "BadgesConfig::default_label")]
+    pub label: String,
+    /// The label of the downloads badges.
+    #[serde(default = "BadgesConfig::default_downloads_label")]
+    pub downloads_label: String,
+    /// The background color of the labels.
+    #[serde(default = "BadgesConfig::default_label_color")]
+    pub label_color: String,
+    /// The background color of the values.
+    #[serde(default = "BadgesConfig::default_color")]
+    pub color: String,
+    /// The background color of the values, for unknown crates.
+    #[serde(default = "BadgesConfig::default_not_found_color")]
+    pub not_found_color: String,
+    /// How long clients (and proxies) can cache badges (in seconds).
+    #[serde(default = "BadgesConfig::default_max_age")]
+    pub max_age: u64,
+}
+
+impl BadgesConfig {
+    fn default_label() -> String {
+        String::from("crates")
+    }
+
+    fn default_downloads_label() -> String {
+        String::from("downloads")
+    }
+
+    fn default_label_color() -> String {
+        String::from("#555")
+    }
+
+    fn default_color() -> String {
+        String::from("#007ec6")
+    }
+
+    fn default_not_found_color() -> String {
+        String::from("#9f9f9f")
+    }
+
+    fn default_max_age() -> u64 {
+        300
+    }
+}
+
+impl Default for BadgesConfig {
+    fn default() -> Self {
+        Self {
+            label: Self::default_label(),
+            downloads_label: Self::default_downloads_label(),
+            label_color: Self::default_label_color(),
+            color: Self::default_color(),
+            not_found_color: Self::default_not_found_color(),
+            max_age: Self::default_max_age(),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// What to do with publications from Cargo clients older than the configured minimum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutdatedCargoAction {
+    /// Accept the publication, but warn the user (Cargo shows the warning after publishing).
+    #[default]
+    Warn,
+    /// Reject the publication, asking the user to upgrade Cargo.
+    Block,
+}
+
+/// The minimum Cargo version configuration (`[cargo_version]` section).
+///
+/// The version of Cargo is detected from the `User-Agent` header of publications (like `cargo 1.70.0 (ec8a8a0ca 2023-04-25)`).
+/// Clients whose version cannot be detected (like other publishing tools) are always allowed.
+///
+/// ```toml
+/// [cargo_version]
+/// minimum = "1.60.0"  # required, the oldest Cargo version to accept publications from without complaining
+/// action = "warn"     # optional, either "warn" (the default) or "block"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CargoVersionConfig {
+    /// The oldest Cargo version to accept publications from without complaining.
+    pub minimum: Version,
+    /// What to do with publications from older Cargo clients.
+    #[serde(default)]
+    pub action: OutdatedCargoAction,
+}
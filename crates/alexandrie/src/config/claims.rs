@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// The crate claims configuration (`[claims]` section).
+///
+/// Unowned crates (like the ones imported without their owners) can always be assigned an owner by superadmins.
+/// This section also lets authors claim them by themselves, by proving that they control the crate's repository:
+/// they must commit a proof file (containing the token given by the registry) to the repository.
+///
+/// ```toml
+/// [claims]
+/// proof_path = ".alexandrie-claim"  # optional, the path of the proof file within the repository
+/// revision = "HEAD"                 # optional, the revision (like a branch name) to look for the proof file in
+/// timeout = 10                      # optional, how long to wait for the repository's host (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimsConfig {
+    /// The path of the proof file within the repository.
+    #[serde(default = "ClaimsConfig::default_proof_path")]
+    pub proof_path: String,
+    /// The revision of the repository to look for the proof file in.
+    #[serde(default = "ClaimsConfig::default_revision")]
+    pub revision: String,
+    /// How long to wait for the repository's host, when fetching the proof file (in seconds).
+    #[serde(default = "ClaimsConfig::default_timeout")]
+    pub timeout: u64,
+}
+
+impl ClaimsConfig {
+    fn default_proof_path() -> String {
+        String::from(".alexandrie-claim")
+    }
+
+    fn default_revision() -> String {
+        String::from("HEAD")
+    }
+
+    fn default_timeout() -> u64 {
+        10
+    }
+}
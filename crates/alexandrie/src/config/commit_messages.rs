@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// The crate index commit messages configuration (`[commit_messages]` section).
+///
+/// Every message is a template, in which the following fields are interpolated:
+/// `{action}` (like "Adding" or "Yanking"), `{crate}`, `{version}`, `{actor}` (the name of the author performing the operation)
+/// and `{reason}` (only given for bulk yanks and tarball replacements, empty otherwise).
+/// Literal braces are written as `{{` and `}}`.
+///
+/// ```toml
+/// [commit_messages]
+/// publish = "{action} crate `{crate}#{version}`"                  # optional, `{action}` is "Adding" or "Updating"
+/// yank = "{action} crate `{crate}#{version}`"                     # optional
+/// unyank = "{action} crate `{crate}#{version}`"                   # optional
+/// bulk_yank = "{action} crate `{crate}` (versions: {version})"    # optional, `{version}` lists the yanked versions
+/// replace_tarball = "Replacing the tarball of crate `{crate}#{version}`" # optional
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitMessagesConfig {
+    /// The commit message of publications (of a new crate, or of a new version of an existing one).
+    #[serde(default = "CommitMessagesConfig::default_publish")]
+    pub publish: String,
+    /// The commit message of yanks.
+    #[serde(default = "CommitMessagesConfig::default_yank")]
+    pub yank: String,
+    /// The commit message of unyanks.
+    #[serde(default = "CommitMessagesConfig::default_unyank")]
+    pub unyank: String,
+    /// The commit message of bulk yanks (of several versions of a crate at once).
+    #[serde(default = "CommitMessagesConfig::default_bulk_yank")]
+    pub bulk_yank: String,
+    /// The commit message of tarball replacements (by superadmins).
+    #[serde(default = "CommitMessagesConfig::default_replace_tarball")]
+    pub replace_tarball: String,
+}
+
+impl CommitMessagesConfig {
+    fn default_publish() -> String {
+        String::from("{action} crate `{crate}#{version}`")
+    }
+
+    fn default_yank() -> String {
+        String::from("{action} crate `{crate}#{version}`")
+    }
+
+    fn default_unyank() -> String {
+        String::from("{action} crate `{crate}#{version}`")
+    }
+
+    fn default_bulk_yank() -> String {
+        String::from("{action} crate `{crate}` (versions: {version})")
+    }
+
+    fn default_replace_tarball() -> String {
+        String::from("Replacing the tarball of crate `{crate}#{version}`")
+    }
+}
+
+impl Default for CommitMessagesConfig {
+    fn default() -> Self {
+        Self {
+            publish: Self::default_publish(),
+            yank: Self::default_yank(),
+            unyank: Self::default_unyank(),
+            bulk_yank: Self::default_bulk_yank(),
+            replace_tarball: Self::default_replace_tarball(),
+        }
+    }
+}
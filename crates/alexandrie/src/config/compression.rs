@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// The response compression configuration (`[compression]` section).
+///
+/// Compression applies to the crate index and to the API (like crate metadata and search results),
+/// negotiated using the `Accept-Encoding` request header.
+/// Crate tarballs (already compressed) are never compressed again.
+///
+/// ```toml
+/// [compression]
+/// min_size = 1024  # optional, responses smaller than this are sent uncompressed (in bytes)
+/// gzip = true      # optional, whether to offer gzip compression
+/// deflate = true   # optional, whether to offer deflate compression
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// The minimum size of a response for it to get compressed (in bytes).
+    #[serde(default = "CompressionConfig::default_min_size")]
+    pub min_size: u16,
+    /// Whether to offer gzip compression.
+    #[serde(default = "CompressionConfig::default_enabled")]
+    pub gzip: bool,
+    /// Whether to offer deflate compression.
+    #[serde(default = "CompressionConfig::default_enabled")]
+    pub deflate: bool,
+}
+
+impl CompressionConfig {
+    fn default_min_size() -> u16 {
+        1024
+    }
+
+    fn default_enabled() -> bool {
+        true
+    }
+}
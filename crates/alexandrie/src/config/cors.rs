@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use axum::http::{self, HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::error::Error;
+
+/// The CORS configuration (`[cors]` section).
+///
+/// ```toml
+/// [cors]
+/// allowed_origins = ["https://dashboard.example.com"]                 # required, "*" allows any origin
+/// allowed_methods = ["GET", "HEAD", "POST", "PUT", "DELETE"]          # optional, for the programmatic API
+/// allowed_headers = ["authorization", "content-type"]                 # optional
+/// max_age = 3600                                                      # optional, how long preflight responses can be cached (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// The origins allowed to make cross-origin requests (`"*"` allows any origin).
+    pub allowed_origins: Vec<String>,
+    /// The methods allowed for cross-origin requests to the programmatic API.
+    #[serde(default = "CorsConfig::default_allowed_methods")]
+    pub allowed_methods: Vec<String>,
+    /// The request headers allowed for cross-origin requests.
+    #[serde(default = "CorsConfig::default_allowed_headers")]
+    pub allowed_headers: Vec<String>,
+    /// How long the outcome of preflight requests can be cached by browsers (in seconds).
+    #[serde(default)]
+    pub max_age: Option<u64>,
+}
+
+impl CorsConfig {
+    fn default_allowed_methods() -> Vec<String> {
+        ["GET", "HEAD", "POST", "PUT", "DELETE"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    fn default_allowed_headers() -> Vec<String> {
+        ["authorization", "content-type"]
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+
+    fn layer(&self, methods: Vec<Method>) -> Result<CorsLayer, Error> {
+        let origin = if self.allowed_origins.iter().any(|origin| origin == "*") {
+            AllowOrigin::any()
+        } else {
+            let origins = self
+                .allowed_origins
+                .iter()
+                .map(|origin| HeaderValue::from_str(origin))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(http::Error::from)?;
+            AllowOrigin::list(origins)
+        };
+        let headers = self
+            .allowed_headers
+            .iter()
+            .map(|header| HeaderName::from_bytes(header.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(http::Error::from)?;
+
+        let layer = CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods(methods)
+            .allow_headers(headers);
+        let layer = match self.max_age {
+            Some(max_age) => layer.max_age(Duration::from_secs(max_age)),
+            None => layer,
+        };
+
+        Ok(layer)
+    }
+}
+
+/// The CORS layers, created from [CorsConfig].
+#[derive(Debug, Clone)]
+pub struct CorsLayers {
+    /// The layer for the programmatic API (allowing the configured methods).
+    pub api: CorsLayer,
+    /// The layer for the crate downloads and the crate index (only allowing `GET` and `HEAD`).
+    pub read_only: CorsLayer,
+}
+
+impl TryFrom<CorsConfig> for CorsLayers {
+    type Error = Error;
+
+    fn try_from(config: CorsConfig) -> Result<Self, Self::Error> {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| Method::from_bytes(method.to_ascii_uppercase().as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(http::Error::from)?;
+
+        Ok(Self {
+            api: config.layer(methods)?,
+            read_only: config.layer(vec![Method::GET, Method::HEAD])?,
+        })
+    }
+}
@@ -12,5 +12,21 @@ pub struct DatabaseConfig {
     #[cfg(any(feature = "mysql", feature = "postgres"))]
     pub password_file: Option<String>,
     /// The maximum number of concurrent database connections.
-    pub max_conns: Option<u32>,
+    #[serde(default, alias = "max_conns")]
+    pub pool_max_size: Option<u32>,
+    /// The minimum number of idle connections kept open by the pool (defaults to the maximum number of connections).
+    #[serde(default)]
+    pub pool_min_idle: Option<u32>,
+    /// How long to wait for a connection from the pool before refusing the request (in milliseconds).
+    #[serde(default = "DatabaseConfig::default_acquire_timeout")]
+    pub acquire_timeout: u64,
+    /// The duration above which database queries are logged as slow (in milliseconds, disabled if not set).
+    #[serde(default)]
+    pub slow_query_threshold: Option<u64>,
+}
+
+impl DatabaseConfig {
+    fn default_acquire_timeout() -> u64 {
+        5000
+    }
 }
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// The download statistics configuration (`[download_stats]` section).
+///
+/// ```toml
+/// [download_stats]
+/// interval = 1    # optional, how often to snapshot the download counts of crate versions (in hours)
+/// retention = 90  # optional, how long to keep the daily snapshots (in days)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadStatsConfig {
+    /// How often to snapshot the download counts of crate versions (in hours).
+    #[serde(default = "DownloadStatsConfig::default_interval")]
+    pub interval: u64,
+    /// How long to keep the daily snapshots (in days).
+    #[serde(default = "DownloadStatsConfig::default_retention")]
+    pub retention: u64,
+}
+
+impl DownloadStatsConfig {
+    fn default_interval() -> u64 {
+        1
+    }
+
+    fn default_retention() -> u64 {
+        90
+    }
+}
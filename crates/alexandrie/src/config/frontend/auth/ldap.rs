@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// The configuration struct for the "ldap" authentication backend.
+///
+/// Users log in using an identifier (their email address or their username), and either:
+/// - their entry is searched for (using the search user, if any), then the server is bound to as that entry to check the password.
+/// - the server is directly bound to using the `bind_dn_template` (like `{username}@corp.example.com`, for Active Directory),
+///   then the entry it bound to is looked up (as themselves) to get their attributes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LdapAuthConfig {
+    /// The URL of the LDAP server (like `ldaps://ldap.example.com`).
+    pub url: String,
+    /// Whether to upgrade plain `ldap://` connections using StartTLS.
+    #[serde(default)]
+    pub starttls: bool,
+    /// The DN (or user principal name, or down-level logon name) to bind to as the user logging in,
+    /// where `{username}` is replaced with the submitted identifier (only escaped for DNs).
+    /// The user's entry is searched for before binding to it if not set.
+    pub bind_dn_template: Option<String>,
+    /// The DN of the user to search for entries as (the search is anonymous if not set).
+    pub bind_dn: Option<String>,
+    /// The password of the search user.
+    pub bind_password: Option<String>,
+    /// The DN under which user entries are searched for.
+    pub base_dn: String,
+    /// The filter to find a user's entry by, where `{username}` (or `{email}`) is replaced with the submitted identifier
+    /// (defaults to matching either the email or the username attribute).
+    pub user_filter: Option<String>,
+    /// The attribute holding the user's username (like `sAMAccountName`, for Active Directory).
+    #[serde(default = "LdapAuthConfig::default_username_attribute")]
+    pub username_attribute: String,
+    /// The attribute holding the user's email address.
+    #[serde(default = "LdapAuthConfig::default_email_attribute")]
+    pub email_attribute: String,
+    /// The attribute holding the user's displayable name.
+    #[serde(default = "LdapAuthConfig::default_name_attribute")]
+    pub name_attribute: String,
+    /// The timeout of the connection, and of every operation (in seconds).
+    #[serde(default = "LdapAuthConfig::default_timeout")]
+    pub timeout: u64,
+    /// Whether to create authors for the users logging in for the first time.
+    #[serde(default = "LdapAuthConfig::default_allow_registration")]
+    pub allow_registration: bool,
+}
+
+impl LdapAuthConfig {
+    /// Returns the filter to find the entry of a user by, from the identifier they submitted.
+    pub fn user_filter(&self, escaped_login: &str) -> String {
+        match self.user_filter.as_deref() {
+            Some(filter) => filter
+                .replace("{username}", escaped_login)
+                .replace("{email}", escaped_login),
+            None => format!(
+                "(|({0}={escaped_login})({1}={escaped_login}))",
+                self.email_attribute, self.username_attribute,
+            ),
+        }
+    }
+
+    fn default_username_attribute() -> String {
+        String::from("uid")
+    }
+
+    fn default_email_attribute() -> String {
+        String::from("mail")
+    }
+
+    fn default_name_attribute() -> String {
+        String::from("cn")
+    }
+
+    fn default_timeout() -> u64 {
+        10
+    }
+
+    fn default_allow_registration() -> bool {
+        true
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::config::password_policy::PasswordPolicyConfig;
 use crate::error::Error;
 
 /// The configuration struct for the "local" authentication strategy.
@@ -9,6 +10,9 @@ pub struct LocalAuthConfig {
     pub enabled: bool,
     /// Whether creating a new account is allowed using this strategy.
     pub allow_registration: bool,
+    /// The policy that new passwords must comply with.
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
 }
 
 impl Default for LocalAuthConfig {
@@ -16,6 +20,7 @@ impl Default for LocalAuthConfig {
         Self {
             enabled: false,
             allow_registration: true,
+            password_policy: PasswordPolicyConfig::default(),
         }
     }
 }
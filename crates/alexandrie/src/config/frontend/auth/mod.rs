@@ -1,9 +1,14 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
 /// Types for the "github" authentication strategy.
 pub mod github;
 /// Types for the "gitlab" authentication strategy.
 pub mod gitlab;
+/// Types for the "ldap" authentication backend.
+#[cfg(feature = "ldap")]
+pub mod ldap;
 /// Types for the "local" authentication strategy.
 pub mod local;
 
@@ -11,12 +16,33 @@ use crate::config::frontend::auth::github::{GithubAuthConfig, GithubAuthState};
 use crate::config::frontend::auth::gitlab::{GitlabAuthConfig, GitlabAuthState};
 use crate::config::frontend::auth::local::{LocalAuthConfig, LocalAuthState};
 use crate::error::Error;
+use crate::utils::auth::backend::{LocalAuthBackend, SharedAuthBackend};
+
+#[cfg(feature = "ldap")]
+use crate::config::frontend::auth::ldap::LdapAuthConfig;
+#[cfg(feature = "ldap")]
+use crate::utils::auth::backend::LdapAuthBackend;
+
+/// The backend used to check the credentials submitted using the login form.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthBackendKind {
+    /// The passwords stored in the database (the "local" strategy).
+    #[default]
+    Local,
+    /// An LDAP server (configured in the `[frontend.auth.ldap]` section).
+    #[cfg(feature = "ldap")]
+    Ldap,
+}
 
 /// The configuration struct for authentication strategies.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AuthConfig {
     /// The origin at which this current instance of Alexandrie is reachable.
     pub origin: String,
+    /// The backend used by the login form (defaults to the "local" strategy).
+    #[serde(default)]
+    pub backend: AuthBackendKind,
     /// Configuration regarding the "local" authentication strategy.
     #[serde(default)]
     pub local: LocalAuthConfig,
@@ -26,12 +52,25 @@ pub struct AuthConfig {
     /// Configuration regarding the "gitlab" authentication strategy.
     #[serde(default)]
     pub gitlab: GitlabAuthConfig,
+    /// Configuration regarding the "ldap" authentication backend.
+    #[cfg(feature = "ldap")]
+    #[serde(default)]
+    pub ldap: Option<LdapAuthConfig>,
 }
 
 impl AuthConfig {
     /// return true if at least one configuration is enabled
     pub fn enabled(&self) -> bool {
-        self.local.enabled || self.github.enabled || self.gitlab.enabled
+        self.login_form_enabled() || self.github.enabled || self.gitlab.enabled
+    }
+
+    /// return true if the email/password login form is enabled (depending on the selected backend)
+    pub fn login_form_enabled(&self) -> bool {
+        match self.backend {
+            AuthBackendKind::Local => self.local.enabled,
+            #[cfg(feature = "ldap")]
+            AuthBackendKind::Ldap => true,
+        }
     }
 
     /// return true if at least one registration is allowed
@@ -50,6 +89,8 @@ pub struct AuthState {
     pub github: Option<GithubAuthState>,
     /// The authentication state for the "gitlab" strategy, if enabled.
     pub gitlab: Option<GitlabAuthState>,
+    /// The backend of the login form, if enabled.
+    pub backend: Option<SharedAuthBackend>,
 }
 
 impl AuthState {
@@ -64,11 +105,21 @@ impl AuthState {
         let gitlab = (config.gitlab.enabled)
             .then(|| GitlabAuthState::new(&config.gitlab, &config.origin))
             .transpose()?;
+        let backend: Option<SharedAuthBackend> = match config.backend {
+            AuthBackendKind::Local => (config.local.enabled)
+                .then(|| Arc::new(LocalAuthBackend::new(&config.local)) as SharedAuthBackend),
+            #[cfg(feature = "ldap")]
+            AuthBackendKind::Ldap => {
+                let ldap = config.ldap.as_ref().ok_or(Error::MissingLdapConfig)?;
+                Some(Arc::new(LdapAuthBackend::new(ldap)))
+            }
+        };
 
         Ok(Self {
             local,
             github,
             gitlab,
+            backend,
         })
     }
 }
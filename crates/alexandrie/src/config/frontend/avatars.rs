@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// The configuration of the avatars shown for authors (like crate owners).
+///
+/// Avatars are looked up by the SHA-256 hash of the author's (trimmed and lowercased) email address,
+/// so that the email address itself is never exposed.
+///
+/// ```toml
+/// [frontend.avatars]
+/// enabled = true
+/// url = "https://www.gravatar.com/avatar/{hash}?s={size}&d=identicon"  # optional, `{hash}` and `{size}` are substituted
+/// size = 64                                                           # optional, in pixels
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AvatarsConfig {
+    /// Whether to show avatars.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The URL template of avatars (where `{hash}` and `{size}` are substituted), defaults to Gravatar.
+    #[serde(default = "AvatarsConfig::default_url")]
+    pub url: String,
+    /// The size of avatars (in pixels).
+    #[serde(default = "AvatarsConfig::default_size")]
+    pub size: u32,
+}
+
+impl AvatarsConfig {
+    fn default_url() -> String {
+        String::from("https://www.gravatar.com/avatar/{hash}?s={size}&d=identicon")
+    }
+
+    fn default_size() -> u32 {
+        64
+    }
+}
+
+impl Default for AvatarsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: Self::default_url(),
+            size: Self::default_size(),
+        }
+    }
+}
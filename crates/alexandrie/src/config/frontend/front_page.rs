@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+/// The kinds of crate lists that the front page can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SectionKind {
+    /// The most recently published crates (by creation date).
+    RecentlyPublished,
+    /// The most recently updated crates.
+    RecentlyUpdated,
+    /// The crates with the highest download growth over the trending window.
+    Trending,
+    /// The most downloaded crates (of all time).
+    MostDownloaded,
+}
+
+/// The configuration of one section of the front page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionConfig {
+    /// The kind of crate list shown by this section.
+    pub kind: SectionKind,
+    /// The number of crates shown by this section.
+    #[serde(default = "SectionConfig::default_size")]
+    pub size: usize,
+}
+
+impl SectionConfig {
+    fn default_size() -> usize {
+        10
+    }
+}
+
+/// The front page configuration (`[frontend.front_page]` section).
+///
+/// ```toml
+/// [frontend.front_page]
+/// trending_window = 7  # optional, the window over which download growth is measured (in days)
+/// refresh_interval = 1 # optional, how often download snapshots are taken and trending crates are computed (in hours)
+/// sections = [         # optional, the sections to show (in order)
+///     { kind = "trending", size = 10 },
+///     { kind = "recently-published", size = 5 },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrontPageConfig {
+    /// The sections to show on the front page (in order).
+    #[serde(default = "FrontPageConfig::default_sections")]
+    pub sections: Vec<SectionConfig>,
+    /// The window over which download growth is measured for trending crates (in days).
+    #[serde(default = "FrontPageConfig::default_trending_window")]
+    pub trending_window: u64,
+    /// How often download snapshots are taken and trending crates are recomputed (in hours).
+    #[serde(default = "FrontPageConfig::default_refresh_interval")]
+    pub refresh_interval: u64,
+}
+
+impl FrontPageConfig {
+    fn default_sections() -> Vec<SectionConfig> {
+        let kinds = [
+            SectionKind::MostDownloaded,
+            SectionKind::RecentlyUpdated,
+            SectionKind::Trending,
+            SectionKind::RecentlyPublished,
+        ];
+        kinds
+            .into_iter()
+            .map(|kind| SectionConfig {
+                kind,
+                size: SectionConfig::default_size(),
+            })
+            .collect()
+    }
+
+    fn default_trending_window() -> u64 {
+        7
+    }
+
+    fn default_refresh_interval() -> u64 {
+        1
+    }
+
+    /// Returns the number of trending crates to keep (the largest size among the trending sections).
+    pub fn trending_size(&self) -> usize {
+        self.sections
+            .iter()
+            .filter(|section| section.kind == SectionKind::Trending)
+            .map(|section| section.size)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl Default for FrontPageConfig {
+    fn default() -> Self {
+        Self {
+            sections: Self::default_sections(),
+            trending_window: Self::default_trending_window(),
+            refresh_interval: Self::default_refresh_interval(),
+        }
+    }
+}
@@ -1,14 +1,23 @@
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
 use handlebars::Handlebars;
 use serde::{Deserialize, Serialize};
 
 use crate::config::frontend::auth::{AuthConfig, AuthState};
+use crate::config::frontend::avatars::AvatarsConfig;
+use crate::config::frontend::front_page::FrontPageConfig;
 
 /// The frontend authentication configuration module.
 pub mod auth;
+/// The author avatars configuration module.
+pub mod avatars;
+/// The front page configuration module.
+pub mod front_page;
 
+use crate::frontend::assets::{AssetHelper, AssetManifest};
 use crate::frontend::helpers;
+use crate::frontend::trending::TrendingCrate;
 
 fn enabled_def() -> bool {
     true
@@ -28,6 +37,18 @@ pub struct Link {
 pub struct AssetsConfig {
     /// Assets directory path.
     pub path: PathBuf,
+    /// Whether to disable the fingerprinting and caching of assets (for local iteration on them).
+    #[serde(default)]
+    pub dev_mode: bool,
+    /// How long clients can cache fingerprinted assets (in seconds).
+    #[serde(default = "AssetsConfig::default_max_age")]
+    pub max_age: u64,
+}
+
+impl AssetsConfig {
+    fn default_max_age() -> u64 {
+        365 * 24 * 60 * 60
+    }
 }
 
 /// The templates configuration options struct.
@@ -37,6 +58,19 @@ pub struct TemplatesConfig {
     pub path: PathBuf,
 }
 
+/// The `SameSite` policy of the session cookie.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SameSitePolicy {
+    /// The cookie is only sent along with same-site requests.
+    Strict,
+    /// The cookie is also sent when navigating to the registry from another site.
+    #[default]
+    Lax,
+    /// The cookie is sent along with every request (this requires the cookie to be `Secure`).
+    None,
+}
+
 /// The session-handling configuration struct.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SessionsConfig {
@@ -44,6 +78,31 @@ pub struct SessionsConfig {
     pub cookie_name: String,
     /// The secret to use to sign cookies with.
     pub secret: String,
+    /// Whether the session cookie is marked as `Secure` (only sent over HTTPS).
+    ///
+    /// This is independent of whether the registry itself is served over HTTPS,
+    /// so that it can be kept enabled behind a TLS-terminating reverse proxy.
+    #[serde(default = "SessionsConfig::default_secure")]
+    pub secure: bool,
+    /// The `SameSite` policy of the session cookie.
+    #[serde(default)]
+    pub same_site: SameSitePolicy,
+    /// The domain of the session cookie (the cookie is restricted to the registry's host if not set).
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// How long sessions last (in seconds).
+    #[serde(default = "SessionsConfig::default_max_age")]
+    pub max_age: u64,
+}
+
+impl SessionsConfig {
+    fn default_secure() -> bool {
+        true
+    }
+
+    fn default_max_age() -> u64 {
+        24 * 60 * 60
+    }
 }
 
 /// The frontend configuration struct.
@@ -62,6 +121,26 @@ pub struct FrontendConfig {
     pub links: Option<Vec<Link>>,
     /// Whether to disallow anonymous browsing of the registry.
     pub login_required: bool,
+    /// The host serving the crates' documentation (like "https://docs.rs"),
+    /// linked to for the crates that do not specify a documentation URL.
+    #[serde(default)]
+    pub docs_host: Option<String>,
+    /// The name under which users declare this registry in their Cargo configuration (like "my-registry").
+    ///
+    /// If set, the installation instructions on crate pages refer to this registry explicitly
+    /// (like `cargo add foo --registry my-registry`).
+    #[serde(default)]
+    pub registry_name: Option<String>,
+    /// Whether crate pages list yanked versions by default (in the version list and the dependency snippet).
+    ///
+    /// Visitors can still show (or hide) them using the `yanked` query parameter.
+    #[serde(default)]
+    pub show_yanked_versions: bool,
+    /// Whether crate pages list pre-release versions by default (in the version list and the dependency snippet).
+    ///
+    /// Visitors can still show (or hide) them using the `prereleases` query parameter.
+    #[serde(default = "FrontendConfig::default_show_prerelease_versions")]
+    pub show_prerelease_versions: bool,
     /// Assets configuration options.
     pub assets: AssetsConfig,
     /// Templates configuration options.
@@ -70,6 +149,12 @@ pub struct FrontendConfig {
     pub sessions: SessionsConfig,
     /// The authentication configuration.
     pub auth: AuthConfig,
+    /// The front page configuration.
+    #[serde(default)]
+    pub front_page: FrontPageConfig,
+    /// The author avatars configuration.
+    #[serde(default)]
+    pub avatars: AvatarsConfig,
 }
 
 /// The frontend state struct, created from [FrontendConfig].
@@ -80,6 +165,16 @@ pub struct FrontendState {
     pub handlebars: Handlebars<'static>,
     /// The frontend configuration.
     pub config: FrontendConfig,
+    /// The trending crates, as last computed by the periodic refresh task.
+    pub trending: RwLock<Vec<TrendingCrate>>,
+    /// The fingerprints of the assets.
+    pub assets: Arc<AssetManifest>,
+}
+
+impl FrontendConfig {
+    fn default_show_prerelease_versions() -> bool {
+        true
+    }
 }
 
 impl From<FrontendConfig> for FrontendState {
@@ -91,12 +186,16 @@ impl From<FrontendConfig> for FrontendState {
             .register_templates_directory(".hbs", &config.templates.path)
             .expect("could not register templates directory to Handlebars");
 
+        let assets = Arc::new(AssetManifest::from(&config.assets));
         handlebars.register_helper("equal", Box::new(helpers::hbs_equal));
+        handlebars.register_helper("asset", Box::new(AssetHelper(Arc::clone(&assets))));
 
         FrontendState {
             auth,
             handlebars,
             config,
+            trending: RwLock::new(Vec::new()),
+            assets,
         }
     }
 }
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// The connection security to use with the SMTP server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SmtpSecurity {
+    /// Use implicit TLS (usually on port 465).
+    Tls,
+    /// Upgrade the connection using STARTTLS (usually on port 587).
+    #[default]
+    Starttls,
+    /// Do not encrypt the connection (only suitable for local relays).
+    None,
+}
+
+/// The outgoing emails configuration (`[mail]` section).
+///
+/// ```toml
+/// [mail]
+/// type = "<...>" # required, replace "<...>" by the selected transport ("smtp" or "log").
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum MailConfig {
+    /// The 'smtp' transport (emails are sent through an SMTP server).
+    Smtp(SmtpMailConfig),
+    /// The 'log' transport (emails are only logged, for development purposes).
+    Log,
+}
+
+/// The 'smtp' email transport configuration.
+///
+/// ```toml
+/// [mail]
+/// type = "smtp"
+/// smtp_host = "smtp.example.com"            # required
+/// smtp_port = 587                           # optional, defaults to the standard port of the selected security
+/// security = "starttls"                     # optional, one of "tls", "starttls" or "none"
+/// username = "alexandrie"                   # optional
+/// password = "..."                          # optional
+/// from = "Alexandrie <noreply@example.com>" # required
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmtpMailConfig {
+    /// The hostname of the SMTP server to send emails through.
+    pub smtp_host: String,
+    /// The port of the SMTP server (defaults to the standard port of the selected security).
+    pub smtp_port: Option<u16>,
+    /// The connection security to use with the SMTP server.
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    /// The username to authenticate to the SMTP server with.
+    pub username: Option<String>,
+    /// The password to authenticate to the SMTP server with.
+    pub password: Option<String>,
+    /// The mailbox to send emails from (like "Alexandrie <noreply@example.com>").
+    pub from: String,
+}
@@ -1,16 +1,55 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
+/// Crate badges configuration (`[badges]` section).
+pub mod badges;
+/// Minimum Cargo version configuration (`[cargo_version]` section).
+pub mod cargo_version;
+/// Unowned crate claims configuration (`[claims]` section).
+pub mod claims;
+/// Crate index commit messages configuration (`[commit_messages]` section).
+pub mod commit_messages;
+/// Response compression configuration (`[compression]` section).
+pub mod compression;
+/// Cross-origin requests configuration (`[cors]` section).
+pub mod cors;
 /// Database configuration (`[database]` section).
 pub mod database;
+/// Download statistics configuration (`[download_stats]` section).
+pub mod download_stats;
 /// Frontend configuration (`[frontend]` section).
 #[cfg(feature = "frontend")]
 pub mod frontend;
+/// Outgoing emails configuration (`[mail]` section).
+pub mod mail;
+/// Publish notifications configuration (`[notifications]` section).
+pub mod notifications;
+/// Crate pages cache configuration (`[page_cache]` section).
+pub mod page_cache;
+/// Listing endpoints pagination configuration (`[pagination]` section).
+pub mod pagination;
+/// Password policy configuration (`[frontend.auth.local.password_policy]` section).
+pub mod password_policy;
+/// Pre-publish hook configuration (`[publish_hook]` section).
+pub mod publish_hook;
+/// Publish concurrency limit configuration (`[publish_queue]` section).
+pub mod publish_queue;
+/// Per-author publish quotas configuration (`[quotas]` section).
+pub mod quotas;
+/// Crate tarball retention policy configuration (`[retention]` section).
+pub mod retention;
+/// Graceful shutdown configuration (`[shutdown]` section).
+pub mod shutdown;
+/// Sparse index configuration (`[sparse]` section).
+pub mod sparse;
 
 /// Serde (de)serialization helper functions.
 pub mod serde_utils;
 
 use alexandrie_index::config::IndexConfig;
-use alexandrie_index::Index;
+use alexandrie_index::{ConfigFile, Index, Indexer};
+#[cfg(feature = "rendering")]
 use alexandrie_rendering::config::{SyntectConfig, SyntectState};
 use alexandrie_storage::config::StorageConfig;
 use alexandrie_storage::Storage;
@@ -21,8 +60,37 @@ use crate::db::Database;
 pub use crate::config::frontend::*;
 use crate::error::Error;
 use crate::fts::Tantivy;
+use crate::metrics::Metrics;
+use crate::rdeps::ReverseDependencies;
+use crate::retention::RetentionPolicy;
+use crate::utils;
+use crate::utils::auth::passwd::PasswordHasher;
+use crate::utils::commit_messages::CommitMessages;
+use crate::utils::mail::Mail;
+use crate::utils::page_cache::PageCache;
+use crate::utils::publish_hook::PublishHook;
+use crate::utils::publish_queue::PublishQueue;
+use crate::utils::shutdown::InFlight;
 
+use self::badges::BadgesConfig;
+use self::cargo_version::CargoVersionConfig;
+use self::claims::ClaimsConfig;
+use self::commit_messages::CommitMessagesConfig;
+use self::compression::CompressionConfig;
+use self::cors::CorsConfig;
 use self::database::DatabaseConfig;
+use self::download_stats::DownloadStatsConfig;
+use self::mail::MailConfig;
+use self::notifications::NotificationsConfig;
+use self::page_cache::PageCacheConfig;
+use self::pagination::PaginationConfig;
+use self::password_policy::PasswordPolicyConfig;
+use self::publish_hook::PublishHookConfig;
+use self::publish_queue::PublishQueueConfig;
+use self::quotas::QuotasConfig;
+use self::retention::RetentionConfig;
+use self::shutdown::ShutdownConfig;
+use self::sparse::SparseConfig;
 
 /// The general configuration options struct.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -32,6 +100,257 @@ pub struct GeneralConfig {
     /// The maximum allowed crate size.
     #[serde(deserialize_with = "serde_utils::deserialize_file_size_opt")]
     max_crate_size: Option<u64>,
+    /// The maximum allowed request body size, for every endpoint other than publication.
+    #[serde(
+        default = "GeneralConfig::default_max_body_size",
+        deserialize_with = "serde_utils::deserialize_file_size"
+    )]
+    max_body_size: u64,
+    /// The maximum size of the decompressed contents of a published crate, as a multiple of its compressed size.
+    #[serde(default = "GeneralConfig::default_max_decompression_ratio")]
+    max_decompression_ratio: u64,
+    /// The maximum size of the decompressed contents of a published crate (regardless of its compressed size).
+    #[serde(
+        default = "GeneralConfig::default_max_decompressed_size",
+        deserialize_with = "serde_utils::deserialize_file_size"
+    )]
+    max_decompressed_size: u64,
+    /// The category tags that crates are allowed to be published under.
+    /// If not set, every category known to the registry is allowed.
+    #[serde(default)]
+    allowed_categories: Option<Vec<String>>,
+    /// The maximum number of keywords per crate.
+    #[serde(default = "GeneralConfig::default_max_keywords")]
+    max_keywords: usize,
+    /// The maximum length of a keyword (in characters).
+    #[serde(default = "GeneralConfig::default_max_keyword_length")]
+    max_keyword_length: usize,
+    /// The maximum length of a crate name (in characters).
+    #[serde(default = "GeneralConfig::default_max_crate_name_length")]
+    max_crate_name_length: usize,
+    /// The crate names (or glob-style prefixes, like `acme-*`) reserved from being published.
+    #[serde(default)]
+    reserved_names: Vec<ReservedName>,
+    /// Restrictions of the dependency registries allowed for some crates (on top of the crate index's allowed registries).
+    #[serde(default)]
+    registry_policies: Vec<RegistryPolicy>,
+    /// How long crate owner invitations remain valid (in days).
+    #[serde(default = "GeneralConfig::default_owner_invitation_lifetime")]
+    owner_invitation_lifetime: u64,
+    /// Whether to validate the features of published crates (rejecting the ones referring to unknown dependencies).
+    #[serde(default = "GeneralConfig::default_validate_features")]
+    validate_features: bool,
+    /// Whether to accept already rendered READMEs from publishing tools (instead of always rendering them server-side).
+    #[serde(default = "GeneralConfig::default_allow_prerendered_readme")]
+    allow_prerendered_readme: bool,
+    /// Whether to reject published crates whose `license` is not a valid SPDX expression (instead of only warning about it).
+    #[serde(default)]
+    strict_licenses: bool,
+    /// The SPDX license identifiers that crates are allowed to be published under.
+    /// If not set, every license is allowed.
+    #[serde(default)]
+    allowed_licenses: Option<Vec<String>>,
+    /// Whether crates without a `license` (but with a `license-file`) can be published when `allowed_licenses` is set
+    /// (their license cannot be checked against the allowed ones).
+    #[serde(default)]
+    allow_license_file: bool,
+    /// The password hashing parameters.
+    #[serde(default)]
+    password_hashing: PasswordHashingConfig,
+    /// Whether the registry is in read-only mode (for maintenance, like migrations or backups).
+    #[serde(default)]
+    read_only: bool,
+    /// The IDs of the superadmins (authors allowed to perform exceptional operations, like replacing a published tarball).
+    ///
+    /// Authors are identified by their IDs rather than their emails, since emails are chosen (and not verified) at registration.
+    #[serde(default)]
+    superadmins: Vec<i64>,
+}
+
+impl GeneralConfig {
+    fn default_max_body_size() -> u64 {
+        2 * 1024 * 1024
+    }
+
+    fn default_max_decompression_ratio() -> u64 {
+        100
+    }
+
+    fn default_max_decompressed_size() -> u64 {
+        512 * 1024 * 1024
+    }
+
+    fn default_max_keywords() -> usize {
+        5
+    }
+
+    fn default_max_keyword_length() -> usize {
+        20
+    }
+
+    fn default_max_crate_name_length() -> usize {
+        64
+    }
+
+    fn default_owner_invitation_lifetime() -> u64 {
+        30
+    }
+
+    fn default_validate_features() -> bool {
+        true
+    }
+
+    fn default_allow_prerendered_readme() -> bool {
+        true
+    }
+}
+
+/// The password hashing parameters (Argon2id).
+///
+/// Stored hashes using weaker parameters are transparently upgraded when their authors log in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordHashingConfig {
+    /// The amount of memory to use (in KiB).
+    #[serde(default = "PasswordHashingConfig::default_memory")]
+    pub memory: u32,
+    /// The number of iterations.
+    #[serde(default = "PasswordHashingConfig::default_iterations")]
+    pub iterations: u32,
+    /// The degree of parallelism.
+    #[serde(default = "PasswordHashingConfig::default_parallelism")]
+    pub parallelism: u32,
+}
+
+impl PasswordHashingConfig {
+    fn default_memory() -> u32 {
+        19_456
+    }
+
+    fn default_iterations() -> u32 {
+        2
+    }
+
+    fn default_parallelism() -> u32 {
+        1
+    }
+}
+
+impl Default for PasswordHashingConfig {
+    fn default() -> Self {
+        Self {
+            memory: Self::default_memory(),
+            iterations: Self::default_iterations(),
+            parallelism: Self::default_parallelism(),
+        }
+    }
+}
+
+/// A reserved crate name (or glob-style prefix, like `acme-*`).
+///
+/// Names are compared in their canonical form (case-insensitive, with `-` and `_` being equivalent).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReservedName {
+    /// A name (or prefix) that nobody is allowed to publish.
+    Name(String),
+    /// A name (or prefix) that only some authors are allowed to publish.
+    Restricted {
+        /// The reserved name (or prefix).
+        pattern: String,
+        /// The emails of the authors allowed to publish crates matching this name (or prefix).
+        #[serde(default)]
+        allowed_authors: Vec<String>,
+    },
+}
+
+impl ReservedName {
+    /// Returns the reserved name (or prefix).
+    pub fn pattern(&self) -> &str {
+        match self {
+            ReservedName::Name(pattern) => pattern.as_str(),
+            ReservedName::Restricted { pattern, .. } => pattern.as_str(),
+        }
+    }
+
+    /// Returns whether the given crate name matches this reserved name (or prefix).
+    pub fn matches(&self, name: &str) -> bool {
+        matches_pattern(self.pattern(), name)
+    }
+
+    /// Returns whether the author (identified by their email) is allowed to publish crates matching this name (or prefix).
+    pub fn allows(&self, email: &str) -> bool {
+        match self {
+            ReservedName::Name(_) => false,
+            ReservedName::Restricted {
+                allowed_authors, ..
+            } => allowed_authors
+                .iter()
+                .any(|it| it.eq_ignore_ascii_case(email)),
+        }
+    }
+}
+
+/// Returns whether a crate name matches a name (or glob-style prefix, like `acme-*`), in their canonical form.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let name = utils::canonical_name(name);
+    let pattern = utils::canonical_name(pattern);
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+/// A restriction of the registries that the dependencies of some crates can come from.
+///
+/// It applies to the crates matching its name (or glob-style prefix), owned by one of its authors
+/// (if either of them is not set, it is not taken into account).  
+/// The owners of a crate are the authors with the `owner` role (the publisher, for a crate's first publication).  
+/// Its registries compose with the ones allowed by the crate index: dependencies must come from registries allowed by both.
+///
+/// ```toml
+/// [[general.registry_policies]]
+/// pattern = "acme-*"                  # optional, the crate names (or glob-style prefix) it applies to
+/// authors = ["platform@acme.com"]     # optional, the emails of the owners it applies to
+/// allowed_registries = []             # required, the allowed registries (empty to only allow this registry)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistryPolicy {
+    /// The crate names (or glob-style prefix) this policy applies to.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// The emails of the crate owners this policy applies to.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// The index URLs of the registries that dependencies are allowed to come from
+    /// (dependencies from this registry are always allowed).
+    pub allowed_registries: Vec<String>,
+}
+
+impl RegistryPolicy {
+    /// Returns whether this policy applies to a crate (by its name), owned by the given authors (by their emails).
+    pub fn applies_to(&self, name: &str, owners: &[String]) -> bool {
+        let matches_name = self
+            .pattern
+            .as_deref()
+            .map_or(true, |pattern| matches_pattern(pattern, name));
+        let matches_author = self.authors.is_empty()
+            || (self.authors.iter())
+                .any(|it| owners.iter().any(|owner| it.eq_ignore_ascii_case(owner)));
+        matches_name && matches_author
+    }
+
+    /// Describes which crates this policy applies to (for error messages).
+    pub fn describe(&self) -> String {
+        match (self.pattern.as_deref(), self.authors.is_empty()) {
+            (Some(pattern), true) => format!("crates matching `{pattern}`"),
+            (Some(pattern), false) => format!(
+                "crates matching `{pattern}` owned by {0}",
+                self.authors.join(", ")
+            ),
+            (None, false) => format!("crates owned by {0}", self.authors.join(", ")),
+            (None, true) => String::from("every crate"),
+        }
+    }
 }
 
 /// Configuration for search index.
@@ -39,6 +358,62 @@ pub struct GeneralConfig {
 pub struct SearchConfig {
     /// Path to the directory where Tantivy will store its index.
     pub path: String,
+    /// The maximum length of the highlighted fragments of search results (in characters).
+    #[serde(default = "SearchConfig::default_highlight_length")]
+    pub highlight_length: usize,
+}
+
+impl SearchConfig {
+    fn default_highlight_length() -> usize {
+        150
+    }
+}
+
+/// The metrics endpoint configuration (`[metrics]` section).
+///
+/// The metrics endpoint is disabled unless at least one of these options is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Serve the metrics endpoint on this dedicated address, instead of the main one.
+    pub bind_address: Option<String>,
+    /// Require this bearer token (in the `Authorization` header) to access the metrics endpoint.
+    pub token: Option<String>,
+}
+
+impl MetricsConfig {
+    /// Returns whether the metrics endpoint should be served on the main address.
+    pub fn served_on_main_address(&self) -> bool {
+        self.bind_address.is_none() && self.token.is_some()
+    }
+}
+
+/// The OpenTelemetry configuration (`[opentelemetry]` section).
+#[cfg(feature = "opentelemetry")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenTelemetryConfig {
+    /// The OTLP (gRPC) endpoint to export spans to (like "http://localhost:4317").
+    /// Spans are not exported if this is not set.
+    pub endpoint: Option<String>,
+    /// The service name to report spans under.
+    #[serde(default = "OpenTelemetryConfig::default_service_name")]
+    pub service_name: String,
+}
+
+#[cfg(feature = "opentelemetry")]
+impl OpenTelemetryConfig {
+    fn default_service_name() -> String {
+        String::from("alexandrie")
+    }
+}
+
+#[cfg(feature = "opentelemetry")]
+impl Default for OpenTelemetryConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            service_name: Self::default_service_name(),
+        }
+    }
 }
 
 /// The application configuration struct.
@@ -53,9 +428,68 @@ pub struct Config {
     /// The database configuration.
     pub database: DatabaseConfig,
     /// The syntax-highlighting configuration.
+    #[cfg(feature = "rendering")]
     pub syntect: SyntectConfig,
     /// Search config
     pub search: SearchConfig,
+    /// The metrics endpoint configuration.
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// The outgoing emails configuration (emails are disabled if not set).
+    #[serde(default)]
+    pub mail: Option<MailConfig>,
+    /// The CORS configuration (cross-origin requests are not allowed if not set).
+    #[serde(default)]
+    pub cors: Option<CorsConfig>,
+    /// The pagination configuration of the listing endpoints.
+    #[serde(default)]
+    pub pagination: PaginationConfig,
+    /// The crate tarball retention policy configuration (tarballs are kept forever if not set).
+    #[serde(default)]
+    pub retention: Option<RetentionConfig>,
+    /// The download statistics configuration (download counts are not snapshotted if not set).
+    #[serde(default)]
+    pub download_stats: Option<DownloadStatsConfig>,
+    /// The publish notifications configuration (subscribers are not notified if not set).
+    #[serde(default)]
+    pub notifications: Option<NotificationsConfig>,
+    /// The per-author publish quotas configuration (publications are unlimited if not set).
+    #[serde(default)]
+    pub quotas: Option<QuotasConfig>,
+    /// The pre-publish hook configuration (publications are not validated externally if not set).
+    #[serde(default)]
+    pub publish_hook: Option<PublishHookConfig>,
+    /// The publish concurrency limit configuration (publications are processed as they come if not set).
+    #[serde(default)]
+    pub publish_queue: Option<PublishQueueConfig>,
+    /// The crate badges configuration.
+    #[serde(default)]
+    pub badges: BadgesConfig,
+    /// The crate pages cache configuration (crate pages are not cached if not set).
+    #[serde(default)]
+    pub page_cache: Option<PageCacheConfig>,
+    /// The sparse index configuration (the committed `config.json` of the crate index is served as-is if not set).
+    #[serde(default)]
+    pub sparse: Option<SparseConfig>,
+    /// The graceful shutdown configuration.
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+    /// The crate index commit messages configuration.
+    #[serde(default)]
+    pub commit_messages: CommitMessagesConfig,
+    /// The unowned crate claims configuration (only superadmins can assign unowned crates if not set).
+    #[serde(default)]
+    pub claims: Option<ClaimsConfig>,
+    /// The response compression configuration (responses are sent uncompressed if not set).
+    #[serde(default)]
+    pub compression: Option<CompressionConfig>,
+    /// The minimum Cargo version configuration (publications are accepted from any Cargo version if not set).
+    #[serde(default)]
+    pub cargo_version: Option<CargoVersionConfig>,
+    /// The OpenTelemetry configuration.
+    #[cfg(feature = "opentelemetry")]
+    #[serde(default)]
+    pub opentelemetry: OpenTelemetryConfig,
     /// The frontend configuration.
     #[cfg(feature = "frontend")]
     pub frontend: FrontendConfig,
@@ -65,6 +499,42 @@ pub struct Config {
 pub struct GeneralState {
     /// The maximum crate size allowed for publication.
     pub max_crate_size: Option<u64>,
+    /// The maximum request body size allowed for every endpoint other than publication.
+    pub max_body_size: u64,
+    /// The maximum decompressed size of a published crate, as a multiple of its compressed size.
+    pub max_decompression_ratio: u64,
+    /// The maximum decompressed size of a published crate (regardless of its compressed size).
+    pub max_decompressed_size: u64,
+    /// The category tags that crates are allowed to be published under (`None` allows every known category).
+    pub allowed_categories: Option<Vec<String>>,
+    /// The maximum number of keywords per crate.
+    pub max_keywords: usize,
+    /// The maximum length of a keyword (in characters).
+    pub max_keyword_length: usize,
+    /// The maximum length of a crate name (in characters).
+    pub max_crate_name_length: usize,
+    /// The crate names (or glob-style prefixes) reserved from being published.
+    pub reserved_names: Vec<ReservedName>,
+    /// Restrictions of the dependency registries allowed for some crates (on top of the crate index's allowed registries).
+    pub registry_policies: Vec<RegistryPolicy>,
+    /// How long crate owner invitations remain valid (in days).
+    pub owner_invitation_lifetime: u64,
+    /// Whether to validate the features of published crates.
+    pub validate_features: bool,
+    /// Whether to accept already rendered READMEs from publishing tools.
+    pub allow_prerendered_readme: bool,
+    /// Whether to reject published crates whose `license` is not a valid SPDX expression.
+    pub strict_licenses: bool,
+    /// The SPDX license identifiers that crates are allowed to be published under (`None` allows every license).
+    pub allowed_licenses: Option<Vec<String>>,
+    /// Whether crates with only a `license-file` can be published when `allowed_licenses` is set.
+    pub allow_license_file: bool,
+    /// The password hasher.
+    pub password_hasher: PasswordHasher,
+    /// Whether the registry is in read-only mode (refusing every request altering the registry).
+    pub read_only: bool,
+    /// The IDs of the superadmins.
+    pub superadmins: Vec<i64>,
 }
 
 /// The application state, created from [Config].
@@ -78,19 +548,76 @@ pub struct AppState {
     /// The current database connection pool.
     pub db: Database,
     /// The syntect configuration.
+    #[cfg(feature = "rendering")]
     pub syntect: SyntectState,
     /// Search config
     pub search: Tantivy,
+    /// The reverse dependencies index.
+    pub rdeps: ReverseDependencies,
+    /// The metrics registry.
+    pub metrics: Metrics,
+    /// The email transport (if configured).
+    pub mail: Option<Mail>,
+    /// The crate tarball retention policy (if configured).
+    pub retention: Option<RetentionPolicy>,
+    /// The pagination configuration of the listing endpoints.
+    pub pagination: PaginationConfig,
+    /// The download statistics configuration (if configured).
+    pub download_stats: Option<DownloadStatsConfig>,
+    /// The publish notifications configuration (if configured).
+    pub notifications: Option<NotificationsConfig>,
+    /// The per-author publish quotas configuration (if configured).
+    pub quotas: Option<QuotasConfig>,
+    /// The pre-publish hook (if configured).
+    pub publish_hook: Option<PublishHook>,
+    /// The publish concurrency limiter (if configured).
+    pub publish_queue: Option<PublishQueue>,
+    /// The crate badges configuration.
+    pub badges: BadgesConfig,
+    /// The crate pages cache (if configured).
+    pub page_cache: Option<PageCache>,
+    /// The sparse index configuration (if configured).
+    pub sparse: Option<SparseConfig>,
+    /// The publications in flight (waited for when shutting down).
+    pub publishes: Arc<InFlight>,
+    /// The crate index commit message templates.
+    pub commit_messages: CommitMessages,
+    /// The unowned crate claims configuration (if configured).
+    pub claims: Option<ClaimsConfig>,
+    /// The response compression configuration (if configured).
+    pub compression: Option<CompressionConfig>,
+    /// The minimum Cargo version configuration (if configured).
+    pub cargo_version: Option<CargoVersionConfig>,
     /// The frontend configured state.
     #[cfg(feature = "frontend")]
     pub frontend: FrontendState,
 }
 
-impl From<GeneralConfig> for GeneralState {
-    fn from(config: GeneralConfig) -> Self {
-        Self {
+impl TryFrom<GeneralConfig> for GeneralState {
+    type Error = Error;
+
+    fn try_from(config: GeneralConfig) -> Result<Self, Self::Error> {
+        Ok(Self {
             max_crate_size: config.max_crate_size,
-        }
+            max_body_size: config.max_body_size,
+            max_decompression_ratio: config.max_decompression_ratio,
+            max_decompressed_size: config.max_decompressed_size,
+            allowed_categories: config.allowed_categories,
+            max_keywords: config.max_keywords,
+            max_keyword_length: config.max_keyword_length,
+            max_crate_name_length: config.max_crate_name_length,
+            reserved_names: config.reserved_names,
+            registry_policies: config.registry_policies,
+            owner_invitation_lifetime: config.owner_invitation_lifetime,
+            validate_features: config.validate_features,
+            allow_prerendered_readme: config.allow_prerendered_readme,
+            strict_licenses: config.strict_licenses,
+            allowed_licenses: config.allowed_licenses,
+            allow_license_file: config.allow_license_file,
+            password_hasher: config.password_hashing.try_into()?,
+            read_only: config.read_only,
+            superadmins: config.superadmins,
+        })
     }
 }
 
@@ -98,13 +625,36 @@ impl TryFrom<Config> for AppState {
     type Error = Error;
 
     fn try_from(config: Config) -> Result<Self, Self::Error> {
+        if let Some(sparse) = config.sparse.as_ref() {
+            sparse.validate()?;
+        }
+
         Ok(Self {
-            general: config.general.into(),
+            general: config.general.try_into()?,
             index: config.index.into(),
             storage: config.storage.into(),
             db: Database::new(&config.database),
+            #[cfg(feature = "rendering")]
             syntect: config.syntect.into(),
             search: config.search.try_into()?,
+            rdeps: ReverseDependencies::new(),
+            metrics: config.metrics.into(),
+            mail: config.mail.map(Mail::try_from).transpose()?,
+            retention: config.retention.map(RetentionPolicy::from),
+            pagination: config.pagination,
+            download_stats: config.download_stats,
+            notifications: config.notifications,
+            quotas: config.quotas,
+            publish_hook: config.publish_hook.map(PublishHook::from),
+            publish_queue: config.publish_queue.map(PublishQueue::from),
+            badges: config.badges,
+            page_cache: config.page_cache.as_ref().and_then(PageCache::new),
+            sparse: config.sparse,
+            publishes: InFlight::new(),
+            commit_messages: config.commit_messages.try_into()?,
+            claims: config.claims,
+            compression: config.compression,
+            cargo_version: config.cargo_version,
             #[cfg(feature = "frontend")]
             frontend: config.frontend.into(),
         })
@@ -112,6 +662,27 @@ impl TryFrom<Config> for AppState {
 }
 
 impl AppState {
+    /// Returns the crate index's configuration (the `config.json` file), as computed from the `[sparse]` section (if set).
+    pub fn index_configuration(&self) -> Result<ConfigFile, Error> {
+        let committed = self.index.configuration()?;
+        Ok(match self.sparse.as_ref() {
+            Some(sparse) => sparse.apply(committed),
+            None => committed,
+        })
+    }
+
+    /// Returns the policy that new passwords must comply with.
+    #[cfg(feature = "frontend")]
+    pub fn password_policy(&self) -> &PasswordPolicyConfig {
+        &self.frontend.config.auth.local.password_policy
+    }
+
+    /// Returns the policy that new passwords must comply with (the default one, as it can only be configured in the `[frontend]` section).
+    #[cfg(not(feature = "frontend"))]
+    pub fn password_policy(&self) -> &PasswordPolicyConfig {
+        &PasswordPolicyConfig::DEFAULT
+    }
+
     /// Returns whether we require users to log in to browse crates.
     #[cfg(feature = "frontend")]
     pub fn is_login_required(&self) -> bool {
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// The publish notifications configuration (`[notifications]` section).
+///
+/// Notifications are sent by email, so they also require the `[mail]` section to be configured.
+///
+/// ```toml
+/// [notifications]
+/// interval = 15       # optional, how often to send the pending notifications (in minutes)
+/// min_interval = 60   # optional, the minimum delay between two notifications to the same author (in minutes)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// How often to send the pending notifications (in minutes).
+    #[serde(default = "NotificationsConfig::default_interval")]
+    pub interval: u64,
+    /// The minimum delay between two notifications to the same author (in minutes).
+    ///
+    /// Publications happening in the meantime are batched into the next notification.
+    #[serde(default = "NotificationsConfig::default_min_interval")]
+    pub min_interval: u64,
+}
+
+impl NotificationsConfig {
+    fn default_interval() -> u64 {
+        15
+    }
+
+    fn default_min_interval() -> u64 {
+        60
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// The crate pages cache configuration (`[page_cache]` section).
+///
+/// Only the pages of public crates, as rendered for anonymous visitors, are cached.
+///
+/// ```toml
+/// [page_cache]
+/// capacity = 256 # optional, how many crates can have their pages cached (`0` disables the cache)
+/// ttl = 60       # optional, how long a cached page is served (in seconds)
+/// downloads_delay = 10 # optional, how long pages can show outdated download counts (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageCacheConfig {
+    /// How many crates can have their pages cached (the least recently visited ones are evicted first).
+    #[serde(default = "PageCacheConfig::default_capacity")]
+    pub capacity: usize,
+    /// How long a cached page is served, before being rendered again (in seconds).
+    #[serde(default = "PageCacheConfig::default_ttl")]
+    pub ttl: u64,
+    /// How long a cached page can keep showing outdated download counts, after a download (in seconds).
+    #[serde(default = "PageCacheConfig::default_downloads_delay")]
+    pub downloads_delay: u64,
+}
+
+impl PageCacheConfig {
+    fn default_capacity() -> usize {
+        256
+    }
+
+    fn default_ttl() -> u64 {
+        60
+    }
+
+    fn default_downloads_delay() -> u64 {
+        10
+    }
+}
+
+impl Default for PageCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: Self::default_capacity(),
+            ttl: Self::default_ttl(),
+            downloads_delay: Self::default_downloads_delay(),
+        }
+    }
+}
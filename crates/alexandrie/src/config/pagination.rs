@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// The pagination configuration of the listing endpoints (`[pagination]` section).
+///
+/// ```toml
+/// [pagination]
+/// default_per_page = 15 # optional, how many items are listed per page when not specified
+/// max_per_page = 100    # optional, how many items can be requested per page at most
+/// default_versions_per_page = 50 # optional, how many versions the crate information endpoint returns when not specified
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaginationConfig {
+    /// How many items are listed per page, when not specified by the request.
+    #[serde(default = "PaginationConfig::default_per_page")]
+    pub default_per_page: usize,
+    /// How many items can be requested per page, at most.
+    #[serde(default = "PaginationConfig::default_max_per_page")]
+    pub max_per_page: usize,
+    /// How many versions are returned by the crate information endpoint, when not specified by the request.
+    #[serde(default = "PaginationConfig::default_versions_per_page")]
+    pub default_versions_per_page: usize,
+}
+
+impl PaginationConfig {
+    fn default_per_page() -> usize {
+        15
+    }
+
+    fn default_max_per_page() -> usize {
+        100
+    }
+
+    fn default_versions_per_page() -> usize {
+        50
+    }
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_per_page: Self::default_per_page(),
+            max_per_page: Self::default_max_per_page(),
+            default_versions_per_page: Self::default_versions_per_page(),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The list of common passwords rejected by the password policy (one per line).
+#[cfg(feature = "common-passwords")]
+pub const COMMON_PASSWORDS: &str = include_str!("../../data/common-passwords.txt");
+
+/// The configuration struct for the password policy of the "local" authentication strategy
+/// (`[frontend.auth.local.password_policy]` section, the default policy applying when built without the frontend).
+///
+/// It is enforced server-side whenever a password gets chosen (when registering, changing or resetting a password).
+///
+/// ```toml
+/// [frontend.auth.local.password_policy]
+/// min_length = 8              # optional, the minimum length of passwords (in characters)
+/// require_lowercase = false   # optional
+/// require_uppercase = false   # optional
+/// require_digit = false       # optional
+/// require_symbol = false      # optional
+/// reject_common = true        # optional, requires the `common-passwords` feature (ignored otherwise)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordPolicyConfig {
+    /// The minimum length of passwords (in characters).
+    #[serde(default = "PasswordPolicyConfig::default_min_length")]
+    pub min_length: usize,
+    /// Whether passwords must contain a lowercase letter.
+    #[serde(default)]
+    pub require_lowercase: bool,
+    /// Whether passwords must contain an uppercase letter.
+    #[serde(default)]
+    pub require_uppercase: bool,
+    /// Whether passwords must contain a digit.
+    #[serde(default)]
+    pub require_digit: bool,
+    /// Whether passwords must contain a symbol (neither a letter or a digit).
+    #[serde(default)]
+    pub require_symbol: bool,
+    /// Whether to reject commonly-used passwords.
+    /// This requires Alexandrie to be built with the `common-passwords` feature (ignored otherwise).
+    #[serde(default = "PasswordPolicyConfig::default_reject_common")]
+    pub reject_common: bool,
+}
+
+/// A rule of the password policy that a password does not comply with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PasswordViolation {
+    /// The password is shorter than the minimum length.
+    #[error("the password must be at least {min_length} characters long.")]
+    TooShort {
+        /// The minimum length of passwords (in characters).
+        min_length: usize,
+    },
+    /// The password does not contain a lowercase letter.
+    #[error("the password must contain a lowercase letter.")]
+    MissingLowercase,
+    /// The password does not contain an uppercase letter.
+    #[error("the password must contain an uppercase letter.")]
+    MissingUppercase,
+    /// The password does not contain a digit.
+    #[error("the password must contain a digit.")]
+    MissingDigit,
+    /// The password does not contain a symbol.
+    #[error("the password must contain a symbol.")]
+    MissingSymbol,
+    /// The password is a commonly-used one.
+    #[error("the password is too common.")]
+    TooCommon,
+}
+
+impl PasswordPolicyConfig {
+    /// The default password policy.
+    pub const DEFAULT: Self = Self {
+        min_length: 8,
+        require_lowercase: false,
+        require_uppercase: false,
+        require_digit: false,
+        require_symbol: false,
+        reject_common: true,
+    };
+
+    /// Checks a (non-hashed) password against every rule of the policy, returning the first one it does not comply with.
+    ///
+    /// The character classes match the ones checked by the frontend's forms (see `assets/js/password-policy.js`).
+    pub fn check(&self, password: &str) -> Result<(), PasswordViolation> {
+        if password.chars().count() < self.min_length {
+            return Err(PasswordViolation::TooShort {
+                min_length: self.min_length,
+            });
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            return Err(PasswordViolation::MissingLowercase);
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Err(PasswordViolation::MissingUppercase);
+        }
+        if self.require_digit && !password.chars().any(char::is_numeric) {
+            return Err(PasswordViolation::MissingDigit);
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            return Err(PasswordViolation::MissingSymbol);
+        }
+        #[cfg(feature = "common-passwords")]
+        if self.reject_common && is_common(password) {
+            return Err(PasswordViolation::TooCommon);
+        }
+        Ok(())
+    }
+
+    fn default_min_length() -> usize {
+        Self::DEFAULT.min_length
+    }
+
+    fn default_reject_common() -> bool {
+        Self::DEFAULT.reject_common
+    }
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Checks whether a password is in the list of common passwords (case-insensitively).
+#[cfg(feature = "common-passwords")]
+fn is_common(password: &str) -> bool {
+    let password = password.to_lowercase();
+    COMMON_PASSWORDS
+        .lines()
+        .any(|common| common.trim().to_lowercase() == password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_the_minimum_length() {
+        let policy = PasswordPolicyConfig::default();
+        let violation = PasswordViolation::TooShort { min_length: 8 };
+        assert_eq!(policy.check("k7#Vq2"), Err(violation));
+        assert_eq!(policy.check("k7#Vq2!x"), Ok(()));
+        //? Lengths are counted in characters, not in bytes.
+        assert_eq!(policy.check("éèàùçœæ"), Err(violation));
+    }
+
+    #[test]
+    fn checks_character_classes() {
+        let policy = PasswordPolicyConfig {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..PasswordPolicyConfig::default()
+        };
+        let violations = [
+            ("K7#VQ2!XZ", PasswordViolation::MissingLowercase),
+            ("k7#vq2!xz", PasswordViolation::MissingUppercase),
+            ("kS#Vqa!xz", PasswordViolation::MissingDigit),
+            ("k7sVq2axZ", PasswordViolation::MissingSymbol),
+        ];
+        for (password, violation) in violations {
+            assert_eq!(policy.check(password), Err(violation), "{password}");
+        }
+        assert_eq!(policy.check("k7#Vq2!xZ"), Ok(()));
+    }
+
+    #[test]
+    fn ignores_disabled_rules() {
+        let policy = PasswordPolicyConfig {
+            min_length: 0,
+            reject_common: false,
+            ..PasswordPolicyConfig::default()
+        };
+        assert_eq!(policy.check(""), Ok(()));
+        assert_eq!(policy.check("password"), Ok(()));
+    }
+
+    #[cfg(feature = "common-passwords")]
+    #[test]
+    fn rejects_common_passwords() {
+        let policy = PasswordPolicyConfig::default();
+        assert_eq!(policy.check("password"), Err(PasswordViolation::TooCommon));
+        assert_eq!(policy.check("PassWord"), Err(PasswordViolation::TooCommon));
+        assert_eq!(policy.check("k7#Vq2!xZ"), Ok(()));
+    }
+}
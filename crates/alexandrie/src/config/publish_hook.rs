@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// The pre-publish hook configuration (`[publish_hook]` section).
+///
+/// The hook is an external validator (like a virus scanner or a license checker) which must accept
+/// every crate version before it gets published.
+///
+/// ```toml
+/// [publish_hook]
+/// type = "<...>"      # required, replace "<...>" by the selected hook kind ("command" or "http").
+/// timeout = 30        # optional, how long to wait for the hook's verdict (in seconds)
+/// fail_open = false   # optional, whether to accept publications when the hook itself fails (or times out)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishHookConfig {
+    /// The kind of hook to invoke.
+    #[serde(flatten)]
+    pub kind: PublishHookKind,
+    /// How long to wait for the hook's verdict (in seconds).
+    #[serde(default = "PublishHookConfig::default_timeout")]
+    pub timeout: u64,
+    /// Whether to accept publications when the hook itself fails (like if it cannot be run or times out).
+    ///
+    /// Publications are refused in that case by default.
+    #[serde(default)]
+    pub fail_open: bool,
+}
+
+impl PublishHookConfig {
+    fn default_timeout() -> u64 {
+        30
+    }
+}
+
+/// The kind of pre-publish hook.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PublishHookKind {
+    /// The 'command' hook (a local command is run for every publication).
+    ///
+    /// The command receives the crate's metadata (as JSON) on its standard input and the path to the
+    /// crate's tarball in the `ALEXANDRIE_TARBALL` environment variable.  
+    /// It accepts the publication by exiting successfully, and otherwise rejects it with its output as the reason.
+    ///
+    /// ```toml
+    /// [publish_hook]
+    /// type = "command"
+    /// command = ["/usr/local/bin/check-crate", "--strict"] # required, the program to run and its arguments
+    /// ```
+    Command {
+        /// The program to run, followed by its arguments.
+        command: Vec<String>,
+    },
+    /// The 'http' hook (an HTTP endpoint is called for every publication).
+    ///
+    /// The endpoint receives a `multipart/form-data` POST request, with the crate's metadata (as JSON)
+    /// in the `metadata` part and the crate's tarball in the `tarball` part.  
+    /// It accepts the publication by responding with a `2xx` status, rejects it with a `4xx` status
+    /// (with the response's body as the reason), and any other outcome is a failure of the hook.
+    ///
+    /// ```toml
+    /// [publish_hook]
+    /// type = "http"
+    /// url = "http://validator.internal:8080/check" # required
+    /// ```
+    Http {
+        /// The URL of the endpoint to call.
+        url: String,
+    },
+}
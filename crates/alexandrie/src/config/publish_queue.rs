@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// The publish concurrency limit configuration (`[publish_queue]` section).
+///
+/// Publications beyond the limit wait for their turn (in arrival order) instead of all contending at once
+/// for the crate index, the storage and the database.
+/// Other requests (like downloads) are not affected by this limit.
+///
+/// ```toml
+/// [publish_queue]
+/// max_concurrent = 4  # required, how many publications can be processed at once
+/// max_wait = 60       # optional, how long a publication can wait for its turn (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishQueueConfig {
+    /// How many publications can be processed at once.
+    pub max_concurrent: usize,
+    /// How long a publication can wait for its turn, before being refused with a `503 Service Unavailable` (in seconds).
+    #[serde(default = "PublishQueueConfig::default_max_wait")]
+    pub max_wait: u64,
+}
+
+impl PublishQueueConfig {
+    fn default_max_wait() -> u64 {
+        60
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+/// The per-author publish quotas configuration (`[quotas]` section).
+///
+/// Quotas are counted separately for the first publication of crates and for the publication of new versions.
+/// Superadmins (see the `superadmins` option of the `[general]` section) are always exempted.
+///
+/// ```toml
+/// [quotas]
+/// new_crates_per_day = 10         # optional, how many new crates an author can publish in 24 hours
+/// new_versions_per_hour = 60      # optional, how many new versions an author can publish in an hour
+/// exempt = ["ci@example.com"]     # optional, the emails of the authors exempted from quotas (like service accounts)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotasConfig {
+    /// How many new crates an author can publish in 24 hours (unlimited if not set).
+    #[serde(default)]
+    pub new_crates_per_day: Option<u64>,
+    /// How many new versions (of existing crates) an author can publish in an hour (unlimited if not set).
+    #[serde(default)]
+    pub new_versions_per_hour: Option<u64>,
+    /// The emails of the authors exempted from quotas (like service accounts).
+    #[serde(default)]
+    pub exempt: Vec<String>,
+}
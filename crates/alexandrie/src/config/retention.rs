@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use alexandrie_storage::config::StorageConfig;
+
+/// The crate tarball retention policy configuration (`[retention]` section).
+///
+/// ```toml
+/// [retention]
+/// keep_patch_releases = 3 # optional, how many of the latest patch releases to keep for each minor version
+/// interval = 24           # optional, how often to apply the policy (in hours)
+/// dry_run = false         # optional, only log which tarballs would be pruned
+///
+/// # optional, the storage to offload pruned tarballs to (they are deleted if not set)
+/// [retention.offload]
+/// type = "disk"
+/// path = "cold-crate-storage"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// How many of the latest patch releases to keep the tarballs of, for each minor version.
+    #[serde(default = "RetentionConfig::default_keep_patch_releases")]
+    pub keep_patch_releases: usize,
+    /// How often to apply the retention policy (in hours).
+    #[serde(default = "RetentionConfig::default_interval")]
+    pub interval: u64,
+    /// Whether to only log which tarballs would be pruned, without altering the storage.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// The storage to offload the pruned tarballs to (they are deleted if not set).
+    #[serde(default)]
+    pub offload: Option<StorageConfig>,
+}
+
+impl RetentionConfig {
+    fn default_keep_patch_releases() -> usize {
+        3
+    }
+
+    fn default_interval() -> u64 {
+        24
+    }
+}
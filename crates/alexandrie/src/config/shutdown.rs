@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// The graceful shutdown configuration (`[shutdown]` section).
+///
+/// On `SIGTERM` (or `Ctrl-C`), the registry stops accepting connections and lets the in-flight requests complete,
+/// before exiting.
+///
+/// ```toml
+/// [shutdown]
+/// read_timeout = 5    # optional, how long other requests than publications are given to complete (in seconds)
+/// drain_timeout = 30  # optional, how long in-flight publications are given to complete (in seconds)
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShutdownConfig {
+    /// How long the in-flight requests (like downloads) are given to complete, before only waiting for publications (in seconds).
+    #[serde(default = "ShutdownConfig::default_read_timeout")]
+    pub read_timeout: u64,
+    /// How long the in-flight publications are given to complete, before exiting anyway (in seconds).
+    #[serde(default = "ShutdownConfig::default_drain_timeout")]
+    pub drain_timeout: u64,
+}
+
+impl ShutdownConfig {
+    fn default_read_timeout() -> u64 {
+        5
+    }
+
+    fn default_drain_timeout() -> u64 {
+        30
+    }
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            read_timeout: Self::default_read_timeout(),
+            drain_timeout: Self::default_drain_timeout(),
+        }
+    }
+}
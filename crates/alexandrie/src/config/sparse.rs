@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::ConfigFile;
+
+use crate::error::Error;
+
+/// The markers Cargo substitutes in the download URL template.
+const DL_MARKERS: &[&str] = &[
+    "crate",
+    "version",
+    "prefix",
+    "lowerprefix",
+    "sha256-checksum",
+];
+
+/// The sparse index configuration (`[sparse]` section).
+///
+/// When set, the `config.json` file served by the sparse index (and used to check the registries of dependencies)
+/// is computed from it, instead of being the one committed in the crate index.  
+/// Options which are not set keep the values of the committed `config.json`.
+///
+/// The `dl` template is independent of `url`, so that crate downloads can be served by a CDN (or an object store)
+/// while the API stays on the registry itself.
+///
+/// ```toml
+/// [sparse]
+/// url = "https://crates.example.com"                  # required, the public URL of the registry (which its API is served under)
+/// dl = "https://cdn.example.com/{crate}/{version}"    # optional, defaults to the registry's download endpoint
+/// allowed_registries = ["sparse+https://index.crates.io/"]  # optional, the registries crates are allowed to depend on
+/// auth_required = false                               # optional, whether Cargo must authenticate every request
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseConfig {
+    /// The public URL of the registry (which its API is served under).
+    pub url: String,
+    /// The URL template from which crate archives are downloaded
+    /// (defaults to `<url>/api/v1/crates/{crate}/{version}/download`).
+    #[serde(default)]
+    pub dl: Option<String>,
+    /// The URLs of the other registries that crates of this registry are allowed to depend on.
+    #[serde(default)]
+    pub allowed_registries: Option<Vec<String>>,
+    /// Whether authentication is required for every request to the registry.
+    #[serde(default)]
+    pub auth_required: Option<bool>,
+}
+
+impl SparseConfig {
+    /// Checks that the download URL template (if set) is one Cargo can use.
+    ///
+    /// If the template contains none of Cargo's markers, Cargo appends `/{crate}/{version}/download` to it.
+    /// Otherwise, the markers must identify a single crate archive (`{crate}` and `{version}`, or `{sha256-checksum}`).
+    pub fn validate(&self) -> Result<(), Error> {
+        let Some(dl) = self.dl.as_deref() else {
+            return Ok(());
+        };
+        let invalid = |reason: String| Error::InvalidDownloadTemplate { reason };
+
+        if !dl.starts_with("https://") && !dl.starts_with("http://") {
+            return Err(invalid(String::from(
+                "the template must be an absolute HTTP(S) URL",
+            )));
+        }
+
+        let mut markers = Vec::new();
+        let mut rest = dl;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                return Err(invalid(String::from("unclosed '{' in the template")));
+            };
+            let marker = &rest[start + 1..start + len];
+            if !DL_MARKERS.contains(&marker) {
+                return Err(invalid(format!(
+                    "unknown marker '{{{marker}}}' (expected one of: {})",
+                    DL_MARKERS.join(", ")
+                )));
+            }
+            markers.push(marker);
+            rest = &rest[start + len + 1..];
+        }
+        if rest.contains('}') {
+            return Err(invalid(String::from("unmatched '}' in the template")));
+        }
+
+        let identifies_crate = markers.is_empty()
+            || markers.contains(&"sha256-checksum")
+            || (markers.contains(&"crate") && markers.contains(&"version"));
+        if !identifies_crate {
+            return Err(invalid(String::from(
+                "the template must contain both '{crate}' and '{version}' (or '{sha256-checksum}')",
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Computes the index configuration, overriding the committed one with these options.
+    pub fn apply(&self, committed: ConfigFile) -> ConfigFile {
+        let url = self.url.trim_end_matches('/');
+        let dl = match self.dl.as_ref() {
+            Some(dl) => dl.clone(),
+            None => format!("{url}/api/v1/crates/{{crate}}/{{version}}/download"),
+        };
+
+        ConfigFile {
+            dl,
+            api: Some(String::from(url)),
+            allowed_registries: (self.allowed_registries.clone())
+                .unwrap_or(committed.allowed_registries),
+            auth_required: self.auth_required.unwrap_or(committed.auth_required),
+        }
+    }
+}
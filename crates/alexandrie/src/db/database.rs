@@ -1,6 +1,137 @@
-use diesel::r2d2::{self, ConnectionManager, Pool};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use diesel::r2d2::{self, ConnectionManager, Pool, PooledConnection};
+use diesel::RunQueryDsl;
+use tokio::task::JoinError;
 
 use crate::config::database::DatabaseConfig;
+use crate::error::Error;
+
+/// Cumulative statistics about connection checkouts from the pool.
+#[derive(Debug, Default)]
+pub struct PoolMetrics {
+    /// The number of connections checked out from the pool so far.
+    acquisitions: AtomicU64,
+    /// The total time spent waiting for a connection (in microseconds).
+    wait_time_us: AtomicU64,
+    /// The number of times no connection could be acquired within the timeout.
+    exhaustions: AtomicU64,
+}
+
+impl PoolMetrics {
+    fn record_wait(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        self.wait_time_us.fetch_add(micros, Ordering::Relaxed);
+    }
+
+    /// Gets the number of connections checked out from the pool so far.
+    pub fn acquisitions(&self) -> u64 {
+        self.acquisitions.load(Ordering::Relaxed)
+    }
+
+    /// Gets the total time spent waiting for a connection from the pool.
+    pub fn wait_time(&self) -> Duration {
+        Duration::from_micros(self.wait_time_us.load(Ordering::Relaxed))
+    }
+
+    /// Gets the number of times no connection could be acquired from the pool within the timeout.
+    pub fn exhaustions(&self) -> u64 {
+        self.exhaustions.load(Ordering::Relaxed)
+    }
+}
+
+/// The error returned when no connection could be acquired from the pool within the timeout.
+///
+/// It gets a `503 Service Unavailable` response from the programmatic API (so that clients can retry later).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("the registry is overloaded (no database connection available), please try again later")]
+pub struct PoolExhausted;
+
+/// Database operations returning plain query errors carry [`PoolExhausted`] within them.
+impl From<PoolExhausted> for diesel::result::Error {
+    fn from(err: PoolExhausted) -> diesel::result::Error {
+        diesel::result::Error::QueryBuilderError(Box::new(err))
+    }
+}
+
+/// The outcome of a database operation, which can be made of a [`PoolExhausted`] error.
+pub trait DbOutcome {
+    /// Builds the outcome of an operation which could not acquire a database connection.
+    fn pool_exhausted() -> Self;
+}
+
+impl<T, E> DbOutcome for Result<T, E>
+where
+    E: From<PoolExhausted>,
+{
+    fn pool_exhausted() -> Self {
+        Err(E::from(PoolExhausted))
+    }
+}
+
+/// Acquires a connection from the pool (failing with [`PoolExhausted`] if none could be acquired within the timeout).
+fn acquire<T>(
+    pool: &Pool<ConnectionManager<T>>,
+    metrics: &PoolMetrics,
+) -> Result<PooledConnection<ConnectionManager<T>>, PoolExhausted>
+where
+    T: diesel::Connection + diesel::r2d2::R2D2Connection + 'static,
+{
+    let start = Instant::now();
+    match pool.get() {
+        Ok(conn) => {
+            metrics.record_wait(start.elapsed());
+            Ok(conn)
+        }
+        Err(error) => {
+            metrics.exhaustions.fetch_add(1, Ordering::Relaxed);
+            let state = pool.state();
+            tracing::warn!(
+                "could not acquire a database connection within {0}ms ({1} connections open, {2} idle, {3} at most), consider increasing `pool_max_size`: {error}",
+                pool.connection_timeout().as_millis(),
+                state.connections,
+                state.idle_connections,
+                pool.max_size(),
+            );
+            Err(PoolExhausted)
+        }
+    }
+}
+
+/// Gets the outcome of a blocking database task, carrying its panics over to the caller.
+fn join<R>(outcome: Result<R, JoinError>) -> R {
+    match outcome {
+        Ok(output) => output,
+        Err(error) if error.is_panic() => std::panic::resume_unwind(error.into_panic()),
+        Err(error) => panic!("database task got cancelled: {error}"),
+    }
+}
+
+/// Runs the given operation, logging it if it took longer than the threshold (nothing is measured if it is not set).
+fn timed<F, R>(threshold: Option<Duration>, operation: &str, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let Some(threshold) = threshold else {
+        return f();
+    };
+    let start = Instant::now();
+    let output = f();
+    let elapsed = start.elapsed();
+    if elapsed >= threshold {
+        tracing::warn!(
+            operation,
+            duration_ms = elapsed.as_millis() as u64,
+            "slow database query: `{operation}` took {0}ms (threshold: {1}ms)",
+            elapsed.as_millis(),
+            threshold.as_millis(),
+        );
+    }
+    output
+}
 
 /// The database connection pool, running blocking tasks in a
 /// way that does not block the async event loop.
@@ -10,6 +141,9 @@ where
     T: diesel::Connection + diesel::r2d2::R2D2Connection + 'static,
 {
     connection_pool: Pool<ConnectionManager<T>>,
+    metrics: Arc<PoolMetrics>,
+    /// The duration above which queries are logged as slow (if enabled).
+    slow_query_threshold: Option<Duration>,
 }
 
 impl<T> Clone for Database<T>
@@ -19,6 +153,8 @@ where
     fn clone(&self) -> Self {
         Self {
             connection_pool: self.connection_pool.clone(),
+            metrics: Arc::clone(&self.metrics),
+            slow_query_threshold: self.slow_query_threshold,
         }
     }
 }
@@ -29,8 +165,10 @@ where
 {
     /// Constructs a `Database<T>` for the given database config (creates a connection pool).
     pub fn new(database_config: &DatabaseConfig) -> Self {
-        let mut builder = r2d2::Builder::default();
-        if let Some(max_size) = database_config.max_conns {
+        let mut builder = r2d2::Builder::default()
+            .min_idle(database_config.pool_min_idle)
+            .connection_timeout(Duration::from_millis(database_config.acquire_timeout));
+        if let Some(max_size) = database_config.pool_max_size {
             builder = builder.max_size(max_size)
         }
 
@@ -64,7 +202,11 @@ where
             url.to_string()
         };
 
-        Self::from_pool_builder(&database_url, builder)
+        let mut database = Self::from_pool_builder(&database_url, builder);
+        database.slow_query_threshold = database_config
+            .slow_query_threshold
+            .map(Duration::from_millis);
+        database
     }
 
     /// Creates a `Database<T>` with a custom connection pool builder.
@@ -76,45 +218,117 @@ where
         let connection_pool = builder
             .build(manager)
             .expect("could not initiate test db pool");
-        Database { connection_pool }
+        let metrics = Arc::new(PoolMetrics::default());
+        Database {
+            connection_pool,
+            metrics,
+            slow_query_threshold: None,
+        }
+    }
+
+    /// Gets the current state of the connection pool (number of open and idle connections).
+    pub fn pool_state(&self) -> r2d2::State {
+        self.connection_pool.state()
+    }
+
+    /// Gets the maximum number of connections managed by the pool.
+    pub fn max_size(&self) -> u32 {
+        self.connection_pool.max_size()
+    }
+
+    /// Gets the cumulative connection checkout statistics of the pool.
+    pub fn metrics(&self) -> &PoolMetrics {
+        self.metrics.as_ref()
+    }
+
+    /// Checks that a connection can be obtained within the given timeout and that
+    /// the database responds to a trivial query.
+    pub async fn ping(&self, timeout: Duration) -> Result<(), Error>
+    where
+        T: Send,
+    {
+        let pool = self.connection_pool.clone();
+        let span = tracing::Span::current();
+        let future = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            let mut conn = pool.get_timeout(timeout)?;
+            diesel::sql_query("SELECT 1").execute(&mut *conn)?;
+            Ok::<_, Error>(())
+        });
+
+        // FIXME: truly figure out what to do with a `JoinError`.
+        future.await.unwrap()
+    }
+
+    /// Runs the given (blocking) database operation, logging it if it took longer than the slow query threshold.
+    ///
+    /// Only the operation's name and duration are logged (never the query's parameters).
+    pub fn timed<F, R>(&self, operation: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        timed(self.slow_query_threshold, operation, f)
+    }
+
+    /// Runs the given closure like [`Database::run`], logging it (under the given operation name)
+    /// if it took longer than the slow query threshold.
+    pub async fn run_timed<F, R>(&self, operation: &'static str, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R + Send + 'static,
+        R: DbOutcome + Send + 'static,
+        T: Send,
+    {
+        let threshold = self.slow_query_threshold;
+        self.run(move |conn| timed(threshold, operation, || f(conn)))
+            .await
     }
 
     /// Runs the given closure in a way that is safe for blocking IO to the database.
     /// The closure will be passed a `Connection` from the pool to use.
+    /// If no connection could be acquired within the timeout, the outcome is a [`PoolExhausted`] error.
     pub async fn run<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut T) -> R + Send + 'static,
-        R: Send + 'static,
+        R: DbOutcome + Send + 'static,
         T: Send,
     {
         let pool = self.connection_pool.clone();
+        let metrics = Arc::clone(&self.metrics);
+        //? The current span (carrying the request's ID) is kept on the blocking thread.
+        let span = tracing::Span::current();
         let future = tokio::task::spawn_blocking(move || {
-            let mut conn = pool.get().unwrap();
-            f(&mut *conn)
+            let _guard = span.enter();
+            match acquire(&pool, metrics.as_ref()) {
+                Ok(mut conn) => f(&mut *conn),
+                Err(PoolExhausted) => R::pool_exhausted(),
+            }
         });
 
-        // FIXME: truly figure out what to do with a `JoinError`.
-        future.await.unwrap()
+        join(future.await)
     }
 
     /// Runs the given closure in a way that is safe for blocking IO to the database.
     /// The closure will be passed a `Connection` from the pool to use.
     /// This closure will run in the context of a database transaction.
     /// If an error occurs, the database changes made in this closure will get rolled back to their original state.
+    /// If no connection could be acquired within the timeout, it fails with a [`PoolExhausted`] error.
     pub async fn transaction<F, R, E>(&self, f: F) -> Result<R, E>
     where
         F: FnOnce(&mut T) -> Result<R, E> + Send + 'static,
         T: Send,
         R: Send + 'static,
-        E: From<diesel::result::Error> + Send + 'static,
+        E: From<diesel::result::Error> + From<PoolExhausted> + Send + 'static,
     {
         let pool = self.connection_pool.clone();
+        let metrics = Arc::clone(&self.metrics);
+        //? The current span (carrying the request's ID) is kept on the blocking thread.
+        let span = tracing::Span::current();
         let future = tokio::task::spawn_blocking(move || {
-            let mut conn = pool.get().unwrap();
+            let _guard = span.enter();
+            let mut conn = acquire(&pool, metrics.as_ref())?;
             conn.transaction(|conn| f(conn))
         });
 
-        // FIXME: truly figure out what to do with a `JoinError`.
-        future.await.unwrap()
+        join(future.await)
     }
 }
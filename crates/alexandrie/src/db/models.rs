@@ -35,6 +35,12 @@ pub struct Crate {
     pub documentation: Option<String>,
     /// The URL to the crate's repository.
     pub repository: Option<String>,
+    /// The native library linked by the crate's latest version (its `links` manifest key).
+    pub links: Option<String>,
+    /// The crate's visibility (`public` or `private`).
+    pub visibility: String,
+    /// The crate's number of available (non-yanked) versions (unknown until it is first computed).
+    pub non_yanked_versions: Option<i64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
@@ -56,6 +62,8 @@ pub struct NewCrate<'a> {
     pub documentation: Option<&'a str>,
     /// The URL to the crate's repository.
     pub repository: Option<&'a str>,
+    /// The native library linked by the crate's latest version (its `links` manifest key).
+    pub links: Option<&'a str>,
 }
 
 #[derive(
@@ -85,6 +93,8 @@ pub struct Author {
     pub github_id: Option<String>,
     /// The author's GitLab user ID.
     pub gitlab_id: Option<String>,
+    /// The author's registration date (unknown for the authors registered before it was recorded).
+    pub created_at: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
@@ -102,6 +112,8 @@ pub struct NewAuthor<'a> {
     pub github_id: Option<&'a str>,
     /// The author's GitLab user ID.
     pub gitlab_id: Option<&'a str>,
+    /// The author's registration date.
+    pub created_at: Option<&'a str>,
 }
 
 #[derive(
@@ -128,17 +140,21 @@ pub struct CrateAuthor {
     pub crate_id: i64,
     /// The author's ID.
     pub author_id: i64,
+    /// The author's role for this crate (`owner` or `collaborator`).
+    pub role: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
 #[diesel(table_name = crate_authors)]
 /// Represents a crate-to-author relationship in the database,
 /// suitable to create an entry while letting the database assign a relationship ID.
-pub struct NewCrateAuthor {
+pub struct NewCrateAuthor<'a> {
     /// The crate's ID.
     pub crate_id: i64,
     /// The author's ID.
     pub author_id: i64,
+    /// The author's role for this crate (`owner` or `collaborator`).
+    pub role: &'a str,
 }
 
 #[derive(
@@ -366,6 +382,14 @@ pub struct Session {
     pub expiry: String,
     /// The session's associated data.
     pub data: String,
+    /// The session's creation date.
+    pub created_at: String,
+    /// The date at which the session was last seen.
+    pub last_seen: String,
+    /// A coarse description of the session's user-agent (eg. "Firefox on Linux").
+    pub user_agent: Option<String>,
+    /// The session's coarse IP address (eg. "192.0.2.0/24").
+    pub ip_address: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
@@ -416,3 +440,457 @@ pub struct NewSalt<'a> {
     /// The salt's related author ID.
     pub author_id: i64,
 }
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = password_resets)]
+#[diesel(belongs_to(Author))]
+#[diesel(primary_key(id))]
+/// Represents a password reset token in the database.
+pub struct PasswordReset {
+    /// The password reset's ID.
+    pub id: i64,
+    /// The SHA512 hash of the password reset token.
+    pub token: String,
+    /// The related author's ID.
+    pub author_id: i64,
+    /// The token's expiry date.
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = password_resets)]
+/// Represents a password reset token in the database,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewPasswordReset<'a> {
+    /// The SHA512 hash of the password reset token.
+    pub token: &'a str,
+    /// The related author's ID.
+    pub author_id: i64,
+    /// The token's expiry date.
+    pub expires_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = pruned_tarballs)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents a crate version whose tarball got pruned by the retention policy.
+pub struct PrunedTarball {
+    /// The pruned tarball's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The pruned version.
+    pub version: String,
+    /// The date at which the tarball was pruned.
+    pub pruned_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = pruned_tarballs)]
+/// Represents a crate version whose tarball got pruned by the retention policy,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewPrunedTarball<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The pruned version.
+    pub version: &'a str,
+    /// The date at which the tarball was pruned.
+    pub pruned_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = crate_provenance)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents the provenance of a crate version (where it was published from).
+pub struct CrateProvenance {
+    /// The provenance record's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The related version.
+    pub version: String,
+    /// The repository URL declared by this version.
+    pub repository: Option<String>,
+    /// The kind of the supplied attestation (like `sigstore-bundle`).
+    pub attestation_kind: Option<String>,
+    /// The attestation supplied along with this version.
+    pub attestation: Option<String>,
+    /// The date at which this version was published.
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = crate_provenance)]
+/// Represents the provenance of a crate version,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewCrateProvenance<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The related version.
+    pub version: &'a str,
+    /// The repository URL declared by this version.
+    pub repository: Option<&'a str>,
+    /// The kind of the supplied attestation (like `sigstore-bundle`).
+    pub attestation_kind: Option<&'a str>,
+    /// The attestation supplied along with this version.
+    pub attestation: Option<&'a str>,
+    /// The date at which this version was published.
+    pub recorded_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = download_snapshots)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents a snapshot of a crate's download count.
+pub struct DownloadSnapshot {
+    /// The snapshot's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The crate's download count at the time of the snapshot.
+    pub downloads: i64,
+    /// The date at which the snapshot was taken.
+    pub taken_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = download_snapshots)]
+/// Represents a snapshot of a crate's download count,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewDownloadSnapshot<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The crate's download count at the time of the snapshot.
+    pub downloads: i64,
+    /// The date at which the snapshot was taken.
+    pub taken_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = owner_invitations)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents an invitation for an author to become an owner of a crate.
+pub struct OwnerInvitation {
+    /// The invitation's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The invited author's ID.
+    pub invitee_id: i64,
+    /// The inviting author's ID.
+    pub inviter_id: i64,
+    /// The invitation's status (`pending`, `accepted`, `declined` or `expired`).
+    pub status: String,
+    /// The invitation's creation date.
+    pub created_at: String,
+    /// The invitation's expiry date.
+    pub expires_at: String,
+    /// The date at which the invitation got accepted, declined or expired.
+    pub resolved_at: Option<String>,
+    /// The role the author is invited to take (`owner` or `collaborator`).
+    pub role: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = owner_invitations)]
+/// Represents an invitation for an author to become an owner of a crate,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewOwnerInvitation<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The invited author's ID.
+    pub invitee_id: i64,
+    /// The inviting author's ID.
+    pub inviter_id: i64,
+    /// The invitation's status (`pending`, `accepted`, `declined` or `expired`).
+    pub status: &'a str,
+    /// The invitation's creation date.
+    pub created_at: &'a str,
+    /// The invitation's expiry date.
+    pub expires_at: &'a str,
+    /// The role the author is invited to take (`owner` or `collaborator`).
+    pub role: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = crate_versions)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents a published crate version.
+pub struct CrateVersionRecord {
+    /// The version record's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The related version.
+    pub version: String,
+    /// The date at which this version was published.
+    pub published_at: String,
+    /// The version's download count.
+    pub downloads: i64,
+    /// The version's SPDX license expression.
+    pub license: Option<String>,
+    /// The path of the version's license file.
+    pub license_file: Option<String>,
+    /// The contents of the version's license file (for versions without a license expression).
+    pub license_text: Option<String>,
+    /// The part of the version's download count which has been backfilled from access logs.
+    pub backfilled_downloads: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = crate_versions)]
+/// Represents a published crate version,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewCrateVersionRecord<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The related version.
+    pub version: &'a str,
+    /// The date at which this version was published.
+    pub published_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = version_download_snapshots)]
+#[diesel(belongs_to(CrateVersionRecord, foreign_key = version_id))]
+#[diesel(primary_key(id))]
+/// Represents a daily snapshot of a crate version's download count.
+pub struct VersionDownloadSnapshot {
+    /// The snapshot's ID.
+    pub id: i64,
+    /// The related version record's ID.
+    pub version_id: i64,
+    /// The version's download count at the time of the snapshot.
+    pub downloads: i64,
+    /// The day of the snapshot (as `YYYY-MM-DD`).
+    pub date: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = version_download_snapshots)]
+/// Represents a daily snapshot of a crate version's download count,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewVersionDownloadSnapshot<'a> {
+    /// The related version record's ID.
+    pub version_id: i64,
+    /// The version's download count at the time of the snapshot.
+    pub downloads: i64,
+    /// The day of the snapshot (as `YYYY-MM-DD`).
+    pub date: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = audit_log)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(primary_key(id))]
+/// Represents an entry of the audit log (a sensitive operation performed on a crate).
+pub struct AuditLogEntry {
+    /// The entry's ID.
+    pub id: i64,
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The ID of the author who performed the operation (if they still exist).
+    pub author_id: Option<i64>,
+    /// The performed operation (like `yank` or `unyank`).
+    pub action: String,
+    /// The related version (if the operation is about a specific version).
+    pub version: Option<String>,
+    /// The reason given for the operation (if any).
+    pub reason: Option<String>,
+    /// The date at which the operation was performed.
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = audit_log)]
+/// Represents an entry of the audit log,
+/// suitable to create an entry while letting the database assign it an ID.
+pub struct NewAuditLogEntry<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The ID of the author who performed the operation.
+    pub author_id: Option<i64>,
+    /// The performed operation (like `yank` or `unyank`).
+    pub action: &'a str,
+    /// The related version (if the operation is about a specific version).
+    pub version: Option<&'a str>,
+    /// The reason given for the operation (if any).
+    pub reason: Option<&'a str>,
+    /// The date at which the operation was performed.
+    pub created_at: &'a str,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    AsChangeset,
+)]
+#[diesel(table_name = crate_subscriptions)]
+#[diesel(belongs_to(Crate, foreign_key = crate_id))]
+#[diesel(belongs_to(Author, foreign_key = author_id))]
+#[diesel(primary_key(id))]
+/// Represents a crate subscription (an author to notify when a crate gets a new version).
+pub struct CrateSubscription {
+    /// The subscription's ID.
+    pub id: i64,
+    /// The followed crate's ID.
+    pub crate_id: i64,
+    /// The subscribed author's ID.
+    pub author_id: i64,
+    /// The date at which the author subscribed.
+    pub created_at: String,
+    /// The date at which the author was last notified through this subscription (if ever).
+    pub last_notified_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = crate_subscriptions)]
+/// Represents a crate subscription,
+/// suitable to create a subscription while letting the database assign it an ID.
+pub struct NewCrateSubscription<'a> {
+    /// The followed crate's ID.
+    pub crate_id: i64,
+    /// The subscribed author's ID.
+    pub author_id: i64,
+    /// The date at which the author subscribed.
+    pub created_at: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = pending_notifications)]
+/// Represents a pending notification (a publication not yet notified to a subscriber),
+/// suitable to create a notification while letting the database assign it an ID.
+pub struct NewPendingNotification<'a> {
+    /// The related subscription's ID.
+    pub subscription_id: i64,
+    /// The published version.
+    pub version: &'a str,
+    /// The date at which the version was published.
+    pub created_at: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = publish_events)]
+/// Represents a publish event (counted against the per-author publish quotas),
+/// suitable to create an event while letting the database assign it an ID.
+pub struct NewPublishEvent<'a> {
+    /// The publishing author's ID.
+    pub author_id: i64,
+    /// The kind of publication (`new-crate` or `new-version`).
+    pub kind: &'a str,
+    /// The date of the publication.
+    pub created_at: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Queryable, Insertable)]
+#[diesel(table_name = crate_aliases)]
+/// Represents a crate alias (a former name of a crate),
+/// suitable to create an alias while letting the database assign it an ID.
+pub struct NewCrateAlias<'a> {
+    /// The related crate's ID.
+    pub crate_id: i64,
+    /// The alias (in its canonical form).
+    pub alias: &'a str,
+    /// The date at which the alias was created.
+    pub created_at: &'a str,
+}
@@ -13,6 +13,8 @@ table! {
         github_id -> Nullable<Varchar>,
         /// The author's GitLab user ID.
         gitlab_id -> Nullable<Varchar>,
+        /// The author's registration date (unknown for the authors registered before it was recorded).
+        created_at -> Nullable<Varchar>,
     }
 }
 
@@ -37,6 +39,12 @@ table! {
         documentation -> Nullable<Varchar>,
         /// The URL to the crate's repository.
         repository -> Nullable<Varchar>,
+        /// The native library linked by the crate's latest version (its `links` manifest key).
+        links -> Nullable<Varchar>,
+        /// The crate's visibility (`public` or `private`).
+        visibility -> Varchar,
+        /// The crate's number of available (non-yanked) versions (unknown until it is first computed).
+        non_yanked_versions -> Nullable<Bigint>,
     }
 }
 
@@ -49,6 +57,8 @@ table! {
         crate_id -> Bigint,
         /// The author's ID.
         author_id -> Bigint,
+        /// The author's role for this crate (`owner` or `collaborator`).
+        role -> Varchar,
     }
 }
 
@@ -139,6 +149,14 @@ table! {
         expiry -> Varchar,
         /// The session's associated data.
         data -> Text,
+        /// The session's creation date.
+        created_at -> Varchar,
+        /// The date at which the session was last seen.
+        last_seen -> Varchar,
+        /// A coarse description of the session's user-agent (eg. "Firefox on Linux").
+        user_agent -> Nullable<Varchar>,
+        /// The session's coarse IP address (eg. "192.0.2.0/24").
+        ip_address -> Nullable<Varchar>,
     }
 }
 
@@ -154,6 +172,208 @@ table! {
     }
 }
 
+table! {
+    /// The password reset tokens table.
+    password_resets (id) {
+        /// The password reset's ID.
+        id -> Bigint,
+        /// The SHA512 hash of the password reset token.
+        token -> Varchar,
+        /// The related author's ID.
+        author_id -> Bigint,
+        /// The token's expiry date.
+        expires_at -> Varchar,
+    }
+}
+
+table! {
+    /// The pruned crate tarballs table (versions whose tarball got removed by the retention policy).
+    pruned_tarballs (id) {
+        /// The pruned tarball's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The pruned version.
+        version -> Varchar,
+        /// The date at which the tarball was pruned.
+        pruned_at -> Varchar,
+    }
+}
+
+table! {
+    /// The crate provenance table (where each crate version was published from).
+    crate_provenance (id) {
+        /// The provenance record's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The related version.
+        version -> Varchar,
+        /// The repository URL declared by this version.
+        repository -> Nullable<Varchar>,
+        /// The kind of the supplied attestation (like `sigstore-bundle`).
+        attestation_kind -> Nullable<Varchar>,
+        /// The attestation supplied along with this version.
+        attestation -> Nullable<Text>,
+        /// The date at which this version was published.
+        recorded_at -> Varchar,
+    }
+}
+
+table! {
+    /// The download snapshots table (periodic samples of the crates' download counts).
+    download_snapshots (id) {
+        /// The snapshot's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The crate's download count at the time of the snapshot.
+        downloads -> Bigint,
+        /// The date at which the snapshot was taken.
+        taken_at -> Varchar,
+    }
+}
+
+table! {
+    /// The crate owner invitations table (pending, and resolved ones for auditing purposes).
+    owner_invitations (id) {
+        /// The invitation's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The invited author's ID.
+        invitee_id -> Bigint,
+        /// The inviting author's ID.
+        inviter_id -> Bigint,
+        /// The invitation's status (`pending`, `accepted`, `declined` or `expired`).
+        status -> Varchar,
+        /// The invitation's creation date.
+        created_at -> Varchar,
+        /// The invitation's expiry date.
+        expires_at -> Varchar,
+        /// The date at which the invitation got accepted, declined or expired.
+        resolved_at -> Nullable<Varchar>,
+        /// The role the author is invited to take (`owner` or `collaborator`).
+        role -> Varchar,
+    }
+}
+
+table! {
+    /// The crate versions table (when each crate version was published).
+    crate_versions (id) {
+        /// The version record's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The related version.
+        version -> Varchar,
+        /// The date at which this version was published.
+        published_at -> Varchar,
+        /// The version's download count.
+        downloads -> Bigint,
+        /// The version's SPDX license expression.
+        license -> Nullable<Varchar>,
+        /// The path of the version's license file.
+        license_file -> Nullable<Varchar>,
+        /// The contents of the version's license file (for versions without a license expression).
+        license_text -> Nullable<Text>,
+        /// The part of the version's download count which has been backfilled from access logs.
+        backfilled_downloads -> Bigint,
+    }
+}
+
+table! {
+    /// The version download snapshots table (the daily download counts of each crate version).
+    version_download_snapshots (id) {
+        /// The snapshot's ID.
+        id -> Bigint,
+        /// The related version record's ID.
+        version_id -> Bigint,
+        /// The version's download count at the time of the snapshot.
+        downloads -> Bigint,
+        /// The day of the snapshot (as `YYYY-MM-DD`).
+        date -> Varchar,
+    }
+}
+
+table! {
+    /// The audit log table (the sensitive operations performed on crates, like yanks).
+    audit_log (id) {
+        /// The entry's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The ID of the author who performed the operation (if they still exist).
+        author_id -> Nullable<Bigint>,
+        /// The performed operation (like `yank` or `unyank`).
+        action -> Varchar,
+        /// The related version (if the operation is about a specific version).
+        version -> Nullable<Varchar>,
+        /// The reason given for the operation (if any).
+        reason -> Nullable<Text>,
+        /// The date at which the operation was performed.
+        created_at -> Varchar,
+    }
+}
+
+table! {
+    /// The crate subscriptions table (the authors to notify when a crate gets a new version).
+    crate_subscriptions (id) {
+        /// The subscription's ID.
+        id -> Bigint,
+        /// The followed crate's ID.
+        crate_id -> Bigint,
+        /// The subscribed author's ID.
+        author_id -> Bigint,
+        /// The date at which the author subscribed.
+        created_at -> Varchar,
+        /// The date at which the author was last notified through this subscription (if ever).
+        last_notified_at -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    /// The pending notifications table (the publications not yet notified to subscribers).
+    pending_notifications (id) {
+        /// The notification's ID.
+        id -> Bigint,
+        /// The related subscription's ID.
+        subscription_id -> Bigint,
+        /// The published version.
+        version -> Varchar,
+        /// The date at which the version was published.
+        created_at -> Varchar,
+    }
+}
+
+table! {
+    /// The publish events table (counted against the per-author publish quotas).
+    publish_events (id) {
+        /// The event's ID.
+        id -> Bigint,
+        /// The publishing author's ID.
+        author_id -> Bigint,
+        /// The kind of publication (`new-crate` or `new-version`).
+        kind -> Varchar,
+        /// The date of the publication.
+        created_at -> Varchar,
+    }
+}
+
+table! {
+    /// The crate aliases table (former names of crates, redirecting to them).
+    crate_aliases (id) {
+        /// The alias's ID.
+        id -> Bigint,
+        /// The related crate's ID.
+        crate_id -> Bigint,
+        /// The alias (in its canonical form).
+        alias -> Varchar,
+        /// The date at which the alias was created.
+        created_at -> Varchar,
+    }
+}
+
 joinable!(author_tokens -> authors (author_id));
 joinable!(crate_authors -> crates (crate_id));
 joinable!(crate_authors -> authors (author_id));
@@ -164,6 +384,21 @@ joinable!(crate_categories -> categories (category_id));
 joinable!(crate_badges -> crates (crate_id));
 joinable!(sessions -> authors (author_id));
 joinable!(salts -> authors (author_id));
+joinable!(password_resets -> authors (author_id));
+joinable!(pruned_tarballs -> crates (crate_id));
+joinable!(crate_provenance -> crates (crate_id));
+joinable!(download_snapshots -> crates (crate_id));
+joinable!(owner_invitations -> crates (crate_id));
+joinable!(owner_invitations -> authors (invitee_id));
+joinable!(crate_versions -> crates (crate_id));
+joinable!(version_download_snapshots -> crate_versions (version_id));
+joinable!(audit_log -> crates (crate_id));
+joinable!(audit_log -> authors (author_id));
+joinable!(crate_subscriptions -> crates (crate_id));
+joinable!(crate_subscriptions -> authors (author_id));
+joinable!(pending_notifications -> crate_subscriptions (subscription_id));
+joinable!(publish_events -> authors (author_id));
+joinable!(crate_aliases -> crates (crate_id));
 
 allow_tables_to_appear_in_same_query!(
     authors,
@@ -177,4 +412,16 @@ allow_tables_to_appear_in_same_query!(
     crate_badges,
     sessions,
     salts,
+    password_resets,
+    pruned_tarballs,
+    crate_provenance,
+    download_snapshots,
+    owner_invitations,
+    crate_versions,
+    version_download_snapshots,
+    audit_log,
+    crate_subscriptions,
+    pending_notifications,
+    publish_events,
+    crate_aliases,
 );
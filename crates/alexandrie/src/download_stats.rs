@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::config::AppState;
+use crate::db::models::NewVersionDownloadSnapshot;
+use crate::db::schema::*;
+use crate::db::{Connection, DATETIME_FORMAT};
+use crate::error::Error;
+
+/// The format of the days of the download snapshots.
+pub static DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The downloads of a crate version over a window of days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionDownloads {
+    /// The version record's ID.
+    pub version_id: i64,
+    /// The related version.
+    pub version: String,
+    /// The date at which this version was published.
+    pub published_at: String,
+    /// The number of downloads of each day (only for the days covered by the snapshots).
+    pub days: BTreeMap<NaiveDate, i64>,
+}
+
+/// Spawns the task periodically snapshotting the download counts of crate versions (if download statistics are configured).
+pub fn spawn(state: Arc<AppState>) {
+    let Some(config) = state.download_stats.as_ref() else {
+        return;
+    };
+    let period = Duration::from_secs(config.interval.max(1) * 60 * 60);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(error) = snapshot(&state).await {
+                tracing::error!("could not snapshot the download counts: {error}");
+            }
+        }
+    });
+}
+
+/// Snapshots the current download counts of every crate version, as the ones of the current day.
+///
+/// Snapshots taken earlier in the same day are replaced, and the ones falling outside of the retention period are deleted.
+pub async fn snapshot(state: &Arc<AppState>) -> Result<(), Error> {
+    let Some(config) = state.download_stats.as_ref() else {
+        return Ok(());
+    };
+
+    let today = Utc::now().date_naive();
+    let date = today.format(DATE_FORMAT).to_string();
+    let expired = (today - chrono::Duration::days(config.retention.max(1) as i64))
+        .format(DATE_FORMAT)
+        .to_string();
+
+    state
+        .db
+        .transaction(move |conn| {
+            let versions: Vec<(i64, i64)> = crate_versions::table
+                .select((crate_versions::id, crate_versions::downloads))
+                .load(conn)?;
+
+            diesel::delete(
+                version_download_snapshots::table
+                    .filter(version_download_snapshots::date.eq(date.as_str())),
+            )
+            .execute(conn)?;
+            for (version_id, downloads) in versions {
+                diesel::insert_into(version_download_snapshots::table)
+                    .values(NewVersionDownloadSnapshot {
+                        version_id,
+                        downloads,
+                        date: date.as_str(),
+                    })
+                    .execute(conn)?;
+            }
+
+            //? Snapshots falling outside of the retention period are no longer needed.
+            diesel::delete(
+                version_download_snapshots::table
+                    .filter(version_download_snapshots::date.lt(expired.as_str())),
+            )
+            .execute(conn)?;
+
+            Ok::<_, Error>(())
+        })
+        .await
+}
+
+/// Computes the daily downloads of each version of a crate, between two days (inclusive).
+///
+/// The downloads of a day are the difference between its snapshot and the previous one.
+/// The first snapshot of a version only counts as downloads if the version was published the day before (or later),
+/// since older versions accumulated their download counts before being snapshotted.
+pub fn daily_downloads(
+    conn: &mut Connection,
+    crate_id: i64,
+    since: NaiveDate,
+    until: NaiveDate,
+) -> QueryResult<Vec<VersionDownloads>> {
+    let versions: Vec<(i64, String, String)> = crate_versions::table
+        .select((
+            crate_versions::id,
+            crate_versions::version,
+            crate_versions::published_at,
+        ))
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .load(conn)?;
+    let snapshots: Vec<(i64, i64, String)> = version_download_snapshots::table
+        .inner_join(crate_versions::table)
+        .select((
+            version_download_snapshots::version_id,
+            version_download_snapshots::downloads,
+            version_download_snapshots::date,
+        ))
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .filter(version_download_snapshots::date.le(until.format(DATE_FORMAT).to_string()))
+        .order_by(version_download_snapshots::date.asc())
+        .load(conn)?;
+
+    let mut snapshots_by_version: HashMap<i64, Vec<(i64, NaiveDate)>> = HashMap::new();
+    for (version_id, downloads, date) in snapshots {
+        let Ok(date) = NaiveDate::parse_from_str(date.as_str(), DATE_FORMAT) else {
+            continue;
+        };
+        snapshots_by_version
+            .entry(version_id)
+            .or_default()
+            .push((downloads, date));
+    }
+
+    let downloads = versions
+        .into_iter()
+        .map(|(version_id, version, published_at)| {
+            let published_on =
+                NaiveDateTime::parse_from_str(published_at.as_str(), DATETIME_FORMAT)
+                    .map(|published_at| published_at.date())
+                    .ok();
+            let mut days = BTreeMap::new();
+            let mut previous: Option<i64> = None;
+            let snapshots = snapshots_by_version.remove(&version_id).unwrap_or_default();
+            for (downloads, date) in snapshots {
+                let count = match previous {
+                    Some(previous) => Some(downloads - previous),
+                    None => published_on
+                        .filter(|published_on| *published_on >= date.pred_opt().unwrap_or(date))
+                        .map(|_| downloads),
+                };
+                previous = Some(downloads);
+                if let Some(count) = count.filter(|_| date >= since) {
+                    days.insert(date, count.max(0));
+                }
+            }
+            VersionDownloads {
+                version_id,
+                version,
+                published_at,
+                days,
+            }
+        })
+        .collect();
+
+    Ok(downloads)
+}
@@ -1,9 +1,13 @@
+use std::any::Any;
 use std::convert::TryInto;
 use std::fmt::{Debug, Display};
 use std::io;
 
+use axum::body::Full;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use diesel::r2d2::PoolError;
 use diesel::result::Error as SQLError;
 use hex::FromHexError as HexError;
 use io::Error as IOError;
@@ -15,22 +19,39 @@ use thiserror::Error;
 use toml::de::Error as TOMLError;
 
 use alexandrie_index::error::Error as IndexError;
+use alexandrie_index::error::IndexError as InnerIndexError;
 use alexandrie_storage::error::Error as StorageError;
 
+use crate::db::database::PoolExhausted;
 use crate::db::models::Author;
+use crate::utils::request_id;
 
 /// Represents an error from the programmatic API.
-pub struct ApiError(anyhow::Error);
+///
+/// It is rendered in the format expected by Cargo (`{"errors":[{"detail":"..."}]}`), along with an appropriate status code.
+/// Internal errors (database failures, IO errors, etc...) are only logged, their details being replaced by a generic message.
+pub struct ApiError {
+    status: StatusCode,
+    inner: anyhow::Error,
+}
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        //? Unavailability errors (like the read-only mode, or an exhausted connection pool) are meant for clients to see.
+        let detail =
+            if self.status.is_server_error() && self.status != StatusCode::SERVICE_UNAVAILABLE {
+                tracing::error!("internal error in the programmatic API: {:#}", self.inner);
+                //? Cargo only shows the error's details, so the request ID is included for it to be reported.
+                match request_id::current() {
+                    Some(id) => format!("internal server error (request ID: {id})"),
+                    None => String::from("internal server error"),
+                }
+            } else {
+                self.inner.to_string()
+            };
+
         // Transform endpoint errors into the format expected by Cargo.
-        Json(json::json!({
-            "errors": [{
-                "detail": self.0.to_string(),
-            }]
-        }))
-        .into_response()
+        (self.status, Json(envelope(detail.as_str()))).into_response()
     }
 }
 
@@ -39,20 +60,143 @@ where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        let inner = err.into();
+        let status = user_facing_status(&inner).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        Self { status, inner }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner, f)
     }
 }
 
 impl ApiError {
-    /// Constructs an instance from a single message.
+    /// Constructs an instance from a single message (responded with a `400 Bad Request` status).
     pub fn msg<M>(message: M) -> Self
     where
         M: Display + Debug + Send + Sync + 'static,
     {
-        Self(anyhow::Error::msg(message))
+        Self::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    /// Constructs an instance from a single message, responded with the given status.
+    pub fn new<M>(status: StatusCode, message: M) -> Self
+    where
+        M: Display + Debug + Send + Sync + 'static,
+    {
+        Self {
+            status,
+            inner: anyhow::Error::msg(message),
+        }
+    }
+}
+
+/// Builds the error body expected by Cargo, from the error's (user-facing) details
+/// (along with the request's ID, if called while handling a request).
+fn envelope(detail: &str) -> json::Value {
+    let mut envelope = json::json!({
+        "errors": [{
+            "detail": detail,
+        }]
+    });
+    if let Some(id) = request_id::current() {
+        envelope["request_id"] = json::Value::String(id);
+    }
+    envelope
+}
+
+/// Gets the status code of errors that can be shown to users (or `None` for internal errors).
+fn user_facing_status(err: &anyhow::Error) -> Option<StatusCode> {
+    //? Failing to acquire a database connection can be wrapped in other errors (like in query errors).
+    if is_pool_exhausted(err) {
+        return Some(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    let index_status = |err: &IndexError| match err {
+        IndexError::IndexError(InnerIndexError::CrateNotFound { .. }) => {
+            Some(StatusCode::NOT_FOUND)
+        }
+        IndexError::IndexError(InnerIndexError::ConcurrentModification { .. }) => {
+            Some(StatusCode::CONFLICT)
+        }
+        IndexError::IndexError(InnerIndexError::UnsupportedSchema { .. }) => {
+            Some(StatusCode::BAD_REQUEST)
+        }
+        _ => None,
+    };
+
+    if let Some(err) = err.downcast_ref::<AlexError>() {
+        return Some(err.status());
+    }
+    if let Some(err) = err.downcast_ref::<IndexError>() {
+        return index_status(err);
+    }
+    match err.downcast_ref::<Error>()? {
+        Error::AlexError(err) => Some(err.status()),
+        Error::IndexError(err) => index_status(err),
+        _ => None,
     }
 }
 
+/// Checks whether an error is (or is caused by) the failure to acquire a database connection within the timeout.
+fn is_pool_exhausted(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<PoolExhausted>())
+}
+
+/// Wraps the error responses of the programmatic API that are not already in the format expected by Cargo
+/// (like the rejections of extractors or of the request body limit) into that format.
+pub async fn wrap_rejections(response: Response) -> Response {
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map_or(false, |value| {
+            value.as_bytes().starts_with(b"application/json")
+        });
+    if is_json || !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    //? Only the details of client errors are kept (these are messages from the extractors' rejections).
+    let (mut parts, body) = response.into_parts();
+    let detail = match hyper::body::to_bytes(body).await {
+        Ok(bytes) if status.is_client_error() && !bytes.is_empty() => {
+            String::from_utf8_lossy(bytes.as_ref()).into_owned()
+        }
+        _ => status
+            .canonical_reason()
+            .unwrap_or("unknown error")
+            .to_ascii_lowercase(),
+    };
+
+    let body = json::to_vec(&envelope(detail.as_str())).unwrap();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    Response::from_parts(parts, axum::body::boxed(Full::from(body)))
+}
+
+/// Turns the panics of request handlers into error responses (in the format expected by Cargo).
+///
+/// The panic's message is only logged (it may reveal internal details), clients get a generic `500` response.
+pub fn handle_panic(payload: Box<dyn Any + Send + 'static>) -> Response {
+    let message = payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic payload");
+    tracing::error!("request handler panicked: {message}");
+    ApiError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "request handler panicked",
+    )
+    .into_response()
+}
+
 #[cfg(feature = "frontend")]
 /// Represents an error from the frontend.
 pub struct FrontendError(anyhow::Error);
@@ -60,6 +204,9 @@ pub struct FrontendError(anyhow::Error);
 #[cfg(feature = "frontend")]
 impl IntoResponse for FrontendError {
     fn into_response(self) -> Response {
+        if is_pool_exhausted(&self.0) {
+            return axum::http::StatusCode::SERVICE_UNAVAILABLE.into_response();
+        }
         axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response()
     }
 }
@@ -105,6 +252,12 @@ pub enum Error {
     /// SQL error (invalid queries, database disconnections, etc...).
     #[error("SQL error: {0}")]
     SQLError(#[from] SQLError),
+    /// Database connection pool error (timed out while acquiring a connection, etc...).
+    #[error("connection pool error: {0}")]
+    PoolError(#[from] PoolError),
+    /// No database connection could be acquired within the configured timeout.
+    #[error("{0}")]
+    PoolExhausted(#[from] PoolExhausted),
     /// Version parsing/requirement errors (invalid version format parsed, etc...).
     #[error("semver error: {0}")]
     SemverError(#[from] SemverError),
@@ -126,12 +279,53 @@ pub enum Error {
     /// Open directory error
     #[error("{0}")]
     OpenDirectoryError(#[from] OpenDirectoryError),
+    /// HTTP error (invalid header value, invalid method, etc...).
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] axum::http::Error),
+    /// SMTP transport error (connection failure, rejected email, etc...).
+    #[error("SMTP error: {0}")]
+    SmtpError(#[from] lettre::transport::smtp::Error),
+    /// Invalid email address.
+    #[error("invalid email address: {0}")]
+    EmailAddressError(#[from] lettre::address::AddressError),
+    /// Email building error (missing sender, recipient, etc...).
+    #[error("email error: {0}")]
+    EmailError(#[from] lettre::error::Error),
+    /// Password hashing error (invalid parameters, etc...).
+    #[error("password hashing error: {0}")]
+    PasswordHashError(#[from] argon2::password_hash::Error),
+    /// Template rendering error (missing template, invalid context, etc...).
+    #[cfg(feature = "frontend")]
+    #[error("template error: {0}")]
+    TemplateError(#[from] handlebars::RenderError),
+    /// LDAP error (connection failure, rejected search, etc...).
+    #[cfg(feature = "ldap")]
+    #[error("LDAP error: {0}")]
+    LdapError(#[from] ldap3::LdapError),
+    /// The `ldap` authentication backend is selected, but not configured.
+    #[cfg(feature = "ldap")]
+    #[error("the `ldap` authentication backend requires a `[frontend.auth.ldap]` section")]
+    MissingLdapConfig,
     /// Empty stop words.
     #[error("Empty stop word filter")]
     EmptyStopWord,
     /// Tantivy's index is poisoned
     #[error("Tantivy's index is poisoned: {0}")]
     PoisonedError(String),
+    /// Invalid crate index commit message template.
+    #[error("invalid `{kind}` commit message template: {reason}")]
+    InvalidCommitMessage {
+        /// The kind of operation the template is for (like `publish`).
+        kind: &'static str,
+        /// Why the template is invalid.
+        reason: String,
+    },
+    /// Invalid download URL template (in the `[sparse]` section).
+    #[error("invalid `dl` template in the `[sparse]` section: {reason}")]
+    InvalidDownloadTemplate {
+        /// Why the template is invalid.
+        reason: String,
+    },
     /// Missing id field or on of nae's field in index schema
     /// Should never happen...
     #[error("Missing {0} in Tantivy's schema")]
@@ -165,6 +359,14 @@ pub enum AlexError {
         /// The proposed version to be published.
         published: Version,
     },
+    /// The published crate version has already been uploaded.
+    #[error("crate version `{version}` is already uploaded")]
+    VersionAlreadyUploaded {
+        /// The crate's name.
+        name: String,
+        /// The already uploaded version.
+        version: Version,
+    },
     /// The token used to access the registry is invalid.
     #[error("invalid token")]
     InvalidToken,
@@ -182,6 +384,188 @@ pub enum AlexError {
         /// The maximum allowed crate size (in bytes).
         max_crate_size: u64,
     },
+    /// The uploaded crate decompresses to more than the maximum allowed decompressed size.
+    #[error("uploaded crate decompresses to more than {limit} bytes (the maximum allowed decompressed size for this crate)")]
+    DecompressionLimitExceeded {
+        /// The maximum allowed decompressed size (in bytes).
+        limit: u64,
+    },
+    /// The published crate has too many keywords.
+    #[error("too many keywords (at most {max_keywords} keywords are allowed)")]
+    TooManyKeywords {
+        /// The maximum number of keywords allowed per crate.
+        max_keywords: usize,
+    },
+    /// The published crate has an invalid keyword.
+    #[error("invalid keyword '{keyword}' (keywords must start with a letter, only contain ASCII alphanumeric characters, '_', '-' or '+', and be at most {max_keyword_length} characters long)")]
+    InvalidKeyword {
+        /// The invalid keyword.
+        keyword: String,
+        /// The maximum allowed keyword length.
+        max_keyword_length: usize,
+    },
+    /// The published crate's name is invalid.
+    #[error("invalid crate name '{name}': {reason}")]
+    InvalidCrateName {
+        /// The published crate's name.
+        name: String,
+        /// Why the name is invalid.
+        reason: String,
+    },
+    /// The published crate's name differs from the one of the existing crate (by case, or by `-` and `_`).
+    #[error("crate was previously named '{existing}' (and cannot be published as '{name}')")]
+    CrateNameMismatch {
+        /// The published crate's name.
+        name: String,
+        /// The name of the existing crate.
+        existing: String,
+    },
+    /// The published crate's name is reserved.
+    #[error("the crate name '{name}' is reserved (it matches the reserved name '{pattern}' of this registry)")]
+    ReservedCrateName {
+        /// The published crate's name.
+        name: String,
+        /// The reserved name (or prefix) matching the crate's name.
+        pattern: String,
+    },
+    /// The published crate's name is an alias of another crate.
+    #[error("the crate name '{name}' is an alias of the '{target}' crate (like its former name), only superadmins can publish a crate under it")]
+    AliasedCrateName {
+        /// The published crate's name.
+        name: String,
+        /// The name of the crate the alias refers to.
+        target: String,
+    },
+    /// The published crate's repository URL is not a well-formed URL.
+    #[error("invalid repository URL '{url}' (it must be an absolute URL, like `https://github.com/owner/repo`)")]
+    InvalidRepositoryUrl {
+        /// The invalid repository URL.
+        url: String,
+    },
+    /// The published crate depends on crates from registries that are not allowed by the crate index.
+    #[error("dependencies on disallowed registries: {dependencies} (the allowed registries are listed in the crate index's `config.json`)")]
+    DisallowedRegistries {
+        /// The offending dependencies (formatted like "`name` (from `registry`)", and comma-separated).
+        dependencies: String,
+    },
+    /// The published crate depends on crates from registries that are not allowed for this crate specifically.
+    #[error("dependencies on registries not allowed for {policy}: {dependencies} (allowed registries: {allowed})")]
+    RestrictedRegistries {
+        /// The crates the violated policy applies to (like "crates matching `acme-*`").
+        policy: String,
+        /// The offending dependencies (formatted like "`name` (from `registry`)", and comma-separated).
+        dependencies: String,
+        /// The registries allowed by the policy (comma-separated).
+        allowed: String,
+    },
+    /// The published crate declares an invalid feature (like one enabling an unknown dependency).
+    #[error("invalid feature `{feature}`: {reason}")]
+    InvalidFeature {
+        /// The invalid feature's name.
+        feature: String,
+        /// Why the feature is invalid.
+        reason: String,
+    },
+    /// The published crate's license is not a valid SPDX expression.
+    #[error("invalid license expression '{license}': {reason} (it must be a valid SPDX expression, like `MIT OR Apache-2.0`)")]
+    InvalidLicense {
+        /// The invalid license expression.
+        license: String,
+        /// Why the expression is invalid.
+        reason: String,
+    },
+    /// The published crate's license is not allowed by this registry.
+    #[error("the license '{license}' is not allowed by this registry (the allowed licenses are: {allowed})")]
+    DisallowedLicense {
+        /// The crate's license expression.
+        license: String,
+        /// The allowed licenses (comma-separated).
+        allowed: String,
+    },
+    /// The published crate has no license expression, whereas this registry only allows some licenses.
+    #[error("{reason} (the allowed licenses are: {allowed})")]
+    MissingLicense {
+        /// Why the crate's license could not be checked.
+        reason: String,
+        /// The allowed licenses (comma-separated).
+        allowed: String,
+    },
+    /// The author exceeded one of their publish quotas.
+    #[error("publish quota exceeded (at most {limit} {quota} are allowed), please try again after {reset_at}")]
+    QuotaExceeded {
+        /// The exceeded quota (like "new crates per day").
+        quota: &'static str,
+        /// The quota's limit.
+        limit: u64,
+        /// The date at which publishing becomes possible again.
+        reset_at: String,
+    },
+    /// The publication was rejected by the pre-publish hook.
+    #[error("the publication was rejected: {reason}")]
+    PublishRejected {
+        /// Why the hook rejected the publication.
+        reason: String,
+    },
+    /// The pre-publish hook failed (and the registry is configured to refuse publications in that case).
+    #[error("the publication could not be validated by the registry, please try again later")]
+    PublishHookFailed,
+    /// The publication waited for too long for its turn (the registry limits how many publications are processed at once).
+    #[error("the registry is busy processing other publications (waited for {max_wait} seconds), please try again later")]
+    PublishQueueTimeout {
+        /// How long the publication waited for its turn (in seconds).
+        max_wait: u64,
+    },
+    /// The publication comes from a Cargo client older than the configured minimum.
+    #[error("this registry requires Cargo {minimum} or newer to publish (you are using Cargo {version}), please upgrade it (like using `rustup update`)")]
+    OutdatedCargo {
+        /// The detected version of Cargo.
+        version: Version,
+        /// The minimum version of Cargo.
+        minimum: Version,
+    },
+    /// The published crate refers to an unknown (or disallowed) category.
+    #[error("unknown category '{category}' (the list of allowed categories is available at `/api/v1/categories`)")]
+    UnknownCategory {
+        /// The unknown category's tag.
+        category: String,
+    },
+}
+
+impl AlexError {
+    /// Gets the status code to respond with, when this error is returned from the programmatic API.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AlexError::CrateNotFound { .. } => StatusCode::NOT_FOUND,
+            AlexError::CrateNotOwned { .. } => StatusCode::FORBIDDEN,
+            AlexError::VersionAlreadyUploaded { .. } => StatusCode::CONFLICT,
+            AlexError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AlexError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AlexError::PublishHookFailed | AlexError::PublishQueueTimeout { .. } => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AlexError::CrateTooLarge { .. } | AlexError::DecompressionLimitExceeded { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            AlexError::VersionTooLow { .. }
+            | AlexError::MissingQueryParams { .. }
+            | AlexError::TooManyKeywords { .. }
+            | AlexError::InvalidKeyword { .. }
+            | AlexError::InvalidCrateName { .. }
+            | AlexError::CrateNameMismatch { .. }
+            | AlexError::ReservedCrateName { .. }
+            | AlexError::AliasedCrateName { .. }
+            | AlexError::InvalidRepositoryUrl { .. }
+            | AlexError::DisallowedRegistries { .. }
+            | AlexError::RestrictedRegistries { .. }
+            | AlexError::InvalidFeature { .. }
+            | AlexError::PublishRejected { .. }
+            | AlexError::InvalidLicense { .. }
+            | AlexError::DisallowedLicense { .. }
+            | AlexError::MissingLicense { .. }
+            | AlexError::OutdatedCargo { .. }
+            | AlexError::UnknownCategory { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
 }
 
 // impl IntoResponse for Error {
@@ -261,3 +645,77 @@ impl TryInto<AlexError> for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn into_parts(response: Response) -> (StatusCode, json::Value) {
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, json::from_slice(body.as_ref()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn invalid_tokens_are_unauthorized() {
+        let response = ApiError::from(AlexError::InvalidToken).into_response();
+        let (status, body) = into_parts(response).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body, envelope("invalid token"));
+    }
+
+    #[tokio::test]
+    async fn missing_tokens_are_wrapped() {
+        let response = StatusCode::UNAUTHORIZED.into_response();
+        let (status, body) = into_parts(wrap_rejections(response).await).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body, envelope("unauthorized"));
+    }
+
+    #[tokio::test]
+    async fn already_uploaded_versions_conflict() {
+        let err = Error::from(AlexError::VersionAlreadyUploaded {
+            name: String::from("serde"),
+            version: Version::new(1, 0, 0),
+        });
+        let (status, body) = into_parts(ApiError::from(err).into_response()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body, envelope("crate version `1.0.0` is already uploaded"));
+    }
+
+    #[tokio::test]
+    async fn size_limits_are_reported() {
+        let err = ApiError::from(AlexError::CrateTooLarge {
+            max_crate_size: 1024,
+        });
+        let (status, body) = into_parts(err.into_response()).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            body,
+            envelope("uploaded crate is larger than the maximum allowed crate size of 1024 bytes"),
+        );
+
+        //? The request body limit rejects requests with a plain-text body.
+        let response = (StatusCode::PAYLOAD_TOO_LARGE, "length limit exceeded").into_response();
+        let (status, body) = into_parts(wrap_rejections(response).await).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body, envelope("length limit exceeded"));
+    }
+
+    #[tokio::test]
+    async fn internal_errors_are_not_leaked() {
+        let err = Error::PoisonedError(String::from("some internal detail"));
+        let (status, body) = into_parts(ApiError::from(err).into_response()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body, envelope("internal server error"));
+    }
+
+    #[tokio::test]
+    async fn successful_responses_are_untouched() {
+        let response = (StatusCode::OK, "ok").into_response();
+        let response = wrap_rejections(response).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body.as_ref(), b"ok");
+    }
+}
@@ -5,6 +5,7 @@ use axum::http::StatusCode;
 use axum::response::Redirect;
 use axum_extra::either::Either;
 use axum_extra::response::Html;
+use chrono::Utc;
 use diesel::prelude::*;
 use oauth2::reqwest::async_http_client;
 use oauth2::{AuthorizationCode, TokenResponse};
@@ -17,6 +18,7 @@ use crate::config::frontend::auth::github::GithubAuthOrganizationConfig;
 use crate::config::AppState;
 use crate::db::models::{Author, NewAuthor, NewSalt};
 use crate::db::schema::{authors, salts};
+use crate::db::DATETIME_FORMAT;
 use crate::error::FrontendError;
 use crate::frontend::account::github::GITHUB_LOGIN_STATE_KEY;
 use crate::utils;
@@ -222,6 +224,7 @@ pub(crate) async fn get(
             };
 
             //? Insert the new author data.
+            let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
             let new_author = NewAuthor {
                 email: primary_email.email.as_str(),
                 name: user_info
@@ -231,6 +234,7 @@ pub(crate) async fn get(
                 passwd: None,
                 github_id: Some(github_id.as_str()),
                 gitlab_id: None,
+                created_at: Some(created_at.as_str()),
             };
             diesel::insert_into(authors::table)
                 .values(new_author)
@@ -5,6 +5,7 @@ use axum::http::StatusCode;
 use axum::response::Redirect;
 use axum_extra::either::Either;
 use axum_extra::response::Html;
+use chrono::Utc;
 use diesel::prelude::*;
 use oauth2::reqwest::async_http_client;
 use oauth2::{AccessToken, AuthorizationCode, TokenResponse};
@@ -19,6 +20,7 @@ use url::Url;
 use crate::config::AppState;
 use crate::db::models::{Author, NewAuthor, NewSalt};
 use crate::db::schema::{authors, salts};
+use crate::db::DATETIME_FORMAT;
 use crate::error::FrontendError;
 use crate::frontend::account::gitlab::GITLAB_LOGIN_STATE_KEY;
 use crate::utils;
@@ -186,6 +188,7 @@ pub(crate) async fn get(
             };
 
             //? Insert the new author data.
+            let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
             let new_author = NewAuthor {
                 email: user_info.email.as_str(),
                 name: user_info
@@ -195,6 +198,7 @@ pub(crate) async fn get(
                 passwd: None,
                 github_id: None,
                 gitlab_id: Some(gitlab_id.as_str()),
+                created_at: Some(created_at.as_str()),
             };
             diesel::insert_into(authors::table)
                 .values(new_author)
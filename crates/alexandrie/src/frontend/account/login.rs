@@ -1,4 +1,3 @@
-use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use axum::extract::State;
@@ -7,24 +6,22 @@ use axum::response::Redirect;
 use axum::Form;
 use axum_extra::either::Either;
 use axum_extra::response::Html;
-use diesel::prelude::*;
 use json::json;
-use ring::pbkdf2;
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 
 use crate::config::AppState;
-use crate::db::schema::*;
 use crate::error::FrontendError;
 use crate::utils;
+use crate::utils::auth::backend::{AuthContext, AuthError, Credentials};
 use crate::utils::auth::frontend::Auth;
 use crate::utils::response::common;
 
-const LOGIN_FLASH: &'static str = "login.flash";
+pub(crate) const LOGIN_FLASH: &'static str = "login.flash";
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "kebab-case")]
-enum LoginFlashMessage {
+pub(crate) enum LoginFlashMessage {
     Error { message: String },
     Success { message: String },
 }
@@ -51,7 +48,9 @@ pub(crate) async fn get(
     let engine = &state.frontend.handlebars;
     let auth = &state.frontend.config.auth;
 
-    let local_enabled = auth.local.enabled;
+    let backend = state.frontend.auth.backend.as_ref();
+    let local_enabled = backend.is_some();
+    let hash_password = backend.map_or(true, |backend| backend.hashes_passwords());
     let github_enabled = auth.github.enabled;
     let gitlab_enabled = auth.gitlab.enabled;
     let local_registration_enabled = auth.local.allow_registration;
@@ -64,10 +63,12 @@ pub(crate) async fn get(
         "instance": &state.frontend.config,
         "flash": flash_message,
         "local_enabled": local_enabled,
+        "hash_password": hash_password,
         "github_enabled": github_enabled,
         "gitlab_enabled": gitlab_enabled,
         "local_registration_enabled": local_registration_enabled,
         "has_separator": has_separator,
+        "password_reset_enabled": super::reset::is_enabled(state.as_ref()),
     });
 
     let rendered = engine.render("account/login", &context)?;
@@ -84,71 +85,56 @@ pub(crate) async fn post(
         return Ok(Either::E2(Redirect::to("/")));
     }
 
-    if !state.frontend.config.auth.local.enabled {
+    let Some(backend) = state.frontend.auth.backend.clone() else {
         let rendered = utils::response::error_html(
             state.as_ref(),
             None,
             "local authentication is not allowed on this instance",
         )?;
         return Ok(Either::E1((StatusCode::BAD_REQUEST, Html(rendered))));
-    }
+    };
+
+    //? Check the credentials against the backend's external source of truth, if it has one.
+    //? This can involve network round trips (like binding to an LDAP server), so no database transaction is held meanwhile.
+    let verifier = Arc::clone(&backend);
+    let (email, password) = (form.email.clone(), form.password.clone());
+    let verification = utils::run_blocking(&state, move |_| {
+        let credentials = Credentials {
+            email: email.as_str(),
+            password: password.as_str(),
+        };
+        verifier.verify(credentials)
+    })
+    .await;
 
     let db = &state.db;
+    let state = Arc::clone(&state);
 
     let transaction = db.transaction(move |conn| {
-        //? Get the users' salt and expected hash.
-        let maybe_results: Option<(i64, String, Option<String>)> = salts::table
-            .inner_join(authors::table)
-            .select((authors::id, salts::salt, authors::passwd))
-            .filter(authors::email.eq(form.email.as_str()))
-            .first(conn)
-            .optional()?;
-
-        //? Does the user exist?
-        let (author_id, encoded_salt, encoded_expected_hash) = match maybe_results {
-            Some((id, salt, Some(passwd))) => (id, salt, passwd),
-            _ => {
-                let message = String::from("invalid email/password combination.");
+        //? Check the credentials against the database (or provision the verified user's author record, if needed).
+        let mut ctx = AuthContext {
+            conn,
+            password_hasher: &state.general.password_hasher,
+        };
+        let credentials = Credentials {
+            email: form.email.as_str(),
+            password: form.password.as_str(),
+        };
+        let outcome = verification.and_then(|identity| match identity {
+            Some(identity) => backend.provision(&mut ctx, &identity),
+            None => backend.authenticate(&mut ctx, credentials),
+        });
+        let author_id = match outcome {
+            Ok(author_id) => author_id,
+            Err(AuthError::Internal(err)) => return Err(err.into()),
+            Err(err) => {
+                let message = err.to_string();
                 let flash_message = LoginFlashMessage::Error { message };
                 session.insert(LOGIN_FLASH, &flash_message)?;
                 return Ok(Either::E2(Redirect::to("/account/login")));
             }
         };
 
-        //? Decode hex-encoded hashes.
-        let maybe_results = hex::decode(encoded_salt.as_str())
-            .and_then(|fst| hex::decode(form.password.as_str()).map(move |snd| (fst, snd)))
-            .and_then(|(fst, snd)| {
-                hex::decode(encoded_expected_hash.as_str()).map(move |trd| (fst, snd, trd))
-            });
-
-        let Ok((decoded_salt, decoded_password, decoded_expected_hash)) = maybe_results else {
-            let message = String::from("password/salt decoding issue.");
-            let flash_message = LoginFlashMessage::Error { message };
-            session.insert(LOGIN_FLASH, &flash_message)?;
-            return Ok(Either::E2(Redirect::to("/account/login")));
-        };
-
-        //? Verify client password against the expected hash (through PBKDF2).
-        let password_match = {
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-            let outcome = pbkdf2::verify(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                decoded_salt.as_slice(),
-                decoded_password.as_slice(),
-                decoded_expected_hash.as_slice(),
-            );
-            outcome.is_ok()
-        };
-
-        if !password_match {
-            let message = String::from("invalid email/password combination.");
-            let flash_message = LoginFlashMessage::Error { message };
-            session.insert(LOGIN_FLASH, &flash_message)?;
-            return Ok(Either::E2(Redirect::to("/account/login")));
-        }
-
         //? Get the maximum duration of the session.
         let expiry = match form.remember.as_deref() {
             Some("on") => time::Duration::seconds(2_592_000), // 30 days
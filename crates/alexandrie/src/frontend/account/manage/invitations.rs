@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use tower_sessions::Session;
+
+use crate::config::AppState;
+use crate::error::FrontendError;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::invitations;
+use crate::utils::page_cache;
+
+use super::{ManageFlashMessage, ACCOUNT_MANAGE_FLASH};
+
+/// Route to accept a crate owner invitation.
+pub(crate) async fn accept(
+    State(state): State<Arc<AppState>>,
+    Path(invitation_id): Path<i64>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Redirect, FrontendError> {
+    resolve(state, invitation_id, maybe_author, session, true).await
+}
+
+/// Route to decline a crate owner invitation.
+pub(crate) async fn decline(
+    State(state): State<Arc<AppState>>,
+    Path(invitation_id): Path<i64>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Redirect, FrontendError> {
+    resolve(state, invitation_id, maybe_author, session, false).await
+}
+
+async fn resolve(
+    state: Arc<AppState>,
+    invitation_id: i64,
+    maybe_author: Option<Auth>,
+    session: Session,
+    accept: bool,
+) -> Result<Redirect, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Redirect::to("/account/manage"));
+    };
+
+    //? Only the invitations of the current author can be resolved.
+    let maybe_crate_name = state
+        .db
+        .transaction(move |conn| invitations::resolve(conn, author.id, invitation_id, accept))
+        .await?;
+
+    let flash_message = match maybe_crate_name {
+        Some(crate_name) if accept => {
+            page_cache::invalidate(&state, &crate_name);
+            let message = format!("you are now an owner of '{crate_name}'.");
+            ManageFlashMessage::InvitationSuccess { message }
+        }
+        Some(crate_name) => {
+            let message = format!("the invitation for '{crate_name}' has been declined.");
+            ManageFlashMessage::InvitationSuccess { message }
+        }
+        None => {
+            let message = String::from("this invitation does not exist (or has expired).");
+            ManageFlashMessage::InvitationError { message }
+        }
+    };
+    session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
+
+    Ok(Redirect::to("/account/manage"))
+}
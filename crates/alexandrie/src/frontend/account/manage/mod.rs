@@ -3,23 +3,32 @@ use std::sync::Arc;
 use axum::extract::State;
 use axum::http::StatusCode;
 use axum_extra::response::Html;
+use chrono::NaiveDateTime;
 use diesel::dsl as sql;
 use diesel::prelude::*;
 use json::json;
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 
+/// Owner invitation routes (eg. "/account/manage/invitations/\<id\>/accept").
+pub mod invitations;
 /// Password management routes (eg. "/account/manage/password").
 pub mod passwd;
+/// Session management routes (eg. "/account/manage/sessions").
+pub mod sessions;
 /// Token management routes (eg. "/account/manage/tokens").
 pub mod tokens;
 
 use crate::config::AppState;
 use crate::db::models::AuthorToken;
 use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
 use crate::error::FrontendError;
+use crate::frontend::account::utils::password_policy;
 use crate::frontend::helpers;
+use crate::utils;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::owners::OwnerRole;
 use crate::utils::response::common;
 
 const ACCOUNT_MANAGE_FLASH: &'static str = "account_manage.flash";
@@ -38,6 +47,14 @@ enum ManageFlashMessage {
     TokenRevocationSuccess { message: String },
     /// Failed token revocation message.
     TokenRevocationError { message: String },
+    /// Successful session revocation message.
+    SessionRevocationSuccess { message: String },
+    /// Failed session revocation message.
+    SessionRevocationError { message: String },
+    /// Successful owner invitation acceptance (or decline) message.
+    InvitationSuccess { message: String },
+    /// Failed owner invitation acceptance (or decline) message.
+    InvitationError { message: String },
 }
 
 pub(crate) async fn get(
@@ -71,6 +88,23 @@ pub(crate) async fn get(
             .filter(author_tokens::author_id.eq(author.id))
             .load::<AuthorToken>(conn)?;
 
+        //? Get this author's pending owner invitations.
+        let invitations = utils::invitations::pending(conn, author.id)?
+            .into_iter()
+            .map(|invitation| {
+                let expires_at =
+                    NaiveDateTime::parse_from_str(invitation.expires_at.as_str(), DATETIME_FORMAT)
+                        .unwrap();
+                json!({
+                    "id": invitation.id,
+                    "crate_name": invitation.crate_name,
+                    "inviter_name": invitation.inviter_name,
+                    "collaborator": invitation.role == OwnerRole::Collaborator,
+                    "expires_at": helpers::humanize_datetime(expires_at),
+                })
+            })
+            .collect::<Vec<_>>();
+
         let flash_message: Option<ManageFlashMessage> = session.remove(ACCOUNT_MANAGE_FLASH)?;
 
         let engine = &state.frontend.handlebars;
@@ -81,7 +115,9 @@ pub(crate) async fn get(
             "owned_crates_count": helpers::humanize_number(owned_crates_count),
             "open_sessions_count": helpers::humanize_number(open_sessions_count),
             "tokens": tokens,
+            "invitations": invitations,
             "flash": flash_message,
+            "password_policy": password_policy(state.as_ref()),
         });
 
         let rendered = engine.render("account/manage", &context)?;
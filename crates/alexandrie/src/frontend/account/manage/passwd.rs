@@ -1,20 +1,20 @@
-use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use axum::extract::State;
 use axum::response::Redirect;
 use axum::Form;
 use diesel::prelude::*;
-use ring::digest as hasher;
-use ring::pbkdf2;
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
 
 use crate::config::AppState;
 use crate::db::schema::*;
 use crate::error::FrontendError;
+use crate::frontend::account::utils::validate_new_password;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::auth::passwd::{self, PasswordVerification};
 
+use super::sessions::revoke_sessions;
 use super::{ManageFlashMessage, ACCOUNT_MANAGE_FLASH};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -35,78 +35,54 @@ pub(crate) async fn post(
         return Ok(Redirect::to("/account/manage"));
     };
 
-    //? Are all fields filled-in?
-    if form.password.is_empty() || form.new_password.is_empty() || form.confirm_password.is_empty()
-    {
+    //? Is the current password filled-in?
+    if form.password.is_empty() {
         let message = String::from("some fields were left empty.");
         let flash_message = ManageFlashMessage::PasswordChangeError { message };
         session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
         return Ok(Redirect::to("/account/manage"));
     }
 
-    //? Does the two passwords match (consistency check)?
-    if form.new_password != form.confirm_password {
-        let message = String::from("the two passwords did not match.");
+    //? Is the new password valid (matching confirmation, complying with the password policy, etc...)?
+    let policy = state.password_policy();
+    if let Err(message) = validate_new_password(policy, &form.new_password, &form.confirm_password)
+    {
         let flash_message = ManageFlashMessage::PasswordChangeError { message };
         session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
         return Ok(Redirect::to("/account/manage"));
     }
 
     let db = &state.db;
+    let state = Arc::clone(&state);
+    let session_id = session.id().to_string();
 
     let transaction = db.transaction(move |conn| {
-        //? Get the users' salt.
+        //? Get the users' salt (only used to verify older password hashes).
         let encoded_salt = salts::table
             .inner_join(authors::table)
             .select(salts::salt)
             .filter(authors::id.eq(author.id))
             .first::<String>(conn)?;
 
-        let decode_results: Result<_, hex::FromHexError> = (|| {
-            let fst = hex::decode(encoded_salt.as_str())?;
-            let snd = hex::decode(form.new_password.as_str())?;
-            Ok((fst, snd))
-        })();
-
-        let Ok((decoded_salt, decoded_desired_password)) = decode_results else {
-            let message = String::from("password/salt decoding issue.");
-            let flash_message = ManageFlashMessage::PasswordChangeError { message };
-            session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
-            return Ok(Redirect::to("/account/manage"));
-        };
-
         //? If the user has a password, check that the actual current password matches the one the user submitted.
         //? (a user may not have a password if they registered using an external mean of authentication, like GitHub or GitLab)
         //? (if the user does not have a password, "changing" a password just sets the password to the provided value)
         if let Some(encoded_expected_hash) = author.passwd.as_deref() {
-            //? Decode hex-encoded hashes.
-            let decode_results: Result<_, hex::FromHexError> = (|| {
-                let fst = hex::decode(form.password.as_str())?;
-                let snd = hex::decode(encoded_expected_hash)?;
-                Ok((fst, snd))
-            })();
-
-            let Ok((decoded_current_password, decoded_expected_hash)) = decode_results else {
-                let message = String::from("password/salt decoding issue.");
+            let Ok(decoded_current_password) = hex::decode(form.password.as_str()) else {
+                let message = String::from("password decoding issue.");
                 let flash_message = ManageFlashMessage::PasswordChangeError { message };
                 session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
                 return Ok(Redirect::to("/account/manage"));
             };
 
-            //? Verify client password against the expected hash (through PBKDF2).
-            let password_match = {
-                let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-                let outcome = pbkdf2::verify(
-                    pbkdf2::PBKDF2_HMAC_SHA512,
-                    iteration_count,
-                    decoded_salt.as_slice(),
-                    decoded_current_password.as_slice(),
-                    decoded_expected_hash.as_slice(),
-                );
-                outcome.is_ok()
-            };
+            //? Verify client password against the expected hash.
+            let verification = state.general.password_hasher.verify(
+                decoded_current_password.as_slice(),
+                encoded_salt.as_str(),
+                encoded_expected_hash,
+            );
 
-            if !password_match {
+            if verification == PasswordVerification::Mismatch {
                 let message = String::from("invalid current password.");
                 let flash_message = ManageFlashMessage::PasswordChangeError { message };
                 session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
@@ -114,25 +90,23 @@ pub(crate) async fn post(
             }
         }
 
-        //? Derive the hashed password data with PBKDF2 (100'000 rounds).
-        let encoded_derived_hash = {
-            let mut out = [0u8; hasher::SHA512_OUTPUT_LEN];
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-            pbkdf2::derive(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                decoded_salt.as_slice(),
-                decoded_desired_password.as_slice(),
-                &mut out,
-            );
-            hex::encode(out.as_ref())
-        };
+        //? Hash the new password (pre-hashed like the login form does, then using Argon2id).
+        let hashed_password = passwd::prehash(author.email.as_str(), form.new_password.as_str());
+        let encoded_derived_hash = state
+            .general
+            .password_hasher
+            .hash(hashed_password.as_ref())?;
 
-        diesel::update(authors::table)
+        diesel::update(authors::table.find(author.id))
             .set(authors::passwd.eq(encoded_derived_hash.as_str()))
             .execute(conn)?;
 
-        let message = String::from("the password was successfully changed.");
+        //? Log the account out of every other session (the current one is kept).
+        revoke_sessions(conn, author.id, Some(session_id.as_str()))?;
+
+        let message = String::from(
+            "the password was successfully changed (all other sessions have been logged out).",
+        );
         let flash_message = ManageFlashMessage::PasswordChangeSuccess { message };
         session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
         Ok(Redirect::to("/account/manage"))
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum_extra::response::Html;
+use diesel::prelude::*;
+use json::json;
+use ring::digest as hasher;
+use tower_sessions::Session;
+
+/// Route to revoke a single session (eg. "/account/manage/sessions/<handle>/revoke").
+pub mod revoke;
+/// Route to log out of all sessions (eg. "/account/manage/sessions/revoke-all").
+pub mod revoke_all;
+/// Route to revoke all other sessions (eg. "/account/manage/sessions/revoke-others").
+pub mod revoke_others;
+
+use crate::config::AppState;
+use crate::db::models::Session as SessionEntry;
+use crate::db::schema::*;
+use crate::db::{Connection, DATETIME_FORMAT};
+use crate::error::FrontendError;
+use crate::frontend::helpers;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::response::common;
+use crate::utils::sessions::SESSION_DATE_FORMAT;
+
+use super::{ManageFlashMessage, ACCOUNT_MANAGE_FLASH};
+
+/// Deletes the open sessions of an author, except for the session with the given ID (if any).
+pub(crate) fn revoke_sessions(
+    conn: &mut Connection,
+    author_id: i64,
+    except: Option<&str>,
+) -> QueryResult<usize> {
+    let sessions = sessions::table.filter(sessions::author_id.eq(author_id));
+    match except {
+        Some(session_id) => {
+            diesel::delete(sessions.filter(sessions::id.ne(session_id))).execute(conn)
+        }
+        None => diesel::delete(sessions).execute(conn),
+    }
+}
+
+/// Returns the public handle of a session, used to refer to it without disclosing its ID.
+///
+/// Session IDs are the secrets stored in the session cookies, so they must never be rendered in pages.
+fn session_handle(session_id: &str) -> String {
+    hex::encode(hasher::digest(&hasher::SHA256, session_id.as_bytes()).as_ref())
+}
+
+fn humanize_session_date(date: &str) -> Option<String> {
+    chrono::NaiveDateTime::parse_from_str(date, DATETIME_FORMAT)
+        .ok()
+        .map(helpers::humanize_datetime)
+}
+
+/// Route to list the open sessions of the current author.
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<(StatusCode, Html<String>), FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        let state = state.as_ref();
+        return common::need_to_login(state);
+    };
+
+    let current_session_id = session.id().to_string();
+    let now = time::OffsetDateTime::now_utc().format(SESSION_DATE_FORMAT)?;
+
+    let author_id = author.id;
+    let entries: Vec<SessionEntry> = state
+        .db
+        .run(move |conn| {
+            //? Get the author's sessions which have not expired yet.
+            sessions::table
+                .filter(sessions::author_id.eq(author_id))
+                .filter(sessions::expiry.gt(now))
+                .order_by(sessions::last_seen.desc())
+                .load(conn)
+        })
+        .await?;
+
+    let sessions: Vec<_> = entries
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "handle": session_handle(entry.id.as_str()),
+                "current": entry.id == current_session_id,
+                "created_at": humanize_session_date(entry.created_at.as_str()),
+                "last_seen": humanize_session_date(entry.last_seen.as_str()),
+                "user_agent": entry.user_agent,
+                "ip_address": entry.ip_address,
+            })
+        })
+        .collect();
+
+    let flash_message: Option<ManageFlashMessage> = session.remove(ACCOUNT_MANAGE_FLASH)?;
+
+    let engine = &state.frontend.handlebars;
+    let context = json!({
+        "user": author,
+        "instance": &state.frontend.config,
+        "sessions": sessions,
+        "flash": flash_message,
+    });
+
+    let rendered = engine.render("account/sessions", &context)?;
+    Ok((StatusCode::OK, Html(rendered)))
+}
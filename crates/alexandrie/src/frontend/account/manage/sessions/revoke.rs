@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::response::Redirect;
+use diesel::prelude::*;
+use tower_sessions::Session;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::FrontendError;
+use crate::utils::auth::frontend::Auth;
+
+use super::{session_handle, ManageFlashMessage, ACCOUNT_MANAGE_FLASH};
+
+pub(crate) async fn post(
+    State(state): State<Arc<AppState>>,
+    Path(handle): Path<String>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Redirect, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Redirect::to("/account/manage"));
+    };
+
+    //? The current session can be closed by logging out instead.
+    if session_handle(session.id().to_string().as_str()) == handle {
+        let message = String::from("the current session cannot be revoked (log out instead).");
+        let flash_message = ManageFlashMessage::SessionRevocationError { message };
+        session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
+        return Ok(Redirect::to("/account/manage/sessions"));
+    }
+
+    let db = &state.db;
+
+    let transaction = db.transaction(move |conn| {
+        //? Only look among the sessions of the current author, so that other authors' sessions cannot be revoked.
+        let session_ids = sessions::table
+            .select(sessions::id)
+            .filter(sessions::author_id.eq(author.id))
+            .load::<String>(conn)?;
+
+        let maybe_session_id = session_ids
+            .into_iter()
+            .find(|session_id| session_handle(session_id.as_str()) == handle);
+
+        match maybe_session_id {
+            Some(session_id) => {
+                diesel::delete(
+                    sessions::table
+                        .filter(sessions::id.eq(session_id))
+                        .filter(sessions::author_id.eq(author.id)),
+                )
+                .execute(conn)?;
+
+                let message = String::from("the session has successfully been revoked.");
+                let flash_message = ManageFlashMessage::SessionRevocationSuccess { message };
+                session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
+                Ok(Redirect::to("/account/manage/sessions"))
+            }
+            None => {
+                let message = String::from("this session does not exist.");
+                let flash_message = ManageFlashMessage::SessionRevocationError { message };
+                session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
+                Ok(Redirect::to("/account/manage/sessions"))
+            }
+        }
+    });
+
+    transaction.await
+}
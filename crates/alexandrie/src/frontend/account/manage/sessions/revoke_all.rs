@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::response::Redirect;
+use tower_sessions::Session;
+
+use crate::config::AppState;
+use crate::error::FrontendError;
+use crate::frontend::account::login::{LoginFlashMessage, LOGIN_FLASH};
+use crate::utils::auth::frontend::Auth;
+
+use super::revoke_sessions;
+
+pub(crate) async fn post(
+    State(state): State<Arc<AppState>>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Redirect, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Redirect::to("/account/manage"));
+    };
+
+    //? Delete every session of this author (including the current one).
+    state
+        .db
+        .run(move |conn| revoke_sessions(conn, author.id, None))
+        .await?;
+
+    //? Log out the current session.
+    session.remove_value("author.id");
+
+    let message = String::from("you have been logged out of all your sessions.");
+    let flash_message = LoginFlashMessage::Success { message };
+    session.insert(LOGIN_FLASH, &flash_message)?;
+    Ok(Redirect::to("/account/login"))
+}
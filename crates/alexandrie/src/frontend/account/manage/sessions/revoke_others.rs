@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::response::Redirect;
+use tower_sessions::Session;
+
+use crate::config::AppState;
+use crate::error::FrontendError;
+use crate::utils::auth::frontend::Auth;
+
+use super::{revoke_sessions, ManageFlashMessage, ACCOUNT_MANAGE_FLASH};
+
+pub(crate) async fn post(
+    State(state): State<Arc<AppState>>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Redirect, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Redirect::to("/account/manage"));
+    };
+
+    //? Delete every session of this author, except the current one.
+    let session_id = session.id().to_string();
+    let revoked_count = state
+        .db
+        .run(move |conn| revoke_sessions(conn, author.id, Some(session_id.as_str())))
+        .await?;
+
+    let message = format!("{revoked_count} other session(s) successfully revoked.");
+    let flash_message = ManageFlashMessage::SessionRevocationSuccess { message };
+    session.insert(ACCOUNT_MANAGE_FLASH, &flash_message)?;
+    Ok(Redirect::to("/account/manage/sessions"))
+}
@@ -10,5 +10,7 @@ pub mod logout;
 pub mod manage;
 /// Account registration pages (eg. "/account/register").
 pub mod register;
+/// Password reset pages (eg. "/account/reset").
+pub mod reset;
 /// Various auth-related utility functions.
 pub mod utils;
@@ -1,4 +1,3 @@
-use std::num::NonZeroU32;
 use std::sync::Arc;
 
 use axum::extract::State;
@@ -7,11 +6,11 @@ use axum::response::Redirect;
 use axum::Form;
 use axum_extra::either::Either;
 use axum_extra::response::Html;
+use chrono::Utc;
 use diesel::dsl as sql;
 use diesel::prelude::*;
 use json::json;
 use ring::digest as hasher;
-use ring::pbkdf2;
 use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
@@ -19,9 +18,12 @@ use tower_sessions::Session;
 use crate::config::AppState;
 use crate::db::models::{NewAuthor, NewSalt};
 use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
 use crate::error::FrontendError;
+use crate::frontend::account::utils::{password_policy, validate_new_password};
 use crate::utils;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::auth::passwd;
 use crate::utils::response::common;
 
 const REGISTER_FLASH: &'static str = "register.flash";
@@ -72,6 +74,7 @@ pub(crate) async fn get(
         "gitlab_enabled": gitlab_enabled,
         "has_separator": has_separator,
         "none_enabled": none_enabled,
+        "password_policy": password_policy(state.as_ref()),
     });
 
     let rendered = engine.render("account/register", &context)?;
@@ -118,13 +121,14 @@ pub(crate) async fn post(
         return Ok(Either::E2(Redirect::to("/account/register")));
     }
 
-    //? Does the two passwords match (consistency check) ?
-    if form.password != form.confirm_password {
-        let message = String::from("the two passwords did not match.");
+    //? Is the password valid (matching confirmation, complying with the password policy, etc...) ?
+    let policy = state.password_policy();
+    if let Err(message) = validate_new_password(policy, &form.password, &form.confirm_password) {
         let flash_message = RegisterFlashMessage::Error { message };
         session.insert(REGISTER_FLASH, &flash_message)?;
         return Ok(Either::E2(Redirect::to("/account/register")));
     }
+    let hashed_password = passwd::prehash(form.email.as_str(), form.password.as_str());
 
     let state = Arc::clone(&state);
     let db = &state.db;
@@ -142,15 +146,6 @@ pub(crate) async fn post(
             return Ok(Either::E2(Redirect::to("/account/register")));
         }
 
-        dbg!(&form);
-        //? Decode hex-encoded password hash.
-        let Ok(decoded_password) = hex::decode(form.password.as_bytes()) else {
-            let message = String::from("password/salt decoding issue.");
-            let flash_message = RegisterFlashMessage::Error { message };
-            session.insert(REGISTER_FLASH, &flash_message)?;
-            return Ok(Either::E2(Redirect::to("/account/register")));
-        };
-
         //? Generate the user's authentication salt.
         let decoded_generated_salt = {
             let mut data = [0u8; 16];
@@ -159,27 +154,21 @@ pub(crate) async fn post(
             hasher::digest(&hasher::SHA512, data.as_ref())
         };
 
-        //? Derive the hashed password data with PBKDF2 (100_000 rounds).
-        let encoded_derived_hash = {
-            let mut out = [0u8; hasher::SHA512_OUTPUT_LEN];
-            let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
-            pbkdf2::derive(
-                pbkdf2::PBKDF2_HMAC_SHA512,
-                iteration_count,
-                decoded_generated_salt.as_ref(),
-                decoded_password.as_slice(),
-                &mut out,
-            );
-            hex::encode(out.as_ref())
-        };
+        //? Hash the password (using Argon2id).
+        let encoded_derived_hash = state
+            .general
+            .password_hasher
+            .hash(hashed_password.as_ref())?;
 
         //? Insert the new author data.
+        let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
         let new_author = NewAuthor {
             email: form.email.as_str(),
             name: form.name.as_str(),
             passwd: Some(encoded_derived_hash.as_str()),
             github_id: None,
             gitlab_id: None,
+            created_at: Some(created_at.as_str()),
         };
         diesel::insert_into(authors::table)
             .values(new_author)
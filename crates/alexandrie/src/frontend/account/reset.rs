@@ -0,0 +1,332 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use axum::Form;
+use axum_extra::either::Either;
+use axum_extra::response::Html;
+use chrono::Utc;
+use diesel::prelude::*;
+use json::json;
+use ring::digest as hasher;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+
+use crate::config::AppState;
+use crate::db::models::NewPasswordReset;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::{Error, FrontendError};
+use crate::frontend::account::login::{LoginFlashMessage, LOGIN_FLASH};
+use crate::frontend::account::utils::{password_policy, validate_new_password};
+use crate::utils;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::auth::passwd;
+
+const RESET_FLASH: &'static str = "reset.flash";
+
+/// How long a password reset link remains valid (in minutes).
+const RESET_TOKEN_LIFETIME: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum ResetFlashMessage {
+    Error { message: String },
+    Success { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ForgotPasswordForm {
+    pub email: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ResetPasswordForm {
+    pub password: String,
+    pub confirm_password: String,
+}
+
+/// Returns whether password resets are available (local authentication enabled and emails configured).
+pub(crate) fn is_enabled(state: &AppState) -> bool {
+    state.frontend.config.auth.local.enabled && state.mail.is_some()
+}
+
+/// Hashes a password reset token, for it to be stored or looked up in the database.
+fn hash_token(token: &str) -> String {
+    hex::encode(hasher::digest(&hasher::SHA512, token.as_bytes()).as_ref())
+}
+
+fn now() -> String {
+    Utc::now().naive_utc().format(DATETIME_FORMAT).to_string()
+}
+
+fn disabled_response(state: &AppState) -> Result<(StatusCode, Html<String>), FrontendError> {
+    let rendered = utils::response::error_html(
+        state,
+        None,
+        "password resets are not available on this instance",
+    )?;
+    Ok((StatusCode::BAD_REQUEST, Html(rendered)))
+}
+
+fn invalid_token_response(state: &AppState) -> Result<(StatusCode, Html<String>), FrontendError> {
+    let rendered = utils::response::error_html(
+        state,
+        None,
+        "this password reset link is invalid or has expired",
+    )?;
+    Ok((StatusCode::BAD_REQUEST, Html(rendered)))
+}
+
+/// Route to display the "forgot password" form.
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    maybe_author: Option<Auth>,
+    session: Session,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    if maybe_author.is_some() {
+        return Ok(Either::E2(Redirect::to("/account/manage")));
+    }
+
+    if !is_enabled(state.as_ref()) {
+        return disabled_response(state.as_ref()).map(Either::E1);
+    }
+
+    let flash_message: Option<ResetFlashMessage> = session.remove(RESET_FLASH)?;
+
+    let engine = &state.frontend.handlebars;
+    let context = json!({
+        "instance": &state.frontend.config,
+        "flash": flash_message,
+    });
+
+    let rendered = engine.render("account/forgot", &context)?;
+    Ok(Either::E1((StatusCode::OK, Html(rendered))))
+}
+
+/// Route to request a password reset link by email.
+pub(crate) async fn post(
+    State(state): State<Arc<AppState>>,
+    maybe_author: Option<Auth>,
+    session: Session,
+    Form(form): Form<ForgotPasswordForm>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    if maybe_author.is_some() {
+        return Ok(Either::E2(Redirect::to("/account/manage")));
+    }
+
+    if !is_enabled(state.as_ref()) {
+        return disabled_response(state.as_ref()).map(Either::E1);
+    }
+
+    //? Are all fields filled-in?
+    if form.email.is_empty() {
+        let message = String::from("some fields were left empty.");
+        let flash_message = ResetFlashMessage::Error { message };
+        session.insert(RESET_FLASH, &flash_message)?;
+        return Ok(Either::E2(Redirect::to("/account/reset")));
+    }
+
+    let email = form.email.clone();
+    let maybe_reset = state
+        .db
+        .transaction(move |conn| {
+            //? Does the user exist?
+            let maybe_author: Option<(i64, String)> = authors::table
+                .select((authors::id, authors::name))
+                .filter(authors::email.eq(email.as_str()))
+                .first(conn)
+                .optional()?;
+            let Some((author_id, name)) = maybe_author else {
+                return Ok(None);
+            };
+
+            //? Generate the reset token.
+            let token = {
+                let mut data = [0u8; 32];
+                let rng = SystemRandom::new();
+                rng.fill(&mut data).unwrap();
+                hex::encode(data.as_ref())
+            };
+
+            //? Only the latest reset token of an author is kept.
+            diesel::delete(password_resets::table.filter(password_resets::author_id.eq(author_id)))
+                .execute(conn)?;
+
+            //? Store the token's hash (along with its expiry date).
+            let expires_at = (Utc::now() + chrono::Duration::minutes(RESET_TOKEN_LIFETIME))
+                .naive_utc()
+                .format(DATETIME_FORMAT)
+                .to_string();
+            let token_hash = hash_token(token.as_str());
+            let new_reset = NewPasswordReset {
+                token: token_hash.as_str(),
+                author_id,
+                expires_at: expires_at.as_str(),
+            };
+            diesel::insert_into(password_resets::table)
+                .values(new_reset)
+                .execute(conn)?;
+
+            Ok::<_, Error>(Some((name, token)))
+        })
+        .await?;
+
+    if let Some((name, token)) = maybe_reset {
+        let config = &state.frontend.config;
+        let title = config.title.as_deref().unwrap_or("Alexandrie");
+        let origin = config.auth.origin.trim_end_matches('/');
+        let subject = format!("Password reset for {title}");
+        let context = json!({
+            "title": title,
+            "name": name,
+            "link": format!("{origin}/account/reset/{token}"),
+            "lifetime": RESET_TOKEN_LIFETIME,
+        });
+
+        //? The email is sent in the background and its outcome is not disclosed to the user,
+        //? so that the response does not reveal (nor take longer for) which emails are registered.
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let Some(mailer) = state.mail.as_ref() else {
+                return;
+            };
+            let engine = &state.frontend.handlebars;
+            let outcome = mailer
+                .send_template(
+                    engine,
+                    form.email.as_str(),
+                    &subject,
+                    "emails/password-reset",
+                    &context,
+                )
+                .await;
+            if let Err(error) = outcome {
+                tracing::error!("could not send password reset email: {error}");
+            }
+        });
+    }
+
+    let message = String::from(
+        "if an account exists for this email, a password reset link has been sent to it.",
+    );
+    let flash_message = ResetFlashMessage::Success { message };
+    session.insert(RESET_FLASH, &flash_message)?;
+    Ok(Either::E2(Redirect::to("/account/reset")))
+}
+
+/// Route to display the new password form (from a password reset link).
+pub(crate) async fn get_token(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    session: Session,
+) -> Result<(StatusCode, Html<String>), FrontendError> {
+    if !is_enabled(state.as_ref()) {
+        return disabled_response(state.as_ref());
+    }
+
+    let token_hash = hash_token(token.as_str());
+    let now = now();
+    let maybe_email: Option<String> = state
+        .db
+        .run(move |conn| {
+            password_resets::table
+                .inner_join(authors::table)
+                .select(authors::email)
+                .filter(password_resets::token.eq(token_hash))
+                .filter(password_resets::expires_at.gt(now))
+                .first(conn)
+                .optional()
+        })
+        .await?;
+
+    //? Is the token valid?
+    let Some(email) = maybe_email else {
+        return invalid_token_response(state.as_ref());
+    };
+
+    let flash_message: Option<ResetFlashMessage> = session.remove(RESET_FLASH)?;
+
+    let engine = &state.frontend.handlebars;
+    let context = json!({
+        "instance": &state.frontend.config,
+        "flash": flash_message,
+        "email": email,
+        "token": token,
+        "password_policy": password_policy(state.as_ref()),
+    });
+
+    let rendered = engine.render("account/reset", &context)?;
+    Ok((StatusCode::OK, Html(rendered)))
+}
+
+/// Route to set a new password (from a password reset link).
+pub(crate) async fn post_token(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    session: Session,
+    Form(form): Form<ResetPasswordForm>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    if !is_enabled(state.as_ref()) {
+        return disabled_response(state.as_ref()).map(Either::E1);
+    }
+
+    //? Is the new password valid (same rules as for registration)?
+    let policy = state.password_policy();
+    if let Err(message) = validate_new_password(policy, &form.password, &form.confirm_password) {
+        let flash_message = ResetFlashMessage::Error { message };
+        session.insert(RESET_FLASH, &flash_message)?;
+        return Ok(Either::E2(Redirect::to(&format!("/account/reset/{token}"))));
+    }
+
+    let token_hash = hash_token(token.as_str());
+    let now = now();
+    let hasher = state.general.password_hasher.clone();
+    let was_reset = state
+        .db
+        .transaction(move |conn| {
+            //? Is the token valid?
+            let maybe_author: Option<(i64, String)> = password_resets::table
+                .inner_join(authors::table)
+                .select((authors::id, authors::email))
+                .filter(password_resets::token.eq(token_hash))
+                .filter(password_resets::expires_at.gt(now))
+                .first(conn)
+                .optional()?;
+            let Some((author_id, email)) = maybe_author else {
+                return Ok(false);
+            };
+
+            //? Reset tokens are single-use.
+            diesel::delete(password_resets::table.filter(password_resets::author_id.eq(author_id)))
+                .execute(conn)?;
+
+            //? Hash the new password (pre-hashed like the login form does, then using Argon2id).
+            let hashed_password = passwd::prehash(email.as_str(), form.password.as_str());
+            let encoded_derived_hash = hasher.hash(hashed_password.as_ref())?;
+
+            diesel::update(authors::table.find(author_id))
+                .set(authors::passwd.eq(encoded_derived_hash.as_str()))
+                .execute(conn)?;
+
+            //? Log the account out of every existing session.
+            diesel::delete(sessions::table.filter(sessions::author_id.eq(author_id)))
+                .execute(conn)?;
+
+            Ok::<_, Error>(true)
+        })
+        .await?;
+
+    if !was_reset {
+        return invalid_token_response(state.as_ref()).map(Either::E1);
+    }
+
+    let message = String::from("your password has been reset, you can now log in with it.");
+    let flash_message = LoginFlashMessage::Success { message };
+    session.insert(LOGIN_FLASH, &flash_message)?;
+    Ok(Either::E2(Redirect::to("/account/login")))
+}
@@ -1,3 +1,7 @@
+use json::json;
+
+use crate::config::password_policy::PasswordPolicyConfig;
+use crate::config::AppState;
 use crate::db::models::Author;
 
 /// Returns the number of authentication methods available for this crate author.
@@ -18,3 +22,59 @@ pub fn count_auth_methods(author: &Author) -> usize {
 
     count
 }
+
+/// Checks a new password (along with its confirmation) submitted through a form.
+///
+/// New passwords are submitted as-is (unlike the ones of the login form, which are hashed client-side),
+/// so this checks that both are filled-in, that they match and that the password complies with the password policy.  
+/// On failure, the returned message is meant to be displayed to the user.
+pub fn validate_new_password(
+    policy: &PasswordPolicyConfig,
+    password: &str,
+    confirm_password: &str,
+) -> Result<(), String> {
+    //? Are all fields filled-in?
+    if password.is_empty() || confirm_password.is_empty() {
+        return Err(String::from("some fields were left empty."));
+    }
+
+    //? Does the two passwords match (consistency check)?
+    if password != confirm_password {
+        return Err(String::from("the two passwords did not match."));
+    }
+
+    //? Does the password comply with the password policy?
+    policy
+        .check(password)
+        .map_err(|violation| violation.to_string())
+}
+
+/// Serializes the instance's password policy (as JSON), for the account forms to check new passwords against it.
+///
+/// The forms check it client-side (see `assets/js/password-policy.js`), before it gets enforced server-side.
+pub fn password_policy(state: &AppState) -> String {
+    let policy = state.password_policy();
+    let policy = json!({
+        "min_length": policy.min_length,
+        "require_lowercase": policy.require_lowercase,
+        "require_uppercase": policy.require_uppercase,
+        "require_digit": policy.require_digit,
+        "require_symbol": policy.require_symbol,
+        "reject_common": policy.reject_common && cfg!(feature = "common-passwords"),
+    });
+    policy.to_string()
+}
+
+/// Route to get the list of common passwords rejected by the password policy.
+#[cfg(feature = "common-passwords")]
+pub(crate) async fn common_passwords() -> impl axum::response::IntoResponse {
+    use axum::http::header;
+
+    use crate::config::password_policy::COMMON_PASSWORDS;
+
+    let headers = [
+        (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+        (header::CACHE_CONTROL, "public, max-age=86400"),
+    ];
+    (headers, COMMON_PASSWORDS)
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::headers::{HeaderMapExt, IfNoneMatch};
+use axum::http::{header, HeaderValue, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::TypedHeader;
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use ring::digest as hasher;
+
+use crate::config::frontend::AssetsConfig;
+use crate::config::AppState;
+use crate::utils::response::conditional;
+
+/// The fingerprints of the frontend assets (truncated SHA-256 digests of their contents),
+/// computed once at startup (unless in development mode).
+///
+/// Fingerprinted asset URLs (like `/assets/icons.css?v=<fingerprint>`) can then be cached indefinitely by clients,
+/// since any change to an asset changes its URL.
+#[derive(Debug, Clone, Default)]
+pub struct AssetManifest {
+    /// The fingerprints, keyed by the assets' paths (relative to the assets directory).
+    fingerprints: HashMap<String, String>,
+    /// Whether fingerprinting and caching are disabled (for local iteration on the assets).
+    dev_mode: bool,
+    /// How long clients can cache fingerprinted assets (in seconds).
+    max_age: u64,
+}
+
+/// Recursively computes the fingerprints of the files of a directory.
+fn fingerprint_files(root: &Path, dir: &Path, found: &mut HashMap<String, String>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            tracing::warn!("couldn't read '{0}' (skipped): {error}", dir.display());
+            return;
+        }
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            fingerprint_files(root, &path, found);
+            continue;
+        }
+        let Ok(contents) = fs::read(&path) else {
+            continue;
+        };
+        let Some(relative) = path.strip_prefix(root).ok().and_then(|it| it.to_str()) else {
+            continue;
+        };
+        let digest = hex::encode(hasher::digest(&hasher::SHA256, &contents).as_ref());
+        found.insert(relative.replace('\\', "/"), String::from(&digest[..16]));
+    }
+}
+
+impl From<&AssetsConfig> for AssetManifest {
+    fn from(config: &AssetsConfig) -> Self {
+        let mut fingerprints = HashMap::new();
+        if !config.dev_mode {
+            fingerprint_files(&config.path, &config.path, &mut fingerprints);
+        }
+        Self {
+            fingerprints,
+            dev_mode: config.dev_mode,
+            max_age: config.max_age,
+        }
+    }
+}
+
+impl AssetManifest {
+    /// Returns the fingerprint of an asset (if known).
+    pub fn fingerprint(&self, path: &str) -> Option<&str> {
+        self.fingerprints
+            .get(path.trim_start_matches('/'))
+            .map(String::as_str)
+    }
+
+    /// Returns the URL of an asset (fingerprinted, unless it is unknown or in development mode).
+    pub fn url(&self, path: &str) -> String {
+        let path = path.trim_start_matches('/');
+        match self.fingerprint(path) {
+            Some(fingerprint) => format!("/assets/{path}?v={fingerprint}"),
+            None => format!("/assets/{path}"),
+        }
+    }
+}
+
+/// The `asset` Handlebars helper, rendering the (fingerprinted) URL of an asset (like `{{asset "icons.css"}}`).
+pub struct AssetHelper(pub Arc<AssetManifest>);
+
+impl HelperDef for AssetHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let path = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or_else(|| RenderError::new("asset: missing path parameter"))?;
+        out.write(self.0.url(path).as_str())?;
+        Ok(())
+    }
+}
+
+/// Middleware adding the caching headers of the assets, and answering conditional requests.
+///
+/// Fingerprinted URLs are cached indefinitely (as immutable), other ones are revalidated using the assets' entity tags.
+/// In development mode, assets are never cached.
+pub(crate) async fn cache<B>(
+    State(state): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let manifest = state.frontend.assets.as_ref();
+    if manifest.dev_mode {
+        let mut response = next.run(request).await;
+        let value = HeaderValue::from_static("no-store");
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+        return response;
+    }
+
+    //? Is this a known asset (and is it requested through its fingerprinted URL)?
+    let Some(fingerprint) = manifest.fingerprint(request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let etag = conditional::etag(fingerprint);
+    let version = format!("v={fingerprint}");
+    let fingerprinted = request
+        .uri()
+        .query()
+        .map_or(false, |query| query.split('&').any(|pair| pair == version));
+    let cache_control = if fingerprinted {
+        format!("public, max-age={0}, immutable", manifest.max_age)
+    } else {
+        String::from("no-cache")
+    };
+    let cache_control =
+        HeaderValue::from_str(cache_control.as_str()).expect("invalid cache-control header");
+
+    //? Is the client's cached copy still up-to-date?
+    let if_none_match = request.headers().typed_get::<IfNoneMatch>();
+    if conditional::is_not_modified(if_none_match.as_ref(), None, &etag, None) {
+        let headers = [(header::CACHE_CONTROL, cache_control)];
+        return (StatusCode::NOT_MODIFIED, TypedHeader(etag), headers).into_response();
+    }
+
+    let mut response = next.run(request).await;
+    if response.status().is_success() {
+        response.headers_mut().typed_insert(etag);
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, cache_control);
+    }
+    response
+}
@@ -0,0 +1,210 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::either::Either;
+use axum_extra::response::Html;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use json::json;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::Indexer;
+
+use crate::config::AppState;
+use crate::db::models::Crate;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::{Error, FrontendError};
+use crate::frontend::helpers;
+use crate::utils;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::owners::OwnerRole;
+use crate::utils::pagination::Page;
+use crate::utils::visibility::{self, CrateVisibility};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    pub page: Option<NonZeroUsize>,
+    pub per_page: Option<NonZeroUsize>,
+}
+
+/// Route to show the profile of an author (their name, avatar, join date and the crates they own or collaborate on).
+///
+/// Email addresses are never shown (avatars are looked up by their hash).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(author_id): Path<i64>,
+    Query(params): Query<QueryParams>,
+    user: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let per_page = match params.per_page {
+        Some(_) => format!("&per_page={0}", page.per_page),
+        None => String::new(),
+    };
+
+    if state.is_login_required() && user.is_none() {
+        return Ok(Either::E2(Redirect::to("/account/login")));
+    }
+
+    let db = &state.db;
+    let state = Arc::clone(&state);
+
+    let transaction = db.transaction(move |conn| {
+        //? Does this author exist?
+        let maybe_author: Option<(String, String, Option<String>)> = authors::table
+            .find(author_id)
+            .select((authors::name, authors::email, authors::created_at))
+            .first(conn)
+            .optional()?;
+        let Some((name, email, created_at)) = maybe_author else {
+            let rendered = utils::response::error_html(
+                state.as_ref(),
+                user.map(|it| it.into_inner()),
+                "No author with this ID has been found.",
+            )?;
+            return Ok(Either::E1((StatusCode::NOT_FOUND, Html(rendered))));
+        };
+
+        //? Private crates are only listed if they are accessible to the user.
+        let viewer_id = user.as_ref().map(|user| user.id);
+        let accessible = visibility::accessible_private_crates(conn, viewer_id)?;
+        let is_visible = crates::visibility
+            .eq(CrateVisibility::Public.as_str())
+            .or(crates::id.eq_any(accessible));
+
+        //? The crates this author owns are paginated, the ones they are a collaborator of are all listed.
+        let owned = crate_authors::table
+            .select(crate_authors::crate_id)
+            .filter(crate_authors::author_id.eq(author_id))
+            .filter(crate_authors::role.eq(OwnerRole::Owner.as_str()));
+        let contributed = crate_authors::table
+            .select(crate_authors::crate_id)
+            .filter(crate_authors::author_id.eq(author_id))
+            .filter(crate_authors::role.ne(OwnerRole::Owner.as_str()));
+
+        //? Get the total count of owned crates.
+        let total_results: i64 = crates::table
+            .select(sql::count(crates::id))
+            .filter(crates::id.eq_any(owned.clone()))
+            .filter(is_visible.clone())
+            .first(conn)?;
+
+        //? Get the total number of downloads of the owned crates.
+        let total_downloads = crates::table
+            .select(sql::sum(crates::downloads))
+            .filter(crates::id.eq_any(owned.clone()))
+            .filter(is_visible.clone())
+            .first::<Option<BigDecimal>>(conn)?
+            .map_or(0, |dec| {
+                dec.to_u64()
+                    .expect("download count exceeding u64::max_value()")
+            });
+
+        //? Get the crates this author collaborates on.
+        let contributed: Vec<String> = crates::table
+            .select(crates::name)
+            .filter(crates::id.eq_any(contributed))
+            .filter(is_visible.clone())
+            .order_by(crates::name.asc())
+            .load(conn)?;
+
+        //? Get the owned crates for the given page number.
+        let results: Vec<Crate> = crates::table
+            .filter(crates::id.eq_any(owned))
+            .filter(is_visible)
+            .order_by(crates::name.asc())
+            .limit(page.per_page as i64)
+            .offset(page.db_offset())
+            .load(conn)?;
+
+        let joined_at = created_at.and_then(|date| {
+            chrono::NaiveDateTime::parse_from_str(date.as_str(), DATETIME_FORMAT).ok()
+        });
+
+        let total_results = total_results as usize;
+        let pagination = helpers::pagination(&page, total_results, |number| {
+            format!("/authors/{author_id}?page={number}{per_page}")
+        });
+
+        let auth = &state.frontend.config.auth;
+        let engine = &state.frontend.handlebars;
+        let context = json!({
+            "auth_disabled": !auth.enabled(),
+            "registration_disabled": !auth.allow_registration(),
+            "user": user.map(|it| it.into_inner()),
+            "instance": &state.frontend.config,
+            "author": {
+                "id": author_id,
+                "name": name,
+                "avatar": helpers::avatar_url(&state.frontend.config.avatars, email.as_str()),
+                "joined_at": joined_at.map(helpers::humanize_datetime),
+            },
+            "total_downloads": helpers::humanize_number(total_downloads),
+            "contributed": contributed,
+            "total_results": total_results,
+            "pagination": pagination,
+            "results": results.into_iter().map(|krate| {
+                let record = state.index.latest_record(&krate.name)?;
+                let updated_at =
+                    chrono::NaiveDateTime::parse_from_str(krate.updated_at.as_str(), DATETIME_FORMAT)
+                        .unwrap();
+                Ok(json!({
+                    "name": krate.name,
+                    "version": record.vers,
+                    "description": krate.description,
+                    "updated_at": helpers::humanize_datetime(updated_at),
+                    "downloads": helpers::humanize_number(krate.downloads),
+                    "yanked": record.yanked,
+                }))
+            }).collect::<Result<Vec<_>, Error>>()?,
+        });
+
+        let rendered = engine.render("author", &context)?;
+        Ok(Either::E1((StatusCode::OK, Html(rendered))))
+    });
+
+    transaction.await
+}
+
+/// Route to find an author by their name, redirecting to their profile (eg. "/users/\<name\>").
+///
+/// Author names are not unique, so ambiguous names are reported as such.
+pub(crate) async fn by_name(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    user: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Response>, FrontendError> {
+    if state.is_login_required() && user.is_none() {
+        return Ok(Either::E2(Redirect::to("/account/login").into_response()));
+    }
+
+    let db = &state.db;
+    let state = Arc::clone(&state);
+
+    db.run(move |conn| {
+        //? Find the authors with this name (more than one means that it is ambiguous).
+        let found: Vec<i64> = authors::table
+            .select(authors::id)
+            .filter(authors::name.eq(name.as_str()))
+            .limit(2)
+            .load(conn)?;
+
+        let message = match found.as_slice() {
+            [author_id] => {
+                let location = format!("/authors/{author_id}");
+                return Ok(Either::E2(Redirect::to(location.as_str()).into_response()));
+            }
+            [] => format!("No author named '{name}' has been found."),
+            _ => format!("Several authors are named '{name}', see the pages of their crates for links to their profiles."),
+        };
+        let rendered =
+            utils::response::error_html(state.as_ref(), user.map(|it| it.into_inner()), message)?;
+        Ok::<_, FrontendError>(Either::E1((StatusCode::NOT_FOUND, Html(rendered))))
+    })
+    .await
+}
@@ -0,0 +1,125 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use axum_extra::either::Either;
+use axum_extra::response::Html;
+use diesel::prelude::*;
+use json::json;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::FrontendError;
+use crate::frontend::helpers;
+use crate::utils;
+use crate::utils::audit;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::pagination::Page;
+use crate::utils::visibility;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    pub page: Option<NonZeroUsize>,
+    pub per_page: Option<NonZeroUsize>,
+}
+
+/// Route to display the events of a crate (publications, yanks, un-yanks and ownership changes), for its owners.
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(crate_name): Path<String>,
+    Query(params): Query<QueryParams>,
+    maybe_author: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Either::E2(Redirect::to("/account/login")));
+    };
+
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let per_page = match params.per_page {
+        Some(_) => format!("&per_page={0}", page.per_page),
+        None => String::new(),
+    };
+
+    let canon_name = utils::canonical_name(crate_name);
+    let db = &state.db;
+    let state = Arc::clone(&state);
+
+    let transaction = db.transaction(move |conn| {
+        //? Does this crate exists (and is it accessible to the user)?
+        let maybe_crate: Option<(i64, String)> = crates::table
+            .select((crates::id, crates::name))
+            .filter(crates::canon_name.eq(canon_name.as_str()))
+            .first(conn)
+            .optional()?;
+        let maybe_crate = match maybe_crate {
+            Some((crate_id, _)) if !visibility::can_access(conn, crate_id, Some(author.id))? => {
+                None
+            }
+            maybe_crate => maybe_crate,
+        };
+        let Some((crate_id, name)) = maybe_crate else {
+            let rendered = utils::response::error_html(
+                state.as_ref(),
+                Some(author),
+                format!("No crate named '{0}' has been found.", canon_name),
+            )?;
+            return Ok(Either::E1((StatusCode::NOT_FOUND, Html(rendered))));
+        };
+
+        //? Is the user an owner of this crate?
+        if !utils::checks::is_crate_owner(conn, canon_name.as_str(), author.id)? {
+            let rendered = utils::response::error_html(
+                state.as_ref(),
+                Some(author),
+                "Only the owners of a crate can see its events.",
+            )?;
+            return Ok(Either::E1((StatusCode::FORBIDDEN, Html(rendered))));
+        }
+
+        //? Get the requested page of the crate's events.
+        let total = audit::count_crate_events(conn, crate_id)? as usize;
+        let events = audit::crate_events(conn, crate_id, page.per_page, page.offset())?;
+        let events: Vec<_> = events
+            .into_iter()
+            .map(|event| {
+                let created_at =
+                    chrono::NaiveDateTime::parse_from_str(&event.created_at, DATETIME_FORMAT)
+                        .map(helpers::humanize_datetime)
+                        .unwrap_or(event.created_at);
+                json!({
+                    "action": event.action,
+                    "version": event.version,
+                    "reason": event.reason,
+                    "author": event.author,
+                    "created_at": created_at,
+                })
+            })
+            .collect();
+
+        let pagination = helpers::pagination(&page, total, |number| {
+            format!("/crates/{name}/events?page={number}{per_page}")
+        });
+
+        let auth = &state.frontend.config.auth;
+        let engine = &state.frontend.handlebars;
+        let context = json!({
+            "auth_disabled": !auth.enabled(),
+            "registration_disabled": !auth.allow_registration(),
+            "user": author,
+            "instance": &state.frontend.config,
+            "crate_name": name,
+            "total_events": total,
+            "events": events,
+            "pagination": pagination,
+        });
+        let rendered = engine.render("crate-events", &context)?;
+
+        Ok::<_, FrontendError>(Either::E1((StatusCode::OK, Html(rendered))))
+    });
+
+    transaction.await
+}
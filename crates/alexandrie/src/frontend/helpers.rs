@@ -3,6 +3,10 @@ use std::str::FromStr;
 use chrono::{NaiveDate, NaiveDateTime};
 use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError};
 use num_format::{Locale, ToFormattedString};
+use ring::digest;
+
+use crate::config::frontend::avatars::AvatarsConfig;
+use crate::utils::pagination::Page;
 
 #[allow(unused)]
 pub(crate) fn hbs_humanize_datetime(
@@ -77,6 +81,30 @@ pub fn humanize_number(num: impl ToFormattedString) -> String {
     num.to_formatted_string(&Locale::en)
 }
 
+/// Returns the avatar URL of an author (if avatars are enabled), from the hash of their email address.
+///
+/// Only the hash ends up in the URL, the email address itself is never exposed.
+pub fn avatar_url(config: &AvatarsConfig, email: &str) -> Option<String> {
+    if !config.enabled {
+        return None;
+    }
+    let email = email.trim().to_lowercase();
+    let hash = hex::encode(digest::digest(&digest::SHA256, email.as_bytes()));
+    let url = (config.url.replace("{hash}", hash.as_str()))
+        .replace("{size}", config.size.to_string().as_str());
+    Some(url)
+}
+
+/// Builds the pagination controls of a listing page (the URLs of the other pages being built by `url`).
+pub fn pagination(page: &Page, total: usize, url: impl Fn(usize) -> String) -> json::Value {
+    json::json!({
+        "current": page.number,
+        "total_count": page.count(total),
+        "next": page.next(total).map(&url),
+        "prev": page.prev().map(&url),
+    })
+}
+
 #[allow(unused)]
 pub(crate) fn hbs_equal(
     h: &Helper,
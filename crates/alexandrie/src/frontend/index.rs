@@ -9,12 +9,15 @@ use diesel::dsl as sql;
 use diesel::prelude::*;
 use json::json;
 
+use crate::config::frontend::front_page::{SectionConfig, SectionKind};
 use crate::config::AppState;
 use crate::db::schema::*;
+use crate::db::Connection;
 use crate::db::DATETIME_FORMAT;
 use crate::error::FrontendError;
 use crate::frontend::helpers;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::visibility::{self, CrateVisibility};
 
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
@@ -28,9 +31,7 @@ pub(crate) async fn get(
     let state = Arc::clone(&state);
     let transaction = db.transaction(move |conn| {
         //? Get total number of crates.
-        let crate_count: i64 = crates::table
-            .select(sql::count(crates::id))
-            .first(conn)?;
+        let crate_count: i64 = crates::table.select(sql::count(crates::id)).first(conn)?;
 
         //? Get total number of crate downloads.
         let total_downloads = crates::table
@@ -41,19 +42,17 @@ pub(crate) async fn get(
                     .expect("download count exceeding u64::max_value()")
             });
 
-        //? Get the 10 most downloaded crates.
-        let most_downloaded: Vec<(String, i64)> = crates::table
-            .select((crates::name, crates::downloads))
-            .order_by(crates::downloads.desc())
-            .limit(10)
-            .load(conn)?;
-
-        //? Get the 10 most recently updated crates.
-        let last_updated: Vec<(String, String)> = crates::table
-            .select((crates::name, crates::updated_at))
-            .order_by(crates::updated_at.desc())
-            .limit(10)
-            .load(conn)?;
+        //? Get the crate lists of the configured sections (private crates are only listed if accessible to the user).
+        let author_id = user.as_ref().map(|user| user.id);
+        let accessible = visibility::accessible_private_crates(conn, author_id)?;
+        let sections = state
+            .frontend
+            .config
+            .front_page
+            .sections
+            .iter()
+            .map(|section| render_section(&state, conn, section, accessible.as_slice()))
+            .collect::<Result<Vec<_>, FrontendError>>()?;
 
         let auth = &state.frontend.config.auth;
         let engine = &state.frontend.handlebars;
@@ -64,17 +63,7 @@ pub(crate) async fn get(
             "instance": &state.frontend.config,
             "total_downloads": helpers::humanize_number(total_downloads),
             "crate_count": helpers::humanize_number(crate_count),
-            "most_downloaded": most_downloaded.into_iter().map(|(name, downloads)| json!({
-                "name": name,
-                "downloads": helpers::humanize_number(downloads),
-            })).collect::<Vec<_>>(),
-            "last_updated": last_updated.into_iter().map(|(name, date)| {
-                let updated_at = chrono::NaiveDateTime::parse_from_str(date.as_str(), DATETIME_FORMAT).unwrap();
-                json!({
-                    "name": name,
-                    "updated_at": helpers::humanize_datetime(updated_at),
-                })
-            }).collect::<Vec<_>>(),
+            "sections": sections,
         });
 
         let rendered = engine.render("index", &context)?;
@@ -84,3 +73,84 @@ pub(crate) async fn get(
 
     transaction.await
 }
+
+/// Fetches the crates listed by a front page section, and renders them as a template context.
+fn render_section(
+    state: &AppState,
+    conn: &mut Connection,
+    section: &SectionConfig,
+    accessible: &[i64],
+) -> Result<json::Value, FrontendError> {
+    let size = section.size as i64;
+    let is_visible = crates::visibility
+        .eq(CrateVisibility::Public.as_str())
+        .or(crates::id.eq_any(accessible));
+    let format_date = |date: String| {
+        let date = chrono::NaiveDateTime::parse_from_str(date.as_str(), DATETIME_FORMAT).unwrap();
+        helpers::humanize_datetime(date)
+    };
+
+    let (title, more, entries) = match section.kind {
+        SectionKind::MostDownloaded => {
+            let crates: Vec<(String, i64)> = crates::table
+                .select((crates::name, crates::downloads))
+                .filter(is_visible)
+                .order_by(crates::downloads.desc())
+                .limit(size)
+                .load(conn)?;
+            let entries = crates
+                .into_iter()
+                .map(|(name, downloads)| {
+                    let detail = format!("{} downloads", helpers::humanize_number(downloads));
+                    json!({ "name": name, "detail": detail })
+                })
+                .collect::<Vec<_>>();
+            ("Most downloaded", Some("/most-downloaded"), entries)
+        }
+        SectionKind::RecentlyUpdated => {
+            let crates: Vec<(String, String)> = crates::table
+                .select((crates::name, crates::updated_at))
+                .filter(is_visible)
+                .order_by(crates::updated_at.desc())
+                .limit(size)
+                .load(conn)?;
+            let entries = crates
+                .into_iter()
+                .map(|(name, date)| json!({ "name": name, "detail": format_date(date) }))
+                .collect::<Vec<_>>();
+            ("Last updated", Some("/last-updated"), entries)
+        }
+        SectionKind::RecentlyPublished => {
+            let crates: Vec<(String, String)> = crates::table
+                .select((crates::name, crates::created_at))
+                .filter(is_visible)
+                .order_by(crates::created_at.desc())
+                .limit(size)
+                .load(conn)?;
+            let entries = crates
+                .into_iter()
+                .map(|(name, date)| json!({ "name": name, "detail": format_date(date) }))
+                .collect::<Vec<_>>();
+            ("Recently published", None, entries)
+        }
+        SectionKind::Trending => {
+            //? Trending crates are computed periodically (see `frontend::trending`), not per request.
+            let trending = state.frontend.trending.read().unwrap();
+            let entries = trending
+                .iter()
+                .take(section.size)
+                .map(|krate| {
+                    let detail = format!("+{} downloads", helpers::humanize_number(krate.growth));
+                    json!({ "name": krate.name, "detail": detail })
+                })
+                .collect::<Vec<_>>();
+            ("Trending", None, entries)
+        }
+    };
+
+    Ok(json!({
+        "title": title,
+        "more": more,
+        "entries": entries,
+    }))
+}
@@ -1,25 +1,32 @@
 use std::sync::Arc;
 
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
-use axum::response::Redirect;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Redirect, Response};
 use axum_extra::either::Either;
 use axum_extra::response::Html;
 use diesel::prelude::*;
 use json::json;
+use semver::Version;
 use serde::{Deserialize, Serialize};
 
-use alexandrie_index::Indexer;
+use alexandrie_index::{CrateDependency, CrateDependencyKind, CrateVersion, Indexer};
 use alexandrie_storage::Store;
 
 use crate::config::AppState;
 use crate::db::models::{Badge, Crate, CrateAuthor, CrateCategory, CrateKeyword, Keyword};
 use crate::db::schema::*;
-use crate::db::DATETIME_FORMAT;
+use crate::db::{Connection, DATETIME_FORMAT};
 use crate::error::FrontendError;
 use crate::frontend::helpers;
 use crate::utils;
+use crate::utils::aliases;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::claims;
+use crate::utils::owners::OwnerRole;
+use crate::utils::subscriptions;
+use crate::utils::versions::{self, TimelineEntry};
+use crate::utils::visibility::{self, CrateVisibility};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct BadgeRepr {
@@ -28,28 +35,236 @@ struct BadgeRepr {
     href: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct QueryParams {
+    /// The version to generate the `Cargo.toml` dependency snippet for.
+    pub version: Option<Version>,
+    /// Whether to also list yanked versions (defaults to the `show_yanked_versions` frontend option).
+    #[serde(default)]
+    pub yanked: Option<bool>,
+    /// Whether to also list pre-release versions (defaults to the `show_prerelease_versions` frontend option).
+    #[serde(default)]
+    pub prereleases: Option<bool>,
+    /// Whether to resolve the dependencies (from this registry) to concrete versions.
+    #[serde(default)]
+    pub tree: bool,
+    /// Whether to bypass the crate pages cache (for debugging purposes).
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+impl QueryParams {
+    /// Returns the key identifying the variant of the crate page requested by these parameters (in the crate pages cache).
+    fn cache_variant(&self) -> String {
+        let version = self.version.as_ref().map(Version::to_string);
+        format!(
+            "version={0}&yanked={1:?}&prereleases={2:?}&tree={3}",
+            version.unwrap_or_default(),
+            self.yanked,
+            self.prereleases,
+            self.tree,
+        )
+    }
+}
+
+/// Generates the `Cargo.toml` dependency snippets for a given crate version,
+/// along with a variant enabling its non-default features (if it has any).
+///
+/// If the registry's name is known, the snippets declare that the crate comes from this registry.
+fn dependency_snippets(record: &CrateVersion, registry: Option<&str>) -> (String, Option<String>) {
+    let name = record.name.as_str();
+    let version = &record.vers;
+    let registry = registry
+        .map(|registry| format!(", registry = \"{registry}\""))
+        .unwrap_or_default();
+    let snippet = match registry.as_str() {
+        "" => format!("{name} = \"{version}\""),
+        registry => format!("{name} = {{ version = \"{version}\"{registry} }}"),
+    };
+
+    let all_features = record.all_features();
+    let default_features = all_features.get("default").cloned().unwrap_or_default();
+    let mut extra_features: Vec<&str> = all_features
+        .keys()
+        .filter(|feature| *feature != "default" && !default_features.contains(feature))
+        .map(String::as_str)
+        .collect();
+    extra_features.sort_unstable();
+
+    let with_features = (!extra_features.is_empty()).then(|| {
+        let features = extra_features
+            .iter()
+            .map(|feature| format!("\"{feature}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{name} = {{ version = \"{version}\"{registry}, features = [{features}] }}")
+    });
+
+    (snippet, with_features)
+}
+
+/// Generates the `cargo add` command adding a given crate version as a dependency
+/// (from this registry, if its name is known).
+fn install_command(record: &CrateVersion, registry: Option<&str>) -> String {
+    let name = record.name.as_str();
+    let version = &record.vers;
+    match registry {
+        Some(registry) => format!("cargo add {name}@{version} --registry {registry}"),
+        None => format!("cargo add {name}@{version}"),
+    }
+}
+
+/// Lists the dependencies of a crate version, grouped by kind (normal, build and dev dependencies).
+///
+/// Dependencies from this registry are linked to their crate pages and, if `resolve` is set,
+/// are resolved to the latest version matching their requirement (only one level deep, to keep this cheap).
+fn dependency_groups(
+    state: &AppState,
+    conn: &mut Connection,
+    record: &CrateVersion,
+    hidden: &[String],
+    resolve: bool,
+) -> Result<Vec<json::Value>, FrontendError> {
+    let crate_name =
+        |dep: &CrateDependency| String::from(dep.package.as_deref().unwrap_or(&dep.name));
+
+    //? Which of the dependencies are crates of this registry (accessible to the user)?
+    let names: Vec<String> = record
+        .deps
+        .iter()
+        .filter(|dep| dep.registry.is_none())
+        .map(|dep| utils::canonical_name(crate_name(dep)))
+        .collect();
+    let mut local: Vec<String> = crates::table
+        .select(crates::canon_name)
+        .filter(crates::canon_name.eq_any(&names))
+        .load(conn)?;
+    local.retain(|name| !hidden.contains(name));
+
+    let kinds = [
+        (CrateDependencyKind::Normal, "Dependencies"),
+        (CrateDependencyKind::Build, "Build dependencies"),
+        (CrateDependencyKind::Dev, "Dev dependencies"),
+    ];
+    let groups = kinds
+        .into_iter()
+        .filter_map(|(kind, title)| {
+            let mut deps: Vec<&CrateDependency> =
+                record.deps.iter().filter(|dep| dep.kind == kind).collect();
+            if deps.is_empty() {
+                return None;
+            }
+            deps.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let deps: Vec<_> = deps
+                .into_iter()
+                .map(|dep| {
+                    let name = crate_name(dep);
+                    let is_local = dep.registry.is_none()
+                        && local.contains(&utils::canonical_name(name.as_str()));
+                    let resolved = (resolve && is_local)
+                        .then(|| state.index.match_record(&name, dep.req.clone()).ok())
+                        .flatten()
+                        .map(|resolved| {
+                            let dependencies: Vec<&str> = resolved
+                                .deps
+                                .iter()
+                                .filter(|dep| dep.kind != CrateDependencyKind::Dev)
+                                .map(|dep| dep.package.as_deref().unwrap_or(&dep.name))
+                                .collect();
+                            json!({
+                                "version": resolved.vers,
+                                "dependencies": dependencies,
+                            })
+                        });
+                    json!({
+                        "name": name,
+                        "rename": dep.package.is_some().then_some(&dep.name),
+                        "req": dep.req.to_string(),
+                        "target": dep.target,
+                        "optional": dep.optional,
+                        "default_features": dep.default_features,
+                        "features": dep.features,
+                        "local": is_local,
+                        "resolved": resolved,
+                    })
+                })
+                .collect();
+
+            Some(json!({
+                "title": title,
+                "dependencies": deps,
+            }))
+        })
+        .collect();
+
+    Ok(groups)
+}
+
+/// Returns the documentation URL of a crate, falling back to the configured documentation host (if any).
+fn documentation_url(state: &AppState, krate: &Crate, version: &Version) -> Option<String> {
+    krate.documentation.clone().or_else(|| {
+        let docs_host = state.frontend.config.docs_host.as_deref()?;
+        let docs_host = docs_host.trim_end_matches('/');
+        Some(format!("{docs_host}/{0}/{version}", krate.name))
+    })
+}
+
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Path(crate_name): Path<String>,
+    Query(params): Query<QueryParams>,
     user: Option<Auth>,
-) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+) -> Result<Either<(StatusCode, Html<String>), Response>, FrontendError> {
     let canon_name = utils::canonical_name(crate_name);
 
     if state.is_login_required() && user.is_none() {
-        return Ok(Either::E2(Redirect::to("/account/login")));
+        return Ok(Either::E2(Redirect::to("/account/login").into_response()));
+    }
+
+    //? Only the pages rendered for anonymous visitors are cached (as the others show user-specific information).
+    let cache = state
+        .page_cache
+        .as_ref()
+        .filter(|_| user.is_none() && !params.no_cache);
+    let cache_variant = params.cache_variant();
+    if let Some(cache) = cache {
+        if let Some(rendered) = cache.get(canon_name.as_str(), cache_variant.as_str()) {
+            return Ok(Either::E1((StatusCode::OK, Html(rendered))));
+        }
     }
+    let cacheable = cache.is_some();
 
     let db = &state.db;
     let state = Arc::clone(&state);
 
     let transaction = db.transaction(move |conn| {
         //? Get this crate's data.
-        let maybe_crate_desc: Option<Crate> = crates::table
-            .filter(crates::canon_name.eq(canon_name.as_str()))
-            .first(conn)
-            .optional()?;
+        let maybe_crate_desc: Option<Crate> = state.db.timed("crate.lookup", || {
+            crates::table
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first(conn)
+                .optional()
+        })?;
+
+        //? Private crates are only shown to their owners and collaborators.
+        let author_id = user.as_ref().map(|user| user.id);
+        let maybe_crate_desc = match maybe_crate_desc {
+            Some(krate) if !visibility::can_access(conn, krate.id, author_id)? => None,
+            maybe_crate_desc => maybe_crate_desc,
+        };
 
         let Some(crate_desc) = maybe_crate_desc else {
+            //? Is this the alias of a crate (like its former name), accessible to the user?
+            let target = match aliases::resolve(conn, canon_name.as_str())? {
+                Some((id, name)) if visibility::can_access(conn, id, author_id)? => Some(name),
+                _ => None,
+            };
+            if let Some(name) = target {
+                let location = format!("/crates/{name}");
+                let response = (StatusCode::MOVED_PERMANENTLY, [(header::LOCATION, location)]);
+                return Ok(Either::E2(response.into_response()));
+            }
             let rendered = utils::response::error_html(
                 state.as_ref(),
                 user.map(|it| it.into_inner()),
@@ -65,11 +280,23 @@ pub(crate) async fn get(
             .get_readme(&crate_desc.name, krate.vers.clone())
             .ok();
 
-        //? Get the authors' names of this crate.
-        let authors: Vec<String> = CrateAuthor::belonging_to(&crate_desc)
+        //? Get the owners of this crate (read-only collaborators are not listed), with their avatars (if enabled).
+        let authors: Vec<(i64, String, String)> = CrateAuthor::belonging_to(&crate_desc)
             .inner_join(authors::table)
-            .select(authors::name)
+            .select((authors::id, authors::name, authors::email))
+            .filter(crate_authors::role.eq(OwnerRole::Owner.as_str()))
             .load(conn)?;
+        let avatars = &state.frontend.config.avatars;
+        let authors: Vec<_> = authors
+            .into_iter()
+            .map(|(id, name, email)| {
+                json!({
+                    "id": id,
+                    "name": name,
+                    "avatar": helpers::avatar_url(avatars, email.as_str()),
+                })
+            })
+            .collect();
 
         //? Get the keywords for this crate.
         let keywords: Vec<Keyword> = CrateKeyword::belonging_to(&crate_desc)
@@ -327,6 +554,143 @@ pub(crate) async fn get(
             })
             .collect();
 
+        //? Get the features of this crate (the default ones being highlighted).
+        let all_features = krate.all_features();
+        let default_features = all_features.get("default").cloned().unwrap_or_default();
+        let mut features: Vec<_> = all_features
+            .into_iter()
+            .filter(|(name, _)| name != "default")
+            .map(|(name, enables)| {
+                json!({
+                    "default": default_features.contains(&name),
+                    "name": name,
+                    "enables": enables,
+                })
+            })
+            .collect();
+        features.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        //? Get the names of the crates depending on this crate (hiding the inaccessible private ones).
+        let hidden = visibility::hidden_crates(conn, author_id)?;
+        let mut dependents = Vec::new();
+        let mut dev_dependents = Vec::new();
+        for dependent in state.rdeps.dependents(canon_name.as_str(), false)? {
+            if hidden.contains(&utils::canonical_name(dependent.name.as_str())) {
+                continue;
+            }
+            let names = match dependent.kind {
+                CrateDependencyKind::Dev => &mut dev_dependents,
+                _ => &mut dependents,
+            };
+            if !names.contains(&dependent.name) {
+                names.push(dependent.name);
+            }
+        }
+
+        //? Get the versions to generate the dependency snippet for (yanked ones are hidden, unless requested or configured otherwise).
+        //? The selected version is always listed (even if yanked), so that yanked versions remain reachable.
+        let show_yanked = params
+            .yanked
+            .unwrap_or(state.frontend.config.show_yanked_versions);
+        //? Pre-releases are listed, unless requested or configured otherwise (the selected version still always is).
+        let show_prereleases = params
+            .prereleases
+            .unwrap_or(state.frontend.config.show_prerelease_versions);
+        let mut records = state.index.all_records(&crate_desc.name)?;
+        records.sort_by(|a, b| b.vers.cmp(&a.vers));
+
+        //? Get the number of available (non-yanked) versions, from its rollup (which is computed once, if still unknown).
+        let version_count = match crate_desc.non_yanked_versions {
+            Some(count) => count,
+            None => {
+                let count = versions::count_available(&records);
+                versions::store_available(conn, crate_desc.id, count)?;
+                count
+            }
+        };
+        let selected = params
+            .version
+            .as_ref()
+            .and_then(|version| records.iter().find(|record| record.vers == *version))
+            .unwrap_or(&krate);
+        let is_listed = |version: &Version, yanked: bool| {
+            let is_prerelease = !version.pre.is_empty();
+            ((show_yanked || !yanked) && (show_prereleases || !is_prerelease))
+                || *version == selected.vers
+        };
+        let registry = state.frontend.config.registry_name.as_deref();
+        let (snippet, snippet_with_features) = dependency_snippets(selected, registry);
+        let install = install_command(selected, registry);
+
+        //? Get the dependencies of the selected version (resolved to concrete versions, if requested).
+        let dependencies = dependency_groups(&state, conn, selected, &hidden, params.tree)?;
+        let versions: Vec<_> = records
+            .iter()
+            .filter(|record| is_listed(&record.vers, record.yanked == Some(true)))
+            .map(|record| {
+                json!({
+                    "version": record.vers,
+                    "yanked": record.yanked == Some(true),
+                    "selected": record.vers == selected.vers,
+                })
+            })
+            .collect();
+
+        //? Get the version timeline of this crate (when each version got published).
+        let timeline = state.db.timed("crate.versions", || {
+            utils::versions::timeline(conn, crate_desc.id, &records)
+        })?;
+        let count_hidden = |is_hidden: fn(&TimelineEntry) -> bool| {
+            let others = timeline.iter().filter(|entry| entry.version != selected.vers);
+            others.filter(|entry| is_hidden(entry)).count()
+        };
+        let hidden_yanked = match show_yanked {
+            true => 0,
+            false => count_hidden(|entry| entry.yanked),
+        };
+        let hidden_prereleases = match show_prereleases {
+            true => 0,
+            false => count_hidden(|entry| !entry.version.pre.is_empty()),
+        };
+        let timeline: Vec<_> = timeline
+            .into_iter()
+            .filter(|entry| is_listed(&entry.version, entry.yanked))
+            .map(|entry| {
+                let published_at = entry.published_at.and_then(|date| {
+                    chrono::NaiveDateTime::parse_from_str(date.as_str(), DATETIME_FORMAT).ok()
+                });
+                json!({
+                    "version": entry.version,
+                    "published_at": published_at.map(helpers::humanize_datetime),
+                    "yanked": entry.yanked,
+                })
+            })
+            .collect();
+
+        //? Get where the latest version was published from.
+        let provenance = utils::provenance::latest(conn, crate_desc.id)?;
+
+        //? Get the license of the displayed version.
+        let license = utils::licenses::get(conn, crate_desc.id, krate.vers.to_string().as_str())?;
+
+        //? Is the user subscribed to this crate (if publish notifications are enabled)?
+        let subscription = match user.as_ref() {
+            Some(user) if subscriptions::is_enabled(state.as_ref()) => {
+                let subscribed = subscriptions::is_subscribed(conn, crate_desc.id, user.id)?;
+                Some(json!({ "subscribed": subscribed }))
+            }
+            _ => None,
+        };
+
+        //? Does this crate have no owners (like if it got imported without them, read-only collaborators do not count)?
+        let unowned = claims::is_unowned(conn, crate_desc.id)?;
+
+        //? Is the user an owner of this crate (to link to the crate's events)?
+        let is_owner = match user.as_ref() {
+            Some(user) => utils::checks::is_crate_owner(conn, canon_name.as_str(), user.id)?,
+            None => false,
+        };
+
         let created_at =
             chrono::NaiveDateTime::parse_from_str(crate_desc.created_at.as_str(), DATETIME_FORMAT)
                 .unwrap();
@@ -347,22 +711,118 @@ pub(crate) async fn get(
                 "version": krate.vers,
                 "description": crate_desc.description,
                 "downloads": helpers::humanize_number(crate_desc.downloads),
+                "version_count": helpers::humanize_number(version_count),
                 "created_at": helpers::humanize_datetime(created_at),
                 "updated_at": helpers::humanize_datetime(updated_at),
-                "documentation": crate_desc.documentation,
+                "documentation": documentation_url(state.as_ref(), &crate_desc, &krate.vers),
                 "repository": crate_desc.repository,
                 "yanked": krate.yanked,
+                "private": CrateVisibility::parse(crate_desc.visibility.as_str()) == CrateVisibility::Private,
             },
             "badges": badges,
             "authors": authors,
             "rendered_readme": rendered_readme,
             "keywords": keywords,
             "categories": categories,
+            "dependents": dependents,
+            "dev_dependents": dev_dependents,
+            "features": features,
+            "provenance": provenance,
+            "license": license,
+            "subscription": subscription,
+            "is_owner": is_owner,
+            "unowned": unowned,
+            "timeline": {
+                "entries": timeline,
+                "hidden_yanked": hidden_yanked,
+                "hidden_prereleases": hidden_prereleases,
+            },
+            "dependencies": {
+                "groups": dependencies,
+                "version": selected.vers,
+                "show_tree": params.tree,
+                "show_yanked": show_yanked,
+                "show_prereleases": show_prereleases,
+            },
+            "snippet": {
+                "versions": versions,
+                "show_yanked": show_yanked,
+                "show_prereleases": show_prereleases,
+                "dependency": snippet,
+                "with_features": snippet_with_features,
+                "install": install,
+                "registry": registry,
+            },
         });
 
         let rendered = engine.render("crate", &context)?;
+
+        //? Cache the rendered page (private crates are never cached, so that they can't be served to unauthorized users).
+        let private = CrateVisibility::parse(crate_desc.visibility.as_str()) == CrateVisibility::Private;
+        if let Some(cache) = state.page_cache.as_ref().filter(|_| cacheable && !private) {
+            cache.put(crate_desc.name.as_str(), cache_variant, rendered.clone());
+        }
+
         Ok::<_, FrontendError>(Either::E1((StatusCode::OK, Html(rendered))))
     });
 
     transaction.await
 }
+
+/// Route to redirect to the documentation of the latest version of a crate.
+pub(crate) async fn docs(
+    State(state): State<Arc<AppState>>,
+    Path(crate_name): Path<String>,
+    user: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Response>, FrontendError> {
+    let canon_name = utils::canonical_name(crate_name);
+
+    if state.is_login_required() && user.is_none() {
+        return Ok(Either::E2(Redirect::to("/account/login").into_response()));
+    }
+
+    let db = &state.db;
+    let state = Arc::clone(&state);
+
+    let transaction = db.transaction(move |conn| {
+        //? Get this crate's data.
+        let maybe_crate_desc: Option<Crate> = crates::table
+            .filter(crates::canon_name.eq(canon_name.as_str()))
+            .first(conn)
+            .optional()?;
+
+        //? Private crates are only shown to their owners and collaborators.
+        let author_id = user.as_ref().map(|user| user.id);
+        let maybe_crate_desc = match maybe_crate_desc {
+            Some(krate) if !visibility::can_access(conn, krate.id, author_id)? => None,
+            maybe_crate_desc => maybe_crate_desc,
+        };
+
+        let Some(crate_desc) = maybe_crate_desc else {
+            let rendered = utils::response::error_html(
+                state.as_ref(),
+                user.map(|it| it.into_inner()),
+                format!("No crate named '{0}' has been found.", canon_name),
+            )?;
+            return Ok(Either::E1((StatusCode::NOT_FOUND, Html(rendered))));
+        };
+        let krate = state.index.latest_record(&crate_desc.name)?;
+
+        match documentation_url(state.as_ref(), &crate_desc, &krate.vers) {
+            Some(url) => {
+                let response = (StatusCode::FOUND, [(header::LOCATION, url)]);
+                Ok(Either::E2(response.into_response()))
+            }
+            None => {
+                let rendered = utils::response::error_html(
+                    state.as_ref(),
+                    user.map(|it| it.into_inner()),
+                    format!("No documentation is available for '{0}'.", crate_desc.name),
+                )?;
+                Ok(Either::E1((StatusCode::NOT_FOUND, Html(rendered))))
+            }
+        }
+    });
+
+    transaction.await
+}
@@ -1,7 +1,8 @@
-use std::num::NonZeroU32;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use axum::extract::{Query, State};
+use axum::http::StatusCode;
 use axum::response::Redirect;
 use axum_extra::either::Either;
 use axum_extra::response::Html;
@@ -15,73 +16,208 @@ use alexandrie_index::Indexer;
 use crate::config::AppState;
 use crate::db::models::Crate;
 use crate::db::schema::*;
-use crate::db::DATETIME_FORMAT;
+use crate::db::{Connection, DATETIME_FORMAT};
 use crate::error::{Error, FrontendError};
 use crate::frontend::helpers;
+use crate::utils;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::pagination::Page;
+use crate::utils::visibility::{self, CrateVisibility};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct QueryParams {
-    pub page: Option<NonZeroU32>,
+    pub page: Option<NonZeroUsize>,
+    pub per_page: Option<NonZeroUsize>,
+    /// Only list the crate versions published at or after this date (as an RFC 3339 timestamp).
+    pub since: Option<String>,
+    /// Only list the crate versions published at or before this date (as an RFC 3339 timestamp).
+    pub until: Option<String>,
+}
+
+/// Parses an RFC 3339 timestamp (from the `since` or `until` query parameters) into the format of the stored dates.
+fn parse_timestamp(name: &str, value: Option<&str>) -> Result<Option<String>, String> {
+    let Some(value) = value.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(date) => Ok(Some(date.naive_utc().format(DATETIME_FORMAT).to_string())),
+        Err(err) => Err(format!(
+            "Invalid `{name}` timestamp '{value}': {err} (expected an RFC 3339 timestamp, like `2023-06-01T12:00:00Z`)."
+        )),
+    }
+}
+
+/// Renders a listed crate (at the given version) as a template context.
+fn render_result(
+    state: &AppState,
+    conn: &mut Connection,
+    krate: Crate,
+    version: Option<&str>,
+    published_at: Option<&str>,
+) -> Result<json::Value, Error> {
+    let keywords: Vec<String> = crate_keywords::table
+        .inner_join(keywords::table)
+        .select(keywords::name)
+        .filter(crate_keywords::crate_id.eq(krate.id))
+        .load(conn)?;
+
+    let record = match version {
+        Some(version) => state
+            .index
+            .all_records(&krate.name)?
+            .into_iter()
+            .find(|record| record.vers.to_string() == version),
+        None => Some(state.index.latest_record(&krate.name)?),
+    };
+    let parse_date =
+        |date: &str| chrono::NaiveDateTime::parse_from_str(date, DATETIME_FORMAT).unwrap();
+    let created_at = parse_date(krate.created_at.as_str());
+    let updated_at = parse_date(krate.updated_at.as_str());
+
+    Ok(json!({
+        "id": krate.id,
+        "name": krate.name,
+        "version": version.map(String::from).or_else(|| record.as_ref().map(|record| record.vers.to_string())),
+        "description": krate.description,
+        "created_at": helpers::humanize_datetime(created_at),
+        "updated_at": helpers::humanize_datetime(updated_at),
+        "published_at": published_at.map(|date| helpers::humanize_datetime(parse_date(date))),
+        "downloads": helpers::humanize_number(krate.downloads),
+        "documentation": krate.documentation,
+        "repository": krate.repository,
+        "keywords": keywords,
+        "yanked": record.and_then(|record| record.yanked),
+    }))
 }
 
 pub(crate) async fn get(
     State(state): State<Arc<AppState>>,
     Query(params): Query<QueryParams>,
     user: Option<Auth>,
-) -> Result<Either<Html<String>, Redirect>, FrontendError> {
-    let page_number = params.page.map_or_else(|| 1, |page| page.get());
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let per_page = match params.per_page {
+        Some(_) => format!("&per_page={0}", page.per_page),
+        None => String::new(),
+    };
 
     if state.is_login_required() && user.is_none() {
         return Ok(Either::E2(Redirect::to("/account/login")));
     }
 
+    //? Are the publication time bounds valid RFC 3339 timestamps?
+    let bounds = parse_timestamp("since", params.since.as_deref()).and_then(|since| {
+        let until = parse_timestamp("until", params.until.as_deref())?;
+        match (&since, &until) {
+            (Some(since), Some(until)) if since > until => Err(String::from(
+                "The `since` timestamp must not be later than the `until` timestamp.",
+            )),
+            _ => Ok((since, until)),
+        }
+    });
+    let (since, until) = match bounds {
+        Ok(bounds) => bounds,
+        Err(message) => {
+            let user = user.map(|it| it.into_inner());
+            let rendered = utils::response::error_html(state.as_ref(), user, message)?;
+            return Ok(Either::E1((StatusCode::BAD_REQUEST, Html(rendered))));
+        }
+    };
+    let window = since.is_some() || until.is_some();
+
     let db = &state.db;
     let state = Arc::clone(&state);
 
     let transaction = db.transaction(move |conn| {
-        //? Get the total count of search results.
-        let total_results: i64 = crates::table
-            .select(sql::count(crates::id))
-            .first(conn)?;
-
-        //? Get the search results for the given page number.
-        let results: Vec<Crate> = crates::table
-            .order_by(crates::updated_at.desc())
-            .limit(15)
-            .offset(15 * i64::from(page_number - 1))
-            .load(conn)?;
-
-        let results: Vec<(Crate, Vec<String>)> = results
-            .into_iter()
-            .map(|result| {
-                let keywords = crate_keywords::table
-                    .inner_join(keywords::table)
-                    .select(keywords::name)
-                    .filter(crate_keywords::crate_id.eq(result.id))
-                    .load(conn)?;
-                Ok((result, keywords))
-            })
-            .collect::<Result<_, Error>>()?;
-
-        //? Make page number starts counting from 1 (instead of 0).
-        let page_count = (total_results / 15
-            + if total_results > 0 && total_results % 15 == 0 {
-                0
-            } else {
-                1
-            }) as u32;
-
-        let next_page = if page_number < page_count {
-            Some(format!("/last-updated?page={0}", page_number + 1))
+        //? Private crates are only listed if they are accessible to the user.
+        let author_id = user.as_ref().map(|user| user.id);
+        let accessible = visibility::accessible_private_crates(conn, author_id)?;
+        let is_visible = crates::visibility
+            .eq(CrateVisibility::Public.as_str())
+            .or(crates::id.eq_any(accessible));
+
+        let (total_results, results) = if window {
+            //? List the crate versions published within the time bounds (the stored dates being lexicographically ordered).
+            let since = since.clone().unwrap_or_default();
+            let until = until
+                .clone()
+                .unwrap_or_else(|| String::from("9999-12-31 23:59:59"));
+            let published = crate_versions::published_at
+                .ge(since)
+                .and(crate_versions::published_at.le(until));
+
+            let total_results: i64 = crate_versions::table
+                .inner_join(crates::table)
+                .select(sql::count(crate_versions::id))
+                .filter(is_visible.clone())
+                .filter(published.clone())
+                .first(conn)?;
+
+            let results: Vec<(Crate, String, String)> = crate_versions::table
+                .inner_join(crates::table)
+                .select((
+                    crates::all_columns,
+                    crate_versions::version,
+                    crate_versions::published_at,
+                ))
+                .filter(is_visible)
+                .filter(published)
+                .order_by((
+                    crate_versions::published_at.desc(),
+                    crate_versions::id.desc(),
+                ))
+                .limit(page.per_page as i64)
+                .offset(page.db_offset())
+                .load(conn)?;
+
+            let results = results
+                .into_iter()
+                .map(|(krate, version, published_at)| {
+                    let (version, published_at) =
+                        (Some(version.as_str()), Some(published_at.as_str()));
+                    render_result(&state, conn, krate, version, published_at)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            (total_results, results)
         } else {
-            None
+            //? Get the total count of search results.
+            let total_results: i64 = crates::table
+                .select(sql::count(crates::id))
+                .filter(is_visible.clone())
+                .first(conn)?;
+
+            //? Get the search results for the given page number.
+            let results: Vec<Crate> = crates::table
+                .filter(is_visible)
+                .order_by(crates::updated_at.desc())
+                .limit(page.per_page as i64)
+                .offset(page.db_offset())
+                .load(conn)?;
+
+            let results = results
+                .into_iter()
+                .map(|krate| render_result(&state, conn, krate, None, None))
+                .collect::<Result<Vec<_>, Error>>()?;
+            (total_results, results)
         };
-        let prev_page = if page_number > 1 {
-            Some(format!("/last-updated?page={0}", page_number - 1))
-        } else {
-            None
+
+        //? The time bounds are kept when navigating between pages.
+        let encode = |value: &str| {
+            percent_encoding::percent_encode(value.as_bytes(), percent_encoding::NON_ALPHANUMERIC)
+                .to_string()
         };
+        let mut bounds = String::new();
+        if let Some(since) = params.since.as_deref().filter(|_| since.is_some()) {
+            bounds.push_str(format!("&since={0}", encode(since)).as_str());
+        }
+        if let Some(until) = params.until.as_deref().filter(|_| until.is_some()) {
+            bounds.push_str(format!("&until={0}", encode(until)).as_str());
+        }
+
+        let total_results = total_results as usize;
+        let pagination = helpers::pagination(&page, total_results, |number| {
+            format!("/last-updated?page={number}{per_page}{bounds}")
+        });
 
         let auth = &state.frontend.config.auth;
         let engine = &state.frontend.handlebars;
@@ -91,39 +227,17 @@ pub(crate) async fn get(
             "user": user.map(|it| it.into_inner()),
             "instance": &state.frontend.config,
             "total_results": total_results,
-            "pagination": {
-                "current": page_number,
-                "total_count": page_count,
-                "next": next_page,
-                "prev": prev_page,
-            },
-            "results": results.into_iter().map(|(krate, keywords)| {
-                let record = state.index.latest_record(&krate.name)?;
-                let created_at =
-                    chrono::NaiveDateTime::parse_from_str(krate.created_at.as_str(), DATETIME_FORMAT)
-                        .unwrap();
-                let updated_at =
-                    chrono::NaiveDateTime::parse_from_str(krate.updated_at.as_str(), DATETIME_FORMAT)
-                        .unwrap();
-                Ok(json!({
-                    "id": krate.id,
-                    "name": krate.name,
-                    "version": record.vers,
-                    "description": krate.description,
-                    "created_at": helpers::humanize_datetime(created_at),
-                    "updated_at": helpers::humanize_datetime(updated_at),
-                    "downloads": helpers::humanize_number(krate.downloads),
-                    "documentation": krate.documentation,
-                    "repository": krate.repository,
-                    "keywords": keywords,
-                    "yanked": record.yanked,
-                }))
-            }).collect::<Result<Vec<_>, Error>>()?,
+            "window": window.then(|| json!({
+                "since": params.since.as_deref().filter(|_| since.is_some()),
+                "until": params.until.as_deref().filter(|_| until.is_some()),
+            })),
+            "pagination": pagination,
+            "results": results,
         });
 
         let rendered = engine.render("last-updated", &context)?;
 
-        Ok(Either::E1(Html(rendered)))
+        Ok(Either::E1((StatusCode::OK, Html(rendered))))
     });
 
     transaction.await
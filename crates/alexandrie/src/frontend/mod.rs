@@ -1,5 +1,11 @@
 /// Account-related routes (eg. "/account/login" or "/account/register").
 pub mod account;
+/// Frontend assets fingerprinting and caching (eg. "/assets/\<path\>").
+pub mod assets;
+/// Author profile pages, listing the crates they own or collaborate on (eg. "/authors/\<id\>" or "/users/\<name\>").
+pub mod authors;
+/// Crate events pages, for owners (eg. "/crates/\<name\>/events").
+pub mod events;
 /// Various helper functions (eg. human-readable (de)serialization).
 pub mod helpers;
 /// The index page (eg. "/").
@@ -12,5 +18,11 @@ pub mod last_updated;
 pub mod me;
 /// Most downloaded crates (eg. "/most-downloaded").
 pub mod most_downloaded;
+/// Publish notifications dispatching (batched emails to the subscribers of crates).
+pub mod notifications;
 /// Search pages (eg. "/search?q=\<term\>").
 pub mod search;
+/// Crate subscriptions (eg. "/crates/\<name\>/subscribe" or "/subscriptions/\<id\>/unsubscribe").
+pub mod subscriptions;
+/// Trending crates computation (from periodic download snapshots).
+pub mod trending;
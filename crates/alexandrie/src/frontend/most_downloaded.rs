@@ -1,4 +1,4 @@
-use std::num::NonZeroU32;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
 use axum::extract::{Query, State};
@@ -19,10 +19,13 @@ use crate::db::DATETIME_FORMAT;
 use crate::error::{Error, FrontendError};
 use crate::frontend::helpers;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::pagination::Page;
+use crate::utils::visibility::{self, CrateVisibility};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct QueryParams {
-    pub page: Option<NonZeroU32>,
+    pub page: Option<NonZeroUsize>,
+    pub per_page: Option<NonZeroUsize>,
 }
 
 pub(crate) async fn get(
@@ -30,7 +33,11 @@ pub(crate) async fn get(
     Query(params): Query<QueryParams>,
     user: Option<Auth>,
 ) -> Result<Either<Html<String>, Redirect>, FrontendError> {
-    let page_number = params.page.map_or_else(|| 1, |page| page.get());
+    let page = Page::new(&state.pagination, params.page, params.per_page);
+    let per_page = match params.per_page {
+        Some(_) => format!("&per_page={0}", page.per_page),
+        None => String::new(),
+    };
 
     if state.is_login_required() && user.is_none() {
         return Ok(Either::E2(Redirect::to("/account/login")));
@@ -40,16 +47,25 @@ pub(crate) async fn get(
     let state = Arc::clone(&state);
 
     let transaction = db.transaction(move |conn| {
+        //? Private crates are only listed if they are accessible to the user.
+        let author_id = user.as_ref().map(|user| user.id);
+        let accessible = visibility::accessible_private_crates(conn, author_id)?;
+        let is_visible = crates::visibility
+            .eq(CrateVisibility::Public.as_str())
+            .or(crates::id.eq_any(accessible));
+
         //? Get the total count of search results.
         let total_results: i64 = crates::table
             .select(sql::count(crates::id))
+            .filter(is_visible.clone())
             .first(conn)?;
 
         //? Get the search results for the given page number.
         let results: Vec<Crate> = crates::table
+            .filter(is_visible)
             .order_by(crates::downloads.desc())
-            .limit(15)
-            .offset(15 * i64::from(page_number - 1))
+            .limit(page.per_page as i64)
+            .offset(page.db_offset())
             .load(conn)?;
 
         let results: Vec<(Crate, Vec<String>)> = results
@@ -64,24 +80,10 @@ pub(crate) async fn get(
             })
             .collect::<Result<_, Error>>()?;
 
-        //? Make page number starts counting from 1 (instead of 0).
-        let page_count = (total_results / 15
-            + if total_results > 0 && total_results % 15 == 0 {
-                0
-            } else {
-                1
-            }) as u32;
-
-        let next_page = if page_number < page_count {
-            Some(format!("/most-downloaded?page={0}", page_number + 1))
-        } else {
-            None
-        };
-        let prev_page = if page_number > 1 {
-            Some(format!("/most-downloaded?page={0}", page_number - 1))
-        } else {
-            None
-        };
+        let total_results = total_results as usize;
+        let pagination = helpers::pagination(&page, total_results, |number| {
+            format!("/most-downloaded?page={number}{per_page}")
+        });
 
         let auth = &state.frontend.config.auth;
         let engine = &state.frontend.handlebars;
@@ -91,12 +93,7 @@ pub(crate) async fn get(
             "user": user.map(|it| it.into_inner()),
             "instance": &state.frontend.config,
             "total_results": total_results,
-            "pagination": {
-                "current": page_number,
-                "total_count": page_count,
-                "next": next_page,
-                "prev": prev_page,
-            },
+            "pagination": pagination,
             "results": results.into_iter().map(|(krate, keywords)| {
                 let record = state.index.latest_record(&krate.name)?;
                 let created_at =
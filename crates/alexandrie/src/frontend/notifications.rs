@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use json::json;
+use ring::hmac;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::Error;
+use crate::utils::subscriptions;
+use crate::utils::visibility;
+
+/// The batch of notifications to send to an author.
+#[derive(Debug, Clone, Default)]
+struct Batch {
+    /// The author's name.
+    name: String,
+    /// The author's email.
+    email: String,
+    /// The date at which the author was last notified (if ever).
+    last_notified_at: Option<String>,
+    /// The published versions, by crate name (along with the related subscription's ID).
+    crates: BTreeMap<String, (i64, Vec<String>)>,
+    /// The IDs of the pending notifications covered by this batch.
+    notification_ids: Vec<i64>,
+}
+
+/// Spawns the task periodically sending the pending publish notifications (if notifications are enabled).
+pub fn spawn(state: Arc<AppState>) {
+    let Some(config) = state.notifications.as_ref() else {
+        return;
+    };
+    if !subscriptions::is_enabled(state.as_ref()) {
+        tracing::warn!("publish notifications are configured (`[notifications]` section), but no email transport is configured (`[mail]` section), notifications are disabled");
+        return;
+    }
+    let period = Duration::from_secs(config.interval.max(1) * 60);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(error) = dispatch(&state).await {
+                tracing::error!("could not send the publish notifications: {error}");
+            }
+        }
+    });
+}
+
+/// Returns the key used to sign unsubscribe tokens (derived from the sessions' secret).
+fn signing_key(state: &AppState) -> hmac::Key {
+    let secret = state.frontend.config.sessions.secret.as_bytes();
+    hmac::Key::new(hmac::HMAC_SHA256, secret)
+}
+
+/// Returns the signed token allowing to unsubscribe from a subscription without logging in (as sent in emails).
+pub fn unsubscribe_token(state: &AppState, subscription_id: i64) -> String {
+    let message = format!("unsubscribe:{subscription_id}");
+    let tag = hmac::sign(&signing_key(state), message.as_bytes());
+    hex::encode(tag.as_ref())
+}
+
+/// Returns whether an unsubscribe token is valid for the given subscription.
+pub fn verify_unsubscribe_token(state: &AppState, subscription_id: i64, token: &str) -> bool {
+    let Ok(tag) = hex::decode(token) else {
+        return false;
+    };
+    let message = format!("unsubscribe:{subscription_id}");
+    hmac::verify(&signing_key(state), message.as_bytes(), tag.as_slice()).is_ok()
+}
+
+/// Sends the pending publish notifications, batched into a single email per author.
+///
+/// Authors which were notified too recently (according to the configured minimum interval) are skipped,
+/// their notifications remain pending until the next run.
+pub async fn dispatch(state: &Arc<AppState>) -> Result<(), Error> {
+    let (Some(config), Some(mailer)) = (state.notifications.as_ref(), state.mail.as_ref()) else {
+        return Ok(());
+    };
+
+    let now = Utc::now().naive_utc();
+    let threshold = (now - chrono::Duration::minutes(config.min_interval as i64))
+        .format(DATETIME_FORMAT)
+        .to_string();
+    let now = now.format(DATETIME_FORMAT).to_string();
+
+    //? Get every pending notification (along with the subscribed author and the related crate).
+    let pending = state
+        .db
+        .run(|conn| {
+            let pending: Vec<(
+                i64,
+                i64,
+                String,
+                i64,
+                String,
+                String,
+                Option<String>,
+                i64,
+                String,
+            )> = pending_notifications::table
+                .inner_join(
+                    crate_subscriptions::table
+                        .inner_join(authors::table)
+                        .inner_join(crates::table),
+                )
+                .select((
+                    pending_notifications::id,
+                    pending_notifications::subscription_id,
+                    pending_notifications::version,
+                    authors::id,
+                    authors::name,
+                    authors::email,
+                    crate_subscriptions::last_notified_at,
+                    crates::id,
+                    crates::name,
+                ))
+                .order_by(pending_notifications::id.asc())
+                .load(conn)?;
+
+            //? Drop the notifications about crates that their subscriber cannot access anymore (like if it became private).
+            let mut accessible = Vec::with_capacity(pending.len());
+            let mut dropped = Vec::new();
+            for notification in pending {
+                let (id, author_id, crate_id) = (notification.0, notification.3, notification.7);
+                if visibility::can_access(conn, crate_id, Some(author_id))? {
+                    accessible.push(notification);
+                } else {
+                    dropped.push(id);
+                }
+            }
+            if !dropped.is_empty() {
+                diesel::delete(
+                    pending_notifications::table.filter(pending_notifications::id.eq_any(dropped)),
+                )
+                .execute(conn)?;
+            }
+
+            Ok::<_, Error>(accessible)
+        })
+        .await?;
+
+    //? Group them by author.
+    let mut batches: BTreeMap<i64, Batch> = BTreeMap::new();
+    for (id, subscription_id, version, author_id, name, email, last_notified_at, _, crate_name) in
+        pending
+    {
+        let batch = batches.entry(author_id).or_default();
+        batch.name = name;
+        batch.email = email;
+        batch.last_notified_at = batch.last_notified_at.take().max(last_notified_at);
+        let (_, versions) = batch
+            .crates
+            .entry(crate_name)
+            .or_insert_with(|| (subscription_id, Vec::new()));
+        if !versions.contains(&version) {
+            versions.push(version);
+        }
+        batch.notification_ids.push(id);
+    }
+
+    let config = &state.frontend.config;
+    let title = config.title.as_deref().unwrap_or("Alexandrie");
+    let origin = config.auth.origin.trim_end_matches('/');
+    let engine = &state.frontend.handlebars;
+    for (author_id, batch) in batches {
+        //? Was this author notified too recently?
+        let rate_limited = batch
+            .last_notified_at
+            .as_deref()
+            .map_or(false, |last_notified_at| {
+                last_notified_at > threshold.as_str()
+            });
+        if rate_limited {
+            continue;
+        }
+
+        let crates: Vec<_> = batch
+            .crates
+            .iter()
+            .map(|(name, (subscription_id, versions))| {
+                let token = unsubscribe_token(state.as_ref(), *subscription_id);
+                json!({
+                    "name": name,
+                    "versions": versions,
+                    "link": format!("{origin}/crates/{name}"),
+                    "unsubscribe": format!("{origin}/subscriptions/{subscription_id}/unsubscribe?token={token}"),
+                })
+            })
+            .collect();
+        let subject = match batch.crates.keys().next() {
+            Some(name) if batch.crates.len() == 1 => format!("New version of {name} on {title}"),
+            _ => format!("New versions of crates you follow on {title}"),
+        };
+        let context = json!({
+            "title": title,
+            "name": batch.name,
+            "crates": crates,
+        });
+
+        let outcome = mailer
+            .send_template(
+                engine,
+                batch.email.as_str(),
+                &subject,
+                "emails/crates-published",
+                &context,
+            )
+            .await;
+        if let Err(error) = outcome {
+            //? The notifications remain pending, to be retried on the next run.
+            tracing::error!(
+                "could not send publish notifications to author '{author_id}': {error}"
+            );
+            continue;
+        }
+
+        //? Mark the notifications as sent.
+        let now = now.clone();
+        let notification_ids = batch.notification_ids;
+        state
+            .db
+            .transaction(move |conn| {
+                diesel::delete(
+                    pending_notifications::table
+                        .filter(pending_notifications::id.eq_any(notification_ids)),
+                )
+                .execute(conn)?;
+                diesel::update(
+                    crate_subscriptions::table.filter(crate_subscriptions::author_id.eq(author_id)),
+                )
+                .set(crate_subscriptions::last_notified_at.eq(now.as_str()))
+                .execute(conn)?;
+                Ok::<_, Error>(())
+            })
+            .await?;
+    }
+
+    Ok(())
+}
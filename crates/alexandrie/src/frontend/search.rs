@@ -18,12 +18,16 @@ use crate::db::schema::*;
 use crate::db::DATETIME_FORMAT;
 use crate::error::{Error, FrontendError};
 use crate::frontend::helpers;
+use crate::fts::Highlights;
 use crate::utils::auth::frontend::Auth;
+use crate::utils::pagination::Page;
+use crate::utils::visibility::{self, CrateVisibility};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct QueryParams {
     pub q: String,
     pub page: Option<NonZeroUsize>,
+    pub per_page: Option<NonZeroUsize>,
 }
 
 /// Route to search through crates (used by `cargo search`) using tantivy index
@@ -33,43 +37,47 @@ pub(crate) async fn get(
     user: Option<Auth>,
 ) -> Result<Either<Html<String>, Redirect>, FrontendError> {
     let searched_text = params.q.clone();
-    let page_number = params.page.map_or_else(|| 1, |page| page.get());
-
-    let offset = (page_number - 1) * crate::fts::DEFAULT_RESULT_PER_PAGE;
+    let page = Page::new(&state.pagination, params.page, params.per_page);
 
     if state.is_login_required() && user.is_none() {
         return Ok(Either::E2(Redirect::to("/account/login")));
     }
 
-    let (count, results) = state.search.search(
-        searched_text.clone(),
-        offset,
-        crate::fts::DEFAULT_RESULT_PER_PAGE,
-    )?;
-
-    let page_count = count / crate::fts::DEFAULT_RESULT_PER_PAGE
-        + if count > 0 && count % crate::fts::DEFAULT_RESULT_PER_PAGE == 0 {
-            0
-        } else {
-            1
-        };
+    state.metrics.record_search();
+    let (count, results) =
+        state
+            .search
+            .search(searched_text.clone(), page.offset(), page.per_page)?;
 
     let repo = &state.db;
     let state = Arc::clone(&state);
 
     let transaction = repo.transaction(move |conn| {
-        let results: Vec<(Crate, Vec<String>)> = results
+        //? Private crates are only listed if they are accessible to the user.
+        let author_id = user.as_ref().map(|user| user.id);
+        let accessible = visibility::accessible_private_crates(conn, author_id)?;
+        let results: Vec<(Crate, Highlights)> = results
             .into_iter()
-            .map(|v| {
+            .map(|hit| {
                 let krate = crates::table
-                    .filter(crates::id.eq(v))
+                    .filter(crates::id.eq(hit.id))
                     .first::<Crate>(conn)?;
+                Ok((krate, hit.highlights))
+            })
+            .collect::<Result<_, diesel::result::Error>>()?;
+        let results: Vec<(Crate, Highlights, Vec<String>)> = results
+            .into_iter()
+            .filter(|(krate, _)| {
+                CrateVisibility::parse(krate.visibility.as_str()) == CrateVisibility::Public
+                    || accessible.contains(&krate.id)
+            })
+            .map(|(krate, highlights)| {
                 let keywords = crate_keywords::table
                     .inner_join(keywords::table)
                     .select(keywords::name)
                     .filter(crate_keywords::crate_id.eq(krate.id))
                     .load::<String>(conn)?;
-                Ok((krate, keywords))
+                Ok((krate, highlights, keywords))
             })
             .collect::<Result<_, Error>>()?;
 
@@ -77,24 +85,13 @@ pub(crate) async fn get(
             params.q.as_bytes(),
             percent_encoding::NON_ALPHANUMERIC,
         );
-        let next_page = if page_number < page_count {
-            Some(format!(
-                "/search?q={0}&page={1}",
-                encoded_q,
-                page_number + 1
-            ))
-        } else {
-            None
-        };
-        let prev_page = if page_number > 1 {
-            Some(format!(
-                "/search?q={0}&page={1}",
-                encoded_q,
-                page_number - 1
-            ))
-        } else {
-            None
+        let per_page = match params.per_page {
+            Some(_) => format!("&per_page={0}", page.per_page),
+            None => String::new(),
         };
+        let pagination = helpers::pagination(&page, count, |number| {
+            format!("/search?q={encoded_q}&page={number}{per_page}")
+        });
 
         let auth = &state.frontend.config.auth;
         let engine = &state.frontend.handlebars;
@@ -105,13 +102,8 @@ pub(crate) async fn get(
             "instance": &state.frontend.config,
             "searched_text": searched_text,
             "total_results": count,
-            "pagination": {
-                "current": page_number,
-                "total_count": page_count,
-                "next": next_page,
-                "prev": prev_page,
-            },
-            "results": results.into_iter().map(|(krate, keywords)| {
+            "pagination": pagination,
+            "results": results.into_iter().map(|(krate, highlights, keywords)| {
                 let record = state.index.latest_record(&krate.name)?;
                 let created_at =
                     chrono::NaiveDateTime::parse_from_str(krate.created_at.as_str(), DATETIME_FORMAT)
@@ -124,6 +116,7 @@ pub(crate) async fn get(
                     "name": krate.name,
                     "version": record.vers,
                     "description": krate.description,
+                    "highlights": highlights,
                     "created_at": helpers::humanize_datetime(created_at),
                     "updated_at": helpers::humanize_datetime(updated_at),
                     "downloads": helpers::humanize_number(krate.downloads),
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Redirect;
+use axum_extra::either::Either;
+use axum_extra::response::Html;
+use diesel::prelude::*;
+use json::json;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::{Error, FrontendError};
+use crate::frontend::notifications;
+use crate::utils;
+use crate::utils::auth::frontend::Auth;
+use crate::utils::subscriptions;
+use crate::utils::visibility;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct UnsubscribeParams {
+    /// The signed token (as sent in notification emails).
+    pub token: String,
+}
+
+/// Route to subscribe to the new versions of a crate.
+pub(crate) async fn subscribe(
+    State(state): State<Arc<AppState>>,
+    Path(crate_name): Path<String>,
+    maybe_author: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    update(state, crate_name, maybe_author, true).await
+}
+
+/// Route to unsubscribe from the new versions of a crate.
+pub(crate) async fn unsubscribe(
+    State(state): State<Arc<AppState>>,
+    Path(crate_name): Path<String>,
+    maybe_author: Option<Auth>,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    update(state, crate_name, maybe_author, false).await
+}
+
+async fn update(
+    state: Arc<AppState>,
+    crate_name: String,
+    maybe_author: Option<Auth>,
+    subscribe: bool,
+) -> Result<Either<(StatusCode, Html<String>), Redirect>, FrontendError> {
+    let Some(Auth(author)) = maybe_author else {
+        return Ok(Either::E2(Redirect::to("/account/login")));
+    };
+
+    if !subscriptions::is_enabled(state.as_ref()) {
+        let rendered = utils::response::error_html(
+            state.as_ref(),
+            Some(author),
+            "publish notifications are not available on this instance",
+        )?;
+        return Ok(Either::E1((StatusCode::BAD_REQUEST, Html(rendered))));
+    }
+
+    let canon_name = utils::canonical_name(crate_name.as_str());
+    let author_id = author.id;
+    let maybe_crate = state
+        .db
+        .transaction(move |conn| {
+            //? Does the crate exist (and is it visible to the user)?
+            let maybe_crate: Option<(i64, String)> = crates::table
+                .select((crates::id, crates::name))
+                .filter(crates::canon_name.eq(canon_name.as_str()))
+                .first(conn)
+                .optional()?;
+            let Some((crate_id, name)) = maybe_crate else {
+                return Ok(None);
+            };
+            if !visibility::can_access(conn, crate_id, Some(author_id))? {
+                return Ok(None);
+            }
+
+            if subscribe {
+                subscriptions::subscribe(conn, crate_id, author_id)?;
+            } else {
+                subscriptions::unsubscribe(conn, crate_id, author_id)?;
+            }
+
+            Ok::<_, Error>(Some(name))
+        })
+        .await?;
+
+    let Some(name) = maybe_crate else {
+        let rendered = utils::response::error_html(
+            state.as_ref(),
+            Some(author),
+            format!("No crate named '{0}' has been found.", crate_name),
+        )?;
+        return Ok(Either::E1((StatusCode::NOT_FOUND, Html(rendered))));
+    };
+
+    Ok(Either::E2(Redirect::to(&format!("/crates/{name}"))))
+}
+
+/// Returns the name of the crate followed through a subscription (if the subscription exists).
+async fn subscribed_crate(
+    state: &AppState,
+    subscription_id: i64,
+) -> Result<Option<String>, FrontendError> {
+    let crate_name = state
+        .db
+        .run(move |conn| {
+            crate_subscriptions::table
+                .inner_join(crates::table)
+                .select(crates::name)
+                .filter(crate_subscriptions::id.eq(subscription_id))
+                .first::<String>(conn)
+                .optional()
+        })
+        .await?;
+    Ok(crate_name)
+}
+
+fn invalid_link_response(
+    state: &AppState,
+    user: Option<Auth>,
+) -> Result<(StatusCode, Html<String>), FrontendError> {
+    let rendered = utils::response::error_html(
+        state,
+        user.map(|it| it.into_inner()),
+        "this unsubscribe link is invalid (or you have already unsubscribed)",
+    )?;
+    Ok((StatusCode::BAD_REQUEST, Html(rendered)))
+}
+
+/// Route to display the confirmation page of an unsubscribe link (as sent in notification emails).
+///
+/// Unsubscribe links are signed, so they work without being logged in.
+pub(crate) async fn get_link(
+    State(state): State<Arc<AppState>>,
+    Path(subscription_id): Path<i64>,
+    Query(params): Query<UnsubscribeParams>,
+    user: Option<Auth>,
+) -> Result<(StatusCode, Html<String>), FrontendError> {
+    let token = params.token;
+    if !notifications::verify_unsubscribe_token(state.as_ref(), subscription_id, token.as_str()) {
+        return invalid_link_response(state.as_ref(), user);
+    }
+    let Some(crate_name) = subscribed_crate(state.as_ref(), subscription_id).await? else {
+        return invalid_link_response(state.as_ref(), user);
+    };
+
+    let engine = &state.frontend.handlebars;
+    let context = json!({
+        "user": user.map(|it| it.into_inner()),
+        "instance": &state.frontend.config,
+        "crate_name": crate_name,
+        "subscription_id": subscription_id,
+        "token": token,
+        "done": false,
+    });
+    let rendered = engine.render("unsubscribe", &context)?;
+    Ok((StatusCode::OK, Html(rendered)))
+}
+
+/// Route to unsubscribe using an unsubscribe link (as sent in notification emails).
+pub(crate) async fn post_link(
+    State(state): State<Arc<AppState>>,
+    Path(subscription_id): Path<i64>,
+    Query(params): Query<UnsubscribeParams>,
+    user: Option<Auth>,
+) -> Result<(StatusCode, Html<String>), FrontendError> {
+    if !notifications::verify_unsubscribe_token(state.as_ref(), subscription_id, &params.token) {
+        return invalid_link_response(state.as_ref(), user);
+    }
+    let Some(crate_name) = subscribed_crate(state.as_ref(), subscription_id).await? else {
+        return invalid_link_response(state.as_ref(), user);
+    };
+
+    state
+        .db
+        .run(move |conn| {
+            diesel::delete(crate_subscriptions::table.find(subscription_id)).execute(conn)
+        })
+        .await?;
+
+    let engine = &state.frontend.handlebars;
+    let context = json!({
+        "user": user.map(|it| it.into_inner()),
+        "instance": &state.frontend.config,
+        "crate_name": crate_name,
+        "done": true,
+    });
+    let rendered = engine.render("unsubscribe", &context)?;
+    Ok((StatusCode::OK, Html(rendered)))
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppState;
+use crate::db::models::NewDownloadSnapshot;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::Error;
+use crate::utils::visibility::CrateVisibility;
+
+/// Represents a trending crate (a crate whose download count grew over the trending window).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrendingCrate {
+    /// The crate's name.
+    pub name: String,
+    /// The number of downloads gained over the trending window.
+    pub growth: i64,
+}
+
+/// Spawns the task periodically snapshotting download counts and recomputing the trending crates
+/// (if the front page shows trending crates).
+pub fn spawn(state: Arc<AppState>) {
+    let config = &state.frontend.config.front_page;
+    if config.trending_size() == 0 {
+        return;
+    }
+    let period = Duration::from_secs(config.refresh_interval.max(1) * 60 * 60);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(error) = refresh(&state).await {
+                tracing::error!("could not refresh the trending crates: {error}");
+            }
+        }
+    });
+}
+
+/// Takes a snapshot of the current download counts and recomputes the trending crates from them.
+pub async fn refresh(state: &Arc<AppState>) -> Result<(), Error> {
+    let config = &state.frontend.config.front_page;
+    let size = config.trending_size();
+    let window = chrono::Duration::days(config.trending_window.max(1) as i64);
+
+    let now = Utc::now().naive_utc();
+    let taken_at = now.format(DATETIME_FORMAT).to_string();
+    let since = (now - window).format(DATETIME_FORMAT).to_string();
+
+    let trending = state
+        .db
+        .transaction(move |conn| {
+            //? Snapshot the current download counts.
+            let crates: Vec<(i64, String, i64, String)> = crates::table
+                .select((
+                    crates::id,
+                    crates::name,
+                    crates::downloads,
+                    crates::visibility,
+                ))
+                .load(conn)?;
+            let snapshots: Vec<NewDownloadSnapshot> = crates
+                .iter()
+                .map(|(crate_id, _, downloads, _)| NewDownloadSnapshot {
+                    crate_id: *crate_id,
+                    downloads: *downloads,
+                    taken_at: taken_at.as_str(),
+                })
+                .collect();
+            for snapshot in snapshots {
+                diesel::insert_into(download_snapshots::table)
+                    .values(snapshot)
+                    .execute(conn)?;
+            }
+
+            //? Snapshots falling outside of the trending window are no longer needed.
+            diesel::delete(
+                download_snapshots::table.filter(download_snapshots::taken_at.lt(since.as_str())),
+            )
+            .execute(conn)?;
+
+            //? The growth is measured from the lowest download count sampled within the window.
+            let baselines: HashMap<i64, i64> = download_snapshots::table
+                .group_by(download_snapshots::crate_id)
+                .select((
+                    download_snapshots::crate_id,
+                    sql::min(download_snapshots::downloads),
+                ))
+                .load::<(i64, Option<i64>)>(conn)?
+                .into_iter()
+                .filter_map(|(crate_id, downloads)| Some((crate_id, downloads?)))
+                .collect();
+
+            //? The trending crates are shown to everyone, so private crates are left out.
+            let mut trending: Vec<TrendingCrate> = crates
+                .into_iter()
+                .filter(|(_, _, _, visibility)| {
+                    CrateVisibility::parse(visibility.as_str()) == CrateVisibility::Public
+                })
+                .filter_map(|(crate_id, name, downloads, _)| {
+                    let growth = downloads - baselines.get(&crate_id)?;
+                    (growth > 0).then_some(TrendingCrate { name, growth })
+                })
+                .collect();
+            trending.sort_by(|a, b| b.growth.cmp(&a.growth).then_with(|| a.name.cmp(&b.name)));
+            trending.truncate(size);
+
+            Ok::<_, Error>(trending)
+        })
+        .await?;
+
+    *state.frontend.trending.write().unwrap() = trending;
+    Ok(())
+}
@@ -106,8 +106,8 @@ impl TantivyDocument {
     }
 
     /// Set crate's description
-    pub fn set_description(&mut self, description: String) {
-        self.description = Some(description);
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
     }
 
     /// Add new crate's keyword
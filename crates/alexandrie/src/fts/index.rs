@@ -3,6 +3,7 @@ use std::num::NonZeroUsize;
 use std::sync::RwLock;
 
 use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
 use tantivy::collector::{Count, TopDocs};
 use tantivy::directory::MmapDirectory;
 use tantivy::query::{AllQuery, QueryParser};
@@ -11,6 +12,7 @@ use tantivy::tokenizer::{
     Language, LowerCaser, RawTokenizer, SimpleTokenizer, StopWordFilter, TextAnalyzer,
     TokenizerManager,
 };
+use tantivy::SnippetGenerator;
 use tantivy::{
     Index as TantivyIndex, IndexReader, IndexWriter, Opstamp, ReloadPolicy, TantivyError, Term,
 };
@@ -27,6 +29,57 @@ const NUMBER_RESULT_PER_PAGE: i64 = 1000;
 
 type CrateKeywordCategory = (Vec<Crate>, Vec<(i64, String)>, Vec<(i64, String)>);
 
+/// A search hit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    /// The crate's database ID.
+    pub id: i64,
+    /// The fragments of the crate's fields matching the query.
+    pub highlights: Highlights,
+}
+
+/// The fragments of a crate's fields matching a query, as HTML (with the matched terms in `<mark>` tags).
+///
+/// The fragments are built from the indexed texts (which are escaped), so they are safe to render as-is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlights {
+    /// The matching fragment of the crate's name.
+    pub name: Option<String>,
+    /// The matching fragment of the crate's description.
+    pub description: Option<String>,
+    /// The matching fragment of the crate's keywords.
+    pub keywords: Option<String>,
+}
+
+/// Escapes a text, to be inserted in an HTML document.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Renders a snippet as HTML, with the matched terms in `<mark>` tags (or `None` if nothing matched).
+fn highlight(snippet: &Snippet) -> Option<String> {
+    let highlighted = snippet.highlighted();
+    if highlighted.is_empty() {
+        return None;
+    }
+    let fragment = snippet.fragment();
+    let mut html = String::with_capacity(fragment.len());
+    let mut start = 0;
+    for range in highlighted {
+        html.push_str(&escape_html(&fragment[start..range.start]));
+        html.push_str("<mark>");
+        html.push_str(&escape_html(&fragment[range.start..range.end]));
+        html.push_str("</mark>");
+        start = range.end;
+    }
+    html.push_str(&escape_html(&fragment[start..]));
+    Some(html)
+}
+
 /// Helper for using Tantivy
 pub struct Tantivy {
     index_reader: IndexReader,
@@ -37,6 +90,8 @@ pub struct Tantivy {
     schema: Schema,
     /// Search tokenizer manager
     search_tokenizer_manager: TokenizerManager,
+    /// The maximum length of the highlighted fragments of search hits (in characters).
+    highlight_length: usize,
 }
 
 impl TryFrom<SearchConfig> for Tantivy {
@@ -155,6 +210,7 @@ impl TryFrom<SearchConfig> for Tantivy {
             index_writer,
             schema,
             search_tokenizer_manager,
+            highlight_length: search.highlight_length,
         })
     }
 }
@@ -246,13 +302,13 @@ impl Tantivy {
         Ok(results)
     }
 
-    /// Search documents. Return document count & database IDs.
+    /// Search documents. Return document count & hits (database IDs, along with highlighted fragments).
     pub fn search<Q: AsRef<str>>(
         &self,
         query: Q,
         offset: usize,
         limit: usize,
-    ) -> Result<(usize, Vec<i64>), TantivyError> {
+    ) -> Result<(usize, Vec<SearchHit>), TantivyError> {
         let query = query.as_ref().trim();
 
         let searcher = self.index_reader.searcher();
@@ -295,6 +351,16 @@ impl Tantivy {
             &(Count, TopDocs::with_limit(limit).and_offset(offset)),
         )?;
 
+        // Highlight the terms of the query which matched within the (queried) fields shown to users
+        let snippet_generator = |field: Field| {
+            let mut generator = SnippetGenerator::create(&searcher, &*query, field)?;
+            generator.set_max_num_chars(self.highlight_length);
+            Ok::<_, TantivyError>(generator)
+        };
+        let name_snippets = snippet_generator(name)?;
+        let description_snippets = snippet_generator(description)?;
+        let keywords_snippets = snippet_generator(keywords)?;
+
         let results = results
             .into_iter()
             .filter_map(|(score, doc_address)| {
@@ -311,12 +377,17 @@ impl Tantivy {
                 }
 
                 let mut field = retrieve_doc.get_all(id);
-                if let Some(x) = field.next() {
-                    x.as_i64()
-                } else {
+                let Some(id) = field.next().and_then(|x| x.as_i64()) else {
                     tracing::warn!("Could not find field id");
-                    None
-                }
+                    return None;
+                };
+
+                let highlights = Highlights {
+                    name: highlight(&name_snippets.snippet_from_doc(&retrieve_doc)),
+                    description: highlight(&description_snippets.snippet_from_doc(&retrieve_doc)),
+                    keywords: highlight(&keywords_snippets.snippet_from_doc(&retrieve_doc)),
+                };
+                Some(SearchHit { id, highlights })
             })
             .collect();
 
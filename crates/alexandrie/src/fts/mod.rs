@@ -3,11 +3,7 @@ mod document;
 mod index;
 
 pub(crate) use document::TantivyDocument;
-pub(crate) use index::Tantivy;
-
-/// Default number of result per page
-/// Perhaps should make this configurable in toml.
-pub const DEFAULT_RESULT_PER_PAGE: usize = 15;
+pub(crate) use index::{Highlights, SearchHit, Tantivy};
 
 /// Database ID.
 const ID_FIELD_NAME: &str = "id";
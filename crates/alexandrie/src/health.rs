@@ -0,0 +1,102 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::Indexer;
+use alexandrie_storage::Store;
+
+use crate::config::AppState;
+use crate::error::Error;
+use crate::utils;
+
+/// The maximum amount of time a single dependency check is allowed to take.
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The outcome of checking a single dependency of the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// Whether the dependency is healthy.
+    pub healthy: bool,
+    /// How long the check took (in milliseconds).
+    pub latency_ms: u128,
+    /// The reason why the dependency is considered unhealthy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The health report of the registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether all the dependencies are healthy.
+    pub healthy: bool,
+    /// The result of the database connectivity check.
+    pub database: CheckResult,
+    /// The result of the crate index check.
+    pub index: CheckResult,
+    /// The result of the crate storage check.
+    pub storage: CheckResult,
+    /// Whether the registry is in read-only mode (for maintenance).
+    pub read_only: bool,
+}
+
+async fn run_check<F>(future: F) -> CheckResult
+where
+    F: Future<Output = Result<(), Error>>,
+{
+    let start = Instant::now();
+    let outcome = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, future).await;
+    let latency_ms = start.elapsed().as_millis();
+
+    let error = match outcome {
+        Ok(Ok(())) => None,
+        Ok(Err(error)) => Some(error.to_string()),
+        Err(_) => Some(format!(
+            "timed out after {0} ms",
+            HEALTH_CHECK_TIMEOUT.as_millis()
+        )),
+    };
+
+    CheckResult {
+        healthy: error.is_none(),
+        latency_ms,
+        error,
+    }
+}
+
+/// Route to check the health of the registry and of its dependencies.
+///
+/// It responds with `503 Service Unavailable` if any of the checks failed.  
+/// The read-only maintenance mode does not make the registry unhealthy, but is reported as well.
+pub async fn get(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthReport>) {
+    let (database, index, storage) = tokio::join!(
+        run_check(state.db.ping(HEALTH_CHECK_TIMEOUT)),
+        run_check(utils::run_blocking(&state, |state| {
+            Ok(state.index.check_health()?)
+        })),
+        run_check(utils::run_blocking(&state, |state| {
+            Ok(state.storage.check_health()?)
+        })),
+    );
+
+    let healthy = database.healthy && index.healthy && storage.healthy;
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let report = HealthReport {
+        healthy,
+        database,
+        index,
+        storage,
+        read_only: state.general.read_only,
+    };
+
+    (status, Json(report))
+}
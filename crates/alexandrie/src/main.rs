@@ -21,14 +21,20 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::fs;
 
+use axum::middleware;
 use axum::routing::{delete, get, post, put};
 use axum::{Router, Server};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use diesel_migrations::MigrationHarness;
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::trace::{self, TraceLayer};
 use tracing::Level;
 
@@ -40,8 +46,6 @@ use tower_http::services::ServeDir;
 use tower_sessions::cookie::SameSite;
 #[cfg(feature = "frontend")]
 use tower_sessions::SessionManagerLayer;
-use tracing_subscriber::filter::LevelFilter;
-use tracing_subscriber::EnvFilter;
 
 /// API endpoints definitions.
 pub mod api;
@@ -51,6 +55,10 @@ pub mod config;
 pub mod db;
 /// Error-related type definitions.
 pub mod error;
+/// Health check endpoint definition.
+pub mod health;
+/// Prometheus metrics endpoint definition.
+pub mod metrics;
 /// Various utilities and helpers.
 pub mod utils;
 
@@ -58,14 +66,36 @@ pub mod utils;
 #[cfg(feature = "frontend")]
 pub mod frontend;
 
+/// Download statistics (daily snapshots of the download counts of crate versions).
+pub mod download_stats;
+
 /// Full text search
 pub mod fts;
 
+/// Reverse dependencies index.
+pub mod rdeps;
+
+/// Crate tarball retention policy.
+pub mod retention;
+
+/// Crate badges endpoints definitions (SVG images, to be embedded in READMEs or dashboards).
+pub mod badge;
+
+/// Crate index endpoint definition (sparse registry protocol).
+pub mod sparse;
+
+/// Download counts backfill (from access logs).
+pub mod backfill;
+/// Registry export and import (as JSON lines).
+pub mod transfer;
+
+use crate::config::cors::CorsLayers;
 use crate::config::{AppState, Config};
 use crate::utils::build;
+use crate::utils::response::compression;
 
 #[cfg(feature = "frontend")]
-use crate::config::FrontendConfig;
+use crate::config::{FrontendConfig, SameSitePolicy};
 #[cfg(feature = "frontend")]
 use crate::utils::sessions::SqlStore;
 
@@ -78,24 +108,54 @@ fn frontend_routes(state: Arc<AppState>, frontend_config: FrontendConfig) -> Rou
     use axum::BoxError;
     use tower::ServiceBuilder;
 
+    let sessions = &frontend_config.sessions;
+    let same_site = match sessions.same_site {
+        SameSitePolicy::Strict => SameSite::Strict,
+        SameSitePolicy::Lax => SameSite::Lax,
+        SameSitePolicy::None => SameSite::None,
+    };
+    if same_site == SameSite::None && !sessions.secure {
+        tracing::warn!("the session cookie uses `SameSite=None` without being `Secure`, so browsers will reject it");
+    }
+
     let store = SqlStore::new(state.db.clone());
-    let session_layer = SessionManagerLayer::new(store)
-        .with_name(frontend_config.sessions.cookie_name.as_str())
-        .with_same_site(SameSite::Lax)
-        .with_max_age(time::Duration::days(1));
+    let mut session_layer = SessionManagerLayer::new(store)
+        .with_name(sessions.cookie_name.as_str())
+        .with_secure(sessions.secure)
+        .with_same_site(same_site)
+        .with_max_age(time::Duration::seconds(sessions.max_age as i64));
+    if let Some(domain) = sessions.domain.as_deref() {
+        session_layer = session_layer.with_domain(domain);
+    }
     let session_service = ServiceBuilder::new()
         .layer(HandleErrorLayer::new(|_: BoxError| async {
             StatusCode::BAD_REQUEST
         }))
         .layer(session_layer);
 
-    Router::new()
+    let router = Router::new()
         .route("/", get(frontend::index::get))
         .route("/me", get(frontend::me::get))
         .route("/search", get(frontend::search::get))
         .route("/most-downloaded", get(frontend::most_downloaded::get))
         .route("/last-updated", get(frontend::last_updated::get))
         .route("/crates/:crate", get(frontend::krate::get))
+        .route("/crates/:crate/docs", get(frontend::krate::docs))
+        .route("/crates/:crate/events", get(frontend::events::get))
+        .route("/authors/:id", get(frontend::authors::get))
+        .route("/users/:name", get(frontend::authors::by_name))
+        .route(
+            "/crates/:crate/subscribe",
+            post(frontend::subscriptions::subscribe),
+        )
+        .route(
+            "/crates/:crate/unsubscribe",
+            post(frontend::subscriptions::unsubscribe),
+        )
+        .route(
+            "/subscriptions/:subscription-id/unsubscribe",
+            get(frontend::subscriptions::get_link).post(frontend::subscriptions::post_link),
+        )
         .route(
             "/account/login",
             get(frontend::account::login::get).post(frontend::account::login::post),
@@ -105,6 +165,14 @@ fn frontend_routes(state: Arc<AppState>, frontend_config: FrontendConfig) -> Rou
             "/account/register",
             get(frontend::account::register::get).post(frontend::account::register::post),
         )
+        .route(
+            "/account/reset",
+            get(frontend::account::reset::get).post(frontend::account::reset::post),
+        )
+        .route(
+            "/account/reset/:token",
+            get(frontend::account::reset::get_token).post(frontend::account::reset::post_token),
+        )
         .route("/account/github", get(frontend::account::github::get))
         .route(
             "/account/github/attach",
@@ -136,6 +204,30 @@ fn frontend_routes(state: Arc<AppState>, frontend_config: FrontendConfig) -> Rou
             "/account/manage/password",
             post(frontend::account::manage::passwd::post),
         )
+        .route(
+            "/account/manage/sessions",
+            get(frontend::account::manage::sessions::get),
+        )
+        .route(
+            "/account/manage/sessions/revoke-all",
+            post(frontend::account::manage::sessions::revoke_all::post),
+        )
+        .route(
+            "/account/manage/sessions/revoke-others",
+            post(frontend::account::manage::sessions::revoke_others::post),
+        )
+        .route(
+            "/account/manage/sessions/:session-handle/revoke",
+            post(frontend::account::manage::sessions::revoke::post),
+        )
+        .route(
+            "/account/manage/invitations/:invitation-id/accept",
+            post(frontend::account::manage::invitations::accept),
+        )
+        .route(
+            "/account/manage/invitations/:invitation-id/decline",
+            post(frontend::account::manage::invitations::decline),
+        )
         .route(
             "/account/manage/tokens",
             post(frontend::account::manage::tokens::post),
@@ -143,16 +235,40 @@ fn frontend_routes(state: Arc<AppState>, frontend_config: FrontendConfig) -> Rou
         .route(
             "/account/manage/tokens/:token-id/revoke",
             get(frontend::account::manage::tokens::revoke::get),
-        )
+        );
+
+    #[cfg(feature = "common-passwords")]
+    let router = router.route(
+        "/account/common-passwords",
+        get(frontend::account::utils::common_passwords),
+    );
+
+    let body_limit = RequestBodyLimitLayer::new(state.general.max_body_size as usize);
+    router
+        .layer(body_limit)
         .nest_service(
             "/assets",
-            ServeDir::new(frontend_config.assets.path).append_index_html_on_directories(false),
+            ServiceBuilder::new()
+                .layer(middleware::from_fn_with_state(
+                    Arc::clone(&state),
+                    frontend::assets::cache,
+                ))
+                .service(
+                    ServeDir::new(frontend_config.assets.path)
+                        .append_index_html_on_directories(false),
+                ),
         )
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            utils::maintenance::frontend_guard,
+        ))
+        .layer(middleware::from_fn(utils::sessions::track))
         .layer(session_service)
 }
 
-fn api_routes() -> Router<Arc<AppState>> {
-    Router::new()
+fn api_routes(state: &Arc<AppState>, cors: Option<&CorsLayers>) -> Router<Arc<AppState>> {
+    let body_limit = RequestBodyLimitLayer::new(state.general.max_body_size as usize);
+    let router = Router::new()
         .route("/account/register", post(api::account::register::post))
         .route("/account/login", post(api::account::login::post))
         .route(
@@ -162,17 +278,43 @@ fn api_routes() -> Router<Arc<AppState>> {
                 .delete(api::account::token::revoke::delete),
         )
         .route("/account/tokens/:name", get(api::account::token::info::get))
+        .route("/account/invitations", get(api::account::invitations::get))
+        .route(
+            "/account/invitations/:id",
+            put(api::account::invitations::put),
+        )
         .route("/categories", get(api::categories::get))
         .route("/crates", get(api::crates::search::get))
-        .route("/crates/new", put(api::crates::publish::put))
         .route("/crates/suggest", get(api::crates::suggest::get))
+        .route("/crates/unowned", get(api::crates::claim::unowned))
         .route("/crates/:name", get(api::crates::info::get))
+        .route(
+            "/crates/:name/aliases",
+            get(api::crates::aliases::get)
+                .put(api::crates::aliases::put)
+                .delete(api::crates::aliases::delete),
+        )
+        .route(
+            "/crates/:name/claim",
+            get(api::crates::claim::get).put(api::crates::claim::put),
+        )
+        .route("/crates/:name/downloads", get(api::crates::downloads::get))
+        .route("/crates/:name/events", get(api::crates::events::get))
+        .route(
+            "/crates/:name/reverse_dependencies",
+            get(api::crates::reverse_dependencies::get),
+        )
         .route(
             "/crates/:name/owners",
             get(api::crates::owners::get)
                 .put(api::crates::owners::put)
                 .delete(api::crates::owners::delete),
         )
+        .route(
+            "/crates/:name/visibility",
+            put(api::crates::visibility::put),
+        )
+        .route("/crates/:name/yank", post(api::crates::bulk_yank::post))
         .route(
             "/crates/:name/:version/yank",
             delete(api::crates::yank::delete),
@@ -182,9 +324,59 @@ fn api_routes() -> Router<Arc<AppState>> {
             put(api::crates::unyank::put),
         )
         .route(
-            "/crates/:name/:version/download",
-            get(api::crates::download::get),
+            "/crates/:name/:version/Cargo.toml",
+            get(api::crates::manifest::get),
         )
+        .route("/keywords", get(api::keywords::get))
+        .layer(body_limit.clone());
+
+    //? The publication (and tarball replacement) endpoints get the maximum crate size as their body size limit, instead of the default one.
+    let router = match state.general.max_crate_size {
+        Some(max_crate_size) => {
+            let crate_limit = RequestBodyLimitLayer::new(max_crate_size as usize);
+            router
+                .route(
+                    "/crates/new",
+                    put(api::crates::publish::put).layer(crate_limit.clone()),
+                )
+                .route(
+                    "/crates/new/validate",
+                    put(api::crates::publish::validate).layer(crate_limit.clone()),
+                )
+                .route(
+                    "/crates/:name/:version/tarball",
+                    put(api::crates::tarball::put).layer(crate_limit),
+                )
+        }
+        None => router
+            .route("/crates/new", put(api::crates::publish::put))
+            .route("/crates/new/validate", put(api::crates::publish::validate))
+            .route(
+                "/crates/:name/:version/tarball",
+                put(api::crates::tarball::put),
+            ),
+    };
+
+    //? Crate downloads only allow read-only cross-origin requests.
+    //? `HEAD` requests get their own handler, so that they neither read the tarball nor count as downloads.
+    let download = get(api::crates::download::get)
+        .head(api::crates::download::head)
+        .layer(body_limit);
+    let router = match cors {
+        Some(cors) => router.layer(cors.api.clone()).route(
+            "/crates/:name/:version/download",
+            download.layer(cors.read_only.clone()),
+        ),
+        None => router.route("/crates/:name/:version/download", download),
+    };
+
+    //? In read-only mode, every request altering the registry is refused.
+    let guard = middleware::from_fn_with_state(Arc::clone(state), utils::maintenance::api_guard);
+
+    //? Every error (including the rejections of extractors and body limits) is sent in the format expected by Cargo.
+    router
+        .layer(guard)
+        .layer(middleware::map_response(error::wrap_rejections))
 }
 
 #[derive(Debug, Parser)]
@@ -193,20 +385,44 @@ struct Opts {
     /// Path to the configuration file
     #[arg(short, long, default_value = "alexandrie.toml")]
     pub config: String,
+    /// Administrative command to run (instead of starting the registry)
+    #[command(subcommand)]
+    pub command: Option<Command>,
 }
 
-async fn run() -> Result<(), anyhow::Error> {
-    let opts = Opts::parse();
-
-    tracing::info!("starting Alexandrie (version: {0})", build::short());
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Export every crate of the registry as JSON lines (one crate per line)
+    Export {
+        /// Path of the export file to write
+        path: PathBuf,
+        /// Resume an interrupted export (appending to the existing export file)
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Import the crates of an export file into the registry (skipping already present crates)
+    Import {
+        /// Path of the export file to read
+        path: PathBuf,
+    },
+    /// Backfill the download counts of crate versions by replaying access logs (safe to re-run with the same logs)
+    BackfillDownloads {
+        /// Paths of the access logs to read (every log to account for, including previously replayed ones)
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Only report the downloads found, without modifying the download counts
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
 
-    let contents = fs::read_to_string(&opts.config).await?;
+async fn load_config(path: &str) -> Result<Config, anyhow::Error> {
+    let contents = fs::read_to_string(path).await?;
     let config: Config = toml::from_str(contents.as_str())?;
-    let addr = config.general.bind_address.clone();
-
-    #[cfg(feature = "frontend")]
-    let frontend_config = config.frontend.clone();
+    Ok(config)
+}
 
+async fn init_state(config: Config) -> Result<Arc<AppState>, anyhow::Error> {
     let state: AppState = config.try_into()?;
 
     let state = Arc::new(state);
@@ -216,48 +432,184 @@ async fn run() -> Result<(), anyhow::Error> {
     state.db.run(|conn| conn.run_pending_migrations(db::MIGRATIONS).map(|_| ())).await
         .expect("migration execution error");
 
+    Ok(state)
+}
+
+async fn run_command(config: Config, command: Command) -> Result<(), anyhow::Error> {
+    let state = init_state(config).await?;
+    match command {
+        Command::Export { path, resume } => transfer::export(state, &path, resume).await?,
+        Command::Import { path } => transfer::import(state, &path).await?,
+        Command::BackfillDownloads { paths, dry_run } => {
+            backfill::backfill_downloads(state, &paths, dry_run).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn run(config: Config) -> Result<(), anyhow::Error> {
+    tracing::info!("starting Alexandrie (version: {0})", build::short());
+
+    let addr = config.general.bind_address.clone();
+
+    #[cfg(feature = "frontend")]
+    let frontend_config = config.frontend.clone();
+
+    let cors: Option<CorsLayers> = config.cors.clone().map(CorsLayers::try_from).transpose()?;
+    let shutdown = config.shutdown.clone();
+
+    let state = init_state(config).await?;
+
+    if state.general.read_only {
+        tracing::warn!("the registry is in read-only mode (`read_only` option of the `[general]` section), publications and other alterations are refused");
+    }
+
+    if state.mail.is_none() {
+        tracing::warn!("no email transport configured (`[mail]` section), email-dependent features (like password resets) are disabled");
+    }
+
     let database = &state.db;
     state.search.index_all(database).await?;
+    rdeps::ReverseDependencies::index_all(&state).await?;
+    retention::spawn(Arc::clone(&state));
+    download_stats::spawn(Arc::clone(&state));
 
-    let app = Router::new().nest("/api/v1", api_routes());
+    let index_route = match cors.as_ref() {
+        Some(cors) => get(sparse::get).layer(cors.read_only.clone()),
+        None => get(sparse::get),
+    };
+
+    //? The crate index and the API responses get compressed, if configured.
+    let api_routes = api_routes(&state, cors.as_ref());
+    let (index_route, api_routes) = match state.compression.as_ref() {
+        Some(config) => {
+            let weaken_etag = middleware::map_response(compression::weaken_etag);
+            (
+                index_route
+                    .layer(compression::layer(config))
+                    .layer(weaken_etag.clone()),
+                api_routes
+                    .layer(compression::layer(config))
+                    .layer(weaken_etag),
+            )
+        }
+        None => (index_route, api_routes),
+    };
+
+    let app = Router::new()
+        .route("/healthz", get(health::get))
+        .route("/index/*path", index_route)
+        .route("/crates/:crate/badge.svg", get(badge::version))
+        .route("/crates/:crate/downloads.svg", get(badge::downloads))
+        .nest("/api/v1", api_routes);
+
+    let app = if state.metrics.config.served_on_main_address() {
+        app.route("/metrics", get(metrics::get))
+    } else {
+        app
+    };
 
     #[cfg(feature = "frontend")]
     let app = if frontend_config.enabled {
+        frontend::trending::spawn(Arc::clone(&state));
+        frontend::notifications::spawn(Arc::clone(&state));
         app.nest("/", frontend_routes(Arc::clone(&state), frontend_config))
     } else {
         app
     };
 
     let app = app
+        .layer(CatchPanicLayer::custom(error::handle_panic))
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            metrics::track_requests,
+        ))
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(trace::DefaultMakeSpan::new().level(Level::INFO))
+                .make_span_with(utils::request_id::make_span)
                 .on_response(trace::DefaultOnResponse::new().level(Level::INFO))
                 .on_failure(trace::DefaultOnFailure::new().level(Level::ERROR)),
         )
+        .layer(middleware::from_fn(utils::request_id::propagate))
         .with_state(Arc::clone(&state));
 
+    if let Some(metrics_addr) = state.metrics.config.bind_address.clone() {
+        let metrics_app = Router::new()
+            .route("/metrics", get(metrics::get))
+            .with_state(Arc::clone(&state));
+        let metrics_server =
+            Server::bind(&metrics_addr.parse()?).serve(metrics_app.into_make_service());
+        tracing::info!("serving metrics on '{metrics_addr}'");
+        tokio::spawn(async move {
+            if let Err(err) = metrics_server.await {
+                tracing::error!("metrics server error: {err}");
+            }
+        });
+    } else if !state.metrics.config.served_on_main_address() {
+        tracing::info!("metrics endpoint disabled (no `bind_address` or `token` configured)");
+    }
+
     tracing::info!("listening on '{addr}'");
-    Server::bind(&addr.parse().unwrap())
-        .serve(app.into_make_service())
-        .await?;
+    let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+    let server = Server::bind(&addr.parse().unwrap())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            stopped.await.ok();
+        });
+    let mut server = tokio::spawn(server);
+
+    tokio::select! {
+        outcome = &mut server => return Ok(outcome??),
+        _ = utils::shutdown::signal() => {}
+    }
+
+    //? Stop accepting connections, and give the in-flight requests some time to complete.
+    tracing::info!(
+        "shutting down, no longer accepting connections ({0} publications in flight)",
+        state.publishes.count(),
+    );
+    stop.send(()).ok();
+    let read_timeout = Duration::from_secs(shutdown.read_timeout);
+    if let Ok(outcome) = tokio::time::timeout(read_timeout, &mut server).await {
+        tracing::info!("every in-flight request completed, exiting");
+        return Ok(outcome??);
+    }
+
+    //? The other requests (like downloads) are cut, but the in-flight publications are given more time to complete.
+    let remaining = shutdown.drain_timeout.saturating_sub(shutdown.read_timeout);
+    let in_flight = state.publishes.count();
+    tracing::info!("waiting for {in_flight} in-flight publications to complete (for up to {remaining} seconds)");
+    let drained = tokio::time::timeout(Duration::from_secs(remaining), state.publishes.wait_idle());
+    match drained.await {
+        Ok(()) => tracing::info!("every in-flight publication completed, exiting"),
+        Err(_) => tracing::warn!(
+            "exiting with {0} publications still in flight (drain timeout elapsed)",
+            state.publishes.count(),
+        ),
+    }
+    server.abort();
 
     Ok(())
 }
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::builder()
-                .with_default_directive(LevelFilter::INFO.into())
-                .from_env_lossy(),
-        )
-        // .with_target(false)
-        .compact()
-        .init();
+    let opts = Opts::parse();
+
+    let outcome = match load_config(&opts.config).await {
+        Ok(config) => match utils::telemetry::init(&config) {
+            Ok(()) => match opts.command {
+                Some(command) => run_command(config, command).await,
+                None => run(config).await,
+            },
+            Err(err) => Err(err),
+        },
+        Err(err) => Err(err),
+    };
+
+    utils::telemetry::shutdown();
 
-    if let Err(err) = run().await {
+    if let Err(err) = outcome {
         eprintln!("{err}");
         std::process::exit(1);
     }
@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, State};
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use ring::constant_time;
+
+use crate::config::{AppState, MetricsConfig};
+
+/// The content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The upper bounds (in seconds) of the request latency histogram buckets.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// The route label used for requests which did not match any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// A latency histogram, using the [`LATENCY_BUCKETS`] boundaries.
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Non-cumulative counts of observations, per bucket.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    /// The sum of all observed values (in seconds).
+    sum: f64,
+    /// The number of observed values.
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[idx] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// The metrics recorded for a given route (and HTTP method).
+#[derive(Debug, Clone, Default)]
+struct RouteMetrics {
+    /// The number of responses, per status code.
+    statuses: BTreeMap<u16, u64>,
+    /// The latency histogram of the route.
+    latency: Histogram,
+}
+
+/// The metrics registry of the application.
+///
+/// Exported metrics (and their labels) are:
+/// - `alexandrie_http_requests_total{method, route, status}` (counter): handled HTTP requests.
+/// - `alexandrie_http_request_duration_seconds{method, route}` (histogram): HTTP request latencies.
+/// - `alexandrie_crate_publishes_total{outcome}` (counter): crate publications, `outcome` being `success` or `error`.
+/// - `alexandrie_crate_yanks_total{action}` (counter): crate version (un)yankings, `action` being `yank` or `unyank`.
+/// - `alexandrie_crate_downloads_total` (counter): crate tarball downloads.
+/// - `alexandrie_search_queries_total` (counter): search queries (from both the API and the frontend).
+/// - `alexandrie_db_pool_*`: database connection pool metrics (see [`render_pool_metrics`]).
+///
+/// The `route` label is the route's path template (eg. `/api/v1/crates/:name`), not the actual requested path.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// The metrics endpoint configuration.
+    pub config: MetricsConfig,
+    routes: Mutex<BTreeMap<(String, String), RouteMetrics>>,
+    publishes_success: AtomicU64,
+    publishes_error: AtomicU64,
+    yanks: AtomicU64,
+    unyanks: AtomicU64,
+    downloads: AtomicU64,
+    searches: AtomicU64,
+}
+
+impl From<MetricsConfig> for Metrics {
+    fn from(config: MetricsConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+}
+
+impl Metrics {
+    /// Records a handled HTTP request.
+    pub fn record_request(&self, method: &str, route: &str, status: StatusCode, elapsed: Duration) {
+        let Ok(mut routes) = self.routes.lock() else {
+            return;
+        };
+        let entry = routes
+            .entry((String::from(method), String::from(route)))
+            .or_default();
+        *entry.statuses.entry(status.as_u16()).or_default() += 1;
+        entry.latency.observe(elapsed.as_secs_f64());
+    }
+
+    /// Records a crate publication attempt.
+    pub fn record_publish(&self, success: bool) {
+        let counter = if success {
+            &self.publishes_success
+        } else {
+            &self.publishes_error
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a crate version yanking (or unyanking).
+    pub fn record_yank(&self, yanked: bool) {
+        let counter = if yanked { &self.yanks } else { &self.unyanks };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a crate tarball download.
+    pub fn record_download(&self) {
+        self.downloads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a search query.
+    pub fn record_search(&self) {
+        self.searches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all the metrics of the application, in the Prometheus text exposition format.
+    pub fn render(&self, state: &AppState) -> String {
+        let mut out = String::new();
+        self.render_http_metrics(&mut out);
+        self.render_event_metrics(&mut out);
+        render_pool_metrics(&mut out, state);
+        out
+    }
+
+    fn render_http_metrics(&self, out: &mut String) {
+        let Ok(routes) = self.routes.lock() else {
+            return;
+        };
+
+        let name = "alexandrie_http_requests_total";
+        write_header(out, name, "counter", "Number of handled HTTP requests.");
+        for ((method, route), metrics) in routes.iter() {
+            for (status, count) in metrics.statuses.iter() {
+                let _ = writeln!(
+                    out,
+                    "{name}{{method=\"{0}\",route=\"{1}\",status=\"{status}\"}} {count}",
+                    escape_label(method),
+                    escape_label(route),
+                );
+            }
+        }
+
+        let name = "alexandrie_http_request_duration_seconds";
+        write_header(out, name, "histogram", "Latency of HTTP requests.");
+        for ((method, route), metrics) in routes.iter() {
+            let labels = format!(
+                "method=\"{0}\",route=\"{1}\"",
+                escape_label(method),
+                escape_label(route),
+            );
+            let histogram = &metrics.latency;
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+            }
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels},le=\"+Inf\"}} {0}",
+                histogram.count
+            );
+            let _ = writeln!(out, "{name}_sum{{{labels}}} {0}", histogram.sum);
+            let _ = writeln!(out, "{name}_count{{{labels}}} {0}", histogram.count);
+        }
+    }
+
+    fn render_event_metrics(&self, out: &mut String) {
+        let name = "alexandrie_crate_publishes_total";
+        write_header(
+            out,
+            name,
+            "counter",
+            "Number of crate publication attempts.",
+        );
+        let _ = writeln!(
+            out,
+            "{name}{{outcome=\"success\"}} {0}",
+            self.publishes_success.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "{name}{{outcome=\"error\"}} {0}",
+            self.publishes_error.load(Ordering::Relaxed)
+        );
+
+        let name = "alexandrie_crate_yanks_total";
+        write_header(
+            out,
+            name,
+            "counter",
+            "Number of crate version (un)yankings.",
+        );
+        let _ = writeln!(
+            out,
+            "{name}{{action=\"yank\"}} {0}",
+            self.yanks.load(Ordering::Relaxed)
+        );
+        let _ = writeln!(
+            out,
+            "{name}{{action=\"unyank\"}} {0}",
+            self.unyanks.load(Ordering::Relaxed)
+        );
+
+        write_metric(
+            out,
+            "alexandrie_crate_downloads_total",
+            "counter",
+            "Number of crate tarball downloads.",
+            self.downloads.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            out,
+            "alexandrie_search_queries_total",
+            "counter",
+            "Number of search queries.",
+            self.searches.load(Ordering::Relaxed) as f64,
+        );
+    }
+}
+
+/// Escapes a label value, as required by the Prometheus text exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes the `HELP` and `TYPE` lines of a metric.
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Writes a single unlabelled metric in the Prometheus text exposition format.
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl Into<f64>) {
+    write_header(out, name, kind, help);
+    let _ = writeln!(out, "{name} {0}", value.into());
+}
+
+/// Renders the database connection pool metrics.
+///
+/// Exported metrics:
+/// - `alexandrie_db_pool_connections_max` (gauge): maximum number of pooled connections.
+/// - `alexandrie_db_pool_connections_open` (gauge): currently open connections.
+/// - `alexandrie_db_pool_connections_idle` (gauge): open connections not currently in use.
+/// - `alexandrie_db_pool_connections_in_use` (gauge): connections currently checked out.
+/// - `alexandrie_db_pool_wait_seconds` (summary): time spent waiting for a connection.
+/// - `alexandrie_db_pool_exhaustions_total` (counter): times no connection could be acquired within the timeout.
+fn render_pool_metrics(out: &mut String, state: &AppState) {
+    let pool_state = state.db.pool_state();
+    let pool_metrics = state.db.metrics();
+
+    write_metric(
+        out,
+        "alexandrie_db_pool_connections_max",
+        "gauge",
+        "Maximum number of connections in the database pool.",
+        state.db.max_size(),
+    );
+    write_metric(
+        out,
+        "alexandrie_db_pool_connections_open",
+        "gauge",
+        "Number of currently open connections in the database pool.",
+        pool_state.connections,
+    );
+    write_metric(
+        out,
+        "alexandrie_db_pool_connections_idle",
+        "gauge",
+        "Number of idle connections in the database pool.",
+        pool_state.idle_connections,
+    );
+    write_metric(
+        out,
+        "alexandrie_db_pool_connections_in_use",
+        "gauge",
+        "Number of connections currently checked out from the database pool.",
+        pool_state.connections - pool_state.idle_connections,
+    );
+
+    let name = "alexandrie_db_pool_wait_seconds";
+    write_header(
+        out,
+        name,
+        "summary",
+        "Time spent waiting for a connection from the database pool.",
+    );
+    let _ = writeln!(
+        out,
+        "{name}_sum {0}",
+        pool_metrics.wait_time().as_secs_f64()
+    );
+    let _ = writeln!(out, "{name}_count {0}", pool_metrics.acquisitions());
+
+    write_metric(
+        out,
+        "alexandrie_db_pool_exhaustions_total",
+        "counter",
+        "Number of times no connection could be acquired from the database pool within the timeout.",
+        pool_metrics.exhaustions() as f64,
+    );
+}
+
+/// Middleware recording the count, status codes and latency of every handled request.
+pub async fn track_requests<B>(
+    State(state): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request.extensions().get::<MatchedPath>().map_or_else(
+        || String::from(UNMATCHED_ROUTE),
+        |it| it.as_str().to_string(),
+    );
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    state
+        .metrics
+        .record_request(&method, &route, response.status(), start.elapsed());
+
+    response
+}
+
+/// Checks whether the request carries the configured metrics token (if any).
+fn is_authorized(config: &MetricsConfig, headers: &HeaderMap) -> bool {
+    let Some(expected) = config.token.as_deref() else {
+        return true;
+    };
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.strip_prefix("Bearer ").unwrap_or(value));
+
+    provided.map_or(false, |provided| {
+        constant_time::verify_slices_are_equal(provided.as_bytes(), expected.as_bytes()).is_ok()
+    })
+}
+
+/// Route to export the registry's metrics, in the Prometheus text exposition format.
+pub async fn get(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    if !is_authorized(&state.metrics.config, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let rendered = state.metrics.render(state.as_ref());
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], rendered).into_response()
+}
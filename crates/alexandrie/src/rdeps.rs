@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use diesel::prelude::*;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::{CrateDependencyKind, CrateVersion, Indexer};
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::Error;
+use crate::utils;
+
+/// Represents a crate version that depends on a given crate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dependent {
+    /// The name of the depending crate.
+    pub name: String,
+    /// The version of the depending crate.
+    pub vers: Version,
+    /// The version requirement on the depended-upon crate.
+    pub req: VersionReq,
+    /// The kind of the dependency ("normal", "build" or "dev").
+    pub kind: CrateDependencyKind,
+    /// Is the dependency optional.
+    pub optional: bool,
+    /// Is the depending crate version yanked.
+    pub yanked: bool,
+}
+
+/// The reverse dependencies index.
+///
+/// It maps the canonical name of a crate to every crate version (hosted in this registry)
+/// which declares a dependency on it.
+/// It is built once at startup from the crate index and then kept up-to-date incrementally
+/// (on publication, yanking and unyanking).
+#[derive(Debug, Default)]
+pub struct ReverseDependencies {
+    dependents: RwLock<HashMap<String, Vec<Dependent>>>,
+}
+
+impl ReverseDependencies {
+    /// Creates an empty reverse dependencies index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert_record(dependents: &mut HashMap<String, Vec<Dependent>>, record: &CrateVersion) {
+        let yanked = record.yanked.unwrap_or(false);
+        let deps: Vec<_> = record
+            .deps
+            .iter()
+            //? Dependencies from other registries cannot be hosted here.
+            .filter(|dep| dep.registry.is_none())
+            .map(|dep| {
+                //? If the dependency is renamed, the actual crate name is in `package`.
+                let dep_name = dep.package.as_deref().unwrap_or(dep.name.as_str());
+                (utils::canonical_name(dep_name), dep)
+            })
+            .collect();
+
+        //? Drop the entries of this version (if it was already added), once per depended-upon crate
+        //? (a crate can be depended upon more than once, like both as a normal and as a dev-dependency).
+        let dep_names: HashSet<&String> = deps.iter().map(|(dep_name, _)| dep_name).collect();
+        for dep_name in dep_names {
+            if let Some(entries) = dependents.get_mut(dep_name.as_str()) {
+                entries.retain(|it| !(it.name == record.name && it.vers == record.vers));
+            }
+        }
+
+        for (dep_name, dep) in deps.iter() {
+            let entries = dependents.entry(dep_name.clone()).or_default();
+            entries.push(Dependent {
+                name: record.name.clone(),
+                vers: record.vers.clone(),
+                req: dep.req.clone(),
+                kind: dep.kind,
+                optional: dep.optional,
+                yanked,
+            });
+        }
+    }
+
+    /// Adds the dependencies of a newly published crate version to the index.
+    pub fn add_record(&self, record: &CrateVersion) -> Result<(), Error> {
+        let mut dependents = self
+            .dependents
+            .write()
+            .map_err(|error| Error::PoisonedError(error.to_string()))?;
+        Self::insert_record(&mut dependents, record);
+        Ok(())
+    }
+
+    /// Updates the yanked status of every dependency declared by the given crate version.
+    pub fn set_yanked(&self, name: &str, version: &Version, yanked: bool) -> Result<(), Error> {
+        let mut dependents = self
+            .dependents
+            .write()
+            .map_err(|error| Error::PoisonedError(error.to_string()))?;
+        dependents
+            .values_mut()
+            .flat_map(|entries| entries.iter_mut())
+            .filter(|it| it.name == name && it.vers == *version)
+            .for_each(|it| it.yanked = yanked);
+        Ok(())
+    }
+
+    /// Lists the crate versions which depend on the given crate (using its canonical name).
+    ///
+    /// Yanked versions are excluded unless `include_yanked` is set.
+    /// The results are sorted by crate name, and then by descending version.
+    pub fn dependents(
+        &self,
+        canon_name: &str,
+        include_yanked: bool,
+    ) -> Result<Vec<Dependent>, Error> {
+        let dependents = self
+            .dependents
+            .read()
+            .map_err(|error| Error::PoisonedError(error.to_string()))?;
+        let mut found: Vec<Dependent> = dependents
+            .get(canon_name)
+            .into_iter()
+            .flatten()
+            .filter(|it| include_yanked || !it.yanked)
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| b.vers.cmp(&a.vers)));
+        Ok(found)
+    }
+
+    /// (Re)builds the whole reverse dependencies index from the crate index.
+    pub async fn index_all(state: &Arc<AppState>) -> Result<(), Error> {
+        tracing::info!("building reverse dependencies index");
+        let names = state
+            .db
+            .run(|conn| crates::table.select(crates::name).load::<String>(conn))
+            .await?;
+
+        //? Reading the crate index is blocking, so it is done on the blocking thread pool.
+        utils::run_blocking(state, move |state| {
+            let mut dependents = HashMap::new();
+            for name in names {
+                let records = match state.index.all_records(name.as_str()) {
+                    Ok(records) => records,
+                    Err(error) => {
+                        tracing::warn!("could not read index records of '{name}': {error}");
+                        continue;
+                    }
+                };
+                for record in records.iter() {
+                    Self::insert_record(&mut dependents, record);
+                }
+            }
+
+            *state
+                .rdeps
+                .dependents
+                .write()
+                .map_err(|error| Error::PoisonedError(error.to_string()))? = dependents;
+
+            Ok(())
+        })
+        .await
+    }
+}
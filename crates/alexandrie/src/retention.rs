@@ -0,0 +1,213 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use semver::Version;
+
+use alexandrie_index::{CrateVersion, Indexer};
+use alexandrie_storage::{Storage, Store};
+
+use crate::config::retention::RetentionConfig;
+use crate::config::AppState;
+use crate::db::models::NewPrunedTarball;
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::Error;
+use crate::utils;
+
+/// The crate tarball retention policy.
+///
+/// For each minor version of a crate, only the tarballs of the latest patch releases are kept in the storage.
+/// The pruned tarballs are either offloaded to another storage or deleted.
+/// Yanked versions are never pruned, and the index records are left untouched (so Cargo still sees every version).
+pub struct RetentionPolicy {
+    /// How many of the latest patch releases to keep the tarballs of, for each minor version.
+    pub keep_patch_releases: usize,
+    /// How often to apply the retention policy.
+    pub interval: Duration,
+    /// Whether to only log which tarballs would be pruned, without altering the storage.
+    pub dry_run: bool,
+    /// The storage to offload the pruned tarballs to (they are deleted if not set).
+    pub offload: Option<Storage>,
+}
+
+impl From<RetentionConfig> for RetentionPolicy {
+    fn from(config: RetentionConfig) -> Self {
+        Self {
+            //? The latest patch release of a minor version is always kept.
+            keep_patch_releases: config.keep_patch_releases.max(1),
+            interval: Duration::from_secs(config.interval.max(1) * 60 * 60),
+            dry_run: config.dry_run,
+            offload: config.offload.map(Storage::from),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Returns the versions whose tarballs fall outside of the retention policy.
+    pub fn prunable_versions(&self, records: Vec<CrateVersion>) -> Vec<Version> {
+        let mut groups: BTreeMap<(u64, u64), Vec<Version>> = BTreeMap::new();
+        for record in records {
+            //? Yanked versions are left untouched.
+            if record.yanked.unwrap_or(false) {
+                continue;
+            }
+            let key = (record.vers.major, record.vers.minor);
+            groups.entry(key).or_default().push(record.vers);
+        }
+
+        groups
+            .into_values()
+            .flat_map(|mut versions| {
+                versions.sort_by(|a, b| b.cmp(a));
+                versions.into_iter().skip(self.keep_patch_releases)
+            })
+            .collect()
+    }
+}
+
+/// Spawns the task applying the retention policy periodically (if one is configured).
+pub fn spawn(state: Arc<AppState>) {
+    let Some(period) = state.retention.as_ref().map(|policy| policy.interval) else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(error) = apply(&state).await {
+                tracing::error!("could not apply the tarball retention policy: {error}");
+            }
+        }
+    });
+}
+
+/// Applies the retention policy to every crate of the registry.
+pub async fn apply(state: &Arc<AppState>) -> Result<(), Error> {
+    if state.retention.is_none() {
+        return Ok(());
+    }
+    if state.general.read_only {
+        tracing::info!("skipping the tarball retention policy (the registry is in read-only mode)");
+        return Ok(());
+    }
+
+    tracing::info!("applying the tarball retention policy");
+    let (crates, already_pruned) = state
+        .db
+        .run(|conn| {
+            let crates = crates::table
+                .select((crates::id, crates::name))
+                .load::<(i64, String)>(conn)?;
+            let already_pruned = pruned_tarballs::table
+                .select((pruned_tarballs::crate_id, pruned_tarballs::version))
+                .load::<(i64, String)>(conn)?;
+            Ok::<_, Error>((crates, already_pruned))
+        })
+        .await?;
+    let already_pruned: HashSet<(i64, String)> = already_pruned.into_iter().collect();
+
+    //? Reading the crate index is blocking, so it is done on the blocking thread pool.
+    let prunable = utils::run_blocking(state, move |state| {
+        let Some(policy) = state.retention.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut prunable = Vec::new();
+        for (crate_id, name) in crates {
+            let records = match state.index.all_records(name.as_str()) {
+                Ok(records) => records,
+                Err(error) => {
+                    tracing::warn!("could not read index records of '{name}': {error}");
+                    continue;
+                }
+            };
+
+            for version in policy.prunable_versions(records) {
+                if already_pruned.contains(&(crate_id, version.to_string())) {
+                    continue;
+                }
+
+                if policy.dry_run {
+                    tracing::info!("(dry-run) would prune the tarball of `{name}#{version}`");
+                    continue;
+                }
+
+                prunable.push((crate_id, name.clone(), version));
+            }
+        }
+
+        prunable
+    })
+    .await;
+
+    for (crate_id, name, version) in prunable {
+        //? Record the pruned tarball before removing it, so that its downloads never reach a missing tarball.
+        let now = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+        let recorded = version.to_string();
+        state
+            .db
+            .run(move |conn| {
+                let entry = NewPrunedTarball {
+                    crate_id,
+                    version: recorded.as_str(),
+                    pruned_at: now.as_str(),
+                };
+                diesel::insert_into(pruned_tarballs::table)
+                    .values(entry)
+                    .execute(conn)?;
+                Ok::<_, Error>(())
+            })
+            .await?;
+
+        //? Offloading and deleting the tarball is blocking, so it is done on the blocking thread pool.
+        let (pruned_name, pruned_version) = (name.clone(), version.clone());
+        let outcome = utils::run_blocking(state, move |state| match state.retention.as_ref() {
+            Some(policy) => prune(state, policy, pruned_name.as_str(), pruned_version),
+            None => Ok(()),
+        })
+        .await;
+        if let Err(error) = outcome {
+            tracing::warn!("could not prune the tarball of `{name}#{version}`: {error}");
+
+            //? The tarball is still there, so its downloads can go on.
+            let recorded = version.to_string();
+            state
+                .db
+                .run(move |conn| {
+                    diesel::delete(
+                        pruned_tarballs::table
+                            .filter(pruned_tarballs::crate_id.eq(crate_id))
+                            .filter(pruned_tarballs::version.eq(recorded.as_str())),
+                    )
+                    .execute(conn)?;
+                    Ok::<_, Error>(())
+                })
+                .await?;
+            continue;
+        }
+
+        tracing::info!("pruned the tarball of `{name}#{version}`");
+    }
+
+    Ok(())
+}
+
+/// Offloads (or deletes) the tarball of a given crate version.
+fn prune(
+    state: &AppState,
+    policy: &RetentionPolicy,
+    name: &str,
+    version: Version,
+) -> Result<(), Error> {
+    if let Some(offload) = policy.offload.as_ref() {
+        let data = state.storage.get_crate(name, version.clone())?;
+        offload.store_crate(name, version.clone(), data)?;
+    }
+
+    state.storage.delete_crate(name, version)?;
+    Ok(())
+}
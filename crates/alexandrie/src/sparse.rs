@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::headers::{ContentType, IfNoneMatch};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::TypedHeader;
+use diesel::prelude::*;
+use ring::digest as hasher;
+
+use alexandrie_index::Indexer;
+
+use crate::config::AppState;
+use crate::db::schema::*;
+use crate::error::ApiError;
+use crate::utils;
+use crate::utils::auth::api::Auth;
+use crate::utils::response::conditional;
+use crate::utils::visibility;
+
+/// Computes the path of a crate's file in the index, relative to the index's root.
+///
+/// This is the same layout as the one of the index's git repository (lowercased, as per the sparse protocol).
+fn record_path(name: &str) -> String {
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{0}/{name}", &name[..1]),
+        _ => format!("{0}/{1}/{name}", &name[0..2], &name[2..4]),
+    }
+}
+
+/// Route to serve the files of the crate index over HTTP (using Cargo's sparse registry protocol).
+///
+/// This makes the index usable with the `sparse+<url>/index/` registry URL.
+/// Responses carry an entity tag (the SHA-256 digest of the file), for Cargo and caches to revalidate them.  
+/// The records of private crates are only served to clients authenticating as one of their owners (or collaborators).
+pub(crate) async fn get(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<String>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+    user: Option<Auth>,
+) -> Result<Response, ApiError> {
+    let path = path.trim_start_matches('/').to_string();
+
+    let contents = if path == "config.json" {
+        let config = utils::run_blocking(&state, |state| state.index_configuration()).await?;
+        json::to_vec(&config)?
+    } else {
+        //? Is the path the one expected for this crate name?
+        let requested = path.rsplit('/').next().unwrap_or_default().to_string();
+        if requested.is_empty() || !requested.is_ascii() || record_path(requested.as_str()) != path
+        {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+
+        //? Does this crate exist (under that exact name), and is it accessible to the client?
+        let canon_name = utils::canonical_name(requested.as_str());
+        let author_id = user.map(|Auth(author)| author.id);
+        let maybe_name: Option<String> = state
+            .db
+            .run(move |conn| {
+                let maybe_crate = crates::table
+                    .select((crates::id, crates::name))
+                    .filter(crates::canon_name.eq(canon_name))
+                    .first::<(i64, String)>(conn)
+                    .optional()?;
+                match maybe_crate {
+                    Some((id, _)) if !visibility::can_access(conn, id, author_id)? => Ok(None),
+                    maybe_crate => {
+                        Ok::<_, diesel::result::Error>(maybe_crate.map(|(_, name)| name))
+                    }
+                }
+            })
+            .await?;
+        let Some(name) = maybe_name.filter(|name| name.to_ascii_lowercase() == requested) else {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        };
+
+        let records =
+            utils::run_blocking(&state, move |state| state.index.all_records(&name)).await?;
+
+        let mut contents = Vec::new();
+        for record in records {
+            json::to_writer(&mut contents, &record)?;
+            contents.push(b'\n');
+        }
+        contents
+    };
+
+    let digest = hasher::digest(&hasher::SHA256, contents.as_slice());
+    let etag = conditional::etag(hex::encode(digest.as_ref()).as_str());
+
+    //? Is the client's cached copy still up-to-date?
+    let if_none_match = if_none_match.as_ref().map(|TypedHeader(header)| header);
+    if conditional::is_not_modified(if_none_match, None, &etag, None) {
+        return Ok((StatusCode::NOT_MODIFIED, TypedHeader(etag)).into_response());
+    }
+
+    let content_type = if path == "config.json" {
+        ContentType::json()
+    } else {
+        ContentType::text()
+    };
+    Ok((TypedHeader(etag), TypedHeader(content_type), contents).into_response())
+}
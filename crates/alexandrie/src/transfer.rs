@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::{CrateVersion, Indexer};
+use alexandrie_storage::Store;
+
+use crate::api::crates::publish::{link_categories, link_keywords};
+use crate::config::AppState;
+use crate::db::models::{Crate, NewAuthor, NewCrate, NewCrateAuthor};
+use crate::db::schema::*;
+use crate::db::{Connection, DATETIME_FORMAT};
+use crate::error::Error;
+use crate::utils;
+use crate::utils::owners::OwnerRole;
+use crate::utils::visibility::CrateVisibility;
+
+/// How many crates are read from the database at once, while exporting.
+const EXPORT_BATCH_SIZE: i64 = 100;
+
+/// An owner of an exported crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedOwner {
+    /// The owner's email address (used to match authors when importing).
+    pub email: String,
+    /// The owner's displayable name.
+    pub name: String,
+    /// The owner's role for the crate.
+    #[serde(default)]
+    pub role: OwnerRole,
+}
+
+/// An exported crate, as written on a single line of an export file.
+///
+/// Tarballs are not part of the export: they are referenced by their storage key
+/// (the crate's name and version), so the crate store must be copied (or shared) separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedCrate {
+    /// The crate's name.
+    pub name: String,
+    /// The crate's description.
+    pub description: Option<String>,
+    /// The crate's creation date.
+    pub created_at: String,
+    /// The crate's last updated date.
+    pub updated_at: String,
+    /// The crate's download count.
+    pub downloads: i64,
+    /// The URL to the crate's documentation.
+    pub documentation: Option<String>,
+    /// The URL to the crate's repository.
+    pub repository: Option<String>,
+    /// The native library linked by the crate's latest version.
+    pub links: Option<String>,
+    /// The crate's visibility.
+    #[serde(default)]
+    pub visibility: CrateVisibility,
+    /// The crate's owners.
+    pub owners: Vec<ExportedOwner>,
+    /// The crate's keywords.
+    pub keywords: Vec<String>,
+    /// The crate's categories (as their tags).
+    pub categories: Vec<String>,
+    /// The crate's index records (one per version).
+    pub records: Vec<CrateVersion>,
+}
+
+/// Reads a batch of crates (along with their owners, keywords and categories) from the database,
+/// starting after the crate of the given ID (in ascending ID order).
+///
+/// The index records are left empty, to be filled from the crate index.
+fn read_batch(
+    conn: &mut Connection,
+    after_id: i64,
+    limit: i64,
+) -> QueryResult<Vec<(i64, ExportedCrate)>> {
+    let crates = crates::table
+        .filter(crates::id.gt(after_id))
+        .order_by(crates::id.asc())
+        .limit(limit)
+        .load::<Crate>(conn)?;
+    let ids: Vec<i64> = crates.iter().map(|krate| krate.id).collect();
+
+    let mut owners: HashMap<i64, Vec<ExportedOwner>> = HashMap::new();
+    let found = crate_authors::table
+        .inner_join(authors::table)
+        .select((
+            crate_authors::crate_id,
+            authors::email,
+            authors::name,
+            crate_authors::role,
+        ))
+        .filter(crate_authors::crate_id.eq_any(&ids))
+        .load::<(i64, String, String, String)>(conn)?;
+    for (crate_id, email, name, role) in found {
+        let role = OwnerRole::parse(role.as_str());
+        owners
+            .entry(crate_id)
+            .or_default()
+            .push(ExportedOwner { email, name, role });
+    }
+
+    let mut keywords: HashMap<i64, Vec<String>> = HashMap::new();
+    let found = crate_keywords::table
+        .inner_join(keywords::table)
+        .select((crate_keywords::crate_id, keywords::name))
+        .filter(crate_keywords::crate_id.eq_any(&ids))
+        .load::<(i64, String)>(conn)?;
+    for (crate_id, keyword) in found {
+        keywords.entry(crate_id).or_default().push(keyword);
+    }
+
+    let mut categories: HashMap<i64, Vec<String>> = HashMap::new();
+    let found = crate_categories::table
+        .inner_join(categories::table)
+        .select((crate_categories::crate_id, categories::tag))
+        .filter(crate_categories::crate_id.eq_any(&ids))
+        .load::<(i64, String)>(conn)?;
+    for (crate_id, category) in found {
+        categories.entry(crate_id).or_default().push(category);
+    }
+
+    let exported = crates
+        .into_iter()
+        .map(|krate| {
+            let exported = ExportedCrate {
+                owners: owners.remove(&krate.id).unwrap_or_default(),
+                keywords: keywords.remove(&krate.id).unwrap_or_default(),
+                categories: categories.remove(&krate.id).unwrap_or_default(),
+                records: Vec::new(),
+                name: krate.name,
+                description: krate.description,
+                created_at: krate.created_at,
+                updated_at: krate.updated_at,
+                downloads: krate.downloads,
+                documentation: krate.documentation,
+                repository: krate.repository,
+                links: krate.links,
+                visibility: CrateVisibility::parse(krate.visibility.as_str()),
+            };
+            (krate.id, exported)
+        })
+        .collect();
+
+    Ok(exported)
+}
+
+/// Finds the name of the last crate fully written to an existing export file,
+/// and truncates the file after it (to drop a partially written line, if any).
+fn resume_point(path: &Path) -> Result<Option<String>, Error> {
+    let mut file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+
+    let mut last_name = None;
+    let mut complete_len = 0;
+    let mut reader = BufReader::new(&mut file);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line)?;
+        //? A line without its final newline has not been fully written.
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        match json::from_str::<ExportedCrate>(line.trim_end()) {
+            Ok(exported) => {
+                last_name = Some(exported.name);
+                complete_len += read as u64;
+            }
+            Err(_) => break,
+        }
+    }
+    drop(reader);
+
+    file.set_len(complete_len)?;
+    Ok(last_name)
+}
+
+/// Exports the whole registry (crates, owners, keywords, categories, download counts and index records)
+/// as JSON lines (one crate per line) into the given file.
+///
+/// Crates are streamed from the database in batches, in ascending ID order.
+/// When resuming, the crates already present in the export file are skipped.
+pub async fn export(state: Arc<AppState>, path: &Path, resume: bool) -> Result<(), Error> {
+    //? Find where to resume from (the ID of the last exported crate).
+    let resume_name = if resume && path.exists() {
+        resume_point(path)?
+    } else {
+        None
+    };
+    let mut after_id = match resume_name {
+        Some(name) => {
+            tracing::info!("resuming the export after crate '{name}'");
+            let canon_name = utils::canonical_name(name.as_str());
+            state
+                .db
+                .run(move |conn| {
+                    crates::table
+                        .select(crates::id)
+                        .filter(crates::canon_name.eq(canon_name))
+                        .first::<i64>(conn)
+                })
+                .await?
+        }
+        None => 0,
+    };
+
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resume)
+        .truncate(!resume)
+        .open(path)?;
+    let mut writer = BufWriter::new(file);
+    let mut count = 0;
+
+    loop {
+        let batch = state
+            .db
+            .run(move |conn| read_batch(conn, after_id, EXPORT_BATCH_SIZE))
+            .await?;
+        let Some((last_id, _)) = batch.last() else {
+            break;
+        };
+        after_id = *last_id;
+
+        let batch = utils::run_blocking(&state, move |state| {
+            batch
+                .into_iter()
+                .map(|(_, mut exported)| {
+                    exported.records = state.index.all_records(exported.name.as_str())?;
+                    Ok(exported)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .await?;
+
+        for exported in batch {
+            json::to_writer(&mut writer, &exported)?;
+            writer.write_all(b"\n")?;
+            count += 1;
+        }
+        writer.flush()?;
+    }
+
+    tracing::info!("exported {count} crates into '{0}'", path.display());
+    Ok(())
+}
+
+/// Gets the ID of the author with the given email address, creating the author if needed.
+///
+/// Created authors have no password nor linked accounts, they can set a password by resetting it.
+fn get_or_create_author(conn: &mut Connection, owner: &ExportedOwner) -> QueryResult<i64> {
+    let query = authors::table
+        .select(authors::id)
+        .filter(authors::email.eq(owner.email.as_str()));
+    if let Some(id) = query.first::<i64>(conn).optional()? {
+        return Ok(id);
+    }
+
+    let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+    let new_author = NewAuthor {
+        email: owner.email.as_str(),
+        name: owner.name.as_str(),
+        passwd: None,
+        github_id: None,
+        gitlab_id: None,
+        created_at: Some(created_at.as_str()),
+    };
+    diesel::insert_into(authors::table)
+        .values(new_author)
+        .execute(conn)?;
+    query.first::<i64>(conn)
+}
+
+/// Inserts an exported crate into the database (its crate entry, versions, owners, keywords and categories).
+fn import_crate(conn: &mut Connection, exported: &ExportedCrate) -> Result<(), Error> {
+    let canon_name = utils::canonical_name(exported.name.as_str());
+    let new_crate = NewCrate {
+        name: exported.name.as_str(),
+        canon_name: canon_name.as_str(),
+        description: exported.description.as_deref(),
+        created_at: exported.created_at.as_str(),
+        updated_at: exported.updated_at.as_str(),
+        documentation: exported.documentation.as_deref(),
+        repository: exported.repository.as_deref(),
+        links: exported.links.as_deref(),
+    };
+    diesel::insert_into(crates::table)
+        .values(new_crate)
+        .execute(conn)?;
+
+    let crate_id = crates::table
+        .select(crates::id)
+        .filter(crates::canon_name.eq(canon_name.as_str()))
+        .first::<i64>(conn)?;
+    diesel::update(crates::table.find(crate_id))
+        .set((
+            crates::downloads.eq(exported.downloads),
+            crates::visibility.eq(exported.visibility.as_str()),
+            crates::non_yanked_versions.eq(utils::versions::count_available(&exported.records)),
+        ))
+        .execute(conn)?;
+
+    //? Record the crate's versions (their publication dates are unknown, so they are approximated).
+    for record in exported.records.iter() {
+        utils::versions::ensure_recorded(conn, crate_id, record.vers.to_string().as_str())?;
+    }
+
+    for owner in exported.owners.iter() {
+        let author_id = get_or_create_author(conn, owner)?;
+        diesel::insert_into(crate_authors::table)
+            .values(NewCrateAuthor {
+                crate_id,
+                author_id,
+                role: owner.role.as_str(),
+            })
+            .execute(conn)?;
+    }
+
+    let keywords = Some(exported.keywords.clone()).filter(|keywords| !keywords.is_empty());
+    link_keywords(conn, crate_id, &keywords)?;
+
+    //? Categories unknown to this registry are dropped.
+    let categories = Some(exported.categories.clone()).filter(|categories| !categories.is_empty());
+    link_categories(conn, crate_id, &categories)?;
+
+    Ok(())
+}
+
+/// Imports the crates of an export file (as produced by `export`) into the registry's database and crate index.
+///
+/// The file is read line by line, and each crate is imported within its own database transaction.
+/// Crates already present in the database are skipped, which makes interrupted imports resumable.
+/// The index records are added before the database entries, and the crate index is only committed once at the end.
+pub async fn import(state: Arc<AppState>, path: &Path) -> Result<(), Error> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    let (mut imported, mut skipped) = (0, 0);
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let exported: ExportedCrate = json::from_str(line.as_str()).map_err(|err| {
+            tracing::error!("invalid crate on line {0} of the export file", idx + 1);
+            err
+        })?;
+
+        //? Has this crate already been imported?
+        let canon_name = utils::canonical_name(exported.name.as_str());
+        let exists = state
+            .db
+            .run(move |conn| {
+                crates::table
+                    .select(crates::id)
+                    .filter(crates::canon_name.eq(canon_name))
+                    .first::<i64>(conn)
+                    .optional()
+            })
+            .await?
+            .is_some();
+        if exists {
+            skipped += 1;
+            continue;
+        }
+
+        //? Add the missing index records (they may have been added by an interrupted import).
+        let exported = utils::run_blocking(&state, move |state| {
+            let name = exported.name.as_str();
+            let present = state.index.all_records(name).unwrap_or_default();
+            for record in exported.records.iter() {
+                if state
+                    .storage
+                    .crate_metadata(name, record.vers.clone())
+                    .is_err()
+                {
+                    tracing::warn!(
+                        "the tarball of `{name}#{0}` is missing from the crate store",
+                        record.vers
+                    );
+                }
+                if !present.iter().any(|it| it.vers == record.vers) {
+                    state.index.add_record(record.clone())?;
+                }
+            }
+            Ok::<_, Error>(exported)
+        })
+        .await?;
+
+        state
+            .db
+            .transaction(move |conn| import_crate(conn, &exported))
+            .await?;
+        imported += 1;
+    }
+
+    //? Commit whenever records were added (even if all crates were skipped, records may remain from an interrupted import).
+    let msg = format!("Importing {imported} crates");
+    utils::run_blocking(&state, move |state| {
+        if state.index.has_uncommitted_changes()? {
+            state.index.commit_and_push(&msg)?;
+        }
+        Ok::<_, Error>(())
+    })
+    .await?;
+
+    tracing::info!(
+        "imported {imported} crates from '{0}' ({skipped} already present)",
+        path.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::db::models::NewCrateAlias;
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::db::DATETIME_FORMAT;
+
+/// Finds the crate an alias refers to, returning its ID and its name.
+///
+/// The alias is expected in its canonical form (see [`crate::utils::canonical_name`]).
+pub fn resolve(conn: &mut Connection, alias: &str) -> QueryResult<Option<(i64, String)>> {
+    crate_aliases::table
+        .inner_join(crates::table)
+        .select((crates::id, crates::name))
+        .filter(crate_aliases::alias.eq(alias))
+        .first(conn)
+        .optional()
+}
+
+/// Lists the aliases of a crate (in their canonical form, sorted alphabetically).
+pub fn list(conn: &mut Connection, crate_id: i64) -> QueryResult<Vec<String>> {
+    crate_aliases::table
+        .select(crate_aliases::alias)
+        .filter(crate_aliases::crate_id.eq(crate_id))
+        .order_by(crate_aliases::alias.asc())
+        .load(conn)
+}
+
+/// Adds an alias to a crate (the alias is expected in its canonical form, and to not be taken yet).
+pub fn add(conn: &mut Connection, crate_id: i64, alias: &str) -> QueryResult<()> {
+    let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+    diesel::insert_into(crate_aliases::table)
+        .values(NewCrateAlias {
+            crate_id,
+            alias,
+            created_at: created_at.as_str(),
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Removes an alias (whichever crate it refers to), returning whether it existed.
+pub fn remove(conn: &mut Connection, alias: &str) -> QueryResult<bool> {
+    let deleted = diesel::delete(crate_aliases::table.filter(crate_aliases::alias.eq(alias)))
+        .execute(conn)?;
+    Ok(deleted > 0)
+}
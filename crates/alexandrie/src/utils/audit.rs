@@ -0,0 +1,125 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::{AuditLogEntry, NewAuditLogEntry};
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::db::DATETIME_FORMAT;
+
+/// An operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditAction {
+    /// A crate version got published.
+    Publish,
+    /// A crate version got yanked.
+    Yank,
+    /// A crate version got un-yanked.
+    Unyank,
+    /// The tarball of a crate version got replaced (by a superadmin).
+    ReplaceTarball,
+    /// An author became an owner (or a collaborator) of the crate, by accepting an invitation.
+    AddOwner,
+    /// An author got removed from the owners (or collaborators) of the crate.
+    RemoveOwner,
+    /// An alias got added to the crate (by a superadmin).
+    AddAlias,
+    /// An alias got removed from the crate (by a superadmin).
+    RemoveAlias,
+    /// An unowned crate got an owner (assigned by a superadmin, or claimed by proving control of its repository).
+    ClaimOwnership,
+}
+
+impl AuditAction {
+    /// Returns the action's representation, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Publish => "publish",
+            AuditAction::Yank => "yank",
+            AuditAction::Unyank => "unyank",
+            AuditAction::ReplaceTarball => "replace-tarball",
+            AuditAction::AddOwner => "add-owner",
+            AuditAction::RemoveOwner => "remove-owner",
+            AuditAction::AddAlias => "add-alias",
+            AuditAction::RemoveAlias => "remove-alias",
+            AuditAction::ClaimOwnership => "claim-ownership",
+        }
+    }
+}
+
+/// Records an operation performed by an author on a crate (or one of its versions) in the audit log.
+pub fn record(
+    conn: &mut Connection,
+    crate_id: i64,
+    author_id: i64,
+    action: AuditAction,
+    version: Option<&str>,
+    reason: Option<&str>,
+) -> QueryResult<()> {
+    let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+    diesel::insert_into(audit_log::table)
+        .values(NewAuditLogEntry {
+            crate_id,
+            author_id: Some(author_id),
+            action: action.as_str(),
+            version,
+            reason,
+            created_at: created_at.as_str(),
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// An entry of a crate's audit log, as shown to its owners.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrateEvent {
+    /// The performed operation (like `publish`, `yank` or `add-owner`).
+    pub action: String,
+    /// The related version (if the operation is about a specific version).
+    pub version: Option<String>,
+    /// The reason given for the operation, or its details for ownership changes (if any).
+    pub reason: Option<String>,
+    /// The name of the author who performed the operation (if they still exist).
+    pub author: Option<String>,
+    /// The date at which the operation was performed.
+    pub created_at: String,
+}
+
+/// Counts the entries of the audit log of a crate.
+pub fn count_crate_events(conn: &mut Connection, crate_id: i64) -> QueryResult<i64> {
+    audit_log::table
+        .filter(audit_log::crate_id.eq(crate_id))
+        .count()
+        .get_result(conn)
+}
+
+/// Lists the entries of the audit log of a crate (the most recent first).
+pub fn crate_events(
+    conn: &mut Connection,
+    crate_id: i64,
+    limit: usize,
+    offset: usize,
+) -> QueryResult<Vec<CrateEvent>> {
+    let entries = audit_log::table
+        .left_join(authors::table)
+        .select((audit_log::all_columns, authors::name.nullable()))
+        .filter(audit_log::crate_id.eq(crate_id))
+        .order_by((audit_log::created_at.desc(), audit_log::id.desc()))
+        .limit(limit as i64)
+        .offset(i64::try_from(offset).unwrap_or(i64::MAX))
+        .load::<(AuditLogEntry, Option<String>)>(conn)?;
+
+    let events = entries
+        .into_iter()
+        .map(|(entry, author)| CrateEvent {
+            action: entry.action,
+            version: entry.version,
+            reason: entry.reason,
+            author,
+            created_at: entry.created_at,
+        })
+        .collect();
+
+    Ok(events)
+}
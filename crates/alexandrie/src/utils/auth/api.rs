@@ -11,6 +11,7 @@ use axum::{async_trait, RequestPartsExt, TypedHeader};
 
 use crate::config::AppState;
 use crate::db::models::Author;
+use crate::error::{AlexError, ApiError};
 use crate::utils;
 
 /// The authentication extractor for the programmatic API of `alexandrie`.
@@ -44,7 +45,7 @@ impl DerefMut for Auth {
 
 #[async_trait]
 impl FromRequestParts<Arc<AppState>> for Auth {
-    type Rejection = StatusCode;
+    type Rejection = ApiError;
 
     async fn from_request_parts(
         parts: &mut Parts,
@@ -53,7 +54,10 @@ impl FromRequestParts<Arc<AppState>> for Auth {
         let TypedHeader(authorization) = parts
             .extract::<TypedHeader<Authorization>>()
             .await
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+            .map_err(|_| {
+                let message = "this endpoint requires an authentication token (in the `Authorization` header)";
+                ApiError::new(StatusCode::UNAUTHORIZED, message)
+            })?;
 
         let token = authorization.token().to_string();
 
@@ -64,8 +68,8 @@ impl FromRequestParts<Arc<AppState>> for Auth {
                 utils::checks::get_author(conn, token)
             })
             .await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-            .ok_or(StatusCode::UNAUTHORIZED)?;
+            .map_err(ApiError::from)?
+            .ok_or(AlexError::InvalidToken)?;
 
         Ok(Auth(author))
     }
@@ -0,0 +1,267 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use ldap3::{dn_escape, ldap_escape, LdapConn, LdapConnSettings, ResultEntry, Scope, SearchEntry};
+use ring::digest as hasher;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::config::frontend::auth::ldap::LdapAuthConfig;
+use crate::db::models::{NewAuthor, NewSalt};
+use crate::db::schema::*;
+use crate::db::DATETIME_FORMAT;
+use crate::error::Error;
+use crate::utils::auth::backend::{AuthBackend, AuthContext, AuthError, AuthorId};
+use crate::utils::auth::backend::{Credentials, Identity};
+
+/// The "ldap" authentication backend, binding to an LDAP server (or an Active Directory) as the user to check their password.
+///
+/// Authors are provisioned (matched by email address) the first time they log in, if registration is allowed.
+/// Existing authors with a local password are never matched, since anyone could have registered them (the email addresses
+/// given at registration are not verified), which would otherwise hand their crates and tokens over to the LDAP user.
+#[derive(Debug, Clone)]
+pub struct LdapAuthBackend {
+    config: LdapAuthConfig,
+}
+
+impl LdapAuthBackend {
+    /// Create a new [`LdapAuthBackend`] from an [`LdapAuthConfig`].
+    pub fn new(config: &LdapAuthConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Binds as the user (to check their password), returning their identity.
+    ///
+    /// Every failure which depends on the user (unknown user, ambiguous entry, wrong password) is reported
+    /// as the same generic error, so that it does not reveal whether the user exists.
+    fn bind(&self, credentials: Credentials<'_>) -> Result<Identity, AuthError> {
+        let config = &self.config;
+        //? The connection timeout only covers establishing the connection, every operation gets its own timeout.
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(config.timeout))
+            .set_starttls(config.starttls);
+        let mut ldap =
+            LdapConn::with_settings(settings, config.url.as_str()).map_err(Error::from)?;
+
+        let outcome = self.bind_user(&mut ldap, credentials);
+        let _ = ldap.unbind();
+        outcome
+    }
+
+    /// Binds as the user over an established connection (see [`LdapAuthBackend::bind`]).
+    fn bind_user(
+        &self,
+        ldap: &mut LdapConn,
+        credentials: Credentials<'_>,
+    ) -> Result<Identity, AuthError> {
+        let config = &self.config;
+        let timeout = Duration::from_secs(config.timeout);
+        let attributes = vec![
+            config.email_attribute.as_str(),
+            config.name_attribute.as_str(),
+        ];
+
+        let entry = match config.bind_dn_template.as_deref() {
+            Some(template) => {
+                //? Bind directly as the user (using their DN, or their user principal name for Active Directory).
+                let bind_name = BindName::new(template, credentials.email);
+                (ldap.with_timeout(timeout))
+                    .simple_bind(bind_name.as_str(), credentials.password)
+                    .and_then(|result| result.success())
+                    .map_err(|_| AuthError::InvalidCredentials)?;
+
+                //? Get the entry the server bound to (searching as the user themselves).
+                //? It is resolved from the bind name rather than from the login filter, which may match other entries.
+                let (base, scope, filter) = match &bind_name {
+                    BindName::Dn(dn) => (dn.as_str(), Scope::Base, String::from("(objectClass=*)")),
+                    BindName::DownLevel(_, username) => (
+                        config.base_dn.as_str(),
+                        Scope::Subtree,
+                        format!("(sAMAccountName={0})", ldap_escape(username.as_str())),
+                    ),
+                    BindName::Principal(name) => (
+                        config.base_dn.as_str(),
+                        Scope::Subtree,
+                        format!("(userPrincipalName={0})", ldap_escape(name.as_str())),
+                    ),
+                };
+                let (entries, _) = (ldap.with_timeout(timeout))
+                    .search(base, scope, &filter, attributes)
+                    .and_then(|result| result.success())
+                    .map_err(Error::from)?;
+                single_entry(entries)?
+            }
+            None => {
+                //? Bind as the search user (if configured, the search is anonymous otherwise).
+                if let Some(bind_dn) = config.bind_dn.as_deref() {
+                    let bind_password = config.bind_password.as_deref().unwrap_or_default();
+                    (ldap.with_timeout(timeout))
+                        .simple_bind(bind_dn, bind_password)
+                        .and_then(|result| result.success())
+                        .map_err(Error::from)?;
+                }
+
+                //? Find the user's entry.
+                let filter = config.user_filter(&ldap_escape(credentials.email));
+                let (entries, _) = (ldap.with_timeout(timeout))
+                    .search(&config.base_dn, Scope::Subtree, &filter, attributes)
+                    .and_then(|result| result.success())
+                    .map_err(Error::from)?;
+                let entry = single_entry(entries)?;
+
+                //? Bind as the user, to check their password.
+                (ldap.with_timeout(timeout))
+                    .simple_bind(&entry.dn, credentials.password)
+                    .and_then(|result| result.success())
+                    .map_err(|_| AuthError::InvalidCredentials)?;
+                entry
+            }
+        };
+
+        let attribute = |name: &str| {
+            let values = entry.attrs.get(name)?;
+            values.first().cloned()
+        };
+
+        //? Authors are identified by their email address, which the submitted identifier might not be.
+        let email = match attribute(config.email_attribute.as_str()) {
+            Some(email) => email,
+            None if credentials.email.contains('@') => String::from(credentials.email),
+            None => {
+                let dn = entry.dn.as_str();
+                tracing::warn!("the LDAP entry '{dn}' has no email address, refusing to log it in");
+                return Err(AuthError::InvalidCredentials);
+            }
+        };
+        let name = attribute(config.name_attribute.as_str()).unwrap_or_else(|| email.clone());
+        Ok(Identity { email, name })
+    }
+}
+
+/// The name to bind as the user logging in with, built from the `bind_dn_template`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BindName {
+    /// A DN (like `uid={username},ou=people,dc=example,dc=com`), where the identifier is escaped as an attribute value.
+    Dn(String),
+    /// A down-level logon name (like `CORP\{username}`, for Active Directory), along with the username.
+    DownLevel(String, String),
+    /// A user principal name (like `{username}@corp.example.com`, for Active Directory), used as-is.
+    Principal(String),
+}
+
+impl BindName {
+    /// Builds the bind name from the template, and the identifier submitted by the user.
+    fn new(template: &str, login: &str) -> Self {
+        //? Only DN templates escape the identifier, since DN escaping would alter user principal names.
+        if template.contains("={username}") {
+            BindName::Dn(template.replace("{username}", &dn_escape(login)))
+        } else if template.contains("\\{username}") {
+            BindName::DownLevel(template.replace("{username}", login), String::from(login))
+        } else {
+            BindName::Principal(template.replace("{username}", login))
+        }
+    }
+
+    /// Returns the name to bind with.
+    fn as_str(&self) -> &str {
+        match self {
+            BindName::Dn(name) | BindName::DownLevel(name, _) | BindName::Principal(name) => {
+                name.as_str()
+            }
+        }
+    }
+}
+
+/// Expects exactly one entry from a search (the user's entry).
+fn single_entry(mut entries: Vec<ResultEntry>) -> Result<SearchEntry, AuthError> {
+    if entries.len() != 1 {
+        return Err(AuthError::InvalidCredentials);
+    }
+    Ok(SearchEntry::construct(entries.remove(0)))
+}
+
+impl AuthBackend for LdapAuthBackend {
+    fn verify(&self, credentials: Credentials<'_>) -> Result<Option<Identity>, AuthError> {
+        //? An empty password would result in an unauthenticated bind, which LDAP servers accept.
+        if credentials.password.is_empty() {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        self.bind(credentials).map(Some)
+    }
+
+    fn provision(
+        &self,
+        ctx: &mut AuthContext<'_>,
+        identity: &Identity,
+    ) -> Result<AuthorId, AuthError> {
+        //? Is there already an author with this email address?
+        let maybe_author = authors::table
+            .select((authors::id, authors::passwd))
+            .filter(authors::email.eq(identity.email.as_str()))
+            .first::<(i64, Option<String>)>(ctx.conn)
+            .optional()?;
+        match maybe_author {
+            Some((author_id, None)) => return Ok(author_id),
+            Some((author_id, Some(_))) => {
+                //? Authors registered locally cannot be taken over by LDAP users (their email address is unverified).
+                tracing::warn!(
+                    "refusing to link the LDAP user '{0}' to the author #{author_id}, which has a local password (clear it to link them)",
+                    identity.email,
+                );
+                return Err(AuthError::InvalidCredentials);
+            }
+            None => {}
+        }
+
+        if !self.config.allow_registration {
+            return Err(AuthError::RegistrationForbidden);
+        }
+
+        //? Generate the user's authentication salt.
+        let decoded_generated_salt = {
+            let mut data = [0u8; 16];
+            let rng = SystemRandom::new();
+            rng.fill(&mut data).unwrap();
+            hasher::digest(&hasher::SHA512, data.as_ref())
+        };
+
+        //? Insert the new author data (without a password, which stays in the LDAP server).
+        let created_at = Utc::now().naive_utc().format(DATETIME_FORMAT).to_string();
+        let new_author = NewAuthor {
+            email: identity.email.as_str(),
+            name: identity.name.as_str(),
+            passwd: None,
+            github_id: None,
+            gitlab_id: None,
+            created_at: Some(created_at.as_str()),
+        };
+        diesel::insert_into(authors::table)
+            .values(new_author)
+            .execute(ctx.conn)?;
+
+        //? Fetch the newly-inserted author back.
+        let author_id = authors::table
+            .select(authors::id)
+            .filter(authors::email.eq(identity.email.as_str()))
+            .first::<i64>(ctx.conn)?;
+
+        //? Store the author's newly-generated authentication salt.
+        let encoded_generated_salt = hex::encode(decoded_generated_salt.as_ref());
+        let new_salt = NewSalt {
+            author_id,
+            salt: encoded_generated_salt.as_str(),
+        };
+        diesel::insert_into(salts::table)
+            .values(new_salt)
+            .execute(ctx.conn)?;
+
+        Ok(author_id)
+    }
+
+    fn hashes_passwords(&self) -> bool {
+        false
+    }
+}
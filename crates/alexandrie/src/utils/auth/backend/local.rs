@@ -0,0 +1,79 @@
+use diesel::prelude::*;
+
+use crate::config::frontend::auth::local::LocalAuthConfig;
+use crate::db::schema::*;
+use crate::utils::auth::backend::{AuthBackend, AuthContext, AuthError, AuthorId};
+use crate::utils::auth::backend::{Credentials, Identity};
+use crate::utils::auth::passwd::PasswordVerification;
+
+/// The "local" authentication backend, checking passwords against the hashes stored in the database.
+///
+/// Accounts are created using the registration form, so this backend never provisions new ones.
+#[derive(Debug, Clone, Default)]
+pub struct LocalAuthBackend {}
+
+impl LocalAuthBackend {
+    /// Create a new [`LocalAuthBackend`] from a [`LocalAuthConfig`].
+    pub fn new(_: &LocalAuthConfig) -> Self {
+        Self {}
+    }
+}
+
+impl AuthBackend for LocalAuthBackend {
+    fn authenticate(
+        &self,
+        ctx: &mut AuthContext<'_>,
+        credentials: Credentials<'_>,
+    ) -> Result<AuthorId, AuthError> {
+        //? Get the users' salt and expected hash.
+        let maybe_results: Option<(i64, String, Option<String>)> = salts::table
+            .inner_join(authors::table)
+            .select((authors::id, salts::salt, authors::passwd))
+            .filter(authors::email.eq(credentials.email))
+            .first(ctx.conn)
+            .optional()?;
+
+        //? Does the user exist?
+        let Some((author_id, encoded_salt, Some(encoded_expected_hash))) = maybe_results else {
+            return Err(AuthError::InvalidCredentials);
+        };
+
+        //? Decode the hex-encoded password.
+        let decoded_password =
+            hex::decode(credentials.password).map_err(|_| AuthError::MalformedPassword)?;
+
+        //? Verify client password against the expected hash.
+        let hasher = ctx.password_hasher;
+        let verification = hasher.verify(
+            decoded_password.as_slice(),
+            encoded_salt.as_str(),
+            encoded_expected_hash.as_str(),
+        );
+        let PasswordVerification::Match { needs_rehash } = verification else {
+            return Err(AuthError::InvalidCredentials);
+        };
+
+        //? Upgrade the stored hash if it uses an older algorithm (or weaker parameters).
+        if needs_rehash {
+            let encoded_derived_hash = hasher.hash(decoded_password.as_slice())?;
+            diesel::update(authors::table.find(author_id))
+                .set(authors::passwd.eq(encoded_derived_hash.as_str()))
+                .execute(ctx.conn)?;
+        }
+
+        Ok(author_id)
+    }
+
+    fn provision(
+        &self,
+        ctx: &mut AuthContext<'_>,
+        identity: &Identity,
+    ) -> Result<AuthorId, AuthError> {
+        let maybe_author_id = authors::table
+            .select(authors::id)
+            .filter(authors::email.eq(identity.email.as_str()))
+            .first::<i64>(ctx.conn)
+            .optional()?;
+        maybe_author_id.ok_or(AuthError::InvalidCredentials)
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::db::Connection;
+use crate::error::Error;
+use crate::utils::auth::passwd::PasswordHasher;
+
+/// The "local" authentication backend (using the passwords stored in the database).
+pub mod local;
+
+/// The "ldap" authentication backend (binding to an LDAP server).
+#[cfg(feature = "ldap")]
+pub mod ldap;
+
+pub use self::local::LocalAuthBackend;
+
+#[cfg(feature = "ldap")]
+pub use self::ldap::LdapAuthBackend;
+
+/// The ID of an author record.
+pub type AuthorId = i64;
+
+/// The credentials submitted using the login form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Credentials<'a> {
+    /// The submitted email address (or username, for the backends accepting them, like LDAP).
+    pub email: &'a str,
+    /// The submitted password (hashed by the browser, if the backend asks for it).
+    pub password: &'a str,
+}
+
+/// The identity of an authenticated user, as reported by an authentication backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    /// The user's email address.
+    pub email: String,
+    /// The user's displayable name.
+    pub name: String,
+}
+
+/// The things authentication backends can make use of.
+pub struct AuthContext<'a> {
+    /// The database connection (where author records live).
+    pub conn: &'a mut Connection,
+    /// The configured password hasher.
+    pub password_hasher: &'a PasswordHasher,
+}
+
+/// An authentication failure.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// The credentials are not valid (the message is intentionally vague about why).
+    #[error("invalid email/password combination.")]
+    InvalidCredentials,
+    /// The submitted password could not be decoded.
+    #[error("password decoding issue.")]
+    MalformedPassword,
+    /// The user is not known yet, and registration is forbidden.
+    #[error("user registration is forbidden for this instance.")]
+    RegistrationForbidden,
+    /// An internal error (shown as a server error, rather than as a login failure).
+    #[error(transparent)]
+    Internal(#[from] Error),
+}
+
+impl From<diesel::result::Error> for AuthError {
+    fn from(err: diesel::result::Error) -> AuthError {
+        AuthError::Internal(Error::from(err))
+    }
+}
+
+/// A source of truth for the email/password login form.
+///
+/// Credentials are either checked against an external source of truth (using [`AuthBackend::verify`],
+/// before provisioning the author record), or against the database (using [`AuthBackend::authenticate`]).
+/// Whatever the backend, sessions reference the author record it returns (which it provisions if needed).
+pub trait AuthBackend: Send + Sync {
+    /// Checks the credentials against an external source of truth (like an LDAP server), returning the user's identity.
+    ///
+    /// This may involve network round trips, so it is called on a blocking thread and outside of any database transaction.
+    /// Returns `None` for backends which check credentials against the database instead.
+    fn verify(&self, _credentials: Credentials<'_>) -> Result<Option<Identity>, AuthError> {
+        Ok(None)
+    }
+
+    /// Authenticates a user from their credentials against the database, returning the ID of their author record.
+    ///
+    /// Only called for backends which did not verify the credentials by themselves (see [`AuthBackend::verify`]).
+    fn authenticate(
+        &self,
+        _ctx: &mut AuthContext<'_>,
+        _credentials: Credentials<'_>,
+    ) -> Result<AuthorId, AuthError> {
+        Err(AuthError::InvalidCredentials)
+    }
+
+    /// Finds the author record of an authenticated user, creating it if the backend allows it.
+    fn provision(
+        &self,
+        ctx: &mut AuthContext<'_>,
+        identity: &Identity,
+    ) -> Result<AuthorId, AuthError>;
+
+    /// Whether the login form must hash passwords before submitting them (the server never sees them otherwise).
+    fn hashes_passwords(&self) -> bool {
+        true
+    }
+}
+
+/// A shared authentication backend.
+pub type SharedAuthBackend = Arc<dyn AuthBackend>;
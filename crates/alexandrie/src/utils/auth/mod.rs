@@ -4,6 +4,13 @@ use ring::rand::{SecureRandom, SystemRandom};
 /// Useful authorization-related types for the programmatic API.
 pub mod api;
 
+/// Password hashing and verification.
+pub mod passwd;
+
+/// Authentication backends for the frontend's login form (like LDAP).
+#[cfg(feature = "frontend")]
+pub mod backend;
+
 /// Useful authorization-related types for the frontend.
 #[cfg(feature = "frontend")]
 pub mod frontend;
@@ -0,0 +1,124 @@
+use std::num::NonZeroU32;
+
+use argon2::password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use ring::digest as hasher;
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::config::PasswordHashingConfig;
+use crate::error::Error;
+
+/// Pre-hashes a password, like the frontend's login form does before submitting it
+/// (5_000 rounds of PBKDF2, salted by the author's email, cf. `wasm-pbkdf2` sub-crate).
+///
+/// This is used for the passwords received as-is (to be checked against the password policy), so that their hashes
+/// can be verified against the ones submitted by the login form.
+pub fn prehash(email: &str, password: &str) -> [u8; hasher::SHA512_OUTPUT_LEN] {
+    let mut out = [0u8; hasher::SHA512_OUTPUT_LEN];
+    let iteration_count = unsafe { NonZeroU32::new_unchecked(5_000) };
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA512,
+        iteration_count,
+        email.as_bytes(),
+        password.as_bytes(),
+        &mut out,
+    );
+    out
+}
+
+/// The outcome of a password verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordVerification {
+    /// The password does not match the stored hash.
+    Mismatch,
+    /// The password matches the stored hash.
+    Match {
+        /// Whether the stored hash uses an older algorithm (or weaker parameters) than the configured ones,
+        /// meaning that the password should be hashed again and the stored hash replaced.
+        needs_rehash: bool,
+    },
+}
+
+/// Hashes and verifies the (client-side pre-hashed) passwords of authors.
+///
+/// New hashes use Argon2id (stored as PHC strings, like `$argon2id$v=19$m=19456,t=2,p=1$...`).
+/// Older hashes (hex-encoded PBKDF2-HMAC-SHA512 digests, salted using the `salts` table) can still be verified,
+/// the algorithm being detected from the stored hash's prefix.
+#[derive(Debug, Clone)]
+pub struct PasswordHasher {
+    params: Params,
+}
+
+impl TryFrom<PasswordHashingConfig> for PasswordHasher {
+    type Error = Error;
+
+    fn try_from(config: PasswordHashingConfig) -> Result<Self, Self::Error> {
+        let params = Params::new(config.memory, config.iterations, config.parallelism, None)
+            .map_err(argon2::password_hash::Error::from)?;
+        Ok(Self { params })
+    }
+}
+
+impl PasswordHasher {
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+
+    /// Hashes a password (using Argon2id, with a newly generated salt).
+    pub fn hash(&self, password: &[u8]) -> Result<String, Error> {
+        let mut salt = [0u8; 16];
+        SystemRandom::new().fill(&mut salt).unwrap();
+        let salt = SaltString::encode_b64(salt.as_ref())?;
+        let hash = self.argon2().hash_password(password, &salt)?;
+        Ok(hash.to_string())
+    }
+
+    /// Verifies a password against a stored hash.
+    ///
+    /// The salt (hex-encoded, from the `salts` table) is only used by older PBKDF2 hashes.
+    pub fn verify(
+        &self,
+        password: &[u8],
+        encoded_salt: &str,
+        stored: &str,
+    ) -> PasswordVerification {
+        if stored.starts_with("$argon2") {
+            let Ok(parsed) = PasswordHash::new(stored) else {
+                return PasswordVerification::Mismatch;
+            };
+            if self.argon2().verify_password(password, &parsed).is_err() {
+                return PasswordVerification::Mismatch;
+            }
+
+            //? Is the stored hash using other parameters than the configured ones?
+            let is_argon2id = parsed.algorithm == Algorithm::Argon2id.ident();
+            let needs_rehash = !is_argon2id
+                || Params::try_from(&parsed).map_or(true, |params| {
+                    params.m_cost() < self.params.m_cost()
+                        || params.t_cost() < self.params.t_cost()
+                        || params.p_cost() < self.params.p_cost()
+                });
+            return PasswordVerification::Match { needs_rehash };
+        }
+
+        //? Older hashes are hex-encoded PBKDF2 digests (100_000 rounds).
+        let decoded = hex::decode(encoded_salt)
+            .and_then(|salt| hex::decode(stored).map(move |hash| (salt, hash)));
+        let Ok((decoded_salt, decoded_hash)) = decoded else {
+            return PasswordVerification::Mismatch;
+        };
+        let iteration_count = unsafe { NonZeroU32::new_unchecked(100_000) };
+        let outcome = pbkdf2::verify(
+            pbkdf2::PBKDF2_HMAC_SHA512,
+            iteration_count,
+            decoded_salt.as_slice(),
+            password,
+            decoded_hash.as_slice(),
+        );
+        match outcome {
+            Ok(()) => PasswordVerification::Match { needs_rehash: true },
+            Err(_) => PasswordVerification::Mismatch,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use semver::Version;
+
+use crate::config::cargo_version::{CargoVersionConfig, OutdatedCargoAction};
+use crate::error::{AlexError, Error};
+
+/// Detects the version of Cargo from a `User-Agent` header (like `cargo 1.70.0 (ec8a8a0ca 2023-04-25)`).
+///
+/// Pre-release and build suffixes (like `-nightly`) are ignored, so that nightly versions compare like their stable counterparts.
+/// Anything else (like other clients, or malformed versions) is treated as unknown.
+pub fn detect(user_agent: &str) -> Option<Version> {
+    let version = user_agent.trim().strip_prefix("cargo ")?;
+    let version = version.split_whitespace().next()?;
+    let version = version.split(['-', '+']).next()?;
+
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some(Version::new(major, minor, patch))
+}
+
+/// Checks the Cargo version of a publication against the configured minimum (if any).
+///
+/// Returns a warning for outdated clients (or an error, if they are blocked).
+/// Clients whose version cannot be detected are always allowed.
+pub fn check(
+    config: Option<&CargoVersionConfig>,
+    user_agent: Option<&str>,
+) -> Result<Option<String>, Error> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    let Some(version) = user_agent.and_then(detect) else {
+        return Ok(None);
+    };
+    if version >= config.minimum {
+        return Ok(None);
+    }
+
+    let minimum = config.minimum.clone();
+    match config.action {
+        OutdatedCargoAction::Warn => Ok(Some(format!(
+            "this registry recommends Cargo {minimum} or newer (you are using Cargo {version}), please upgrade it (like using `rustup update`)"
+        ))),
+        OutdatedCargoAction::Block => {
+            Err(Error::from(AlexError::OutdatedCargo { version, minimum }))
+        }
+    }
+}
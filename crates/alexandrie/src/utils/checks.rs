@@ -1,10 +1,18 @@
 use diesel::dsl as sql;
 use diesel::prelude::*;
 
+use crate::config::GeneralState;
 use crate::db::models::Author;
 use crate::db::schema::*;
 use crate::db::Connection;
-use crate::error::Error;
+use crate::error::{AlexError, Error};
+use crate::utils;
+use crate::utils::owners::OwnerRole;
+
+/// Checks whether an author is a superadmin (listed, by ID, in the `superadmins` option of the `[general]` section).
+pub fn is_superadmin(general: &GeneralState, author: &Author) -> bool {
+    general.superadmins.contains(&author.id)
+}
 
 /// Checks if a crate exists in the database given a connection and the crate's name.
 pub fn crate_exists(conn: &mut Connection, canon_name: &str) -> Result<bool, Error> {
@@ -16,8 +24,71 @@ pub fn crate_exists(conn: &mut Connection, canon_name: &str) -> Result<bool, Err
     Ok(exists)
 }
 
-/// Checks if a user is an author of the named crate.
-pub fn is_crate_author(
+/// The names reserved by Windows for devices, which cannot be used as file names
+/// (and thus as crate names, since the crate index has a file per crate).
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Checks that a crate's name is valid and not reserved for the author (only done when a crate is first published,
+/// so that existing crates stay publishable if their name gets reserved later on).
+///
+/// Like Cargo, names must start with an ASCII letter, only contain ASCII alphanumeric characters, `-` or `_`,
+/// and be at most `max_crate_name_length` characters long.
+/// Reserved names are compared in their canonical form (see [`canonical_name`](crate::utils::canonical_name)).
+pub fn validate_crate_name(
+    general: &GeneralState,
+    author: &Author,
+    name: &str,
+) -> Result<(), Error> {
+    let invalid = |reason: String| {
+        Err(Error::from(AlexError::InvalidCrateName {
+            name: String::from(name),
+            reason,
+        }))
+    };
+
+    let max_length = general.max_crate_name_length;
+    match name.chars().next() {
+        None => return invalid(String::from("crate names cannot be empty")),
+        Some(ch) if !ch.is_ascii_alphabetic() => {
+            return invalid(String::from("crate names must start with an ASCII letter"));
+        }
+        Some(_) => {}
+    }
+    if let Some(ch) = name
+        .chars()
+        .find(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_')))
+    {
+        return invalid(format!("invalid character `{ch}` (crate names can only contain ASCII alphanumeric characters, `-` or `_`)"));
+    }
+    if name.len() > max_length {
+        return invalid(format!(
+            "crate names must be at most {max_length} characters long"
+        ));
+    }
+
+    let canon_name = utils::canonical_name(name);
+    if WINDOWS_RESERVED_NAMES.contains(&canon_name.as_str()) {
+        return invalid(String::from("this name is reserved by Windows for devices"));
+    }
+
+    let reserved = general
+        .reserved_names
+        .iter()
+        .find(|reserved| reserved.matches(name) && !reserved.allows(author.email.as_str()));
+    match reserved {
+        Some(reserved) => Err(Error::from(AlexError::ReservedCrateName {
+            name: String::from(name),
+            pattern: String::from(reserved.pattern()),
+        })),
+        None => Ok(()),
+    }
+}
+
+/// Checks if a user is an owner of the named crate (and not only a read-only collaborator).
+pub fn is_crate_owner(
     conn: &mut Connection,
     canon_crate_name: &str,
     author_id: i64,
@@ -27,7 +98,8 @@ pub fn is_crate_author(
             .inner_join(authors::table)
             .inner_join(crates::table)
             .filter(crates::canon_name.eq(canon_crate_name))
-            .filter(authors::id.eq(author_id)),
+            .filter(authors::id.eq(author_id))
+            .filter(crate_authors::role.eq(OwnerRole::Owner.as_str())),
     ))
     .get_result(conn)?;
 
@@ -44,3 +116,105 @@ pub fn get_author(conn: &mut Connection, token: String) -> QueryResult<Option<Au
         .first::<Author>(conn)
         .optional()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GeneralConfig;
+
+    use super::*;
+
+    fn general() -> GeneralState {
+        let config: GeneralConfig = toml::from_str(
+            r#"
+            bind_address = "127.0.0.1:3000"
+            max_crate_size = "50 MB"
+            max_crate_name_length = 16
+            reserved_names = [
+                "std",
+                { pattern = "acme-*", allowed_authors = ["platform@acme.com"] },
+            ]
+            "#,
+        )
+        .unwrap();
+        GeneralState::try_from(config).unwrap()
+    }
+
+    fn author(email: &str) -> Author {
+        Author {
+            id: 1,
+            email: String::from(email),
+            name: String::from("John Doe"),
+            passwd: None,
+            github_id: None,
+            gitlab_id: None,
+            created_at: None,
+        }
+    }
+
+    fn is_invalid(result: Result<(), Error>) -> bool {
+        matches!(
+            result,
+            Err(Error::AlexError(AlexError::InvalidCrateName { .. }))
+        )
+    }
+
+    fn is_reserved(result: Result<(), Error>) -> bool {
+        matches!(
+            result,
+            Err(Error::AlexError(AlexError::ReservedCrateName { .. }))
+        )
+    }
+
+    #[test]
+    fn accepts_valid_names() {
+        let (general, author) = (general(), author("john@example.com"));
+        for name in ["serde", "serde_json", "tokio-util", "h2"] {
+            assert!(
+                validate_crate_name(&general, &author, name).is_ok(),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let (general, author) = (general(), author("john@example.com"));
+        for name in [
+            "",
+            "2fast",
+            "-serde",
+            "serde.json",
+            "séri",
+            "a-very-long-crate-name",
+        ] {
+            let result = validate_crate_name(&general, &author, name);
+            assert!(is_invalid(result), "`{name}` should be invalid");
+        }
+    }
+
+    #[test]
+    fn rejects_windows_device_names() {
+        let (general, author) = (general(), author("john@example.com"));
+        for name in ["con", "NUL", "com1", "Lpt9"] {
+            let result = validate_crate_name(&general, &author, name);
+            assert!(is_invalid(result), "`{name}` should be invalid");
+        }
+    }
+
+    #[test]
+    fn rejects_reserved_names() {
+        let (general, author) = (general(), author("john@example.com"));
+        for name in ["std", "STD", "acme-core", "acme_core"] {
+            let result = validate_crate_name(&general, &author, name);
+            assert!(is_reserved(result), "`{name}` should be reserved");
+        }
+    }
+
+    #[test]
+    fn allows_reserved_names_to_their_authors() {
+        let general = general();
+        let author = author("Platform@acme.com");
+        assert!(validate_crate_name(&general, &author, "acme-core").is_ok());
+        assert!(is_reserved(validate_crate_name(&general, &author, "std")));
+    }
+}
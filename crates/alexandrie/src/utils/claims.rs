@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use ring::digest as hasher;
+
+use crate::config::claims::ClaimsConfig;
+use crate::db::models::{Author, NewCrateAuthor};
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::utils::owners::OwnerRole;
+
+/// Checks whether a crate has no owners (read-only collaborators do not count).
+pub fn is_unowned(conn: &mut Connection, crate_id: i64) -> QueryResult<bool> {
+    let has_owners: bool = sql::select(sql::exists(
+        crate_authors::table
+            .filter(crate_authors::crate_id.eq(crate_id))
+            .filter(crate_authors::role.eq(OwnerRole::Owner.as_str())),
+    ))
+    .get_result(conn)?;
+    Ok(!has_owners)
+}
+
+/// Makes an author an owner of a crate (if they are already a collaborator, only their role changes).
+pub fn assign(conn: &mut Connection, crate_id: i64, author_id: i64) -> QueryResult<()> {
+    let maybe_existing = crate_authors::table
+        .select(crate_authors::id)
+        .filter(crate_authors::crate_id.eq(crate_id))
+        .filter(crate_authors::author_id.eq(author_id))
+        .first::<i64>(conn)
+        .optional()?;
+    if let Some(id) = maybe_existing {
+        diesel::update(crate_authors::table.find(id))
+            .set(crate_authors::role.eq(OwnerRole::Owner.as_str()))
+            .execute(conn)?;
+    } else {
+        diesel::insert_into(crate_authors::table)
+            .values(NewCrateAuthor {
+                crate_id,
+                author_id,
+                role: OwnerRole::Owner.as_str(),
+            })
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Computes the token that an author must commit to a crate's repository to claim it.
+///
+/// The token is bound to both the crate and the author, so it cannot be reused to claim another crate (or by someone else).
+pub fn token(canon_name: &str, author: &Author) -> String {
+    let claim = format!(
+        "alexandrie-claim:{canon_name}:{0}:{1}",
+        author.id, author.email
+    );
+    hex::encode(hasher::digest(&hasher::SHA256, claim.as_bytes()).as_ref())
+}
+
+/// Determines the URL of the raw proof file within a repository.
+///
+/// This follows the `<repository>/raw/<revision>/<path>` scheme, supported by GitHub and GitLab (among others).
+pub fn proof_url(config: &ClaimsConfig, repository: &str) -> String {
+    let repository = repository.trim().trim_end_matches('/');
+    let repository = repository.strip_suffix(".git").unwrap_or(repository);
+    let path = config.proof_path.trim_start_matches('/');
+    format!("{repository}/raw/{0}/{path}", config.revision)
+}
+
+/// Checks that the repository contains the proof file, with the claim token on one of its lines.
+///
+/// The error describes why the proof could not be verified (to be shown to the claimer).
+pub async fn verify(config: &ClaimsConfig, repository: &str, token: &str) -> Result<(), String> {
+    let url = proof_url(config, repository);
+    if !url.starts_with("https://") {
+        return Err(format!(
+            "the repository must be an `https://` URL to be verified (got '{repository}')"
+        ));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout))
+        .build()
+        .map_err(|err| format!("could not fetch '{url}': {err}"))?;
+    let response = client
+        .get(url.as_str())
+        .send()
+        .await
+        .map_err(|err| format!("could not fetch '{url}': {err}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("could not fetch '{url}' (status: {status})"));
+    }
+    let contents = response
+        .text()
+        .await
+        .map_err(|err| format!("could not read '{url}': {err}"))?;
+
+    if contents.lines().any(|line| line.trim() == token) {
+        Ok(())
+    } else {
+        Err(format!("'{url}' does not contain the claim token"))
+    }
+}
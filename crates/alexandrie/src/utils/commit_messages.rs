@@ -0,0 +1,197 @@
+use crate::config::commit_messages::CommitMessagesConfig;
+use crate::error::Error;
+
+/// A field interpolated in commit message templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Action,
+    Crate,
+    Version,
+    Actor,
+    Reason,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "action" => Some(Self::Action),
+            "crate" => Some(Self::Crate),
+            "version" => Some(Self::Version),
+            "actor" => Some(Self::Actor),
+            "reason" => Some(Self::Reason),
+            _ => None,
+        }
+    }
+}
+
+/// A part of a commit message template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed commit message template (see [`CommitMessagesConfig`] for its syntax).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+/// The values of the fields interpolated in commit message templates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitFields<'a> {
+    /// The operation performed (like "Adding" or "Yanking").
+    pub action: &'a str,
+    /// The crate's name.
+    pub krate: &'a str,
+    /// The crate's version (or versions, for bulk yanks).
+    pub version: &'a str,
+    /// The name of the author performing the operation.
+    pub actor: &'a str,
+    /// The reason given for the operation (if any).
+    pub reason: &'a str,
+}
+
+impl Template {
+    /// Parses a template, reporting unknown fields and unbalanced braces.
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for ch in chars.by_ref() {
+                        if ch == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(ch);
+                    }
+                    if !closed {
+                        return Err(format!(
+                            "unclosed field `{{{name}` (literal braces are written `{{{{`)"
+                        ));
+                    }
+                    let field = Field::from_name(name.as_str())
+                        .ok_or_else(|| format!("unknown field `{{{name}}}`"))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => {
+                    return Err(String::from(
+                        "unmatched `}` (literal braces are written `}}`)",
+                    ))
+                }
+                ch => text.push(ch),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Renders the commit message, interpolating the given fields.
+    pub fn render(&self, fields: &CommitFields) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.as_str(),
+                Part::Field(Field::Action) => fields.action,
+                Part::Field(Field::Crate) => fields.krate,
+                Part::Field(Field::Version) => fields.version,
+                Part::Field(Field::Actor) => fields.actor,
+                Part::Field(Field::Reason) => fields.reason,
+            })
+            .collect()
+    }
+}
+
+/// The crate index commit message templates, created (and validated) from [`CommitMessagesConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessages {
+    /// The commit message of publications.
+    pub publish: Template,
+    /// The commit message of yanks.
+    pub yank: Template,
+    /// The commit message of unyanks.
+    pub unyank: Template,
+    /// The commit message of bulk yanks.
+    pub bulk_yank: Template,
+    /// The commit message of tarball replacements.
+    pub replace_tarball: Template,
+}
+
+impl TryFrom<CommitMessagesConfig> for CommitMessages {
+    type Error = Error;
+
+    fn try_from(config: CommitMessagesConfig) -> Result<Self, Self::Error> {
+        let parse = |kind: &'static str, template: &str| {
+            Template::parse(template).map_err(|reason| Error::InvalidCommitMessage { kind, reason })
+        };
+        Ok(Self {
+            publish: parse("publish", &config.publish)?,
+            yank: parse("yank", &config.yank)?,
+            unyank: parse("unyank", &config.unyank)?,
+            bulk_yank: parse("bulk_yank", &config.bulk_yank)?,
+            replace_tarball: parse("replace_tarball", &config.replace_tarball)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> CommitFields<'static> {
+        CommitFields {
+            action: "Adding",
+            krate: "example",
+            version: "1.2.3",
+            actor: "John Doe",
+            reason: "",
+        }
+    }
+
+    #[test]
+    fn renders_the_default_templates() {
+        let template = Template::parse("{action} crate `{crate}#{version}`").unwrap();
+        assert_eq!(template.render(&fields()), "Adding crate `example#1.2.3`");
+    }
+
+    #[test]
+    fn renders_escaped_braces() {
+        let template = Template::parse("{{{crate}}} by {actor}").unwrap();
+        assert_eq!(template.render(&fields()), "{example} by John Doe");
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(Template::parse("{action} crate `{name}`").is_err());
+    }
+
+    #[test]
+    fn rejects_unclosed_fields() {
+        assert!(Template::parse("{action").is_err());
+        assert!(Template::parse("{action} crate `{crate`").is_err());
+    }
+
+    #[test]
+    fn rejects_unmatched_closing_braces() {
+        assert!(Template::parse("{action}} crate").is_err());
+        assert!(Template::parse("crate }").is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use chrono::Utc;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::{NewCrateAuthor, NewOwnerInvitation, OwnerInvitation};
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::db::DATETIME_FORMAT;
+use crate::utils::audit::{self, AuditAction};
+use crate::utils::owners::OwnerRole;
+
+/// The status of an owner invitation.
+///
+/// Invitations are never deleted, they are only resolved (accepted, declined or expired),
+/// so that they remain available for auditing purposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InvitationStatus {
+    /// The invitation awaits an answer from the invited author.
+    Pending,
+    /// The invited author accepted the invitation (and became an owner of the crate).
+    Accepted,
+    /// The invited author declined the invitation.
+    Declined,
+    /// The invitation expired before being answered.
+    Expired,
+}
+
+impl InvitationStatus {
+    /// Returns the status' representation, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvitationStatus::Pending => "pending",
+            InvitationStatus::Accepted => "accepted",
+            InvitationStatus::Declined => "declined",
+            InvitationStatus::Expired => "expired",
+        }
+    }
+}
+
+/// A pending owner invitation, as shown to the invited author.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingInvitation {
+    /// The invitation's ID.
+    pub id: i64,
+    /// The name of the crate the author is invited to become an owner of.
+    pub crate_name: String,
+    /// The name of the inviting author.
+    pub inviter_name: String,
+    /// The role the author is invited to take.
+    pub role: OwnerRole,
+    /// The invitation's creation date.
+    pub created_at: String,
+    /// The invitation's expiry date.
+    pub expires_at: String,
+}
+
+fn now() -> String {
+    Utc::now().naive_utc().format(DATETIME_FORMAT).to_string()
+}
+
+/// Marks the pending invitations whose expiry date has passed as expired.
+pub fn expire(conn: &mut Connection) -> QueryResult<()> {
+    let now = now();
+    let expired = owner_invitations::table
+        .select((
+            owner_invitations::id,
+            owner_invitations::crate_id,
+            owner_invitations::invitee_id,
+        ))
+        .filter(owner_invitations::status.eq(InvitationStatus::Pending.as_str()))
+        .filter(owner_invitations::expires_at.le(now.as_str()))
+        .load::<(i64, i64, i64)>(conn)?;
+
+    for (id, crate_id, invitee_id) in expired {
+        diesel::update(owner_invitations::table.find(id))
+            .set((
+                owner_invitations::status.eq(InvitationStatus::Expired.as_str()),
+                owner_invitations::resolved_at.eq(now.as_str()),
+            ))
+            .execute(conn)?;
+        tracing::info!(
+            "owner invitation #{id} (crate #{crate_id}, author #{invitee_id}) has expired"
+        );
+    }
+
+    Ok(())
+}
+
+/// Invites an author to become an owner (or a collaborator) of a crate (valid for the given number of days).
+///
+/// If the author already has a pending invitation for this crate, it gets renewed instead (with the new role).
+pub fn invite(
+    conn: &mut Connection,
+    crate_id: i64,
+    invitee_id: i64,
+    inviter_id: i64,
+    role: OwnerRole,
+    lifetime: u64,
+) -> QueryResult<()> {
+    expire(conn)?;
+
+    let now = Utc::now().naive_utc();
+    let created_at = now.format(DATETIME_FORMAT).to_string();
+    let expires_at = (now + chrono::Duration::days(lifetime as i64))
+        .format(DATETIME_FORMAT)
+        .to_string();
+
+    let maybe_pending = owner_invitations::table
+        .select(owner_invitations::id)
+        .filter(owner_invitations::crate_id.eq(crate_id))
+        .filter(owner_invitations::invitee_id.eq(invitee_id))
+        .filter(owner_invitations::status.eq(InvitationStatus::Pending.as_str()))
+        .first::<i64>(conn)
+        .optional()?;
+
+    if let Some(id) = maybe_pending {
+        diesel::update(owner_invitations::table.find(id))
+            .set((
+                owner_invitations::inviter_id.eq(inviter_id),
+                owner_invitations::expires_at.eq(expires_at.as_str()),
+                owner_invitations::role.eq(role.as_str()),
+            ))
+            .execute(conn)?;
+    } else {
+        let invitation = NewOwnerInvitation {
+            crate_id,
+            invitee_id,
+            inviter_id,
+            status: InvitationStatus::Pending.as_str(),
+            created_at: created_at.as_str(),
+            expires_at: expires_at.as_str(),
+            role: role.as_str(),
+        };
+        diesel::insert_into(owner_invitations::table)
+            .values(invitation)
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+/// Gets the pending invitations of an author.
+pub fn pending(conn: &mut Connection, author_id: i64) -> QueryResult<Vec<PendingInvitation>> {
+    expire(conn)?;
+
+    let invitations = owner_invitations::table
+        .inner_join(crates::table)
+        .select((owner_invitations::all_columns, crates::name))
+        .filter(owner_invitations::invitee_id.eq(author_id))
+        .filter(owner_invitations::status.eq(InvitationStatus::Pending.as_str()))
+        .order_by(owner_invitations::created_at.desc())
+        .load::<(OwnerInvitation, String)>(conn)?;
+
+    invitations
+        .into_iter()
+        .map(|(invitation, crate_name)| {
+            let inviter_name = authors::table
+                .select(authors::name)
+                .filter(authors::id.eq(invitation.inviter_id))
+                .first::<String>(conn)?;
+            Ok(PendingInvitation {
+                id: invitation.id,
+                crate_name,
+                inviter_name,
+                role: OwnerRole::parse(invitation.role.as_str()),
+                created_at: invitation.created_at,
+                expires_at: invitation.expires_at,
+            })
+        })
+        .collect()
+}
+
+/// Accepts or declines a pending invitation of an author, and returns the name of the related crate.
+///
+/// Accepting an invitation adds the author to the owners of the crate (with the invitation's role).
+/// Returns `None` if the author has no such pending invitation (it may have expired).
+pub fn resolve(
+    conn: &mut Connection,
+    author_id: i64,
+    invitation_id: i64,
+    accept: bool,
+) -> QueryResult<Option<String>> {
+    expire(conn)?;
+
+    let maybe_invitation = owner_invitations::table
+        .inner_join(crates::table)
+        .select((owner_invitations::all_columns, crates::name))
+        .filter(owner_invitations::id.eq(invitation_id))
+        .filter(owner_invitations::invitee_id.eq(author_id))
+        .filter(owner_invitations::status.eq(InvitationStatus::Pending.as_str()))
+        .first::<(OwnerInvitation, String)>(conn)
+        .optional()?;
+    let Some((invitation, crate_name)) = maybe_invitation else {
+        return Ok(None);
+    };
+
+    let status = if accept {
+        InvitationStatus::Accepted
+    } else {
+        InvitationStatus::Declined
+    };
+    diesel::update(owner_invitations::table.find(invitation.id))
+        .set((
+            owner_invitations::status.eq(status.as_str()),
+            owner_invitations::resolved_at.eq(now()),
+        ))
+        .execute(conn)?;
+
+    if accept {
+        //? The author might already be an owner (or a collaborator), in which case only their role changes.
+        let maybe_existing = crate_authors::table
+            .select((crate_authors::id, crate_authors::role))
+            .filter(crate_authors::crate_id.eq(invitation.crate_id))
+            .filter(crate_authors::author_id.eq(author_id))
+            .first::<(i64, String)>(conn)
+            .optional()?;
+        if let Some((id, current)) = maybe_existing {
+            //? An owner is never downgraded to a collaborator if that would leave the crate without any owners.
+            let is_downgrade = OwnerRole::parse(current.as_str()) == OwnerRole::Owner
+                && OwnerRole::parse(invitation.role.as_str()) == OwnerRole::Collaborator;
+            let other_owners: i64 = crate_authors::table
+                .filter(crate_authors::crate_id.eq(invitation.crate_id))
+                .filter(crate_authors::author_id.ne(author_id))
+                .filter(crate_authors::role.eq(OwnerRole::Owner.as_str()))
+                .count()
+                .get_result(conn)?;
+            if is_downgrade && other_owners == 0 {
+                tracing::warn!(
+                    "owner invitation #{0} (crate #{1}, author #{author_id}) was accepted, but the crate's last owner has been kept as such",
+                    invitation.id,
+                    invitation.crate_id,
+                );
+            } else {
+                diesel::update(crate_authors::table.find(id))
+                    .set(crate_authors::role.eq(invitation.role.as_str()))
+                    .execute(conn)?;
+            }
+        } else {
+            let new_owner = NewCrateAuthor {
+                crate_id: invitation.crate_id,
+                author_id,
+                role: invitation.role.as_str(),
+            };
+            diesel::insert_into(crate_authors::table)
+                .values(new_owner)
+                .execute(conn)?;
+        }
+
+        //? Record the ownership change in the crate's audit log.
+        let details = format!("joined as {0}", invitation.role);
+        let action = AuditAction::AddOwner;
+        audit::record(
+            conn,
+            invitation.crate_id,
+            author_id,
+            action,
+            None,
+            Some(&details),
+        )?;
+    }
+
+    tracing::info!(
+        "owner invitation #{0} (crate '{crate_name}', author #{author_id}) has been {1}",
+        invitation.id,
+        status.as_str(),
+    );
+
+    Ok(Some(crate_name))
+}
@@ -0,0 +1,158 @@
+use std::path::{Component, Path, PathBuf};
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use spdx::{Expression, ParseMode};
+
+use crate::config::GeneralState;
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::error::{AlexError, Error};
+
+/// The license of a crate version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionLicense {
+    /// The version's SPDX license expression (its `license` manifest key).
+    pub license: Option<String>,
+    /// The path of the version's license file (its `license_file` manifest key).
+    pub license_file: Option<String>,
+    /// The contents of the version's license file (only stored for versions without a license expression).
+    pub license_text: Option<String>,
+}
+
+/// Parses a license expression, the same way as Cargo (which still accepts `/` as an alias of `OR`).
+fn parse(license: &str) -> Result<Expression, Error> {
+    Expression::parse_mode(license, ParseMode::LAX).map_err(|err| {
+        Error::from(AlexError::InvalidLicense {
+            license: String::from(license),
+            reason: err.reason.to_string(),
+        })
+    })
+}
+
+/// Checks the license expression of a published crate against the registry's policy.
+///
+/// Invalid expressions are only rejected in strict mode (or if an allowlist is configured, as it cannot be checked then),
+/// and expressions are allowed if they can be satisfied using only the allowed licenses (like `MIT OR GPL-3.0` with `MIT`).  
+/// With an allowlist, crates without a license expression are rejected, unless they have a license file
+/// and the `allow_license_file` option is enabled.
+pub fn check(
+    general: &GeneralState,
+    license: Option<&str>,
+    license_file: Option<&str>,
+) -> Result<(), Error> {
+    let Some(license) = license else {
+        let Some(allowed) = general.allowed_licenses.as_deref() else {
+            return Ok(());
+        };
+        let reason = match license_file {
+            Some(_) if general.allow_license_file => return Ok(()),
+            Some(_) => "crates with only a license file cannot be published to this registry, as their license cannot be checked",
+            None => "crates must declare their license (using the `license` key of their manifest)",
+        };
+        return Err(Error::from(AlexError::MissingLicense {
+            reason: String::from(reason),
+            allowed: allowed.join(", "),
+        }));
+    };
+
+    let expression = match parse(license) {
+        Ok(expression) => expression,
+        Err(error) if general.strict_licenses || general.allowed_licenses.is_some() => {
+            return Err(error);
+        }
+        Err(_) => return Ok(()),
+    };
+
+    let Some(allowed) = general.allowed_licenses.as_deref() else {
+        return Ok(());
+    };
+    let satisfied = expression.evaluate(|req| {
+        let id = req.license.id();
+        id.map_or(false, |id| {
+            allowed.iter().any(|it| it.eq_ignore_ascii_case(id.name))
+        })
+    });
+    if satisfied {
+        Ok(())
+    } else {
+        Err(Error::from(AlexError::DisallowedLicense {
+            license: String::from(license),
+            allowed: allowed.join(", "),
+        }))
+    }
+}
+
+/// Returns a warning if the license expression of a published crate is not a valid SPDX expression
+/// (when it got accepted anyway, outside of strict mode).
+pub fn warning(license: Option<&str>) -> Option<String> {
+    let error = parse(license?).err()?;
+    Some(format!(
+        "{error}: it is accepted, but may be rejected by other tools"
+    ))
+}
+
+/// Gets the path of a license file within a crate tarball, from the `license_file` key of its manifest.
+///
+/// Cargo packages license files located outside of the crate's directory at the root of the tarball.
+pub fn archive_path(license_file: &str) -> String {
+    let path = Path::new(license_file);
+    let is_outside = path
+        .components()
+        .any(|component| matches!(component, Component::ParentDir | Component::RootDir));
+    let path = if is_outside {
+        path.file_name().map(PathBuf::from).unwrap_or_default()
+    } else {
+        path.components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect()
+    };
+    path.to_string_lossy().into_owned()
+}
+
+/// Records the license of a crate version (its publication must have already been recorded).
+pub fn record(
+    conn: &mut Connection,
+    crate_id: i64,
+    version: &str,
+    license: &VersionLicense,
+) -> QueryResult<()> {
+    diesel::update(
+        crate_versions::table
+            .filter(crate_versions::crate_id.eq(crate_id))
+            .filter(crate_versions::version.eq(version)),
+    )
+    .set((
+        crate_versions::license.eq(license.license.as_deref()),
+        crate_versions::license_file.eq(license.license_file.as_deref()),
+        crate_versions::license_text.eq(license.license_text.as_deref()),
+    ))
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Gets the license of a crate version (if its publication was recorded).
+pub fn get(
+    conn: &mut Connection,
+    crate_id: i64,
+    version: &str,
+) -> QueryResult<Option<VersionLicense>> {
+    let license = crate_versions::table
+        .select((
+            crate_versions::license,
+            crate_versions::license_file,
+            crate_versions::license_text,
+        ))
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .filter(crate_versions::version.eq(version))
+        .first::<(Option<String>, Option<String>, Option<String>)>(conn)
+        .optional()?;
+
+    Ok(
+        license.map(|(license, license_file, license_text)| VersionLicense {
+            license,
+            license_file,
+            license_text,
+        }),
+    )
+}
@@ -0,0 +1,149 @@
+use axum::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+#[cfg(feature = "frontend")]
+use handlebars::Handlebars;
+#[cfg(feature = "frontend")]
+use serde::Serialize;
+
+use crate::config::mail::{MailConfig, SmtpMailConfig, SmtpSecurity};
+use crate::error::Error;
+
+/// The required functionality of an email transport.
+#[async_trait]
+pub trait Mailer {
+    /// Sends an email to the given address (with both an HTML and a plain-text body).
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), Error>;
+}
+
+/// The email transport enum, representing any email transport.
+pub enum Mail {
+    /// Sends emails through an SMTP server.
+    Smtp(SmtpMailer),
+    /// Only logs emails (for development purposes).
+    Log(LogMailer),
+}
+
+impl TryFrom<MailConfig> for Mail {
+    type Error = Error;
+
+    fn try_from(config: MailConfig) -> Result<Self, Self::Error> {
+        match config {
+            MailConfig::Smtp(config) => Ok(Mail::Smtp(config.try_into()?)),
+            MailConfig::Log => Ok(Mail::Log(LogMailer)),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for Mail {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), Error> {
+        match self {
+            Mail::Smtp(mailer) => mailer.send(to, subject, body_html, body_text).await,
+            Mail::Log(mailer) => mailer.send(to, subject, body_html, body_text).await,
+        }
+    }
+}
+
+impl Mail {
+    /// Renders an email from its templates and sends it to the given address.
+    ///
+    /// The HTML and plain-text bodies are rendered from the `<template>.html` and `<template>.txt` templates.
+    #[cfg(feature = "frontend")]
+    pub async fn send_template<T: Serialize>(
+        &self,
+        engine: &Handlebars<'_>,
+        to: &str,
+        subject: &str,
+        template: &str,
+        context: &T,
+    ) -> Result<(), Error> {
+        let body_html = engine.render(format!("{template}.html").as_str(), context)?;
+        let body_text = engine.render(format!("{template}.txt").as_str(), context)?;
+        self.send(to, subject, body_html.as_str(), body_text.as_str())
+            .await
+    }
+}
+
+/// Sends emails through the configured SMTP server.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl TryFrom<SmtpMailConfig> for SmtpMailer {
+    type Error = Error;
+
+    fn try_from(config: SmtpMailConfig) -> Result<Self, Self::Error> {
+        let host = config.smtp_host.as_str();
+        let mut builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpSecurity::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        };
+        if let Some(port) = config.smtp_port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = config.username {
+            let password = config.password.unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.from.parse()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body_html: &str,
+        body_text: &str,
+    ) -> Result<(), Error> {
+        let body = MultiPart::alternative_plain_html(body_text.to_string(), body_html.to_string());
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to.parse()?)
+            .subject(subject)
+            .multipart(body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Logs emails instead of sending them (meant for development setups).
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        _body_html: &str,
+        body_text: &str,
+    ) -> Result<(), Error> {
+        tracing::info!("email to '{to}' (subject: '{subject}'):\n{body_text}");
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::{Method, Request, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+#[cfg(feature = "frontend")]
+use axum::response::Html;
+
+use crate::config::AppState;
+use crate::error::ApiError;
+#[cfg(feature = "frontend")]
+use crate::utils;
+
+/// The message sent when a request is refused because the registry is in read-only mode.
+pub const READ_ONLY_MESSAGE: &str =
+    "the registry is in read-only mode for maintenance, please try again later";
+
+/// The mutating requests which are still allowed in read-only mode (as they don't alter the registry).
+const ALLOWED_REQUESTS: &[(&str, &str)] = &[
+    //? Logging in (only creates a session, or returns a token).
+    ("POST", "/account/login"),
+    //? Getting information about a token.
+    ("POST", "/account/tokens"),
+];
+
+/// Returns whether a request is allowed while the registry is in read-only mode.
+///
+/// Only safe requests (like browsing, searching, downloading crates or fetching the index) are allowed,
+/// along with logging in.
+pub fn is_allowed(method: &Method, path: &str) -> bool {
+    method.is_safe()
+        || ALLOWED_REQUESTS
+            .iter()
+            .any(|(allowed, allowed_path)| *allowed == method.as_str() && *allowed_path == path)
+}
+
+/// Middleware refusing the mutating requests to the programmatic API when the registry is in read-only mode.
+pub(crate) async fn api_guard<B>(
+    State(state): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if state.general.read_only && !is_allowed(request.method(), request.uri().path()) {
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, READ_ONLY_MESSAGE).into_response();
+    }
+    next.run(request).await
+}
+
+/// Middleware refusing the mutating frontend actions when the registry is in read-only mode.
+#[cfg(feature = "frontend")]
+pub(crate) async fn frontend_guard<B>(
+    State(state): State<Arc<AppState>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if state.general.read_only && !is_allowed(request.method(), request.uri().path()) {
+        let message = "The registry is in read-only mode for maintenance, please try again later.";
+        return match utils::response::error_html(state.as_ref(), None, message) {
+            Ok(rendered) => (StatusCode::SERVICE_UNAVAILABLE, Html(rendered)).into_response(),
+            Err(_) => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+        };
+    }
+    next.run(request).await
+}
@@ -1,16 +1,86 @@
+use std::sync::Arc;
+
+use crate::config::AppState;
+
+/// Crate aliases (former names of crates, redirecting to them).
+pub mod aliases;
+/// Audit log of the sensitive operations performed on crates.
+pub mod audit;
 /// Various authentication-related utilities.
 pub mod auth;
 /// Build time debug information.
 pub mod build;
+/// Cargo client version detection (from the `User-Agent` header).
+pub mod cargo_version;
 /// Various utilities to check for common properties.
 pub mod checks;
+/// Ownership claims of unowned crates (like the ones imported without their owners).
+pub mod claims;
+/// Crate index commit message templates.
+pub mod commit_messages;
+/// Crate owner invitations (sent to authors, who can accept or decline them).
+pub mod invitations;
+/// Crate licenses (validation of SPDX expressions, and per-version records).
+pub mod licenses;
+/// Outgoing emails (through SMTP).
+pub mod mail;
+/// Read-only maintenance mode (refusing every request altering the registry).
+pub mod maintenance;
+/// Crate ownership roles (owners and read-only collaborators).
+pub mod owners;
+/// In-memory cache of the rendered crate pages.
+pub mod page_cache;
+/// Pagination of the listing endpoints.
+pub mod pagination;
+/// Crate provenance records (where crate versions were published from).
+pub mod provenance;
+/// The pre-publish hook (external validation of publications).
+pub mod publish_hook;
+/// The publish concurrency limiter (making excess publications wait for their turn).
+pub mod publish_queue;
+/// Per-author publish quotas (limiting how many crates and versions an author can publish).
+pub mod quotas;
+/// README rendering (or raw display, when built without the `rendering` feature).
+pub mod readme;
+/// Request IDs (correlating the log lines emitted while handling a request).
+pub mod request_id;
 /// Various utilities to assist building HTTP responses.
 pub mod response;
+/// Graceful shutdown (waiting for in-flight publications before exiting).
+pub mod shutdown;
+/// Crate subscriptions (authors notified when a crate gets a new version).
+pub mod subscriptions;
+/// Bounded decompression of uploaded crate tarballs (guarding against decompression bombs).
+pub mod tarball;
+/// Tracing subscriber setup and span helpers.
+pub mod telemetry;
+/// Crate version records (when crate versions were published).
+pub mod versions;
+/// Crate visibility (public or private crates).
+pub mod visibility;
 
 /// Various session-related utilities.
 #[cfg(feature = "frontend")]
 pub mod sessions;
 
+/// Runs the given closure on the blocking thread pool, with access to the application state.
+///
+/// The crate index and crate storage operations are blocking (filesystem, `git` or network IO),
+/// so async handlers must not call them directly, and can instead await them through this function.
+pub async fn run_blocking<F, R>(state: &Arc<AppState>, func: F) -> R
+where
+    F: FnOnce(&AppState) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let state = Arc::clone(state);
+    //? The current span (carrying the request's ID) is kept on the blocking thread.
+    let span = tracing::Span::current();
+    let future = tokio::task::spawn_blocking(move || span.in_scope(|| func(state.as_ref())));
+
+    // FIXME: truly figure out what to do with a `JoinError`.
+    future.await.unwrap()
+}
+
 /// Transforms a crate name to its canonical form.
 ///
 /// A canonical crate name means that all dashes ('-') have
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// The role of an author for a crate.
+///
+/// Both roles can view the crate's management information,
+/// but only owners can publish, yank or un-yank versions, and manage the crate's owners.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OwnerRole {
+    /// A full owner of the crate.
+    #[default]
+    Owner,
+    /// A read-only collaborator of the crate.
+    Collaborator,
+}
+
+impl OwnerRole {
+    /// Returns the role's representation, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OwnerRole::Owner => "owner",
+            OwnerRole::Collaborator => "collaborator",
+        }
+    }
+
+    /// Parses a role, as stored in the database.
+    ///
+    /// Unknown roles are treated as collaborators (the least privileged role).
+    pub fn parse(role: &str) -> OwnerRole {
+        match role {
+            "owner" => OwnerRole::Owner,
+            _ => OwnerRole::Collaborator,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+
+use crate::config::page_cache::PageCacheConfig;
+use crate::config::AppState;
+use crate::utils;
+
+/// A rendered crate page, along with when it got cached.
+#[derive(Debug, Clone)]
+struct CachedPage {
+    rendered: String,
+    cached_at: Instant,
+}
+
+/// The cached pages of a crate.
+#[derive(Debug, Clone, Default)]
+struct CachedCrate {
+    /// The cached variants of the crate's page.
+    variants: HashMap<String, CachedPage>,
+    /// When the crate got downloaded for the first time since its pages got cached (if it did).
+    downloaded_at: Option<Instant>,
+}
+
+/// A thread-safe, bounded (LRU) in-memory cache of rendered crate pages.
+///
+/// Pages are grouped by crate (a crate page having several variants, depending on its query parameters),
+/// so that every variant of a crate's page gets invalidated at once whenever that crate changes.
+///
+/// Downloads do not invalidate pages right away (popular crates would otherwise never stay cached):
+/// pages cached before a download are only considered stale once `downloads_delay` has elapsed since that download.
+#[derive(Debug)]
+pub struct PageCache {
+    ttl: Duration,
+    downloads_delay: Duration,
+    pages: Mutex<LruCache<String, CachedCrate>>,
+}
+
+impl PageCache {
+    /// Creates a new cache from its configuration (`None` if its capacity is `0`, which disables the cache).
+    pub fn new(config: &PageCacheConfig) -> Option<PageCache> {
+        let capacity = NonZeroUsize::new(config.capacity)?;
+        Some(PageCache {
+            ttl: Duration::from_secs(config.ttl),
+            downloads_delay: Duration::from_secs(config.downloads_delay),
+            pages: Mutex::new(LruCache::new(capacity)),
+        })
+    }
+
+    /// Gets a variant of a crate's page, if it is cached (and still fresh).
+    pub fn get(&self, name: &str, variant: &str) -> Option<String> {
+        let key = utils::canonical_name(name);
+        let mut pages = self.pages.lock().unwrap();
+        let cached_crate = pages.get(&key)?;
+        let cached = cached_crate.variants.get(variant)?;
+
+        //? Pages showing outdated download counts are kept for a little while (see `downloads_delay`).
+        let outdated = cached_crate.downloaded_at.map_or(false, |downloaded_at| {
+            cached.cached_at <= downloaded_at && downloaded_at.elapsed() >= self.downloads_delay
+        });
+        (cached.cached_at.elapsed() < self.ttl && !outdated).then(|| cached.rendered.clone())
+    }
+
+    /// Caches a variant of a crate's page.
+    pub fn put(&self, name: &str, variant: String, rendered: String) {
+        let key = utils::canonical_name(name);
+        let mut pages = self.pages.lock().unwrap();
+        let cached_crate = pages.get_or_insert_mut(key, CachedCrate::default);
+        let cached_at = Instant::now();
+        let (ttl, downloaded_at) = (self.ttl, cached_crate.downloaded_at);
+        cached_crate.variants.retain(|_, cached| {
+            let downloaded = downloaded_at.map_or(false, |at| cached.cached_at <= at);
+            cached.cached_at.elapsed() < ttl && !downloaded
+        });
+        //? The remaining variants were all cached after the last download, as is this one.
+        cached_crate.downloaded_at = None;
+        cached_crate.variants.insert(
+            variant,
+            CachedPage {
+                rendered,
+                cached_at,
+            },
+        );
+    }
+
+    /// Invalidates every cached variant of a crate's page.
+    pub fn invalidate(&self, name: &str) {
+        let key = utils::canonical_name(name);
+        self.pages.lock().unwrap().pop(&key);
+    }
+
+    /// Records a download of a crate, whose cached pages then become stale once `downloads_delay` has elapsed.
+    pub fn downloaded(&self, name: &str) {
+        let key = utils::canonical_name(name);
+        let mut pages = self.pages.lock().unwrap();
+        if let Some(cached_crate) = pages.peek_mut(&key) {
+            cached_crate.downloaded_at.get_or_insert_with(Instant::now);
+        }
+    }
+}
+
+/// Invalidates the cached pages of a crate (if the crate pages cache is enabled).
+///
+/// This must be called whenever something shown on the crate's page changes
+/// (like publications, yanks, ownership or visibility changes), except for download counts (see [`downloaded`]).
+pub fn invalidate(state: &AppState, name: &str) {
+    if let Some(cache) = state.page_cache.as_ref() {
+        cache.invalidate(name);
+    }
+}
+
+/// Records a download of a crate (if the crate pages cache is enabled), so that its cached pages
+/// eventually show the updated download counts (without getting invalidated on every download).
+pub fn downloaded(state: &AppState, name: &str) {
+    if let Some(cache) = state.page_cache.as_ref() {
+        cache.downloaded(name);
+    }
+}
@@ -0,0 +1,93 @@
+use std::num::NonZeroUsize;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::pagination::PaginationConfig;
+
+/// A page of a listing, as requested by the `page` and `per_page` query parameters
+/// (the number of items per page being clamped to the configured maximum).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// The page number (starting from 1).
+    pub number: usize,
+    /// How many items are listed per page.
+    pub per_page: usize,
+}
+
+/// The pagination metadata returned by the listing endpoints (as their `meta` field).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMeta {
+    /// The total number of items of the listing.
+    pub total: usize,
+    /// The number of the next page (if there is one).
+    pub next_page: Option<usize>,
+}
+
+impl Page {
+    /// Determines the requested page from the query parameters.
+    pub fn new(
+        config: &PaginationConfig,
+        page: Option<NonZeroUsize>,
+        per_page: Option<NonZeroUsize>,
+    ) -> Self {
+        Self::with_default(config, config.default_per_page, page, per_page)
+    }
+
+    /// Determines the requested page from the query parameters, using a specific default number of items per page.
+    pub fn with_default(
+        config: &PaginationConfig,
+        default_per_page: usize,
+        page: Option<NonZeroUsize>,
+        per_page: Option<NonZeroUsize>,
+    ) -> Self {
+        let max_per_page = config.max_per_page.max(1);
+        let per_page = per_page.map_or(default_per_page, NonZeroUsize::get);
+        Self {
+            number: page.map_or(1, NonZeroUsize::get),
+            per_page: per_page.clamp(1, max_per_page),
+        }
+    }
+
+    /// The number of items to skip to get to this page.
+    pub fn offset(&self) -> usize {
+        (self.number - 1).saturating_mul(self.per_page)
+    }
+
+    /// The number of items to skip to get to this page, as a database query offset
+    /// (clamped, as overly large page numbers would otherwise wrap around to a negative offset).
+    pub fn db_offset(&self) -> i64 {
+        i64::try_from(self.offset()).unwrap_or(i64::MAX)
+    }
+
+    /// The number of pages needed to list every item (at least one, even when there are none).
+    pub fn count(&self, total: usize) -> usize {
+        ((total + self.per_page - 1) / self.per_page).max(1)
+    }
+
+    /// The number of the previous page (if there is one).
+    pub fn prev(&self) -> Option<usize> {
+        (self.number > 1).then(|| self.number - 1)
+    }
+
+    /// The number of the next page (if there is one).
+    pub fn next(&self, total: usize) -> Option<usize> {
+        (self.number < self.count(total)).then(|| self.number + 1)
+    }
+
+    /// Builds the pagination metadata of this page.
+    pub fn meta(&self, total: usize) -> PageMeta {
+        PageMeta {
+            total,
+            next_page: self.next(total),
+        }
+    }
+
+    /// Gets the items of this page, out of the whole listing.
+    pub fn slice<T>(&self, items: Vec<T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.offset())
+            .take(self.per_page)
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeSet;
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::db::models::CrateProvenance;
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::error::{AlexError, Error};
+
+/// An attestation supplied along with a published crate version.
+///
+/// Attestations are stored as-is (they are not verified yet), along with their kind,
+/// so that they can be verified later on, whatever their format is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attestation {
+    /// The kind of attestation (like `sigstore-bundle`).
+    pub kind: String,
+    /// The attestation itself (usually base64-encoded or JSON).
+    pub payload: String,
+}
+
+/// The provenance of a crate version (where it was published from).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The version this provenance is about.
+    pub version: String,
+    /// The repository URL declared by this version.
+    pub repository: Option<String>,
+    /// The attestation supplied along with this version.
+    pub attestation: Option<Attestation>,
+    /// Whether the declared repository URL changed between the versions of this crate.
+    pub repository_changed: bool,
+    /// The date at which this version was published.
+    pub recorded_at: String,
+}
+
+/// Checks that a repository URL is well-formed (an absolute URL with a host).
+pub fn validate_repository(repository: &str) -> Result<(), Error> {
+    let is_valid = Url::parse(repository).map_or(false, |url| url.has_host());
+    if !is_valid {
+        return Err(Error::from(AlexError::InvalidRepositoryUrl {
+            url: String::from(repository),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Gets the repository URL declared by the most recently published version of a crate.
+pub fn last_repository(conn: &mut Connection, crate_id: i64) -> QueryResult<Option<String>> {
+    let repository = crate_provenance::table
+        .select(crate_provenance::repository)
+        .filter(crate_provenance::crate_id.eq(crate_id))
+        .order_by(crate_provenance::id.desc())
+        .first::<Option<String>>(conn)
+        .optional()?;
+
+    Ok(repository.flatten())
+}
+
+/// Gets the provenance of the most recently published version of a crate.
+pub fn latest(conn: &mut Connection, crate_id: i64) -> QueryResult<Option<Provenance>> {
+    let records = crate_provenance::table
+        .filter(crate_provenance::crate_id.eq(crate_id))
+        .order_by(crate_provenance::id.desc())
+        .load::<CrateProvenance>(conn)?;
+
+    let repositories: BTreeSet<&str> = records
+        .iter()
+        .filter_map(|record| record.repository.as_deref())
+        .collect();
+    let repository_changed = repositories.len() > 1;
+
+    let provenance = records.into_iter().next().map(|record| Provenance {
+        version: record.version,
+        repository: record.repository,
+        attestation: record
+            .attestation_kind
+            .zip(record.attestation)
+            .map(|(kind, payload)| Attestation { kind, payload }),
+        repository_changed,
+        recorded_at: record.recorded_at,
+    });
+
+    Ok(provenance)
+}
@@ -0,0 +1,233 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use reqwest::multipart::{Form, Part};
+use ring::rand::{SecureRandom, SystemRandom};
+use semver::Version;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::publish_hook::{PublishHookConfig, PublishHookKind};
+use crate::error::{AlexError, Error};
+use crate::utils::request_id;
+
+/// What a pre-publish hook is told about a publication.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HookPayload<'a> {
+    /// The crate's name.
+    pub name: &'a str,
+    /// The crate's version.
+    pub vers: &'a Version,
+    /// The SHA-256 checksum of the crate's tarball.
+    pub cksum: &'a str,
+    /// The email of the publishing author.
+    pub author: &'a str,
+    /// The ID of the publication request.
+    pub request_id: Option<String>,
+    /// The crate's metadata, as sent by Cargo.
+    pub metadata: json::Value,
+}
+
+/// The outcome of running a pre-publish hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// The hook accepted the publication.
+    Accepted,
+    /// The hook rejected the publication (with the given reason).
+    Rejected(String),
+    /// The hook itself failed (for the given reason), so it gave no verdict.
+    Failed(String),
+}
+
+/// The pre-publish hook, created from [PublishHookConfig].
+pub struct PublishHook {
+    config: PublishHookConfig,
+    client: reqwest::Client,
+}
+
+impl From<PublishHookConfig> for PublishHook {
+    fn from(config: PublishHookConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl PublishHook {
+    /// Runs the hook for a publication, returning an error if the publication must be refused.
+    ///
+    /// If the hook itself fails, the publication is only refused if the hook is not configured to fail open.
+    pub async fn check(&self, payload: &HookPayload<'_>, tarball: &[u8]) -> Result<(), Error> {
+        let timeout = Duration::from_secs(self.config.timeout);
+        let metadata = json::to_vec(payload)?;
+        let outcome = match &self.config.kind {
+            PublishHookKind::Command { command } => {
+                run_command(command, timeout, metadata, tarball).await
+            }
+            PublishHookKind::Http { url } => {
+                let request = call_endpoint(&self.client, url, metadata, tarball);
+                match tokio::time::timeout(timeout, request).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => Outcome::Failed(format!("timed out after {0}s", timeout.as_secs())),
+                }
+            }
+        };
+
+        match outcome {
+            Outcome::Accepted => Ok(()),
+            Outcome::Rejected(reason) => {
+                tracing::info!(
+                    request_id = ?payload.request_id,
+                    "the pre-publish hook rejected `{0}` v{1}: {reason}",
+                    payload.name,
+                    payload.vers,
+                );
+                Err(Error::from(AlexError::PublishRejected { reason }))
+            }
+            Outcome::Failed(reason) if self.config.fail_open => {
+                tracing::warn!(
+                    request_id = ?payload.request_id,
+                    "the pre-publish hook failed for `{0}` v{1} (accepting it anyway): {reason}",
+                    payload.name,
+                    payload.vers,
+                );
+                Ok(())
+            }
+            Outcome::Failed(reason) => {
+                tracing::error!(
+                    request_id = ?payload.request_id,
+                    "the pre-publish hook failed for `{0}` v{1}: {reason}",
+                    payload.name,
+                    payload.vers,
+                );
+                Err(Error::from(AlexError::PublishHookFailed))
+            }
+        }
+    }
+}
+
+/// Runs a command hook, with the crate's tarball written to a temporary file for the duration of the command.
+async fn run_command(
+    command: &[String],
+    timeout: Duration,
+    metadata: Vec<u8>,
+    tarball: &[u8],
+) -> Outcome {
+    let mut suffix = [0u8; 8];
+    let _ = SystemRandom::new().fill(&mut suffix);
+    let file_name = format!("alexandrie-{0}.crate", hex::encode(suffix));
+    let path = std::env::temp_dir().join(file_name);
+
+    if let Err(err) = tokio::fs::write(&path, tarball).await {
+        return Outcome::Failed(format!("could not write the crate's tarball: {err}"));
+    }
+
+    //? The command is killed if it does not complete in time.
+    let outcome = match tokio::time::timeout(timeout, spawn_command(command, &path, metadata)).await
+    {
+        Ok(outcome) => outcome,
+        Err(_) => Outcome::Failed(format!("timed out after {0}s", timeout.as_secs())),
+    };
+
+    let _ = tokio::fs::remove_file(&path).await;
+    outcome
+}
+
+/// Spawns a command hook and waits for its verdict.
+async fn spawn_command(command: &[String], tarball_path: &Path, metadata: Vec<u8>) -> Outcome {
+    let Some((program, args)) = command.split_first() else {
+        return Outcome::Failed(String::from("no command is configured"));
+    };
+
+    let child = Command::new(program)
+        .args(args)
+        .env("ALEXANDRIE_TARBALL", tarball_path)
+        .env(
+            "ALEXANDRIE_REQUEST_ID",
+            request_id::current().unwrap_or_default(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => return Outcome::Failed(format!("could not run `{program}`: {err}")),
+    };
+
+    //? The metadata is written while collecting the output, so that large outputs cannot deadlock both processes.
+    //? The command is free not to read its standard input, so failing to write to it is not an error.
+    let stdin = child.stdin.take();
+    let write = async move {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&metadata).await;
+        }
+    };
+    let (_, output) = tokio::join!(write, child.wait_with_output());
+    let output = match output {
+        Ok(output) => output,
+        Err(err) => return Outcome::Failed(format!("could not wait for `{program}`: {err}")),
+    };
+
+    if output.status.success() {
+        return Outcome::Accepted;
+    }
+    if output.status.code().is_none() {
+        return Outcome::Failed(format!("`{program}` was terminated by a signal"));
+    }
+
+    //? The reason of the rejection is whatever the command printed (preferably on its standard output).
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = [stdout.trim(), stderr.trim()]
+        .into_iter()
+        .find(|it| !it.is_empty())
+        .unwrap_or("no reason given");
+    Outcome::Rejected(String::from(reason))
+}
+
+/// Calls an HTTP hook and interprets its response.
+async fn call_endpoint(
+    client: &reqwest::Client,
+    url: &str,
+    metadata: Vec<u8>,
+    tarball: &[u8],
+) -> Outcome {
+    let metadata = Part::bytes(metadata).mime_str("application/json");
+    let tarball = Part::bytes(tarball.to_vec()).file_name("crate.crate");
+    let form = match metadata {
+        Ok(metadata) => Form::new()
+            .part("metadata", metadata)
+            .part("tarball", tarball),
+        Err(err) => return Outcome::Failed(format!("could not build the request: {err}")),
+    };
+
+    let mut request = client.post(url).multipart(form);
+    if let Some(id) = request_id::current() {
+        request = request.header(request_id::HEADER, id);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => return Outcome::Failed(format!("could not call `{url}`: {err}")),
+    };
+
+    let status = response.status();
+    if status.is_success() {
+        return Outcome::Accepted;
+    }
+    if !status.is_client_error() {
+        return Outcome::Failed(format!("`{url}` responded with `{status}`"));
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    let reason = match body.trim() {
+        "" => String::from("no reason given"),
+        reason => String::from(reason),
+    };
+    Outcome::Rejected(reason)
+}
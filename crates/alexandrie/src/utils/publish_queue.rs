@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::config::publish_queue::PublishQueueConfig;
+use crate::error::{AlexError, Error};
+
+/// Limits how many publications are processed at once (the others wait for their turn, in arrival order).
+#[derive(Debug)]
+pub struct PublishQueue {
+    /// One permit per publication allowed to be processed at once.
+    permits: Semaphore,
+    /// How long a publication can wait for its turn.
+    max_wait: Duration,
+}
+
+impl From<PublishQueueConfig> for PublishQueue {
+    fn from(config: PublishQueueConfig) -> Self {
+        Self {
+            permits: Semaphore::new(config.max_concurrent.max(1)),
+            max_wait: Duration::from_secs(config.max_wait),
+        }
+    }
+}
+
+impl PublishQueue {
+    /// Waits for the turn of a publication, which lasts until the returned permit gets dropped.
+    ///
+    /// Fails if the publication had to wait for longer than the configured maximum.
+    pub async fn enter(&self) -> Result<SemaphorePermit<'_>, Error> {
+        let max_wait = self.max_wait.as_secs();
+        match tokio::time::timeout(self.max_wait, self.permits.acquire()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // the semaphore is never closed.
+            Ok(Err(_)) | Err(_) => Err(Error::from(AlexError::PublishQueueTimeout { max_wait })),
+        }
+    }
+}
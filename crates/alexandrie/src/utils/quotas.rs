@@ -0,0 +1,132 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+
+use crate::config::AppState;
+use crate::db::models::{Author, NewPublishEvent};
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::db::DATETIME_FORMAT;
+use crate::error::{AlexError, Error};
+
+/// A kind of publication, each being subject to its own quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishKind {
+    /// The first publication of a crate.
+    NewCrate,
+    /// The publication of a new version of an existing crate.
+    NewVersion,
+}
+
+impl PublishKind {
+    /// Returns the kind's representation, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PublishKind::NewCrate => "new-crate",
+            PublishKind::NewVersion => "new-version",
+        }
+    }
+
+    /// Returns the window over which publications of this kind are counted.
+    fn window(&self) -> Duration {
+        match self {
+            PublishKind::NewCrate => Duration::days(1),
+            PublishKind::NewVersion => Duration::hours(1),
+        }
+    }
+
+    /// Returns a description of the quota of this kind (like "new crates per day").
+    fn description(&self) -> &'static str {
+        match self {
+            PublishKind::NewCrate => "new crates per day",
+            PublishKind::NewVersion => "new versions per hour",
+        }
+    }
+}
+
+/// Returns whether an author is exempted from the publish quotas (superadmins and explicitly exempted authors).
+pub fn is_exempt(state: &AppState, author: &Author) -> bool {
+    let exempted = state.quotas.as_ref().map_or(false, |config| {
+        config
+            .exempt
+            .iter()
+            .any(|email| email.eq_ignore_ascii_case(author.email.as_str()))
+    });
+    exempted || super::checks::is_superadmin(&state.general, author)
+}
+
+/// Checks whether an author can publish (according to the configured quota for this kind of publication).
+///
+/// The outer result carries database errors, while the inner one is the outcome of the check.
+/// The error of an exceeded quota carries the date at which publishing becomes possible again.
+pub fn check(
+    state: &AppState,
+    conn: &mut Connection,
+    author: &Author,
+    kind: PublishKind,
+) -> Result<Result<(), Error>, Error> {
+    let Some(config) = state.quotas.as_ref() else {
+        return Ok(Ok(()));
+    };
+    let limit = match kind {
+        PublishKind::NewCrate => config.new_crates_per_day,
+        PublishKind::NewVersion => config.new_versions_per_hour,
+    };
+    let Some(limit) = limit else {
+        return Ok(Ok(()));
+    };
+    if is_exempt(state, author) {
+        return Ok(Ok(()));
+    }
+
+    let since = (Utc::now().naive_utc() - kind.window())
+        .format(DATETIME_FORMAT)
+        .to_string();
+    let published: Vec<String> = publish_events::table
+        .select(publish_events::created_at)
+        .filter(publish_events::author_id.eq(author.id))
+        .filter(publish_events::kind.eq(kind.as_str()))
+        .filter(publish_events::created_at.gt(since.as_str()))
+        .order_by(publish_events::created_at.asc())
+        .load(conn)?;
+    if (published.len() as u64) < limit {
+        return Ok(Ok(()));
+    }
+
+    //? Publishing becomes possible again once enough of the counted publications leave the window.
+    let excess = published.len() - (limit as usize);
+    let reset_at = published
+        .get(excess)
+        .and_then(|date| NaiveDateTime::parse_from_str(date, DATETIME_FORMAT).ok())
+        .map_or_else(|| Utc::now().naive_utc(), |date| date + kind.window());
+    Ok(Err(Error::from(AlexError::QuotaExceeded {
+        quota: kind.description(),
+        limit,
+        reset_at: format!("{0} UTC", reset_at.format(DATETIME_FORMAT)),
+    })))
+}
+
+/// Records a publication, to be counted against the author's quota for this kind of publication.
+///
+/// The author's events which are too old to be counted against any quota anymore are deleted along the way.
+pub fn record(conn: &mut Connection, author_id: i64, kind: PublishKind) -> QueryResult<()> {
+    let now = Utc::now().naive_utc();
+    let created_at = now.format(DATETIME_FORMAT).to_string();
+    diesel::insert_into(publish_events::table)
+        .values(NewPublishEvent {
+            author_id,
+            kind: kind.as_str(),
+            created_at: created_at.as_str(),
+        })
+        .execute(conn)?;
+
+    let expired = (now - PublishKind::NewCrate.window())
+        .format(DATETIME_FORMAT)
+        .to_string();
+    diesel::delete(
+        publish_events::table
+            .filter(publish_events::author_id.eq(author_id))
+            .filter(publish_events::created_at.le(expired.as_str())),
+    )
+    .execute(conn)?;
+    Ok(())
+}
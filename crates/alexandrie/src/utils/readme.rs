@@ -0,0 +1,140 @@
+use std::path::{Component, Path};
+
+use serde::Deserialize;
+
+use crate::config::AppState;
+
+/// The file names looked for in crate tarballs when the README's location is unknown (in order of preference).
+pub const README_FILE_NAMES: &[&str] = &["README.md", "README.adoc", "README.rst", "README"];
+
+/// The name of the file in which Cargo records where a crate got packaged from (at the root of crate tarballs).
+pub const VCS_INFO_FILE_NAME: &str = ".cargo_vcs_info.json";
+
+/// The contents of a `.cargo_vcs_info.json` file (only the fields of interest).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct VcsInfo {
+    /// The git commit the crate was packaged from.
+    #[serde(default)]
+    git: Option<GitInfo>,
+    /// The path of the crate's package within its repository (absent for older Cargo versions).
+    #[serde(default)]
+    path_in_vcs: String,
+}
+
+/// The git-specific part of a `.cargo_vcs_info.json` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct GitInfo {
+    /// The SHA-1 hash of the commit.
+    sha1: String,
+}
+
+/// Where a README comes from, within its crate's repository (used to resolve its relative links).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadmeOrigin {
+    /// The URL of the crate's repository.
+    pub repository: Option<String>,
+    /// The commit the crate was packaged from (if known).
+    pub commit: Option<String>,
+    /// The README's directory within the repository (empty for the repository's root).
+    pub directory: String,
+}
+
+impl ReadmeOrigin {
+    /// Determines where a README comes from, using the crate's `.cargo_vcs_info.json` file (if it has one)
+    /// and the `readme` key of its manifest (if specified).
+    ///
+    /// Cargo places the README at the root of the crate tarball, wherever it is in the crate's package,
+    /// so its original location is found back from the manifest.  
+    /// The repository is dropped if the README's directory cannot be determined (like if it is outside of the repository).
+    pub fn new(
+        repository: Option<&str>,
+        vcs_info: Option<&str>,
+        readme_file: Option<&str>,
+    ) -> ReadmeOrigin {
+        let vcs_info: VcsInfo = vcs_info
+            .and_then(|contents| json::from_str(contents).ok())
+            .unwrap_or_default();
+
+        //? Resolve the README's directory from the package's directory (`..` components included).
+        let readme_dir = readme_file
+            .and_then(|readme_file| Path::new(readme_file).parent())
+            .unwrap_or_else(|| Path::new(""));
+        let mut segments: Vec<&str> = Vec::new();
+        let mut outside = false;
+        let path = Path::new(vcs_info.path_in_vcs.as_str()).join(readme_dir);
+        for component in path.components() {
+            match component {
+                Component::Normal(segment) => match segment.to_str() {
+                    Some(segment) => segments.push(segment),
+                    None => outside = true,
+                },
+                Component::CurDir => {}
+                Component::ParentDir => outside |= segments.pop().is_none(),
+                Component::RootDir | Component::Prefix(_) => outside = true,
+            }
+        }
+
+        ReadmeOrigin {
+            repository: repository.filter(|_| !outside).map(String::from),
+            commit: vcs_info.git.map(|git| git.sha1),
+            directory: segments.join("/"),
+        }
+    }
+}
+
+/// Determines the file name of a crate's README, from the `readme` key of its manifest (if specified).
+///
+/// Cargo places the README at the root of the crate tarball (wherever it is in the crate's repository),
+/// so only its file name is kept.
+pub fn file_name(readme_file: Option<&str>) -> Option<String> {
+    let readme_file = readme_file?;
+    let file_name = Path::new(readme_file).file_name()?.to_str()?;
+    Some(String::from(file_name))
+}
+
+/// Renders a README to the HTML stored along with a crate version (its format being detected from its file name).
+///
+/// The README's origin is used to resolve its relative links against the crate's repository, if configured to.
+#[cfg(feature = "rendering")]
+pub fn render(state: &AppState, file_name: &str, contents: &str, origin: &ReadmeOrigin) -> String {
+    let format = alexandrie_rendering::ReadmeFormat::from_path(file_name);
+    let location =
+        origin
+            .repository
+            .as_deref()
+            .map(|url| alexandrie_rendering::RepositoryLocation {
+                url,
+                commit: origin.commit.as_deref(),
+                directory: origin.directory.as_str(),
+            });
+    alexandrie_rendering::render(&state.syntect, format, contents, location.as_ref())
+}
+
+/// Renders a README as preformatted raw text, as this build of Alexandrie has no README rendering.
+#[cfg(not(feature = "rendering"))]
+pub fn render(_: &AppState, _: &str, contents: &str, _: &ReadmeOrigin) -> String {
+    format!(r#"<pre class="readme-raw">{0}</pre>"#, escape(contents))
+}
+
+/// Sanitizes an already rendered README (as provided by a publishing tool).
+#[cfg(feature = "rendering")]
+pub fn sanitize(state: &AppState, html: &str) -> Option<String> {
+    Some(alexandrie_rendering::sanitize_html(&state.syntect, html))
+}
+
+/// Already rendered READMEs cannot be sanitized without README rendering, so they are ignored
+/// (the README from the crate tarball is used instead).
+#[cfg(not(feature = "rendering"))]
+pub fn sanitize(_: &AppState, _: &str) -> Option<String> {
+    None
+}
+
+/// Escapes a text, to be inserted in an HTML document.
+#[cfg(not(feature = "rendering"))]
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
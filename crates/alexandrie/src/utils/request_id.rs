@@ -0,0 +1,76 @@
+use axum::http::{HeaderName, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use ring::rand::{SecureRandom, SystemRandom};
+use tracing::Span;
+
+/// The header carrying the request ID (accepted from clients, and always echoed in responses).
+pub const HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The maximum length of an inbound request ID (longer ones are replaced by a generated one).
+const MAX_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// The ID of the request being handled by the current task.
+    static REQUEST_ID: String;
+}
+
+/// Returns the ID of the request being handled (if called while handling a request).
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(String::clone).ok()
+}
+
+/// Returns whether an inbound request ID can be used as-is (so that it cannot garble the logs).
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_LENGTH
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | ':'))
+}
+
+/// Generates a new (random) request ID.
+fn generate() -> String {
+    let mut bytes = [0u8; 16];
+    let _ = SystemRandom::new().fill(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Gets the ID of a request (as set by [`propagate`]).
+fn of<B>(request: &Request<B>) -> &str {
+    let id = request.headers().get(HEADER);
+    id.and_then(|id| id.to_str().ok()).unwrap_or_default()
+}
+
+/// Creates the span of a request, carrying its ID (so that it appears in every log line emitted while handling it).
+pub fn make_span<B>(request: &Request<B>) -> Span {
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        request_id = %of(request),
+    )
+}
+
+/// Middleware assigning an ID to every request (or keeping the one sent by the client, if valid),
+/// making it available for the duration of the request and echoing it in the response.
+pub(crate) async fn propagate<B>(mut request: Request<B>, next: Next<B>) -> Response {
+    let inbound = request.headers().get(HEADER);
+    let inbound = inbound
+        .and_then(|id| id.to_str().ok())
+        .filter(|id| is_valid(id));
+    let id = inbound.map_or_else(generate, String::from);
+
+    //? The ID is only made of visible ASCII characters, so it is always a valid header value.
+    let value = HeaderValue::from_str(id.as_str()).ok();
+    if let Some(value) = value.clone() {
+        request.headers_mut().insert(HEADER, value);
+    }
+
+    let mut response = REQUEST_ID.scope(id, next.run(request)).await;
+    if let Some(value) = value {
+        response.headers_mut().insert(HEADER, value);
+    }
+    response
+}
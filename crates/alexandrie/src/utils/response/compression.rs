@@ -0,0 +1,43 @@
+use axum::http::{header, HeaderValue, Response};
+use tower_http::compression::predicate::{And, DefaultPredicate, NotForContentType, SizeAbove};
+use tower_http::compression::{CompressionLayer, Predicate};
+
+use crate::config::compression::CompressionConfig;
+
+/// The predicate deciding which responses get compressed.
+pub type CompressionPredicate = And<And<DefaultPredicate, SizeAbove>, NotForContentType>;
+
+/// Constructs the compression layer corresponding to the configuration.
+///
+/// Besides the responses smaller than the configured threshold, binary responses (like crate tarballs,
+/// which are already compressed) and images are sent uncompressed.
+pub fn layer(config: &CompressionConfig) -> CompressionLayer<CompressionPredicate> {
+    let predicate = DefaultPredicate::new()
+        .and(SizeAbove::new(config.min_size))
+        .and(NotForContentType::const_new("application/octet-stream"));
+    CompressionLayer::new()
+        .gzip(config.gzip)
+        .deflate(config.deflate)
+        .compress_when(predicate)
+}
+
+/// Weakens the entity tag of compressed responses.
+///
+/// Entity tags are computed from the uncompressed contents, so a strong entity tag would wrongly claim
+/// that the compressed bytes are identical to the uncompressed ones (conditional requests keep working with weak ones).
+pub async fn weaken_etag<B>(mut response: Response<B>) -> Response<B> {
+    if !response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+
+    let weakened = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .filter(|etag| !etag.starts_with("W/"))
+        .and_then(|etag| HeaderValue::from_str(format!("W/{etag}").as_str()).ok());
+    if let Some(etag) = weakened {
+        response.headers_mut().insert(header::ETAG, etag);
+    }
+    response
+}
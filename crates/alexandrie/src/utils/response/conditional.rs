@@ -0,0 +1,31 @@
+use std::time::SystemTime;
+
+use axum::headers::{ETag, IfModifiedSince, IfNoneMatch};
+
+/// Constructs a strong entity tag from a hex-encoded digest (like the SHA-256 checksum of a tarball).
+pub fn etag(digest: &str) -> ETag {
+    format!("\"{digest}\"")
+        .parse()
+        .expect("hex-encoded digests should always be valid entity tags")
+}
+
+/// Determines whether the client's cached copy of a resource is still up-to-date,
+/// in which case the request should be answered with a `304 Not Modified`.
+pub fn is_not_modified(
+    if_none_match: Option<&IfNoneMatch>,
+    if_modified_since: Option<&IfModifiedSince>,
+    etag: &ETag,
+    last_modified: Option<SystemTime>,
+) -> bool {
+    //? `If-None-Match` takes precedence over `If-Modified-Since`, when both are present.
+    if let Some(if_none_match) = if_none_match {
+        return !if_none_match.precondition_passes(etag);
+    }
+
+    match (if_modified_since, last_modified) {
+        (Some(if_modified_since), Some(last_modified)) => {
+            !if_modified_since.is_modified(last_modified)
+        }
+        _ => false,
+    }
+}
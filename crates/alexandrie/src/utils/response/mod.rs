@@ -7,6 +7,12 @@ use crate::error::FrontendError;
 /// Various utilities to construct common response pages.
 #[cfg(feature = "frontend")]
 pub mod common;
+/// Response compression (negotiated using `Accept-Encoding`).
+pub mod compression;
+/// Various utilities to answer conditional requests (using `ETag` and `Last-Modified`).
+pub mod conditional;
+/// Various utilities to answer byte range requests.
+pub mod range;
 
 #[cfg(feature = "frontend")]
 use crate::config::AppState;
@@ -0,0 +1,103 @@
+use std::ops::Range;
+
+/// The error returned when a `Range` header is malformed or cannot be satisfied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsatisfiableRange;
+
+/// Parses the value of a `Range` header, for a resource of the given size (in bytes).
+///
+/// Only single byte ranges are supported: requests for multiple ranges are answered with the whole resource,
+/// which is signaled by returning `Ok(None)`.
+pub fn parse_byte_range(header: &str, size: u64) -> Result<Option<Range<u64>>, UnsatisfiableRange> {
+    let spec = header
+        .trim()
+        .strip_prefix("bytes=")
+        .ok_or(UnsatisfiableRange)?;
+    if spec.contains(',') {
+        return Ok(None);
+    }
+
+    let (start, end) = spec.trim().split_once('-').ok_or(UnsatisfiableRange)?;
+    let range = match (start.trim(), end.trim()) {
+        //? `bytes=-<suffix-length>`: the last N bytes of the resource.
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| UnsatisfiableRange)?;
+            if suffix == 0 {
+                return Err(UnsatisfiableRange);
+            }
+            size.saturating_sub(suffix)..size
+        }
+        //? `bytes=<first>-`: from the given offset to the end of the resource.
+        (first, "") => {
+            let first: u64 = first.parse().map_err(|_| UnsatisfiableRange)?;
+            first..size
+        }
+        //? `bytes=<first>-<last>` (both inclusive, the last position being clamped to the end of the resource).
+        (first, last) => {
+            let first: u64 = first.parse().map_err(|_| UnsatisfiableRange)?;
+            let last: u64 = last.parse().map_err(|_| UnsatisfiableRange)?;
+            if last < first {
+                return Err(UnsatisfiableRange);
+            }
+            first..last.saturating_add(1).min(size)
+        }
+    };
+
+    if range.start >= size {
+        return Err(UnsatisfiableRange);
+    }
+
+    Ok(Some(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bounded_ranges() {
+        assert_eq!(parse_byte_range("bytes=0-99", 1000), Ok(Some(0..100)));
+        assert_eq!(parse_byte_range("bytes=500-", 1000), Ok(Some(500..1000)));
+    }
+
+    #[test]
+    fn parses_suffix_ranges() {
+        assert_eq!(parse_byte_range("bytes=-100", 1000), Ok(Some(900..1000)));
+        assert_eq!(parse_byte_range("bytes=-5000", 1000), Ok(Some(0..1000)));
+        assert_eq!(parse_byte_range("bytes=-0", 1000), Err(UnsatisfiableRange));
+    }
+
+    #[test]
+    fn clamps_overlong_ranges() {
+        assert_eq!(
+            parse_byte_range("bytes=900-4999", 1000),
+            Ok(Some(900..1000))
+        );
+        assert_eq!(
+            parse_byte_range(format!("bytes=0-{}", u64::MAX).as_str(), 1000),
+            Ok(Some(0..1000)),
+        );
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        assert_eq!(
+            parse_byte_range("bytes=1000-", 1000),
+            Err(UnsatisfiableRange)
+        );
+        assert_eq!(
+            parse_byte_range("bytes=10-5", 1000),
+            Err(UnsatisfiableRange)
+        );
+        assert_eq!(parse_byte_range("bytes=-", 1000), Err(UnsatisfiableRange));
+        assert_eq!(
+            parse_byte_range("items=0-99", 1000),
+            Err(UnsatisfiableRange)
+        );
+    }
+
+    #[test]
+    fn ignores_multiple_ranges() {
+        assert_eq!(parse_byte_range("bytes=0-9, 20-29", 1000), Ok(None));
+    }
+}
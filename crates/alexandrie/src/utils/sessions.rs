@@ -1,8 +1,13 @@
 use std::fmt;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 
 use time::format_description;
 // use async_session::{Session, SessionStore};
 use axum::async_trait;
+use axum::extract::ConnectInfo;
+use axum::http::{header, Request};
+use axum::middleware::Next;
+use axum::response::Response;
 use diesel::dsl;
 use diesel::prelude::*;
 use thiserror::Error;
@@ -10,6 +15,7 @@ use time::macros::format_description;
 use tower_sessions::session::{Session, SessionId, SessionRecord};
 use tower_sessions::session_store::SessionStore;
 
+use crate::db::database::PoolExhausted;
 use crate::db::models::Session as SessionEntry;
 use crate::db::schema::*;
 use crate::db::Database;
@@ -18,6 +24,16 @@ use crate::db::Database;
 pub static SESSION_DATE_FORMAT: &[format_description::FormatItem] =
     format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
 
+/// The session data key for the date at which the session was last seen.
+pub const LAST_SEEN_KEY: &str = "session.last_seen";
+/// The session data key for the coarse description of the session's user-agent.
+pub const USER_AGENT_KEY: &str = "session.user_agent";
+/// The session data key for the session's coarse IP address.
+pub const IP_ADDRESS_KEY: &str = "session.ip_address";
+
+/// How often the last-seen details of a session are refreshed (in seconds).
+const LAST_SEEN_REFRESH_INTERVAL: i64 = 60;
+
 /// The error type when interacting with the session store.
 #[derive(Debug, Error)]
 pub enum SqlStoreError {
@@ -36,6 +52,9 @@ pub enum SqlStoreError {
     /// Diesel query error.
     #[error("diesel error: {0}")]
     Diesel(#[from] diesel::result::Error),
+    /// No database connection could be acquired within the configured timeout.
+    #[error("{0}")]
+    PoolExhausted(#[from] PoolExhausted),
 }
 
 /// A SQL-based session store.
@@ -70,6 +89,27 @@ impl SessionStore for SqlStore {
             .map(json::from_value)
             .transpose()?;
 
+        let user_agent: Option<String> = data
+            .get(USER_AGENT_KEY)
+            .cloned()
+            .map(json::from_value)
+            .transpose()?
+            .flatten();
+        let ip_address: Option<String> = data
+            .get(IP_ADDRESS_KEY)
+            .cloned()
+            .map(json::from_value)
+            .transpose()?
+            .flatten();
+
+        let now = time::OffsetDateTime::now_utc().format(SESSION_DATE_FORMAT)?;
+        let last_seen: String = data
+            .get(LAST_SEEN_KEY)
+            .cloned()
+            .map(json::from_value)
+            .transpose()?
+            .unwrap_or_else(|| now.clone());
+
         let data = json::to_string(&data)?;
 
         let expiry = session
@@ -83,6 +123,10 @@ impl SessionStore for SqlStore {
             author_id,
             expiry,
             data,
+            created_at: now,
+            last_seen,
+            user_agent,
+            ip_address,
         };
 
         self.database
@@ -91,8 +135,16 @@ impl SessionStore for SqlStore {
                     dsl::select(dsl::exists(sessions::table.find(&record.id))).get_result(conn)?;
 
                 if exists {
+                    //? The creation date of an existing session is left untouched.
                     diesel::update(sessions::table.find(&record.id))
-                        .set(&record)
+                        .set((
+                            sessions::author_id.eq(record.author_id),
+                            sessions::expiry.eq(&record.expiry),
+                            sessions::data.eq(&record.data),
+                            sessions::last_seen.eq(&record.last_seen),
+                            sessions::user_agent.eq(&record.user_agent),
+                            sessions::ip_address.eq(&record.ip_address),
+                        ))
                         .execute(conn)?;
                 } else {
                     diesel::insert_into(sessions::table)
@@ -143,3 +195,108 @@ impl SessionStore for SqlStore {
         Ok(())
     }
 }
+
+/// Middleware keeping track of when (and from where) the sessions of logged-in authors are used.
+///
+/// The details are stored in the session's data (refreshed at most once per minute, to avoid saving the session on every request),
+/// and are then persisted in their own columns by the [`SqlStore`].
+pub async fn track<B>(
+    session: Session,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let is_logged_in = matches!(session.get::<i64>("author.id"), Ok(Some(_)));
+    if is_logged_in {
+        let user_agent = request
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|it| it.to_str().ok())
+            .map(describe_user_agent);
+        if let Err(err) = refresh_last_seen(&session, user_agent, addr.ip()) {
+            tracing::warn!("could not refresh the session's details: {err}");
+        }
+    }
+
+    next.run(request).await
+}
+
+fn refresh_last_seen(
+    session: &Session,
+    user_agent: Option<String>,
+    addr: IpAddr,
+) -> Result<(), SqlStoreError> {
+    let now = time::OffsetDateTime::now_utc();
+
+    let last_seen: Option<String> = session.get(LAST_SEEN_KEY)?;
+    let last_seen = last_seen
+        .map(|it| time::PrimitiveDateTime::parse(&it, SESSION_DATE_FORMAT))
+        .transpose()?
+        .map(|it| it.assume_utc());
+
+    //? Are the session's details recent enough?
+    if last_seen.map_or(false, |it| {
+        (now - it).whole_seconds() < LAST_SEEN_REFRESH_INTERVAL
+    }) {
+        return Ok(());
+    }
+
+    session.insert(LAST_SEEN_KEY, now.format(SESSION_DATE_FORMAT)?)?;
+    session.insert(USER_AGENT_KEY, user_agent)?;
+    session.insert(IP_ADDRESS_KEY, coarse_ip_address(addr))?;
+
+    Ok(())
+}
+
+/// Returns a coarse description of a user-agent (eg. "Firefox on Linux").
+pub fn describe_user_agent(user_agent: &str) -> String {
+    //? The order matters, since user-agents often mention other browsers for compatibility.
+    const BROWSERS: &[(&str, &str)] = &[
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+        ("curl/", "curl"),
+    ];
+    const PLATFORMS: &[(&str, &str)] = &[
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("CrOS", "ChromeOS"),
+        ("Linux", "Linux"),
+    ];
+
+    let find = |patterns: &[(&str, &'static str)]| {
+        patterns
+            .iter()
+            .find(|(pattern, _)| user_agent.contains(pattern))
+            .map(|(_, name)| *name)
+    };
+
+    match (find(BROWSERS), find(PLATFORMS)) {
+        (Some(browser), Some(platform)) => format!("{browser} on {platform}"),
+        (Some(browser), None) => String::from(browser),
+        (None, Some(platform)) => format!("Unknown browser on {platform}"),
+        (None, None) => String::from("Unknown browser"),
+    }
+}
+
+/// Returns a coarse version of an IP address (its /24 network for IPv4, or its /48 network for IPv6).
+pub fn coarse_ip_address(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(addr) => {
+            let [a, b, c, _] = addr.octets();
+            format!("{a}.{b}.{c}.0/24")
+        }
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(addr) => coarse_ip_address(IpAddr::V4(addr)),
+            None => {
+                let [a, b, c, ..] = addr.segments();
+                format!("{}/48", Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0))
+            }
+        },
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Waits for the registry to be asked to shut down (using `SIGTERM`, or `Ctrl-C`).
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("could not listen for `Ctrl-C`: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("could not listen for `SIGTERM`: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Keeps track of the operations in flight which must complete before the registry shuts down (like publications).
+#[derive(Debug, Default)]
+pub struct InFlight {
+    /// How many operations are in flight.
+    count: AtomicUsize,
+    /// Notified when the last operation in flight completes.
+    idle: Notify,
+}
+
+/// Marks an operation as in flight, until it gets dropped.
+#[derive(Debug)]
+pub struct InFlightGuard {
+    tracker: Arc<InFlight>,
+}
+
+impl InFlight {
+    /// Constructs a new tracker, without any operation in flight.
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Marks an operation as in flight (until the returned guard gets dropped).
+    pub fn track(self: &Arc<Self>) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            tracker: Arc::clone(self),
+        }
+    }
+
+    /// Returns how many operations are in flight.
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Waits for every operation in flight to complete.
+    pub async fn wait_idle(&self) {
+        loop {
+            //? The notification is registered before checking the count, so that it cannot be missed.
+            let idle = self.idle.notified();
+            if self.count() == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.tracker.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.tracker.idle.notify_waiters();
+        }
+    }
+}
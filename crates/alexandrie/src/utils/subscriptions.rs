@@ -0,0 +1,104 @@
+use chrono::Utc;
+use diesel::dsl as sql;
+use diesel::prelude::*;
+
+use crate::config::AppState;
+use crate::db::models::{NewCrateSubscription, NewPendingNotification};
+use crate::db::schema::*;
+use crate::db::Connection;
+use crate::db::DATETIME_FORMAT;
+use crate::utils::visibility;
+
+fn now() -> String {
+    Utc::now().naive_utc().format(DATETIME_FORMAT).to_string()
+}
+
+/// Returns whether publish notifications are enabled.
+///
+/// Notifications are sent by email (using the frontend's templates), so this requires both
+/// the `[notifications]` and `[mail]` sections to be configured, and the frontend to be enabled.
+pub fn is_enabled(state: &AppState) -> bool {
+    #[cfg(feature = "frontend")]
+    let frontend_enabled = state.frontend.config.enabled;
+    #[cfg(not(feature = "frontend"))]
+    let frontend_enabled = false;
+
+    frontend_enabled && state.notifications.is_some() && state.mail.is_some()
+}
+
+/// Returns whether an author is subscribed to a crate.
+pub fn is_subscribed(conn: &mut Connection, crate_id: i64, author_id: i64) -> QueryResult<bool> {
+    sql::select(sql::exists(
+        crate_subscriptions::table
+            .filter(crate_subscriptions::crate_id.eq(crate_id))
+            .filter(crate_subscriptions::author_id.eq(author_id)),
+    ))
+    .get_result(conn)
+}
+
+/// Subscribes an author to a crate (does nothing if they already are).
+pub fn subscribe(conn: &mut Connection, crate_id: i64, author_id: i64) -> QueryResult<()> {
+    if is_subscribed(conn, crate_id, author_id)? {
+        return Ok(());
+    }
+    let created_at = now();
+    diesel::insert_into(crate_subscriptions::table)
+        .values(NewCrateSubscription {
+            crate_id,
+            author_id,
+            created_at: created_at.as_str(),
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Unsubscribes an author from a crate, returning whether they were subscribed to it.
+///
+/// The notifications still pending for this subscription are dropped along with it.
+pub fn unsubscribe(conn: &mut Connection, crate_id: i64, author_id: i64) -> QueryResult<bool> {
+    let deleted = diesel::delete(
+        crate_subscriptions::table
+            .filter(crate_subscriptions::crate_id.eq(crate_id))
+            .filter(crate_subscriptions::author_id.eq(author_id)),
+    )
+    .execute(conn)?;
+    Ok(deleted > 0)
+}
+
+/// Queues a notification of a new version of a crate for all of its subscribers (except its publisher),
+/// as long as they can access the crate.
+///
+/// The queued notifications are sent in batches by the notification dispatcher
+/// (which checks again that the subscribers can still access the crate).
+pub fn enqueue(
+    conn: &mut Connection,
+    crate_id: i64,
+    version: &str,
+    publisher_id: i64,
+) -> QueryResult<usize> {
+    let subscriptions: Vec<(i64, i64)> = crate_subscriptions::table
+        .select((crate_subscriptions::id, crate_subscriptions::author_id))
+        .filter(crate_subscriptions::crate_id.eq(crate_id))
+        .filter(crate_subscriptions::author_id.ne(publisher_id))
+        .load(conn)?;
+
+    let mut subscription_ids = Vec::with_capacity(subscriptions.len());
+    for (subscription_id, author_id) in subscriptions {
+        if visibility::can_access(conn, crate_id, Some(author_id))? {
+            subscription_ids.push(subscription_id);
+        }
+    }
+
+    let created_at = now();
+    for subscription_id in subscription_ids.iter().copied() {
+        diesel::insert_into(pending_notifications::table)
+            .values(NewPendingNotification {
+                subscription_id,
+                version,
+                created_at: created_at.as_str(),
+            })
+            .execute(conn)?;
+    }
+
+    Ok(subscription_ids.len())
+}
@@ -0,0 +1,124 @@
+use std::io::{self, Read};
+
+use flate2::read::GzDecoder;
+
+use crate::config::GeneralState;
+use crate::error::{AlexError, Error};
+
+/// A reader failing once more than a given number of bytes have been read from it.
+///
+/// Wrapped around the decompression of uploaded tarballs, it guards against decompression bombs
+/// (small archives decompressing to huge contents) before anything gets buffered in memory.
+#[derive(Debug)]
+pub struct BoundedReader<R> {
+    inner: R,
+    limit: u64,
+    remaining: u64,
+}
+
+impl<R: Read> BoundedReader<R> {
+    /// Wraps a reader, allowing at most `limit` bytes to be read from it.
+    pub fn new(inner: R, limit: u64) -> Self {
+        Self {
+            inner,
+            limit,
+            remaining: limit,
+        }
+    }
+}
+
+impl<R: Read> Read for BoundedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read as u64 > self.remaining {
+            let limit = self.limit;
+            let err = AlexError::DecompressionLimitExceeded { limit };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Returns the maximum decompressed size allowed for a tarball of the given compressed size.
+///
+/// It is the lowest of the configured multiple of the compressed size and the configured absolute cap.
+pub fn decompression_limit(general: &GeneralState, compressed_size: usize) -> u64 {
+    let relative = (compressed_size as u64).saturating_mul(general.max_decompression_ratio);
+    relative.min(general.max_decompressed_size)
+}
+
+/// Returns a reader decompressing a gzipped tarball, which fails once the decompressed size exceeds the given limit.
+pub fn decompress(compressed: &[u8], limit: u64) -> BoundedReader<GzDecoder<&[u8]>> {
+    BoundedReader::new(GzDecoder::new(compressed), limit)
+}
+
+/// Converts an IO error encountered while reading a tarball into an error,
+/// recovering the decompression limit error if that is what it is.
+pub fn into_error(err: io::Error) -> Error {
+    let exceeded = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<AlexError>())
+        .and_then(|inner| match inner {
+            AlexError::DecompressionLimitExceeded { limit } => Some(*limit),
+            _ => None,
+        });
+    match exceeded {
+        Some(limit) => Error::from(AlexError::DecompressionLimitExceeded { limit }),
+        None => Error::from(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    fn read_all<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    #[test]
+    fn reads_contents_within_the_limit() {
+        let contents = vec![7u8; 1024];
+        let reader = BoundedReader::new(contents.as_slice(), 1024);
+        assert_eq!(read_all(reader).unwrap(), contents);
+    }
+
+    #[test]
+    fn fails_past_the_limit() {
+        let contents = vec![7u8; 1025];
+        let reader = BoundedReader::new(contents.as_slice(), 1024);
+        let err = read_all(reader).unwrap_err();
+        assert!(matches!(
+            into_error(err),
+            Error::AlexError(AlexError::DecompressionLimitExceeded { limit: 1024 }),
+        ));
+    }
+
+    #[test]
+    fn bounds_decompressed_contents() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0u8; 1024 * 1024]).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(read_all(decompress(&compressed, 1024 * 1024)).is_ok());
+        let err = read_all(decompress(&compressed, 64 * 1024)).unwrap_err();
+        assert!(matches!(
+            into_error(err),
+            Error::AlexError(AlexError::DecompressionLimitExceeded { .. }),
+        ));
+    }
+
+    #[test]
+    fn keeps_other_errors() {
+        let err = io::Error::new(io::ErrorKind::UnexpectedEof, "truncated archive");
+        assert!(matches!(into_error(err), Error::IOError(_)));
+    }
+}
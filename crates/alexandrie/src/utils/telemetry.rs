@@ -0,0 +1,115 @@
+use std::fmt::Display;
+
+use tracing::field::{self, Empty};
+use tracing::Span;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::config::Config;
+
+/// Initializes the global tracing subscriber.
+///
+/// Logs are always written to the standard output (filtered by the `RUST_LOG` environment variable).
+/// When built with the `opentelemetry` feature and an OTLP endpoint is configured,
+/// spans are also exported to that endpoint (regardless of the `RUST_LOG` filter).
+pub fn init(config: &Config) -> Result<(), anyhow::Error> {
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        // .with_target(false)
+        .compact()
+        .with_filter(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy(),
+        );
+
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+
+    #[cfg(feature = "opentelemetry")]
+    let registry = registry.with(otlp_layer(config)?);
+    #[cfg(not(feature = "opentelemetry"))]
+    let _ = config;
+
+    registry.try_init()?;
+
+    Ok(())
+}
+
+/// Flushes the pending spans and shuts the exporter down (if any).
+pub fn shutdown() {
+    #[cfg(feature = "opentelemetry")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+#[cfg(feature = "opentelemetry")]
+fn otlp_layer<S>(config: &Config) -> Result<Option<impl Layer<S>>, anyhow::Error>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::sdk::{trace, Resource};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+
+    let Some(endpoint) = config.opentelemetry.endpoint.as_deref() else {
+        return Ok(None);
+    };
+
+    let service_name = config.opentelemetry.service_name.clone();
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name,
+            )])),
+        )
+        .install_batch(opentelemetry::runtime::Tokio)?;
+
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(LevelFilter::DEBUG);
+
+    Ok(Some(layer))
+}
+
+/// Creates a span for a single step of an operation on a crate version.
+///
+/// These spans are at the `DEBUG` level, so they are essentially free unless
+/// explicitly enabled (with `RUST_LOG`) or exported to an OTLP collector.
+pub fn step_span(step: &'static str, crate_name: &str, crate_version: &impl Display) -> Span {
+    tracing::debug_span!(
+        "step",
+        otel.name = step,
+        otel.status_code = Empty,
+        crate_name = crate_name,
+        crate_version = %crate_version,
+        error = Empty,
+    )
+}
+
+/// Records the given error on a span (and marks that span as failed).
+pub fn record_error(span: &Span, error: &impl Display) {
+    span.record("otel.status_code", "ERROR");
+    span.record("error", field::display(error));
+}
+
+/// Runs the given closure within a span, recording its error on that span (if it fails).
+pub fn in_span<T, E, F>(span: Span, func: F) -> Result<T, E>
+where
+    E: Display,
+    F: FnOnce() -> Result<T, E>,
+{
+    span.in_scope(|| {
+        let result = func();
+        if let Err(error) = result.as_ref() {
+            record_error(&span, error);
+        }
+        result
+    })
+}
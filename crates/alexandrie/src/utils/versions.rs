@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use alexandrie_index::CrateVersion;
+
+use crate::db::models::NewCrateVersionRecord;
+use crate::db::schema::*;
+use crate::db::Connection;
+
+/// An entry of a crate's version timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// The version this entry is about.
+    pub version: Version,
+    /// The date at which this version was published (unknown for versions published before it was recorded).
+    pub published_at: Option<String>,
+    /// Whether this version has been yanked.
+    pub yanked: bool,
+    /// The version's SPDX license expression (if it has one, and it was recorded).
+    pub license: Option<String>,
+}
+
+/// Records the publication date of a crate version (unless it already has been recorded).
+pub fn record_published(
+    conn: &mut Connection,
+    crate_id: i64,
+    version: &str,
+    published_at: &str,
+) -> QueryResult<()> {
+    let recorded: bool = sql::select(sql::exists(
+        crate_versions::table
+            .filter(crate_versions::crate_id.eq(crate_id))
+            .filter(crate_versions::version.eq(version)),
+    ))
+    .get_result(conn)?;
+    if !recorded {
+        diesel::insert_into(crate_versions::table)
+            .values(NewCrateVersionRecord {
+                crate_id,
+                version,
+                published_at,
+            })
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Counts the available (non-yanked) versions of a crate, from its index records.
+pub fn count_available(records: &[CrateVersion]) -> i64 {
+    let count = records
+        .iter()
+        .filter(|record| record.yanked != Some(true))
+        .count();
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+/// Stores a crate's number of available (non-yanked) versions, which crate pages are rendered from.
+///
+/// It must be updated whenever versions get published, yanked or un-yanked.
+pub fn store_available(conn: &mut Connection, crate_id: i64, count: i64) -> QueryResult<()> {
+    diesel::update(crates::table.find(crate_id))
+        .set(crates::non_yanked_versions.eq(count))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Gets the ID of a crate version's record, creating it if it is missing (like for versions published before
+/// their records existed, or imported without them).
+///
+/// The publication date of the created records is unknown, so it gets approximated by the crate's creation date.
+pub fn ensure_recorded(conn: &mut Connection, crate_id: i64, version: &str) -> QueryResult<i64> {
+    let id = crate_versions::table
+        .select(crate_versions::id)
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .filter(crate_versions::version.eq(version))
+        .first::<i64>(conn)
+        .optional()?;
+    if let Some(id) = id {
+        return Ok(id);
+    }
+
+    let created_at = crates::table
+        .find(crate_id)
+        .select(crates::created_at)
+        .first::<String>(conn)?;
+    record_published(conn, crate_id, version, created_at.as_str())?;
+
+    crate_versions::table
+        .select(crate_versions::id)
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .filter(crate_versions::version.eq(version))
+        .first::<i64>(conn)
+}
+
+/// Builds the version timeline of a crate from its index records, the most recently published versions first.
+///
+/// Versions without a recorded publication date are listed last (from the highest to the lowest version).
+pub fn timeline(
+    conn: &mut Connection,
+    crate_id: i64,
+    records: &[CrateVersion],
+) -> QueryResult<Vec<TimelineEntry>> {
+    let mut published: HashMap<String, (String, Option<String>)> = crate_versions::table
+        .select((
+            crate_versions::version,
+            crate_versions::published_at,
+            crate_versions::license,
+        ))
+        .filter(crate_versions::crate_id.eq(crate_id))
+        .load::<(String, String, Option<String>)>(conn)?
+        .into_iter()
+        .map(|(version, published_at, license)| (version, (published_at, license)))
+        .collect();
+
+    let mut entries: Vec<TimelineEntry> = records
+        .iter()
+        .map(|record| {
+            let recorded = published.remove(record.vers.to_string().as_str());
+            let (published_at, license) = recorded.unzip();
+            TimelineEntry {
+                version: record.vers.clone(),
+                published_at,
+                yanked: record.yanked == Some(true),
+                license: license.flatten(),
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.published_at
+            .cmp(&a.published_at)
+            .then_with(|| b.version.cmp(&a.version))
+    });
+
+    Ok(entries)
+}
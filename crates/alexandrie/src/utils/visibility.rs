@@ -0,0 +1,94 @@
+use diesel::dsl as sql;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db::schema::*;
+use crate::db::Connection;
+
+/// The visibility of a crate.
+///
+/// Private crates (and their index records, when served through the sparse index) are only accessible
+/// to their owners and collaborators, and are hidden from the search results of other users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrateVisibility {
+    /// The crate is accessible to anyone.
+    #[default]
+    Public,
+    /// The crate is only accessible to its owners and collaborators.
+    Private,
+}
+
+impl CrateVisibility {
+    /// Returns the visibility's representation, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrateVisibility::Public => "public",
+            CrateVisibility::Private => "private",
+        }
+    }
+
+    /// Parses a visibility, as stored in the database.
+    ///
+    /// Unknown visibilities are treated as private (the most restrictive one).
+    pub fn parse(visibility: &str) -> CrateVisibility {
+        match visibility {
+            "public" => CrateVisibility::Public,
+            _ => CrateVisibility::Private,
+        }
+    }
+}
+
+/// Checks whether an author (or an anonymous user, if `None`) can access the crate of the given ID.
+pub fn can_access(
+    conn: &mut Connection,
+    crate_id: i64,
+    author_id: Option<i64>,
+) -> QueryResult<bool> {
+    let visibility = crates::table
+        .select(crates::visibility)
+        .filter(crates::id.eq(crate_id))
+        .first::<String>(conn)?;
+    if CrateVisibility::parse(visibility.as_str()) == CrateVisibility::Public {
+        return Ok(true);
+    }
+
+    let Some(author_id) = author_id else {
+        return Ok(false);
+    };
+    sql::select(sql::exists(
+        crate_authors::table
+            .filter(crate_authors::crate_id.eq(crate_id))
+            .filter(crate_authors::author_id.eq(author_id)),
+    ))
+    .get_result(conn)
+}
+
+/// Gets the IDs of the private crates accessible to an author (the ones they own or collaborate on).
+///
+/// Listings can then be restricted to the public crates and to these ones.
+pub fn accessible_private_crates(
+    conn: &mut Connection,
+    author_id: Option<i64>,
+) -> QueryResult<Vec<i64>> {
+    let Some(author_id) = author_id else {
+        return Ok(Vec::new());
+    };
+    crate_authors::table
+        .inner_join(crates::table)
+        .select(crates::id)
+        .filter(crate_authors::author_id.eq(author_id))
+        .filter(crates::visibility.ne(CrateVisibility::Public.as_str()))
+        .load(conn)
+}
+
+/// Gets the names of the private crates that are not accessible to an author,
+/// as canonical names (to hide them from the listings that are not built from the database).
+pub fn hidden_crates(conn: &mut Connection, author_id: Option<i64>) -> QueryResult<Vec<String>> {
+    let accessible = accessible_private_crates(conn, author_id)?;
+    crates::table
+        .select(crates::canon_name)
+        .filter(crates::visibility.ne(CrateVisibility::Public.as_str()))
+        .filter(crates::id.ne_all(accessible))
+        .load(conn)
+}
@@ -0,0 +1,7 @@
+create table `password_resets` (
+    `id` bigint not null auto_increment unique primary key,
+    `token` varchar(255) not null unique,
+    `author_id` bigint not null,
+    `expires_at` varchar(25) not null,
+    foreign key (`author_id`) references `authors`(`id`) on update cascade on delete cascade
+);
@@ -0,0 +1,11 @@
+create table `crate_provenance` (
+    `id` bigint not null auto_increment unique primary key,
+    `crate_id` bigint not null,
+    `version` varchar(255) not null,
+    `repository` varchar(1024),
+    `attestation_kind` varchar(255),
+    `attestation` text,
+    `recorded_at` varchar(25) not null,
+    unique (`crate_id`, `version`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
@@ -0,0 +1,8 @@
+create table `download_snapshots` (
+    `id` bigint not null auto_increment unique primary key,
+    `crate_id` bigint not null,
+    `downloads` bigint not null,
+    `taken_at` varchar(25) not null,
+    index (`taken_at`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
@@ -0,0 +1,11 @@
+create table `crate_versions` (
+    `id` bigint not null auto_increment unique primary key,
+    `crate_id` bigint not null,
+    `version` varchar(255) not null,
+    `published_at` varchar(25) not null,
+    unique (`crate_id`, `version`),
+    index (`published_at`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
+insert into `crate_versions` (`crate_id`, `version`, `published_at`)
+    select `crate_id`, `version`, `recorded_at` from `crate_provenance`;
@@ -0,0 +1,2 @@
+alter table `owner_invitations` drop column `role`;
+alter table `crate_authors` drop column `role`;
@@ -0,0 +1,2 @@
+alter table `crate_authors` add column `role` varchar(16) not null default 'owner';
+alter table `owner_invitations` add column `role` varchar(16) not null default 'owner';
@@ -0,0 +1,19 @@
+create table `crate_subscriptions` (
+    `id` bigint not null auto_increment unique primary key,
+    `crate_id` bigint not null,
+    `author_id` bigint not null,
+    `created_at` varchar(25) not null,
+    `last_notified_at` varchar(25),
+    unique (`crate_id`, `author_id`),
+    index (`author_id`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade,
+    foreign key (`author_id`) references `authors`(`id`) on update cascade on delete cascade
+);
+create table `pending_notifications` (
+    `id` bigint not null auto_increment unique primary key,
+    `subscription_id` bigint not null,
+    `version` varchar(255) not null,
+    `created_at` varchar(25) not null,
+    index (`subscription_id`),
+    foreign key (`subscription_id`) references `crate_subscriptions`(`id`) on update cascade on delete cascade
+);
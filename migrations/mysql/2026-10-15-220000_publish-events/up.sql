@@ -0,0 +1,8 @@
+create table `publish_events` (
+    `id` bigint not null auto_increment unique primary key,
+    `author_id` bigint not null,
+    `kind` varchar(255) not null,
+    `created_at` varchar(25) not null,
+    index (`author_id`, `created_at`),
+    foreign key (`author_id`) references `authors`(`id`) on update cascade on delete cascade
+);
@@ -0,0 +1,3 @@
+alter table `crate_versions` drop column `license_text`;
+alter table `crate_versions` drop column `license_file`;
+alter table `crate_versions` drop column `license`;
@@ -0,0 +1,8 @@
+create table `crate_aliases` (
+    `id` bigint not null auto_increment unique primary key,
+    `crate_id` bigint not null,
+    `alias` varchar(255) not null unique,
+    `created_at` varchar(25) not null,
+    index (`crate_id`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
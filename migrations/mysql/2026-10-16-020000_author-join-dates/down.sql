@@ -0,0 +1 @@
+alter table `authors` drop column `created_at`;
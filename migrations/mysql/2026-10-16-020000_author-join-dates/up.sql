@@ -0,0 +1 @@
+alter table `authors` add column `created_at` varchar(25);
@@ -0,0 +1 @@
+alter table `crates` drop column `non_yanked_versions`;
@@ -0,0 +1,4 @@
+alter table "sessions" drop column "created_at";
+alter table "sessions" drop column "last_seen";
+alter table "sessions" drop column "user_agent";
+alter table "sessions" drop column "ip_address";
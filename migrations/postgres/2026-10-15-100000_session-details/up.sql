@@ -0,0 +1,4 @@
+alter table "sessions" add column "created_at" varchar(25) not null default '';
+alter table "sessions" add column "last_seen" varchar(25) not null default '';
+alter table "sessions" add column "user_agent" varchar(255);
+alter table "sessions" add column "ip_address" varchar(64);
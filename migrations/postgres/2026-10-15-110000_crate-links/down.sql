@@ -0,0 +1 @@
+alter table "crates" drop column "links";
@@ -0,0 +1 @@
+alter table "crates" add column "links" varchar(255);
@@ -0,0 +1 @@
+drop table "pruned_tarballs";
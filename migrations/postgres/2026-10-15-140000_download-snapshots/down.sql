@@ -0,0 +1 @@
+drop table "download_snapshots";
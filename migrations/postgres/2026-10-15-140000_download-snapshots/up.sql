@@ -0,0 +1,8 @@
+create table "download_snapshots" (
+    "id" bigserial primary key,
+    "crate_id" bigint not null,
+    "downloads" bigint not null,
+    "taken_at" varchar(25) not null,
+    foreign key ("crate_id") references "crates"("id") on update cascade on delete cascade
+);
+create index "download_snapshots_taken_at" on "download_snapshots"("taken_at");
@@ -0,0 +1 @@
+drop table "owner_invitations";
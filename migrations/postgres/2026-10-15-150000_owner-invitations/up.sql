@@ -0,0 +1,13 @@
+create table "owner_invitations" (
+    "id" bigserial primary key,
+    "crate_id" bigint not null,
+    "invitee_id" bigint not null,
+    "inviter_id" bigint not null,
+    "status" varchar(16) not null,
+    "created_at" varchar(25) not null,
+    "expires_at" varchar(25) not null,
+    "resolved_at" varchar(25),
+    foreign key ("crate_id") references "crates"("id") on update cascade on delete cascade,
+    foreign key ("invitee_id") references "authors"("id") on update cascade on delete cascade,
+    foreign key ("inviter_id") references "authors"("id") on update cascade on delete cascade
+);
@@ -0,0 +1,2 @@
+drop table "version_download_snapshots";
+alter table "crate_versions" drop column "downloads";
@@ -0,0 +1,10 @@
+alter table "crate_versions" add column "downloads" bigint not null default 0;
+create table "version_download_snapshots" (
+    "id" bigserial primary key,
+    "version_id" bigint not null,
+    "downloads" bigint not null,
+    "date" varchar(10) not null,
+    unique ("version_id", "date"),
+    foreign key ("version_id") references "crate_versions"("id") on update cascade on delete cascade
+);
+create index "version_download_snapshots_date" on "version_download_snapshots"("date");
@@ -0,0 +1,12 @@
+create table "audit_log" (
+    "id" bigserial primary key,
+    "crate_id" bigint not null,
+    "author_id" bigint,
+    "action" varchar(32) not null,
+    "version" varchar(255),
+    "reason" text,
+    "created_at" varchar(25) not null,
+    foreign key ("crate_id") references "crates"("id") on update cascade on delete cascade,
+    foreign key ("author_id") references "authors"("id") on update cascade on delete set null
+);
+create index "audit_log_crate_id" on "audit_log"("crate_id");
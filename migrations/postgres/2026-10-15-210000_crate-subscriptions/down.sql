@@ -0,0 +1,2 @@
+drop table "pending_notifications";
+drop table "crate_subscriptions";
@@ -0,0 +1,8 @@
+create table "publish_events" (
+    "id" bigserial primary key,
+    "author_id" bigint not null,
+    "kind" varchar(255) not null,
+    "created_at" varchar(25) not null,
+    foreign key ("author_id") references "authors"("id") on update cascade on delete cascade
+);
+create index "publish_events_author_id_created_at" on "publish_events"("author_id", "created_at");
@@ -0,0 +1,3 @@
+alter table "crate_versions" add column "license" varchar(255) null;
+alter table "crate_versions" add column "license_file" varchar(255) null;
+alter table "crate_versions" add column "license_text" text null;
@@ -0,0 +1 @@
+alter table "crate_versions" drop column "backfilled_downloads";
@@ -0,0 +1 @@
+alter table "crate_versions" add column "backfilled_downloads" bigint not null default 0;
@@ -0,0 +1,8 @@
+create table "crate_aliases" (
+    "id" bigserial primary key,
+    "crate_id" bigint not null,
+    "alias" varchar(255) not null unique,
+    "created_at" varchar(25) not null,
+    foreign key ("crate_id") references "crates"("id") on update cascade on delete cascade
+);
+create index "crate_aliases_crate_id" on "crate_aliases"("crate_id");
@@ -0,0 +1,11 @@
+alter table `sessions` rename to `sessions_old`;
+create table `sessions` (
+    `id` varchar(255) primary key,
+    `author_id` integer,
+    `expiry` varchar(25) not null,
+    `data` text not null,
+    foreign key (`author_id`) references `authors`(`id`) on update cascade on delete cascade
+);
+insert into `sessions` (`id`, `author_id`, `expiry`, `data`)
+    select `id`, `author_id`, `expiry`, `data` from `sessions_old`;
+drop table `sessions_old`;
@@ -0,0 +1,8 @@
+create table `pruned_tarballs` (
+    `id` integer primary key,
+    `crate_id` bigint not null,
+    `version` varchar(255) not null,
+    `pruned_at` varchar(25) not null,
+    unique (`crate_id`, `version`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
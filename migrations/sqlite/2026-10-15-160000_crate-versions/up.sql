@@ -0,0 +1,11 @@
+create table `crate_versions` (
+    `id` integer primary key,
+    `crate_id` bigint not null,
+    `version` varchar(255) not null,
+    `published_at` varchar(25) not null,
+    unique (`crate_id`, `version`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade
+);
+create index `crate_versions_published_at` on `crate_versions`(`published_at`);
+insert into `crate_versions` (`crate_id`, `version`, `published_at`)
+    select `crate_id`, `version`, `recorded_at` from `crate_provenance`;
@@ -0,0 +1 @@
+alter table `crates` add column `visibility` varchar(16) not null default 'public';
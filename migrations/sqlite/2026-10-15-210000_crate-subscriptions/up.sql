@@ -0,0 +1,19 @@
+create table `crate_subscriptions` (
+    `id` integer primary key,
+    `crate_id` bigint not null,
+    `author_id` bigint not null,
+    `created_at` varchar(25) not null,
+    `last_notified_at` varchar(25),
+    unique (`crate_id`, `author_id`),
+    foreign key (`crate_id`) references `crates`(`id`) on update cascade on delete cascade,
+    foreign key (`author_id`) references `authors`(`id`) on update cascade on delete cascade
+);
+create index `crate_subscriptions_author_id` on `crate_subscriptions`(`author_id`);
+create table `pending_notifications` (
+    `id` integer primary key,
+    `subscription_id` bigint not null,
+    `version` varchar(255) not null,
+    `created_at` varchar(25) not null,
+    foreign key (`subscription_id`) references `crate_subscriptions`(`id`) on update cascade on delete cascade
+);
+create index `pending_notifications_subscription_id` on `pending_notifications`(`subscription_id`);
@@ -0,0 +1 @@
+alter table `crates` add column `non_yanked_versions` bigint;